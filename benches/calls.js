@@ -0,0 +1,10 @@
+// Makes a large number of small function calls.
+// Run with: cargo bench -- calls
+function add3(a, b, c) { return a + b + c; }
+var total = 0;
+var i = 0;
+while (i < 300000) {
+    total = add3(total, i, 1) - add3(0, i, 0);
+    i = i + 1;
+}
+total;
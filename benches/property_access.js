@@ -0,0 +1,13 @@
+// Reads and writes the same few properties of objects built the same way.
+// Run with: cargo bench -- property_access
+function point(x, y) { return { x: x, y: y, z: 0, label: "p", weight: 1 }; }
+var points = [point(1, 2), point(3, 4), point(5, 6)];
+var sum = 0;
+var i = 0;
+while (i < 300000) {
+    var p = points[i % 3];
+    p.z = p.z + 1;
+    sum = sum + p.x * p.weight + p.y + p.z;
+    i = i + 1;
+}
+sum;
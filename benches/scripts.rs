@@ -0,0 +1,52 @@
+// benches/scripts.rs
+use jays::interpreter::Interpreter;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+// Times the scripts in benches/, each of which leans on one of the
+// representations chosen for speed: string_concat on ropes, calls on
+// arguments kept in a small vector, property_access on shapes and inline
+// caches. Every run parses and evaluates the script in a fresh interpreter,
+// and the fastest and median of RUNS runs are reported.
+//
+// `cargo bench` runs them all; `cargo bench -- calls` only the scripts
+// whose names contain "calls".
+const RUNS: usize = 7;
+
+fn main() {
+    // cargo passes --bench, which is not a script name
+    let filters: Vec<String> = std::env::args().skip(1).filter(|argument| !argument.starts_with("--")).collect();
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("benches");
+    
+    for script in scripts(&directory) {
+        let name = script.file_stem().unwrap().to_string_lossy().into_owned();
+        if !filters.is_empty() && !filters.iter().any(|filter| name.contains(filter.as_str())) {
+            continue;
+        }
+        
+        let source = fs::read_to_string(&script).unwrap();
+        let mut times: Vec<Duration> = (0..RUNS).map(|_| run(&name, &source)).collect();
+        times.sort();
+        println!("{:<20} fastest {:>8.1?}   median {:>8.1?}", name, times[0], times[RUNS / 2]);
+    }
+}
+
+fn scripts(directory: &Path) -> Vec<PathBuf> {
+    let mut scripts: Vec<PathBuf> = fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "js"))
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+fn run(name: &str, source: &str) -> Duration {
+    let mut interpreter = Interpreter::new();
+    let start = Instant::now();
+    if let Err(error) = interpreter.eval(source) {
+        panic!("{}: {}", name, error);
+    }
+    start.elapsed()
+}
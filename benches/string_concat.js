@@ -0,0 +1,9 @@
+// Builds a large string one chunk at a time.
+// Run with: cargo bench -- string_concat
+var s = "";
+var i = 0;
+while (i < 200000) {
+    s = s + "chunk";
+    i = i + 1;
+}
+s == "";
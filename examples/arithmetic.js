@@ -0,0 +1,5 @@
+// Operator precedence and numeric results
+var a = 1 + 2 * 3;
+var b = (1 + 2) * 3;
+var c = 10 / 4 - -1;
+a + b + c;
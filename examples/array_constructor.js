@@ -0,0 +1,13 @@
+console.log(Array.isArray([1, 2]), Array.isArray({ length: 0 }), Array.isArray("ab"));
+console.log(Array(3), Array(3).length, Array("3"), Array(1, 2), Array());
+console.log(Array.of(7), Array.of(1, 2, 3), Array.of());
+console.log(Array.from("héllo"), Array.from([1, 2, 3], function (x, i) { return x * 10 + i; }));
+console.log(Array.from(Set([1, 1, 2])), Array.from(Map([["a", 1]])));
+console.log(Array.from({ length: 3, 0: "a", 2: "c" }), Array.from(5));
+console.log(Array.from({ length: 2 }, function (_, i) { return i * i; }));
+try {
+    Array(-1);
+} catch (error) {
+    console.log(error.name, error.message);
+}
+Array.from(null);
@@ -0,0 +1,14 @@
+// requires: json, assert
+// Passing assertions print nothing
+assert(1 < 2);
+assert.ok("non-empty", "strings are truthy");
+assert.equal(0 / 0, 0 / 0);
+assert.equal(Math, Math);
+assert.deepEqual(JSON.parse("{}"), JSON.parse("{}"));
+assert.throws(() => String.fromCodePoint(-1));
+console.log(assert);
+console.log("all passed");
+
+// The first failing one throws an AssertionError
+assert.equal(1, "1");
+console.log("not reached");
@@ -0,0 +1,45 @@
+// requires: timers, async
+// An async function runs until its first await and then lets its caller
+// carry on; the rest of it runs from a microtask
+async function greet(name) {
+    console.log("greeting", name);
+    let greeting = await Promise.resolve("hello " + name);
+    console.log(greeting);
+    return greeting.length;
+}
+greet("jay").then((length) => console.log("length", length));
+console.log("after the call");
+
+// Awaiting a rejected promise throws inside the function
+async function attempt() {
+    try {
+        await Promise.reject("refused");
+    } catch (reason) {
+        console.log("caught", reason);
+    } finally {
+        console.log("finally");
+    }
+}
+attempt();
+
+// A throw rejects the function's promise
+const failing = async () => {
+    null.missing;
+};
+failing().catch((error) => console.log(error.name, error.message));
+
+// Timers resolve promises made with the Promise function
+function sleep(ms, value) {
+    return Promise((resolve) => setTimeout(() => resolve(value), ms));
+}
+async function sequence() {
+    let total = 0;
+    for (let i = 1; i <= 3; i = i + 1) {
+        total = total + await sleep(10, i);
+    }
+    console.log("total", total);
+}
+sequence();
+
+Promise.resolve(1).then((value) => value + 1).finally(() => console.log("settled")).then((value) => console.log("chained", value));
+console.log(Promise.resolve("ready"), Promise.reject("no").catch(() => 0), Promise(() => {}));
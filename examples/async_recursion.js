@@ -0,0 +1,15 @@
+// requires: async
+// Async functions run on smaller stacks than the main thread, and recursion
+// that outgrows one throws instead of crashing
+async function deep() {
+    function down(n) { if (n == 0) { return 0; } return 1 + down(n - 1); }
+    console.log("depth", down(100));
+    function runaway() { return 1 + runaway(); }
+    try {
+        runaway();
+    } catch (error) {
+        console.log("caught", error.message);
+    }
+    return "done";
+}
+deep().then(function(value) { console.log(value); });
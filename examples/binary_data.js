@@ -0,0 +1,36 @@
+// Typed arrays and data views share the bytes of an ArrayBuffer
+let buffer = new ArrayBuffer(8);
+let bytes = new Uint8Array(buffer);
+let words = new Int32Array(buffer, 4);
+words[0] = -2;
+console.log(bytes, words, words.byteOffset, words.buffer == buffer, bytes.buffer == words.buffer);
+console.log(buffer, buffer.byteLength, ArrayBuffer.isView(bytes), ArrayBuffer.isView(buffer));
+
+let floats = new Float64Array([1.5, -0.25, NaN]);
+console.log(floats, floats.byteLength, Float64Array.BYTES_PER_ELEMENT, new Float32Array([0.1])[0]);
+console.log(new Int8Array([127, 128, 255]), new Uint16Array([65536, -1]), new Uint32Array([-1]), new Int16Array(2));
+
+// Views onto part of an array see its changes
+let middle = bytes.subarray(1, -4);
+middle[0] = 42;
+console.log(middle, bytes[1], bytes.subarray(-2), new Uint8Array(buffer.slice(4, 6)));
+
+let view = new DataView(buffer, 2);
+view.setUint16(0, 258);
+view.setFloat32(2, 1.5, true);
+console.log(view.getUint16(0), view.getUint16(0, true), view.getFloat32(2, true), view.getInt8(5));
+console.log(view);
+for (let n of new Int32Array([3, 4])) {
+    console.log(n);
+}
+try {
+    new Int32Array(buffer, 1);
+} catch (error) {
+    console.log(error.name, error.message);
+}
+try {
+    new Uint8Array(-1);
+} catch (error) {
+    console.log(error.name, error.message);
+}
+view.getFloat64(0);
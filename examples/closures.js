@@ -0,0 +1,13 @@
+// Functions capture the environment they were created in
+function counter() {
+    var count = 0;
+    return function() {
+        count = count + 1;
+        return count;
+    };
+}
+
+var next = counter();
+next();
+next();
+next();
@@ -0,0 +1,30 @@
+// Maps take keys of any type, compared by value for primitives and by
+// identity for objects
+let key = { id: 1 };
+let map = new Map([["a", 1], [2, "two"]]);
+map.set(key, "object").set(0 / 0, "not a number");
+console.log(map.size, map.get("a"), map.get(2), map.get("2"));
+console.log(map.get(key), map.get({ id: 1 }), map.get(0 / 0));
+console.log(map.has(key), map.delete(key), map.has(key), map.size);
+console.log(map);
+
+// Sets keep the first occurrence of each value
+let set = new Set([3, 1, 3, 2, 1]);
+set.add(0).add(-0);
+console.log(set.size, set.has(2), set.has(5), set);
+
+// Iteration is live, in insertion order
+for (const entry of map) {
+    console.log(entry[0], entry[1]);
+}
+for (const value of set) {
+    if (value == 1) {
+        set.delete(2);
+        set.add(4);
+    }
+    console.log(value);
+}
+set.forEach((value) => console.log("each", value));
+console.log(Array.from(map.keys()), Array.from(set.values()));
+map.clear();
+console.log(map);
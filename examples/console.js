@@ -0,0 +1,11 @@
+console.log("log", 1, true);
+console.info("info", [1, 2], { a: 1 });
+console.debug("debug");
+console.warn("warn", null);
+console.error("error", void 0);
+console.log();
+
+// console.memory is measured again each time it is read
+let before = console.memory;
+let kept = [{ a: 1 }];
+console.log(console.memory.objects - before.objects, console.memory == console.memory, before.bytes > 0);
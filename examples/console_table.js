@@ -0,0 +1,5 @@
+console.table([{ name: "ada", year: 1815 }, { name: "grace", year: 1906, navy: true }]);
+console.table({ first: { x: 1 }, second: 2 });
+console.table([{ a: 1, b: 2, c: 3 }], ["c", "a"]);
+console.table([]);
+console.table("not tabular");
@@ -0,0 +1,11 @@
+console.count();
+console.count("loop");
+console.count("loop");
+console.count();
+console.countReset("loop");
+console.count("loop");
+console.countReset("missing");
+console.time("setup");
+console.time("setup");
+console.timeLog("missing");
+console.timeEnd("missing");
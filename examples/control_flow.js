@@ -0,0 +1,16 @@
+// Conditionals, loops and the short-circuiting `and` and `or` operators
+var i = 0;
+var even = true;
+var evens = 0;
+while (i < 10) {
+    if (even) {
+        evens = evens + 1;
+    } else {
+        evens = evens;
+    }
+    even = !even;
+    i = i + 1;
+}
+
+var fallback = null or "default";
+evens == 5 and fallback;
@@ -0,0 +1,19 @@
+// Object patterns in declarations take each name from a property
+let user = { name: "jay", role: "admin", address: { city: "Oslo" } };
+var { name, role: title, age = 30 } = user;
+console.log(name, title, age);
+
+// Patterns nest, and defaults apply only to undefined properties
+const { address: { city, zip = "none" }, missing = name + "!" } = user;
+let { length } = "hello";
+console.log(city, zip, missing, length);
+
+// Parameters can be destructured too, in functions and arrows
+function describe({ name, role = "guest" }, suffix) {
+    return name + " (" + role + ")" + suffix;
+}
+let shout = ({ name }) => name + "!";
+console.log(describe(user, "."), describe({ name: "ann" }, ""), shout(user));
+
+// Destructuring null or undefined is a TypeError
+let { value } = null;
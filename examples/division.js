@@ -0,0 +1,7 @@
+// Division and remainder follow IEEE 754 like JavaScript
+let zero = 0;
+let negativeZero = -zero;
+let divisions = "" + 1 / zero + " " + -1 / zero + " " + 1 / negativeZero + " " + zero / zero;
+let remainders = 7 % 3 + " " + -7 % 3 + " " + 7 % -3 + " " + 5.5 % 2 + " " + 1 % zero;
+let signs = 1 / (-4 % 2) + " " + 1 / (zero * -1);
+divisions + " | " + remainders + " | " + signs;
@@ -0,0 +1,29 @@
+// Error constructors also make an error when called without new, as in
+// JavaScript
+let error = new TypeError("expected a number");
+console.log(error.name, error.message, Error().message == "");
+console.log(Object.getPrototypeOf(error) == TypeError.prototype, Object.getPrototypeOf(TypeError.prototype) == Error.prototype);
+
+// Errors record where they were created, and print as their stack
+function validate(value) {
+    let problem = null;
+    if (value < 0) {
+        problem = new RangeError("negative: " + value);
+    }
+    return problem;
+}
+console.log(validate(-1));
+console.log(new Error("wrapped", { cause: error }).cause.message);
+
+// Errors raised by the engine and the built-ins are caught as the same
+// kinds of objects
+let failures = [function () { null.value; }, function () { missing; }, function () { (1).toFixed(101); }];
+for (const fail of failures) {
+    try {
+        fail();
+    } catch (caught) {
+        console.log(caught.name, Object.getPrototypeOf(Object.getPrototypeOf(caught)) == Error.prototype, caught.message);
+    }
+}
+
+throw new SyntaxError("unexpected token");
@@ -0,0 +1,70 @@
+// throw raises any value, and catch receives it as it was thrown
+try {
+    throw { code: 42 };
+} catch (error) {
+    console.log("caught", error.code);
+}
+
+// Errors raised by the engine are caught as error objects
+try {
+    let nothing = null;
+    nothing.value;
+} catch ({ name, message }) {
+    console.log(name + ": " + message);
+}
+
+// finally runs however the block ends, and a return in it wins
+let steps = "";
+function attempt(fail) {
+    try {
+        steps = steps + "try ";
+        if (fail) {
+            throw "oops";
+        }
+        return "done";
+    } catch (error) {
+        steps = steps + "catch ";
+        return "failed with " + error;
+    } finally {
+        steps = steps + "finally ";
+    }
+}
+console.log(attempt(false), attempt(true), steps);
+
+function overridden() {
+    try {
+        throw "lost";
+    } finally {
+        return "finally wins";
+    }
+}
+console.log(overridden());
+
+// Calls in return position still throw inside the try block
+let thrower = function () { throw "from a tail call"; };
+function wrapped() {
+    try {
+        return thrower();
+    } catch (error) {
+        return "caught " + error;
+    }
+}
+console.log(wrapped());
+
+// break leaves the loop after the finally block runs
+for (let i = 0; i < 5; i = i + 1) {
+    try {
+        if (i == 2) {
+            break;
+        }
+    } finally {
+        console.log("finally", i);
+    }
+}
+
+// Exceptions nobody catches end the script
+try {
+    throw "rethrown";
+} catch {
+    throw "uncaught";
+}
@@ -0,0 +1,14 @@
+// requires: math
+// ** is right-associative and binds tighter than * and /
+console.log(2 ** 10, 2 ** 3 ** 2, (2 ** 3) ** 2, 3 * 2 ** 2, 2 ** -1);
+console.log((-2) ** 3, 4 ** 0.5, 0 ** 0, 1 ** (1 / 0), 2 ** (0 / 0), Math.pow(1, 1 / 0));
+
+// **= raises a variable or property in place, evaluating its target once
+let n = 3;
+n **= 2;
+let reads = 0;
+let box = { size: 2 };
+let target = function () { reads = reads + 1; return box; };
+target().size **= 3;
+target()["size"] **= 0.5;
+console.log(n, box.size, reads);
@@ -0,0 +1,19 @@
+// requires: timers
+// A let in a for head is a fresh binding on every iteration, so each
+// closure sees the value of its own iteration
+for (let i = 0; i < 3; i = i + 1) {
+    setTimeout(() => console.log("let", i), 0);
+}
+
+// A var is shared by every iteration
+for (var j = 0; j < 3; j = j + 1) {
+    setTimeout(() => console.log("var", j), 0);
+}
+
+// Updating the variable in the body carries over to the next iteration
+let total = 0;
+for (let k = 1; k < 10; k = k + 1) {
+    total = total + k;
+    k = k + 1;
+}
+total;
@@ -0,0 +1,28 @@
+// requires: json
+// for-of walks the elements of an array and the characters of a string
+let total = 0;
+for (const n of [1, 2, 3, 4]) {
+    total = total + n;
+}
+console.log(total);
+
+let reversed = "";
+for (let c of "héllo") {
+    reversed = c + reversed;
+}
+console.log(reversed);
+
+// Each iteration gets its own binding, so closures keep their value
+let getters = [];
+for (const name of ["a", "b", "c"]) {
+    getters[getters.length] = () => name;
+}
+console.log(getters[0]() + getters[1]() + getters[2]());
+
+// Without a declaration the loop assigns to an existing variable
+let last;
+for (last of [7, 8, 9]) {}
+console.log(last, [1, [2, 3], "four"], JSON.stringify([1, [2, null]]));
+
+// Numbers are not iterable
+for (const digit of 42) {}
@@ -0,0 +1,18 @@
+// requires: timers
+// Functions convert to their source text in string concatenation and with
+// toString
+function add(a, b) {
+    return a + b;
+}
+
+let double = function (x) { return x * 2; };
+
+function makeCounter() {
+    let count = 0;
+    return function () { count = count + 1; return count; };
+}
+
+console.log(add.toString());
+console.log(makeCounter().toString(), parseInt.toString());
+
+"" + add + " | " + double + " | " + makeCounter() + " | " + setTimeout;
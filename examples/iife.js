@@ -0,0 +1,24 @@
+// Immediately invoked function expressions and arrow functions
+let counter = (function () {
+    let count = 0;
+    return function () { count = count + 1; return count; };
+})();
+counter();
+
+let greeting;
+(function (name) { greeting = "hello " + name; })("world");
+
+// A named function expression can call itself; the name is not visible outside
+let factorial = (function fact(n) { if (n < 2) { return 1; } return n * fact(n - 1); }(5));
+let fact = "outer";
+console.log(factorial, fact);
+
+let square = x => x * x;
+let add = (a, b) => a + b;
+let none = () => "no parameters";
+let nested = (a => b => a - b)(10)(3);
+
+(() => {
+    let result = counter() + square(3) + add(1, 2) + nested;
+    return "" + result + " " + none() + " " + greeting;
+})();
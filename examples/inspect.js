@@ -0,0 +1,23 @@
+// requires: math, json
+// console.log prints objects like Node's util.inspect
+let q = String.fromCharCode(34);
+function quoted(text) {
+    return q + text + q;
+}
+
+console.log("top-level strings are not quoted", 1, -0, true, null);
+console.log(JSON.parse("{}"), JSON.parse("{" + quoted("name") + ":" + quoted("jay") + "," + quoted("my key") + ":1}"));
+console.log(JSON.parse("{" + quoted("a") + ":{" + quoted("b") + ":{" + quoted("c") + ":{" + quoted("d") + ":1}}}}"));
+function greet() {}
+console.log(greet, () => 1, Math.max);
+
+let wide = "{";
+let i = 0;
+while (i < 8) {
+    if (i > 0) {
+        wide = wide + ",";
+    }
+    wide = wide + quoted("property" + i) + ":" + quoted("value " + i);
+    i = i + 1;
+}
+console.log(JSON.parse(wide + "}"));
@@ -0,0 +1,16 @@
+// requires: timers
+// An interval runs until it is cleared, even from its own callback
+let ticks = 0;
+let interval = setInterval(function (label) {
+    ticks = ticks + 1;
+    console.log(label, ticks);
+    if (ticks == 3) {
+        clearInterval(interval);
+    }
+}, 10, "tick");
+setTimeout(() => console.log("timeout between ticks"), 15);
+
+// clearTimeout and clearInterval share ids
+let other = setInterval(() => console.log("never"), 1);
+clearTimeout(other);
+console.log("script done");
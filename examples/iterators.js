@@ -0,0 +1,58 @@
+// Built-in iterables and script-defined ones share the iteration protocol
+let map = Map([["a", 1], ["b", 2]]);
+let set = Set([1, 2, 2, 3]);
+for (let key of map.keys()) {
+    console.log(key);
+}
+console.log(map.values(), map.entries().next(), set.entries().next());
+
+let iterator = [1, 2][Symbol.iterator]();
+console.log(iterator.next(), iterator.next(), iterator.next(), iterator[Symbol.iterator]() == iterator);
+console.log("héllo"[Symbol.iterator]().next().value, Symbol.iterator);
+
+// An object becomes iterable by defining the method itself
+let range = {
+    [Symbol.iterator]() {
+        let i = 0;
+        return {
+            next() {
+                i = i + 1;
+                return { value: i, done: i > 3 };
+            },
+        };
+    },
+};
+for (let n of range) {
+    console.log(n);
+}
+console.log(Array.from(range), Set(range), Array.from(map.keys()));
+
+// which also overrides the built-in one
+let array = [1, 2];
+array[Symbol.iterator] = function () {
+    return ["replaced"][Symbol.iterator]();
+};
+for (let item of array) {
+    console.log(item);
+}
+
+// An iterator picks up where an earlier loop left it
+let values = set.values();
+for (let value of values) {
+    if (value == 2) {
+        break;
+    }
+}
+for (let value of values) {
+    console.log("rest", value);
+}
+
+for (let item of Proxy([7, 8], {})) {
+    console.log("proxied", item);
+}
+try {
+    for (let item of { [Symbol.iterator]: 1 }) {}
+} catch (error) {
+    console.log(error.message);
+}
+for (let item of { [Symbol.iterator]() { return 1; } }) {}
@@ -0,0 +1,47 @@
+// var belongs to the whole function and can be used before its
+// declaration runs; let and const belong to their block
+function scopes() {
+    console.log("before", early);
+    if (true) {
+        var early = 1;
+        let inner = 2;
+        console.log("inside", early, inner);
+    }
+    console.log("after", early);
+    try {
+        console.log(inner);
+    } catch (error) {
+        console.log(error.name, error.message);
+    }
+}
+scopes();
+
+// A const cannot be assigned again
+const limit = 10;
+try {
+    limit = 11;
+} catch (error) {
+    console.log(error.name, error.message);
+}
+console.log("limit", limit);
+
+// A let or const shadows outer names from the start of its block, and
+// using it before the declaration is an error
+let shadowed = "outer";
+{
+    try {
+        console.log(shadowed);
+    } catch (error) {
+        console.log(error.name, error.message);
+    }
+    let shadowed = "inner";
+    console.log(shadowed);
+}
+console.log(shadowed);
+
+// Each iteration of a let loop has its own binding
+let readers = [];
+for (let i = 0; i < 3; i = i + 1) {
+    readers[i] = () => i;
+}
+console.log(readers[0](), readers[1](), readers[2]());
@@ -0,0 +1,11 @@
+// Locale-aware formatting and comparison, in en-US unless a locale is given
+console.log((1234567.891).toLocaleString());
+console.log((1234567.891).toLocaleString("de-DE"));
+console.log((-1000).toLocaleString("fr"));
+console.log((0.12345).toLocaleString(), (999).toLocaleString(), (1000).toLocaleString());
+console.log((1 / 0).toLocaleString(), (0 / 0).toLocaleString());
+
+// Case and accents only break ties between otherwise equal strings
+console.log("a".localeCompare("b"), "b".localeCompare("a"), "a".localeCompare("a"));
+console.log("a".localeCompare("B"), "a".localeCompare("A"), "résumé".localeCompare("resume"));
+console.log("éclair".localeCompare("ezra"));
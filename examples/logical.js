@@ -0,0 +1,16 @@
+// && and || return one of their operands and skip the right one when the
+// left decides the result
+function loud(value) {
+    console.log("evaluated", value);
+    return value;
+}
+
+console.log(1 && 2, 0 && 2, 0 || "fallback", "first" || "second");
+console.log(false && loud("skipped"), true || loud("skipped"));
+console.log(true && loud("needed"));
+
+// && binds tighter than ||, as do the word forms
+console.log(true || false && false, (true || false) && false, 1 and 0 or 5);
+
+// ?: runs only the branch its condition picks, and nests to the right
+console.log(1 ? "yes" : loud("skipped"), 0 ? loud("skipped") : "no", -1 > 0 ? "positive" : -1 < 0 ? "negative" : "zero");
@@ -0,0 +1,16 @@
+// requires: math
+// Rounding and signs
+console.log(Math.trunc(-4.7), Math.sign(-3), Math.sign(0), Math.sign(-0), Math.round(2.5), Math.round(-2.5));
+console.log(Math.round(-0.4), Math.round(-0.5), Math.round(-0), Math.round(0.4), Math.round(0.49999999999999994), Math.round(-0.6));
+console.log(Math.abs(-2), Math.floor(1.9), Math.ceil(1.1), Math.min(3, 1, 2), Math.max(), Math.min(1, 0 / 0));
+
+// Powers, roots and logarithms
+console.log(Math.pow(2, 10), Math.sqrt(16), Math.cbrt(27), Math.hypot(3, 4), Math.hypot(0 / 0, 1 / 0));
+console.log(Math.exp(0), Math.log(Math.E), Math.log2(8), Math.log10(1000), Math.log1p(0), Math.expm1(0));
+
+// Trigonometry
+console.log(Math.sin(0), Math.cos(Math.PI), Math.atan2(1, 1) * 4 == Math.PI, Math.tanh(0));
+
+// 32-bit integer helpers
+console.log(Math.clz32(1), Math.clz32(0), Math.imul(0xffffffff, 5), Math.fround(5.5));
+console.log(Math.SQRT2, Math.LN2, Math.LOG10E);
@@ -0,0 +1,15 @@
+// requires: math, json
+// Methods on built-in objects, and `this` in plain and arrow functions
+console.log("max", Math.max(3, 9, 4), "floor", Math.floor(2.7));
+console.log(JSON.stringify(Math));
+
+function plain() {
+    return this;
+}
+
+function outer() {
+    let arrow = () => this;
+    return arrow();
+}
+
+console.log(plain(), outer());
@@ -0,0 +1,17 @@
+// requires: timers
+// Microtasks run as soon as the script or the current task finishes, in
+// the order they were queued and before any timer
+setTimeout(() => console.log("timeout 1"), 0);
+queueMicrotask(() => {
+    console.log("microtask 1");
+    queueMicrotask(() => console.log("microtask 3, queued by microtask 1"));
+});
+queueMicrotask(() => console.log("microtask 2"));
+
+// Microtasks queued by a timer callback run before the next timer
+setTimeout(() => {
+    console.log("timeout 2");
+    queueMicrotask(() => console.log("microtask from timeout 2"));
+}, 0);
+setTimeout(() => console.log("timeout 3"), 0);
+console.log("script done");
@@ -0,0 +1,30 @@
+// `new` runs a function with `this` bound to a fresh object, which is the
+// result unless the function returns an object of its own
+function Point(x, y) {
+    this.x = x;
+    this.y = y;
+}
+function Wrapped(value) {
+    this.ignored = true;
+    return { value: value };
+}
+function Plain() {
+    return 5;
+}
+let shapes = { Point: Point };
+console.log(new Point(1, 2), new Wrapped(3), new Plain(), new shapes.Point(4).x, new shapes["Point"](5, 6).y);
+
+// Built-in constructors, which may also be used without arguments
+console.log(new Map, new Set([1, 2]).size, new Error("made").message, new Map().set("a", 1).get("a"));
+
+// Arrow functions, async functions and values that are not functions are
+// not constructors
+async function task() {}
+let values = [() => 1, task, 5, undefined];
+for (const value of values) {
+    try {
+        new value();
+    } catch (error) {
+        console.log(error.name);
+    }
+}
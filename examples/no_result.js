@@ -0,0 +1,2 @@
+// A script ending in a declaration has no result to print
+var x = 42;
@@ -0,0 +1,16 @@
+// ?? falls back only for null and undefined, unlike ||
+let settings = { retries: 0, name: "", timeout: null };
+console.log(settings.retries ?? 3, settings.retries || 3);
+console.log(settings.name ?? "default", settings.name || "default");
+console.log(settings.timeout ?? 1000, settings.missing ?? "none");
+
+// The right operand is not evaluated when the left one is kept
+let calls = 0;
+let fallback = function () { calls = calls + 1; return "fallback"; };
+console.log(settings.retries ?? fallback(), null ?? fallback(), calls);
+
+// It binds more loosely than the other operators and pairs with ?.
+console.log(null ?? 1 + 2, settings.options?.depth ?? "shallow");
+
+// Mixing it with && or || takes parentheses
+console.log((null || 0) ?? 5, null ?? (0 || 7), (1 && null) ?? "none");
@@ -0,0 +1,2 @@
+// ?? cannot be mixed with || without parentheses
+let port = null ?? 0 || 8080;
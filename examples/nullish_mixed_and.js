@@ -0,0 +1,2 @@
+// or with && on its left
+let ready = true && null ?? false;
@@ -0,0 +1,9 @@
+// toFixed and friends round halfway cases up, judged on the exact value of
+// the double: 1.005 is really 1.00499999999999989...
+console.log((1.005).toFixed(2), (2.5).toFixed(0), (-1.5).toFixed(0), (0).toFixed(2));
+console.log((123456).toExponential(2), (0.00015).toExponential(), (1.5).toExponential(0));
+console.log((123.456).toPrecision(4), (0.000123).toPrecision(2), (0.0000001234).toPrecision(2), (99.99).toPrecision(3));
+console.log((255).toString(16), (-10.25).toString(8), (0.1).toString(2));
+
+// Out-of-range arguments are RangeErrors
+(1).toFixed(101);
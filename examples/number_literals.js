@@ -0,0 +1,8 @@
+// Hex, octal and binary integers, exponents and numeric separators
+console.log(0xFF, 0Xff, 0o755, 0b1010);
+console.log(1e9, 1.5e-3, 2E+2, 5e0);
+console.log(1_000_000, 0x1_00, 0b1111_0000, 3.141_592);
+console.log(0xFFFFFFFFFFFFFFFFFF);
+
+// A fraction may be empty
+console.log(1., 2.50);
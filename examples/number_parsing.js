@@ -0,0 +1,19 @@
+// parseInt reads digits until the first that does not belong
+console.log(parseInt("42px"), parseInt("  -17"), parseInt("0x1F"), parseInt("ff", 16), parseInt("101", 2));
+console.log(parseInt("z", 36), parseInt("12", 1), parseInt(""), parseInt("0.9"), parseInt("1e3"));
+
+// parseFloat takes the longest decimal prefix
+console.log(parseFloat("3.14abc"), parseFloat("  -.5"), parseFloat("1e3x"), parseFloat("1e"), parseFloat("-Infinity!"), parseFloat("x1"));
+
+// Number converts the whole string, or NaN
+console.log(Number("12"), Number(" 12 "), Number(""), Number("12px"), Number("0x10"), Number("0b11"), Number("-Infinity"), Number("inf"));
+console.log(Number(true), Number(null), Number(), Number({ valueOf: () => 7 }), +"1_000");
+console.log(Number(undefined), isNaN(undefined), parseInt(undefined), undefined);
+
+// The global isNaN and isFinite convert their argument; Number's do not
+console.log(isNaN("abc"), isNaN("12"), Number.isNaN("abc"), Number.isNaN(NaN));
+console.log(isFinite("12"), Number.isFinite("12"), isFinite(Infinity), Number.isInteger(5), Number.isInteger(5.5));
+console.log(Number.isSafeInteger(Number.MAX_SAFE_INTEGER), Number.isSafeInteger(Number.MAX_SAFE_INTEGER + 1), Number.EPSILON > 0);
+
+// Formatting methods
+console.log((3.14159).toFixed(2), (1234.5).toPrecision(2), (255).toString(16));
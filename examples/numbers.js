@@ -0,0 +1,7 @@
+// Numbers print the way JavaScript prints them
+let billion = 1000000000;
+let large = "" + billion * billion * 1000 + " " + billion * billion * 100 + " " + billion * billion * billion * 2;
+let small = 1 / billion / 1000 + " " + 0.000001 + " " + 0.0000001;
+let fractions = 0.1 + 0.2 + " " + 1 / 3 + " " + -2.5;
+let special = 1 / 0 + " " + -1 * 0;
+large + " | " + small + " | " + fractions + " | " + special;
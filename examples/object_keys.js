@@ -0,0 +1,10 @@
+// Object.keys lists array index keys first in ascending order, then the
+// other keys in the order they were added
+let object = { zebra: 1, 10: 2, apple: 3 };
+object["2"] = 4;
+object["02"] = 5;
+object.zebra = 6;
+console.log(Object.keys(object));
+console.log(Object.keys([7, 8, 9]));
+console.log(Object.keys({}));
+Object.keys(object).length;
@@ -0,0 +1,34 @@
+// requires: async
+// Shorthand properties take the value of the variable of the same name
+let x = 1;
+let y = 2;
+let point = { x, y, z: 3 };
+console.log(point);
+
+// Computed keys are evaluated in order with the values
+let prefix = "key";
+let count = 0;
+let computed = { [prefix + 1]: "first", [prefix + (count = count + 2)]: count, [3 * 3]: "nine" };
+console.log(computed);
+
+// Methods are ordinary functions, so `this` is the object they are called on
+let counter = {
+    value: 10,
+    increment(by) {
+        this.value = this.value + by;
+        return this;
+    },
+    ["dec" + "rement"]() {
+        this.value = this.value - 1;
+        return this;
+    },
+    async later() {
+        return this.value;
+    },
+};
+console.log(counter.increment(5).decrement().value);
+counter.later().then((value) => console.log("later", value));
+
+// async is still an ordinary key
+let flags = { async: true, get: 1 };
+console.log(flags);
@@ -0,0 +1,20 @@
+// Object literals, dot and bracket access, and assignment to properties
+let point = { x: 1, y: 2, "label": "origin", 0: "first" };
+console.log(point.x, point["y"], point.label, point[0], point.missing);
+
+let axis = "x";
+point[axis] = point[axis] + 10;
+point.z = 3;
+point.nested = { depth: 1 };
+point.nested.depth = point.nested.depth + 1;
+console.log(point);
+
+// Methods read the object they were called on, however they are reached
+let counter = { count: 0, increment: function () { this.count = this.count + 1; return this.count; } };
+counter.increment();
+counter["increment"]();
+console.log(counter.count);
+
+// Reading or setting a property of null is a TypeError
+let nothing = null;
+nothing.value = 1;
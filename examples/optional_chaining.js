@@ -0,0 +1,14 @@
+// Optional chaining reads through null and undefined as undefined
+let config = { server: { port: 8080, start: function () { return "listening on " + this.port; } }, tags: null };
+console.log(config.server?.port, config.client?.port, config.tags?.[0], config?.["server"].port);
+
+// Calls are skipped when the function, or the object it is read from, is missing
+console.log(config.server.start?.(), config.server.stop?.(), config.client?.start());
+
+// The whole rest of the chain is skipped, not just the next access
+let calls = 0;
+let count = function () { calls = calls + 1; return calls; };
+console.log(config.client?.settings.deep.value, config.client?.[count()], calls);
+
+// Parentheses end the chain, so reading past them can still fail
+(config.client?.settings).value;
@@ -0,0 +1,17 @@
+// requires: performance
+// Marks record when a phase was reached and measures the time between two
+// marks or timestamps; the interpreter keeps every entry for the host
+performance.mark("start");
+let sum = 0;
+for (let i = 0; i < 1000; i = i + 1) {
+    sum = sum + i;
+}
+performance.mark("summed");
+
+let phase = performance.measure("sum", "start", "summed");
+console.log(phase.name, phase.entryType, phase.duration >= 0);
+console.log(performance.measure("fixed", 10, 25));
+console.log(performance.getEntriesByType("mark").length, performance.getEntriesByType("measure").length);
+
+// Measuring from a mark that was never made is an error
+performance.measure("broken", "finish");
@@ -0,0 +1,10 @@
+// requires: json
+// Properties keep the order they were added in, except that array index
+// keys come first in ascending order
+let q = String.fromCharCode(34);
+function key(name, value) {
+    return q + name + q + ":" + value;
+}
+
+let text = "{" + key("zebra", 1) + "," + key("10", 2) + "," + key("apple", 3) + "," + key("2", 4) + "," + key("02", 5) + "," + key("zebra", 6) + "}";
+JSON.stringify(JSON.parse(text));
@@ -0,0 +1,20 @@
+// Properties missing from an object are looked up along its prototype chain
+let animal = { kind: "pet", describe: function () { return this.name + " is a " + this.kind; } };
+let dog = Object.create(animal);
+dog.name = "Rex";
+console.log(dog.describe(), dog.kind);
+
+// Own properties shadow inherited ones, and writes never reach the prototype
+let puppy = Object.create(dog);
+puppy.kind = "young pet";
+console.log(puppy.describe(), dog.kind, animal.kind);
+console.log(Object.getPrototypeOf(puppy) == dog, Object.getPrototypeOf(dog) == animal);
+
+// Objects made from null, and object literals, have no prototype
+console.log(Object.getPrototypeOf(Object.create(null)), Object.getPrototypeOf({}));
+
+Object.setPrototypeOf(animal, { legs: 4 });
+console.log(puppy.legs);
+
+// A prototype chain cannot loop
+Object.setPrototypeOf(animal, puppy);
@@ -0,0 +1,52 @@
+let target = { name: "jay", age: 3 };
+let handler = {
+    get: function (object, key, receiver) {
+        if (key == "secret") {
+            return "hidden";
+        }
+        return Reflect.get(object, key);
+    },
+    set: function (object, key, value) {
+        if (key == "age" && value < 0) {
+            return false;
+        }
+        return Reflect.set(object, key, value * 2);
+    },
+    has: function (object, key) {
+        return key != "age" && Reflect.has(object, key);
+    },
+    deleteProperty: function (object, key) {
+        console.log("deleting", key);
+        return Reflect.deleteProperty(object, key);
+    },
+};
+let proxy = new Proxy(target, handler);
+console.log(proxy.name, proxy.secret, proxy["age"], proxy.missing);
+proxy.age = 4;
+console.log(target.age, proxy);
+console.log(Reflect.has(proxy, "name"), Reflect.has(proxy, "age"), Reflect.has(target, "age"));
+console.log(Reflect.deleteProperty(proxy, "name"), target);
+console.log(Reflect.set(proxy, "age", -1), target.age);
+
+// Without traps every operation reaches the target
+let plain = new Proxy([1, 2], {});
+plain[2] = 3;
+console.log(plain.length, plain, Reflect.get(plain, 0));
+
+// Methods read through a proxy are called on the proxy
+let counter = new Proxy({ count: 0, bump: function () { this.count = this.count + 1; return this.count; } }, {});
+counter.bump();
+console.log(counter.bump());
+
+console.log(Reflect.get(new Proxy(new Proxy({ deep: true }, {}), {}), "deep"));
+try {
+    new Proxy(1, {});
+} catch (error) {
+    console.log(error.name, error.message);
+}
+try {
+    Reflect.get("text", "length");
+} catch (error) {
+    console.log(error.name, error.message);
+}
+proxy.age = -5;
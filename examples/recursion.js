@@ -0,0 +1,34 @@
+// Plain recursion, and a tail-recursive loop deep enough to need tail calls
+function fib(n) {
+    if (n < 2) {
+        return n;
+    }
+    return fib(n - 1) + fib(n - 2);
+}
+
+function sum(n, total) {
+    if (n == 0) {
+        return total;
+    }
+    return sum(n - 1, total + n);
+}
+
+// Calls that give the value of &&, ||, ?? and ?: are in tail position too
+function loop(n) {
+    return n > 0 && loop(n - 1);
+}
+
+function countdown(n) {
+    return n == 0 ? "done" : countdown(n - 1);
+}
+
+function either(n) {
+    return n == 0 || either(n - 1);
+}
+
+function fallback(n) {
+    return n == 0 ? "found" : null ?? fallback(n - 1);
+}
+
+console.log(loop(100000), countdown(100000), either(100000), fallback(100000));
+fib(15) + sum(100000, 0);
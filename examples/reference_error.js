@@ -0,0 +1,3 @@
+// Reading an undeclared variable fails at runtime
+var defined = 1;
+defined + undefinedName;
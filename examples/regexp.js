@@ -0,0 +1,39 @@
+// requires: regex
+// Regular expression literals, told apart from division by what precedes them
+let re = /(\d+)-(\d+)/g;
+console.log(re, re.source, re.flags, re.global);
+let first = re.exec("10-20 and 30-40");
+console.log(first[1], first[2], first.index, re.lastIndex);
+console.log(re.exec("10-20 and 30-40").index, re.exec("10-20 and 30-40"));
+let a = 8;
+let b = 2;
+console.log(a / b / 2, (a) / b);
+
+// String methods take regular expressions
+console.log("a1b22c333".match(/\d+/g));
+console.log("x-y".replace(/(\w)-(\w)/, "$2-$1 [$&] $$"));
+console.log("hello world".replace(/o/g, (match, offset) => "<" + match + offset + ">"));
+console.log("2024-01-05".replace(/(?<year>\d+)-(?<month>\d+)-(?<day>\d+)/, "$<day>/$<month>/$<year>"));
+console.log("2024-01-05".match(/(?<year>\d+)-/).groups.year);
+console.log("abc".replace("b", "[$&]"));
+
+// JavaScript syntax is translated for the regex crate
+console.log(/a[/]b/.test("a/b"), /\bfoo\b/i.test("a FOO b"), /^[^]$/.test("x"));
+console.log(RegExp("a+", "i").test("AAA"), "" + /x\/y/);
+
+// Sticky expressions only match at lastIndex
+let sticky = /a/y;
+console.log(sticky.test("ba"), sticky.lastIndex);
+sticky.lastIndex = 1;
+console.log(sticky.test("ba"), sticky.lastIndex);
+
+try {
+    /(a)\1/;
+} catch (e) {
+    console.log(e.name, e.message);
+}
+try {
+    RegExp("a", "gg");
+} catch (e) {
+    console.log(e.message);
+}
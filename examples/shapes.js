@@ -0,0 +1,49 @@
+// Property reads and writes through the same site stay correct while the
+// objects passing through it change shape
+function getX(o) { return o.x; }
+function setX(o, value) { o.x = value; return o.x; }
+
+let a = { x: 1, y: 2 };
+let b = { y: 3, x: 4 };
+let c = { x: 5 };
+console.log(getX(a), getX(b), getX(c), getX(a), getX({}), getX(b));
+console.log(setX(a, 10), setX(b, 11), setX(c, 12), setX({ z: 0 }, 13), a.x, b.x, c.x);
+
+// Adding a property moves an object to a new shape; reads see it
+let grows = { x: 1 };
+console.log(getX(grows));
+grows.y = 2;
+grows.x = 3;
+console.log(getX(grows), grows.y);
+
+// A property found on the prototype, then shadowed by an own one
+let proto = { x: "inherited" };
+let child = Object.create(proto);
+console.log(getX(child), getX(proto));
+child.x = "own";
+console.log(getX(child), getX(proto));
+
+// Deleting a property takes the object off its shape
+let removed = { x: 1, y: 2 };
+console.log(getX(removed));
+Reflect.deleteProperty(removed, "x");
+console.log(getX(removed), removed.y, removed);
+
+// Proxies and arrays go through their own rules
+let proxy = Proxy({ x: 1 }, { get: (target, key) => "trapped " + key });
+console.log(getX(a), getX(proxy));
+let list = [1, 2];
+list.x = "named";
+console.log(getX(list), list.length, setX(list, "renamed"), list);
+
+// Many keys, index keys and the order they are listed in
+let wide = {};
+let i = 0;
+while (i < 70) {
+    wide["k" + i] = i;
+    i = i + 1;
+}
+wide.x = "wide";
+console.log(getX(wide), wide.k0, wide.k69, setX(wide, "wider"));
+let indexed = { x: 1, b: 2, 1: "one", 0: "zero" };
+console.log(getX(indexed), indexed);
@@ -0,0 +1,32 @@
+// Spread in array literals and call arguments walks the iteration protocol
+function sum(a, b, c) {
+    return a + b + c;
+}
+let numbers = [1, 2, 3];
+console.log([0, ...numbers, 4], [..."héllo"], [...new Set([1, 1, 2])]);
+console.log(sum(...numbers), sum(10, ...[20, 30]), sum(...[1], ...numbers));
+console.log([...new Map([["a", 1]]).entries()], [...[]]);
+
+// Script-defined iterables are asked for their values once, in order
+let calls = 0;
+let range = {
+    [Symbol.iterator]() {
+        calls = calls + 1;
+        let i = 0;
+        return {
+            next() {
+                i = i + 1;
+                return { value: i, done: i > 3 };
+            },
+        };
+    },
+};
+console.log([...range], sum(...range), calls);
+
+// Spreading a value without an iterator is an error
+try {
+    sum(...5);
+} catch (error) {
+    console.log(error.message);
+}
+[...numbers, ...numbers].length;
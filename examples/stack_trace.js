@@ -0,0 +1,21 @@
+// requires: json
+// Error.captureStackTrace records where it was called from. A call in
+// return position replaces its caller's frame, so outer never appears.
+function inner() {
+    let error = JSON.parse("{}");
+    Error.captureStackTrace(error);
+    return error;
+}
+
+function outer() {
+    return inner();
+}
+
+let viaTailCall = outer();
+console.log(viaTailCall.stack);
+
+function wrapper() {
+    let error = outer();
+    return error.stack;
+}
+console.log(wrapper());
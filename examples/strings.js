@@ -0,0 +1,5 @@
+// String concatenation and comparison
+var greeting = "Hello";
+var name = "world";
+var message = greeting + ", " + name + "!";
+message == "Hello, world!";
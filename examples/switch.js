@@ -0,0 +1,34 @@
+// A switch runs from the first matching case and falls through the
+// clauses after it until a break; the default clause runs when nothing
+// matches, wherever it is placed
+function describe(x) {
+    let out = "";
+    switch (x) {
+        case 1:
+            out = out + "one ";
+        case 2:
+            out = out + "two ";
+            break;
+        default:
+            out = out + "other ";
+        case 3:
+            out = out + "three";
+    }
+    return out;
+}
+console.log(describe(1), "|", describe(2), "|", describe(3), "|", describe(9));
+
+// break inside a switch ends the switch, not the loop around it
+let visited = "";
+for (const letter of ["a", "b", "stop", "c"]) {
+    switch (letter) {
+        case "stop":
+            break;
+        default:
+            visited = visited + letter;
+    }
+    if (letter == "stop") {
+        break;
+    }
+}
+console.log(visited);
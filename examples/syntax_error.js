@@ -0,0 +1,2 @@
+// A missing closing parenthesis is reported with its position
+var x = (1 + 2;
@@ -0,0 +1,21 @@
+let encoder = new TextEncoder();
+let bytes = encoder.encode("héllo ✓");
+console.log(encoder.encoding, bytes, bytes.length, bytes.byteLength);
+console.log(new TextDecoder().decode(bytes), new TextDecoder("UTF-8").encoding);
+
+// Bytes can be edited in place and read back as text
+bytes[0] = 72;
+bytes[1] = 300;
+bytes[99] = 1;
+console.log(bytes[0], bytes[1], bytes[99], new TextDecoder().decode(bytes));
+console.log(new Uint8Array(3), new Uint8Array([1, -1, 256, 3.7, NaN]), Array.from(new Uint8Array([5, 6])));
+
+// Malformed input is replaced, or rejected when decoding is fatal
+let broken = new Uint8Array([104, 105, 255]);
+console.log(new TextDecoder().decode(broken), new TextDecoder().decode(new Uint8Array([239, 187, 191, 111, 107])));
+try {
+    new TextDecoder("utf-8", { fatal: true }).decode(broken);
+} catch (error) {
+    console.log(error.name, error.message);
+}
+new TextDecoder("latin1");
@@ -0,0 +1,7 @@
+// requires: timers
+// Timer callbacks run after the script, earliest first
+setTimeout(function (label) { console.log("second", label); }, 20, "after 20ms");
+let cancelled = setTimeout(function () { console.log("never"); }, 10);
+setTimeout(() => console.log("first"), 0);
+clearTimeout(cancelled);
+console.log("script done");
@@ -0,0 +1,9 @@
+// Objects convert themselves with valueOf and toString in operators
+let price = { amount: 3, valueOf() { return this.amount; } };
+let label = { name: "b", toString() { return this.name; } };
+console.log(price + 1, price > 2, "total: " + price, "[" + label + "]");
+
+// Strings compare by UTF-16 code units; anything else compares as numbers
+console.log("a" < "b", "b" < "a", "a" < "ab", "Z" < "a", label > "a", label <= "a");
+console.log(1 < "2", "10" < "9", 10 < "9", "x" < 1, "x" >= 1, null <= 0, true > false);
+console.log("😀" > "￿", "😀" > "ｚ");
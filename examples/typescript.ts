@@ -0,0 +1,17 @@
+// Type annotations, interfaces and aliases are parsed and then ignored
+interface Point {
+    x: number;
+    y: number;
+}
+
+type Scale = number;
+
+function scale(value: number, factor?: Scale): number {
+    return value * factor;
+}
+
+let double = (x: number): number => x * 2;
+console.log(double(5));
+
+let size: number = scale(3, 4) as number;
+size;
@@ -0,0 +1,10 @@
+// Unary plus converts its operand to a number
+console.log(+"42", +" 7 ", +"", +"abc", +true, +null, +void 0);
+let n = 5;
+console.log(1 + +n, -+n);
+let price = { valueOf: () => 9.5 };
+console.log(+price);
+
+// void evaluates its operand and gives undefined
+let calls = 0;
+console.log(void 0, void (calls = calls + 1), calls);
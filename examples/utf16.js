@@ -0,0 +1,11 @@
+// Strings are measured and indexed in UTF-16 code units
+let word = "naïve";
+let emoji = "a😀b";
+console.log(word.length, emoji.length);
+console.log(word.charAt(2), word.charCodeAt(2), emoji.charCodeAt(1), emoji.charCodeAt(2));
+console.log(emoji.codePointAt(1), emoji.codePointAt(2), emoji.codePointAt(9), word.charCodeAt(9));
+console.log(String.fromCharCode(72, 105, 65601), String.fromCharCode(55357, 56832));
+console.log(String.fromCodePoint(128512, 97).length);
+console.log(word.at(0), word.at(-1), word.at(-5), word.at(5), word.at(-6), emoji.at(-1), emoji.at(1.5).charCodeAt(0));
+console.log(word[0], word["2"], emoji[1].charCodeAt(0), emoji[2].charCodeAt(0), word[5], word["02"], word[-1]);
+String.fromCodePoint(-1);
@@ -0,0 +1,62 @@
+/* include/jays.h */
+/* C interface to the jays engine. Build with `cargo build --release --features ffi`
+   and link against the resulting libjays shared library. */
+#ifndef JAYS_H
+#define JAYS_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct JaysEngine JaysEngine;
+typedef struct JaysValue JaysValue;
+
+/* Value kinds reported by jays_value_type */
+#define JAYS_UNDEFINED 0
+#define JAYS_NULL 1
+#define JAYS_BOOLEAN 2
+#define JAYS_NUMBER 3
+#define JAYS_STRING 4
+#define JAYS_FUNCTION 5
+#define JAYS_OBJECT 6
+
+/* Host function. The arguments are only valid during the call. Return a new
+   value (ownership passes to the engine) or NULL for undefined. */
+typedef JaysValue *(*JaysCallback)(void *user_data, const JaysValue *const *arguments, size_t count);
+
+/* Engines are single-threaded: use each one only from the thread that created it. */
+JaysEngine *jays_engine_new(void);
+void jays_engine_free(JaysEngine *engine);
+
+/* Run a script, or call a function value. Both return NULL on failure; see jays_last_error. */
+JaysValue *jays_eval(JaysEngine *engine, const char *source);
+JaysValue *jays_call(JaysEngine *engine, const JaysValue *function, const JaysValue *const *arguments, size_t count);
+
+/* Message for the last failure, or NULL. Owned by the engine; valid until the next eval or call. */
+const char *jays_last_error(const JaysEngine *engine);
+
+/* Define a global function backed by a C callback. Returns 0, or -1 if name is not UTF-8. */
+int jays_register_fn(JaysEngine *engine, const char *name, JaysCallback callback, void *user_data);
+
+/* Values returned by the functions above are owned by the caller. */
+void jays_value_free(JaysValue *value);
+int jays_value_type(const JaysValue *value);
+double jays_value_as_number(const JaysValue *value);
+int jays_value_as_bool(const JaysValue *value);
+/* Release the result with jays_string_free */
+char *jays_value_to_string(const JaysValue *value);
+void jays_string_free(char *string);
+
+JaysValue *jays_value_undefined(void);
+JaysValue *jays_value_null(void);
+JaysValue *jays_value_bool(int value);
+JaysValue *jays_value_number(double value);
+JaysValue *jays_value_string(const char *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,172 @@
+// jays-derive/src/lib.rs
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, FnArg, ItemFn, LitStr, ReturnType, Type};
+
+// Binding macros for the jays engine, re-exported by its derive feature.
+//
+//     #[js_function]
+//     fn add(a: f64, b: Option<f64>) -> f64 { a + b.unwrap_or(0.0) }
+//
+//     add::register(&mut interpreter); // defines the global `add`
+//
+// Arguments convert with FromValue and the result with IntoValue. Trailing
+// Option parameters are optional; missing required arguments are a
+// TypeError. A function returning Result<T, JsEngineError> fails the call
+// with its error. `#[js_function(name = "addNumbers")]` sets the global name.
+#[proc_macro_attribute]
+pub fn js_function(attribute: TokenStream, item: TokenStream) -> TokenStream {
+    let function = parse_macro_input!(item as ItemFn);
+    
+    let mut name = None;
+    if !attribute.is_empty() {
+        let parser = syn::meta::parser(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"...\"`"))
+            }
+        });
+        parse_macro_input!(attribute with parser);
+    }
+    
+    match expand_function(&function, name) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand_function(function: &ItemFn, name: Option<String>) -> syn::Result<proc_macro2::TokenStream> {
+    let signature = &function.sig;
+    if !signature.generics.params.is_empty() {
+        return Err(Error::new_spanned(&signature.generics, "js_function cannot be generic"));
+    }
+    if let Some(asyncness) = &signature.asyncness {
+        return Err(Error::new_spanned(asyncness, "js_function cannot be async"));
+    }
+    
+    let mut types = Vec::new();
+    for input in &signature.inputs {
+        match input {
+            FnArg::Receiver(receiver) => return Err(Error::new_spanned(receiver, "js_function cannot take self")),
+            FnArg::Typed(parameter) => types.push(&*parameter.ty),
+        }
+    }
+    
+    let ident = &signature.ident;
+    let visibility = &function.vis;
+    let name = name.unwrap_or_else(|| ident.to_string());
+    let required = types.iter().rposition(|ty| !is_option(ty)).map_or(0, |index| index + 1);
+    let arguments = (0..types.len()).map(|index| quote!(::jays::convert::argument(arguments, #index, #name)?));
+    
+    let call = if returns_result(&signature.output) {
+        quote!(super::#ident(#(#arguments),*)?)
+    } else {
+        quote!(super::#ident(#(#arguments),*))
+    };
+    
+    Ok(quote! {
+        #function
+        
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        #visibility mod #ident {
+            pub fn native() -> ::jays::interpreter::Value {
+                ::jays::convert::native(|arguments: &[::jays::interpreter::Value]| {
+                    ::jays::convert::check_arity(arguments, #required, #name)?;
+                    let result = #call;
+                    Ok(::jays::convert::IntoValue::into_value(result))
+                })
+            }
+            
+            pub fn register(interpreter: &mut ::jays::interpreter::Interpreter) {
+                interpreter.define_global(#name, native());
+            }
+        }
+    })
+}
+
+// The struct becomes a plain object with one property per field, and gets a
+// global constructor taking the fields in declaration order. Fields of
+// Option type may be left out.
+#[proc_macro_derive(JsClass)]
+pub fn derive_js_class(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    
+    match expand_class(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand_class(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(Span::call_site(), "JsClass can only be derived for structs"));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new(Span::call_site(), "JsClass requires named fields"));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "JsClass cannot be derived for generic structs"));
+    }
+    
+    let ident = &input.ident;
+    let class = ident.to_string();
+    let fields: Vec<_> = fields.named.iter().collect();
+    let idents: Vec<_> = fields.iter().map(|field| field.ident.as_ref().unwrap()).collect();
+    let names: Vec<String> = idents.iter().map(|ident| ident.to_string()).collect();
+    let indexes = 0..fields.len();
+    let required = fields.iter().rposition(|field| !is_option(&field.ty)).map_or(0, |index| index + 1);
+    
+    Ok(quote! {
+        impl ::jays::convert::IntoValue for #ident {
+            fn into_value(self) -> ::jays::interpreter::Value {
+                ::jays::convert::object(vec![
+                    #((#names, ::jays::convert::IntoValue::into_value(self.#idents))),*
+                ])
+            }
+        }
+        
+        impl ::jays::convert::FromValue for #ident {
+            fn from_value(value: &::jays::interpreter::Value) -> Result<Self, ::jays::error::JsEngineError> {
+                Ok(#ident {
+                    #(#idents: ::jays::convert::property(value, #names, #class)?),*
+                })
+            }
+        }
+        
+        impl ::jays::convert::JsClass for #ident {
+            const NAME: &'static str = #class;
+            
+            fn constructor() -> ::jays::interpreter::Value {
+                ::jays::convert::native(|arguments: &[::jays::interpreter::Value]| {
+                    ::jays::convert::check_arity(arguments, #required, #class)?;
+                    let instance = #ident {
+                        #(#idents: ::jays::convert::argument(arguments, #indexes, #class)?),*
+                    };
+                    Ok(::jays::convert::IntoValue::into_value(instance))
+                })
+            }
+        }
+    })
+}
+
+fn is_option(ty: &Type) -> bool {
+    last_segment(ty).is_some_and(|name| name == "Option")
+}
+
+fn returns_result(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Type(_, ty) => last_segment(ty).is_some_and(|name| name == "Result"),
+        ReturnType::Default => false,
+    }
+}
+
+fn last_segment(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,783 @@
+// src/ast.rs
+use crate::interpreter::GlobalCache;
+use crate::lexer::Span;
+use crate::shape::PropertyCache;
+use std::rc::Rc;
+
+// Syntax tree produced by the parser and consumed by the resolver and the
+// interpreter. Tools that inspect or rewrite programs can build nodes with the
+// constructors below and traverse them with `Visitor` / `VisitorMut` instead of
+// matching every variant by hand.
+//
+// Identifiers carry the span of their name in the source, and functions the
+// span of their whole text. Nodes built by hand use `Span::default()`.
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "cache", derive(serde::Serialize, serde::Deserialize))]
+pub enum Expr {
+    // Literal values
+    Number(f64),
+    String(Rc<str>),
+    Boolean(bool),
+    Null,
+    RegExp(Rc<str>, Rc<str>), // /pattern/flags, a new object each time it is evaluated
+    
+    // Variables
+    Variable(String, Span),
+    // A variable the resolver proved can only be global, and the cache of
+    // where the site last found it
+    Global(
+        String,
+        Span,
+        #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+        #[cfg_attr(feature = "cache", serde(skip))]
+        GlobalCache,
+    ),
+    This,
+    
+    // Operations
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    Unary(UnaryOp, Box<Expr>),
+    
+    // Control flow
+    Conditional(Box<Expr>, Box<Expr>, Option<Box<Expr>>), // condition, then-branch, else-branch
+    
+    // Variables and functions
+    Assign(String, Box<Expr>, Span),
+    // target op= value, where the target is a Variable, Global, Get or
+    // Index expression and is only evaluated once
+    CompoundAssign(Box<Expr>, BinaryOp, Box<Expr>),
+    Call(Box<Expr>, Vec<Expr>, Span), // callee, arguments, span of the whole call
+    New(Box<Expr>, Vec<Expr>, Span), // new constructor(arguments), with the same parts as Call
+    
+    // Objects and property access
+    Object(Vec<(PropertyKey, Expr)>), // { name: value, [key]: value, ... } in source order
+    Array(Vec<Expr>), // [element, ...]
+    // `...iterable` as an array element or call argument, which stands for
+    // the values the iterable gives
+    Spread(Box<Expr>),
+    // object.name, and the inline cache of the site
+    Get(
+        Box<Expr>,
+        Rc<str>,
+        #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+        #[cfg_attr(feature = "cache", serde(skip))]
+        PropertyCache,
+    ),
+    Index(Box<Expr>, Box<Expr>), // object[key]
+    // object.name = value, and the inline cache of the site
+    Set(
+        Box<Expr>,
+        Rc<str>,
+        Box<Expr>,
+        #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+        #[cfg_attr(feature = "cache", serde(skip))]
+        PropertyCache,
+    ),
+    SetIndex(Box<Expr>, Box<Expr>, Box<Expr>), // object[key] = value
+    // An optional chain such as `a?.b.c()`, holding the accesses and calls
+    // that make it up. Optional(base) marks each `base?.` in it; when that
+    // base is null or undefined the rest of the chain is skipped and the
+    // chain evaluates to undefined.
+    Chain(Box<Expr>),
+    Optional(Box<Expr>),
+    // await operand, only inside async functions
+    Await(Box<Expr>),
+    
+    // Function definition
+    Function(Vec<Parameter>, Rc<Stmt>, Span, FunctionKind),
+    // `function name() {}` as an expression: the Stmt::Function, whose name
+    // is bound only inside the function itself
+    NamedFunction(Box<Stmt>),
+}
+
+// The key of a property in an object literal: a name, which is also what
+// string and number keys become, or `[expr]`, computed when the literal is
+// evaluated
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "cache", derive(serde::Serialize, serde::Deserialize))]
+pub enum PropertyKey {
+    Name(Rc<str>),
+    Computed(Expr),
+}
+
+// Arrow functions take `this` from where they are defined instead of from
+// how they are called. Async functions return a promise and may await.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "cache", derive(serde::Serialize, serde::Deserialize))]
+pub enum FunctionKind {
+    Function,
+    Arrow,
+    AsyncFunction,
+    AsyncArrow,
+}
+
+impl FunctionKind {
+    pub fn is_arrow(self) -> bool {
+        matches!(self, FunctionKind::Arrow | FunctionKind::AsyncArrow)
+    }
+    
+    pub fn is_async(self) -> bool {
+        matches!(self, FunctionKind::AsyncFunction | FunctionKind::AsyncArrow)
+    }
+}
+
+// `var` binds in the enclosing function; `let` and `const` in the block,
+// where they cannot be used before the declaration runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "cache", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeclarationKind {
+    Var,
+    Let,
+    Const,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "cache", derive(serde::Serialize, serde::Deserialize))]
+pub enum Stmt {
+    Expression(Expr),
+    Declaration(String, Option<Expr>, Span, DeclarationKind), // var name = expr
+    Destructure(Pattern, Expr, DeclarationKind), // var { a, b: c = 1 } = expr
+    Block(Vec<Stmt>),
+    If(Expr, Box<Stmt>, Option<Box<Stmt>>),
+    While(Expr, Box<Stmt>),
+    // for (initializer; condition; update) body. The flag is set when the
+    // initializer declares with let or const, which gives each iteration
+    // its own copy of the loop variables.
+    For(Option<Box<Stmt>>, Option<Expr>, Option<Expr>, Box<Stmt>, bool),
+    // for (const name of iterable) body. The flag is set when the loop
+    // declares the variable, which gives each iteration its own binding;
+    // otherwise the loop assigns to a variable declared elsewhere.
+    ForOf(String, Expr, Box<Stmt>, Span, bool),
+    // switch (discriminant) { case test: ... default: ... }. The clauses
+    // share one block scope.
+    Switch(Expr, Vec<SwitchClause>),
+    Break,
+    // try { } catch (parameter) { } finally { }, with at least one of the
+    // catch clause and the finally block
+    Try(Box<Stmt>, Option<CatchClause>, Option<Box<Stmt>>),
+    Throw(Expr),
+    Return(Option<Expr>),
+    Function(String, Vec<Parameter>, Rc<Stmt>, Span, FunctionKind), // name, params, body
+}
+
+// A case's test, None for the default clause, and its statements
+pub type SwitchClause = (Option<Expr>, Vec<Stmt>);
+
+// The exception's binding, None for `catch { }`, and the handler
+pub type CatchClause = (Option<Pattern>, Box<Stmt>);
+
+// A parameter's name and where it is written. A destructured parameter is
+// named `{parameter N}` and placed at its pattern.
+pub type Parameter = (Rc<str>, Span);
+
+// What a destructuring declaration binds: a name, or an object pattern
+// taking each target's value from a property of the value being bound
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "cache", derive(serde::Serialize, serde::Deserialize))]
+pub enum Pattern {
+    Identifier(String, Span),
+    Object(Vec<PatternProperty>),
+}
+
+// { key: target = default } with the default used when the property is
+// undefined
+pub type PatternProperty = (Rc<str>, Pattern, Option<Expr>);
+
+impl Pattern {
+    // The names the pattern binds, in source order
+    pub fn bindings(&self) -> Vec<(&str, Span)> {
+        match self {
+            Pattern::Identifier(name, span) => vec![(name.as_str(), *span)],
+            Pattern::Object(properties) => properties.iter().flat_map(|(_, target, _)| target.bindings()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "cache", derive(serde::Serialize, serde::Deserialize))]
+pub enum BinaryOp {
+    Add, Subtract, Multiply, Divide, Remainder, Exponent,
+    Equal, NotEqual, Less, LessEqual, Greater, GreaterEqual,
+    And, Or, Nullish,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "cache", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnaryOp {
+    Negate, Not,
+    Plus, // ToNumber
+    Void, // Evaluates the operand for its effects and gives undefined
+}
+
+// Constructors that take care of boxing and string conversion
+
+impl Expr {
+    pub fn number(value: f64) -> Expr {
+        Expr::Number(value)
+    }
+    
+    pub fn string(value: &str) -> Expr {
+        Expr::String(Rc::from(value))
+    }
+    
+    pub fn boolean(value: bool) -> Expr {
+        Expr::Boolean(value)
+    }
+    
+    pub fn regexp(pattern: &str, flags: &str) -> Expr {
+        Expr::RegExp(Rc::from(pattern), Rc::from(flags))
+    }
+    
+    pub fn variable(name: &str) -> Expr {
+        Expr::Variable(name.to_string(), Span::default())
+    }
+    
+    pub fn binary(left: Expr, operator: BinaryOp, right: Expr) -> Expr {
+        Expr::Binary(Box::new(left), operator, Box::new(right))
+    }
+    
+    pub fn unary(operator: UnaryOp, right: Expr) -> Expr {
+        Expr::Unary(operator, Box::new(right))
+    }
+    
+    pub fn conditional(condition: Expr, then_branch: Expr, else_branch: Option<Expr>) -> Expr {
+        Expr::Conditional(Box::new(condition), Box::new(then_branch), else_branch.map(Box::new))
+    }
+    
+    pub fn assign(name: &str, value: Expr) -> Expr {
+        Expr::Assign(name.to_string(), Box::new(value), Span::default())
+    }
+    
+    pub fn compound_assign(target: Expr, operator: BinaryOp, value: Expr) -> Expr {
+        Expr::CompoundAssign(Box::new(target), operator, Box::new(value))
+    }
+    
+    pub fn call(callee: Expr, arguments: Vec<Expr>) -> Expr {
+        Expr::Call(Box::new(callee), arguments, Span::default())
+    }
+    
+    pub fn new_object(constructor: Expr, arguments: Vec<Expr>) -> Expr {
+        Expr::New(Box::new(constructor), arguments, Span::default())
+    }
+    
+    pub fn object(properties: Vec<(&str, Expr)>) -> Expr {
+        Expr::Object(properties.into_iter().map(|(name, value)| (PropertyKey::Name(Rc::from(name)), value)).collect())
+    }
+    
+    pub fn array(elements: Vec<Expr>) -> Expr {
+        Expr::Array(elements)
+    }
+    
+    // `...iterable`, for the elements of Expr::array and the arguments of
+    // Expr::call
+    pub fn spread(iterable: Expr) -> Expr {
+        Expr::Spread(Box::new(iterable))
+    }
+    
+    pub fn get(object: Expr, name: &str) -> Expr {
+        Expr::Get(Box::new(object), Rc::from(name), PropertyCache::default())
+    }
+    
+    pub fn index(object: Expr, key: Expr) -> Expr {
+        Expr::Index(Box::new(object), Box::new(key))
+    }
+    
+    pub fn set(object: Expr, name: &str, value: Expr) -> Expr {
+        Expr::Set(Box::new(object), Rc::from(name), Box::new(value), PropertyCache::default())
+    }
+    
+    pub fn set_index(object: Expr, key: Expr, value: Expr) -> Expr {
+        Expr::SetIndex(Box::new(object), Box::new(key), Box::new(value))
+    }
+    
+    // `chain` built from accesses and calls whose bases are wrapped with
+    // Expr::optional
+    pub fn chain(chain: Expr) -> Expr {
+        Expr::Chain(Box::new(chain))
+    }
+    
+    pub fn optional(base: Expr) -> Expr {
+        Expr::Optional(Box::new(base))
+    }
+    
+    pub fn awaited(operand: Expr) -> Expr {
+        Expr::Await(Box::new(operand))
+    }
+    
+    pub fn function(parameters: &[&str], body: Vec<Stmt>) -> Expr {
+        Expr::Function(
+            parameters.iter().map(|name| (Rc::from(*name), Span::default())).collect(),
+            Rc::new(Stmt::Block(body)),
+            Span::default(),
+            FunctionKind::Function,
+        )
+    }
+}
+
+impl Stmt {
+    pub fn expression(expr: Expr) -> Stmt {
+        Stmt::Expression(expr)
+    }
+    
+    // Declares with `let`
+    pub fn declaration(name: &str, initializer: Option<Expr>) -> Stmt {
+        Stmt::Declaration(name.to_string(), initializer, Span::default(), DeclarationKind::Let)
+    }
+    
+    pub fn declaration_of(kind: DeclarationKind, name: &str, initializer: Option<Expr>) -> Stmt {
+        Stmt::Declaration(name.to_string(), initializer, Span::default(), kind)
+    }
+    
+    pub fn destructure(pattern: Pattern, initializer: Expr) -> Stmt {
+        Stmt::Destructure(pattern, initializer, DeclarationKind::Let)
+    }
+    
+    pub fn block(statements: Vec<Stmt>) -> Stmt {
+        Stmt::Block(statements)
+    }
+    
+    pub fn if_else(condition: Expr, then_branch: Stmt, else_branch: Option<Stmt>) -> Stmt {
+        Stmt::If(condition, Box::new(then_branch), else_branch.map(Box::new))
+    }
+    
+    pub fn while_loop(condition: Expr, body: Stmt) -> Stmt {
+        Stmt::While(condition, Box::new(body))
+    }
+    
+    pub fn for_loop(initializer: Option<Stmt>, condition: Option<Expr>, update: Option<Expr>, body: Stmt) -> Stmt {
+        let per_iteration = matches!(initializer, Some(Stmt::Declaration(.., DeclarationKind::Let | DeclarationKind::Const)));
+        Stmt::For(initializer.map(Box::new), condition, update, Box::new(body), per_iteration)
+    }
+    
+    // Declares `name` for each iteration
+    pub fn for_of(name: &str, iterable: Expr, body: Stmt) -> Stmt {
+        Stmt::ForOf(name.to_string(), iterable, Box::new(body), Span::default(), true)
+    }
+    
+    pub fn switch(discriminant: Expr, clauses: Vec<SwitchClause>) -> Stmt {
+        Stmt::Switch(discriminant, clauses)
+    }
+    
+    pub fn try_catch(block: Stmt, handler: Option<(Option<Pattern>, Stmt)>, finalizer: Option<Stmt>) -> Stmt {
+        let handler = handler.map(|(parameter, body)| (parameter, Box::new(body)));
+        Stmt::Try(Box::new(block), handler, finalizer.map(Box::new))
+    }
+    
+    pub fn throw(value: Expr) -> Stmt {
+        Stmt::Throw(value)
+    }
+    
+    pub fn return_value(value: Option<Expr>) -> Stmt {
+        Stmt::Return(value)
+    }
+    
+    pub fn function(name: &str, parameters: &[&str], body: Vec<Stmt>) -> Stmt {
+        Stmt::Function(
+            name.to_string(),
+            parameters.iter().map(|parameter| (Rc::from(*parameter), Span::default())).collect(),
+            Rc::new(Stmt::Block(body)),
+            Span::default(),
+            FunctionKind::Function,
+        )
+    }
+}
+
+// Read-only traversal. Override the methods for the nodes of interest and
+// call the matching `walk_*` function to continue into their children.
+pub trait Visitor {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        walk_stmt(self, stmt);
+    }
+    
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+}
+
+pub fn walk_stmt<V: Visitor + ?Sized>(visitor: &mut V, stmt: &Stmt) {
+    match stmt {
+        Stmt::Expression(expr) => visitor.visit_expr(expr),
+        Stmt::Declaration(_, initializer, ..) => {
+            if let Some(expr) = initializer {
+                visitor.visit_expr(expr);
+            }
+        },
+        Stmt::Destructure(pattern, initializer, _) => {
+            visitor.visit_expr(initializer);
+            walk_pattern(visitor, pattern);
+        },
+        Stmt::Block(statements) => {
+            for statement in statements {
+                visitor.visit_stmt(statement);
+            }
+        },
+        Stmt::If(condition, then_branch, else_branch) => {
+            visitor.visit_expr(condition);
+            visitor.visit_stmt(then_branch);
+            
+            if let Some(else_stmt) = else_branch {
+                visitor.visit_stmt(else_stmt);
+            }
+        },
+        Stmt::While(condition, body) => {
+            visitor.visit_expr(condition);
+            visitor.visit_stmt(body);
+        },
+        Stmt::For(initializer, condition, update, body, _) => {
+            if let Some(stmt) = initializer {
+                visitor.visit_stmt(stmt);
+            }
+            if let Some(expr) = condition {
+                visitor.visit_expr(expr);
+            }
+            if let Some(expr) = update {
+                visitor.visit_expr(expr);
+            }
+            visitor.visit_stmt(body);
+        },
+        Stmt::ForOf(_, iterable, body, ..) => {
+            visitor.visit_expr(iterable);
+            visitor.visit_stmt(body);
+        },
+        Stmt::Switch(discriminant, clauses) => {
+            visitor.visit_expr(discriminant);
+            
+            for (test, body) in clauses {
+                if let Some(test) = test {
+                    visitor.visit_expr(test);
+                }
+                for statement in body {
+                    visitor.visit_stmt(statement);
+                }
+            }
+        },
+        Stmt::Break => {},
+        Stmt::Try(block, handler, finalizer) => {
+            visitor.visit_stmt(block);
+            
+            if let Some((parameter, body)) = handler {
+                if let Some(parameter) = parameter {
+                    walk_pattern(visitor, parameter);
+                }
+                visitor.visit_stmt(body);
+            }
+            if let Some(finalizer) = finalizer {
+                visitor.visit_stmt(finalizer);
+            }
+        },
+        Stmt::Throw(value) => visitor.visit_expr(value),
+        Stmt::Return(value) => {
+            if let Some(expr) = value {
+                visitor.visit_expr(expr);
+            }
+        },
+        Stmt::Function(_, _, body, ..) => visitor.visit_stmt(body),
+    }
+}
+
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Number(_) | Expr::String(_) | Expr::Boolean(_) | Expr::Null | Expr::RegExp(..) => {},
+        Expr::Variable(..) | Expr::Global(..) | Expr::This => {},
+        Expr::Binary(left, _, right) => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        },
+        Expr::Unary(_, right) => visitor.visit_expr(right),
+        Expr::Conditional(condition, then_branch, else_branch) => {
+            visitor.visit_expr(condition);
+            visitor.visit_expr(then_branch);
+            
+            if let Some(else_expr) = else_branch {
+                visitor.visit_expr(else_expr);
+            }
+        },
+        Expr::Assign(_, value, _) => visitor.visit_expr(value),
+        Expr::CompoundAssign(target, _, value) => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(value);
+        },
+        Expr::Call(callee, arguments, _) | Expr::New(callee, arguments, _) => {
+            visitor.visit_expr(callee);
+            
+            for argument in arguments {
+                visitor.visit_expr(argument);
+            }
+        },
+        Expr::Object(properties) => {
+            for (key, value) in properties {
+                if let PropertyKey::Computed(key) = key {
+                    visitor.visit_expr(key);
+                }
+                visitor.visit_expr(value);
+            }
+        },
+        Expr::Array(elements) => {
+            for element in elements {
+                visitor.visit_expr(element);
+            }
+        },
+        Expr::Get(object, ..) => visitor.visit_expr(object),
+        Expr::Index(object, key) => {
+            visitor.visit_expr(object);
+            visitor.visit_expr(key);
+        },
+        Expr::Set(object, _, value, _) => {
+            visitor.visit_expr(object);
+            visitor.visit_expr(value);
+        },
+        Expr::SetIndex(object, key, value) => {
+            visitor.visit_expr(object);
+            visitor.visit_expr(key);
+            visitor.visit_expr(value);
+        },
+        Expr::Chain(chain) => visitor.visit_expr(chain),
+        Expr::Optional(base) => visitor.visit_expr(base),
+        Expr::Spread(iterable) => visitor.visit_expr(iterable),
+        Expr::Await(operand) => visitor.visit_expr(operand),
+        Expr::Function(_, body, ..) => visitor.visit_stmt(body),
+        Expr::NamedFunction(function) => visitor.visit_stmt(function),
+    }
+}
+
+// The names declared with `var` in a function body or script, including
+// those in nested blocks but not in nested functions. They are bound
+// before the body runs.
+pub fn var_bindings(statements: &[Stmt]) -> Vec<(&str, Span)> {
+    struct Collector<'a> {
+        bindings: Vec<(&'a str, Span)>,
+    }
+    
+    impl<'a> Collector<'a> {
+        fn statement(&mut self, stmt: &'a Stmt) {
+            match stmt {
+                Stmt::Declaration(name, _, span, DeclarationKind::Var) => self.bindings.push((name, *span)),
+                Stmt::Destructure(pattern, _, DeclarationKind::Var) => self.bindings.extend(pattern.bindings()),
+                Stmt::Block(statements) => statements.iter().for_each(|statement| self.statement(statement)),
+                Stmt::If(_, then_branch, else_branch) => {
+                    self.statement(then_branch);
+                    if let Some(else_branch) = else_branch {
+                        self.statement(else_branch);
+                    }
+                },
+                Stmt::While(_, body) | Stmt::ForOf(_, _, body, ..) => self.statement(body),
+                Stmt::For(initializer, _, _, body, _) => {
+                    if let Some(initializer) = initializer {
+                        self.statement(initializer);
+                    }
+                    self.statement(body);
+                },
+                Stmt::Switch(_, clauses) => {
+                    for (_, body) in clauses {
+                        body.iter().for_each(|statement| self.statement(statement));
+                    }
+                },
+                Stmt::Try(block, handler, finalizer) => {
+                    self.statement(block);
+                    if let Some((_, body)) = handler {
+                        self.statement(body);
+                    }
+                    if let Some(finalizer) = finalizer {
+                        self.statement(finalizer);
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+    
+    let mut collector = Collector { bindings: Vec::new() };
+    statements.iter().for_each(|statement| collector.statement(statement));
+    collector.bindings
+}
+
+// Defaults are visited in the order they would be evaluated
+pub fn walk_pattern<V: Visitor + ?Sized>(visitor: &mut V, pattern: &Pattern) {
+    if let Pattern::Object(properties) = pattern {
+        for (_, target, default) in properties {
+            if let Some(default) = default {
+                visitor.visit_expr(default);
+            }
+            walk_pattern(visitor, target);
+        }
+    }
+}
+
+// Traversal that may rewrite nodes in place. Function bodies are shared
+// through `Rc`, so walking into one that is still referenced elsewhere
+// clones it first.
+pub trait VisitorMut {
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        walk_stmt_mut(self, stmt);
+    }
+    
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+    }
+}
+
+pub fn walk_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut Stmt) {
+    match stmt {
+        Stmt::Expression(expr) => visitor.visit_expr_mut(expr),
+        Stmt::Declaration(_, initializer, ..) => {
+            if let Some(expr) = initializer {
+                visitor.visit_expr_mut(expr);
+            }
+        },
+        Stmt::Destructure(pattern, initializer, _) => {
+            visitor.visit_expr_mut(initializer);
+            walk_pattern_mut(visitor, pattern);
+        },
+        Stmt::Block(statements) => {
+            for statement in statements {
+                visitor.visit_stmt_mut(statement);
+            }
+        },
+        Stmt::If(condition, then_branch, else_branch) => {
+            visitor.visit_expr_mut(condition);
+            visitor.visit_stmt_mut(then_branch);
+            
+            if let Some(else_stmt) = else_branch {
+                visitor.visit_stmt_mut(else_stmt);
+            }
+        },
+        Stmt::While(condition, body) => {
+            visitor.visit_expr_mut(condition);
+            visitor.visit_stmt_mut(body);
+        },
+        Stmt::For(initializer, condition, update, body, _) => {
+            if let Some(stmt) = initializer {
+                visitor.visit_stmt_mut(stmt);
+            }
+            if let Some(expr) = condition {
+                visitor.visit_expr_mut(expr);
+            }
+            if let Some(expr) = update {
+                visitor.visit_expr_mut(expr);
+            }
+            visitor.visit_stmt_mut(body);
+        },
+        Stmt::ForOf(_, iterable, body, ..) => {
+            visitor.visit_expr_mut(iterable);
+            visitor.visit_stmt_mut(body);
+        },
+        Stmt::Switch(discriminant, clauses) => {
+            visitor.visit_expr_mut(discriminant);
+            
+            for (test, body) in clauses {
+                if let Some(test) = test {
+                    visitor.visit_expr_mut(test);
+                }
+                for statement in body {
+                    visitor.visit_stmt_mut(statement);
+                }
+            }
+        },
+        Stmt::Break => {},
+        Stmt::Try(block, handler, finalizer) => {
+            visitor.visit_stmt_mut(block);
+            
+            if let Some((parameter, body)) = handler {
+                if let Some(parameter) = parameter {
+                    walk_pattern_mut(visitor, parameter);
+                }
+                visitor.visit_stmt_mut(body);
+            }
+            if let Some(finalizer) = finalizer {
+                visitor.visit_stmt_mut(finalizer);
+            }
+        },
+        Stmt::Throw(value) => visitor.visit_expr_mut(value),
+        Stmt::Return(value) => {
+            if let Some(expr) = value {
+                visitor.visit_expr_mut(expr);
+            }
+        },
+        Stmt::Function(_, _, body, ..) => visitor.visit_stmt_mut(Rc::make_mut(body)),
+    }
+}
+
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    match expr {
+        Expr::Number(_) | Expr::String(_) | Expr::Boolean(_) | Expr::Null | Expr::RegExp(..) => {},
+        Expr::Variable(..) | Expr::Global(..) | Expr::This => {},
+        Expr::Binary(left, _, right) => {
+            visitor.visit_expr_mut(left);
+            visitor.visit_expr_mut(right);
+        },
+        Expr::Unary(_, right) => visitor.visit_expr_mut(right),
+        Expr::Conditional(condition, then_branch, else_branch) => {
+            visitor.visit_expr_mut(condition);
+            visitor.visit_expr_mut(then_branch);
+            
+            if let Some(else_expr) = else_branch {
+                visitor.visit_expr_mut(else_expr);
+            }
+        },
+        Expr::Assign(_, value, _) => visitor.visit_expr_mut(value),
+        Expr::CompoundAssign(target, _, value) => {
+            visitor.visit_expr_mut(target);
+            visitor.visit_expr_mut(value);
+        },
+        Expr::Call(callee, arguments, _) | Expr::New(callee, arguments, _) => {
+            visitor.visit_expr_mut(callee);
+            
+            for argument in arguments {
+                visitor.visit_expr_mut(argument);
+            }
+        },
+        Expr::Object(properties) => {
+            for (key, value) in properties {
+                if let PropertyKey::Computed(key) = key {
+                    visitor.visit_expr_mut(key);
+                }
+                visitor.visit_expr_mut(value);
+            }
+        },
+        Expr::Array(elements) => {
+            for element in elements {
+                visitor.visit_expr_mut(element);
+            }
+        },
+        Expr::Get(object, ..) => visitor.visit_expr_mut(object),
+        Expr::Index(object, key) => {
+            visitor.visit_expr_mut(object);
+            visitor.visit_expr_mut(key);
+        },
+        Expr::Set(object, _, value, _) => {
+            visitor.visit_expr_mut(object);
+            visitor.visit_expr_mut(value);
+        },
+        Expr::SetIndex(object, key, value) => {
+            visitor.visit_expr_mut(object);
+            visitor.visit_expr_mut(key);
+            visitor.visit_expr_mut(value);
+        },
+        Expr::Chain(chain) => visitor.visit_expr_mut(chain),
+        Expr::Optional(base) => visitor.visit_expr_mut(base),
+        Expr::Spread(iterable) => visitor.visit_expr_mut(iterable),
+        Expr::Await(operand) => visitor.visit_expr_mut(operand),
+        Expr::Function(_, body, ..) => visitor.visit_stmt_mut(Rc::make_mut(body)),
+        Expr::NamedFunction(function) => visitor.visit_stmt_mut(function),
+    }
+}
+
+pub fn walk_pattern_mut<V: VisitorMut + ?Sized>(visitor: &mut V, pattern: &mut Pattern) {
+    if let Pattern::Object(properties) = pattern {
+        for (_, target, default) in properties {
+            if let Some(default) = default {
+                visitor.visit_expr_mut(default);
+            }
+            walk_pattern_mut(visitor, target);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+// src/asynchronous.rs
+use crate::error::JsEngineError;
+use crate::interpreter::{ExecutionResult, Interpreter, JsFunction, Value};
+use crate::native_stack;
+use crate::promise::{Outcome, Promise};
+use corosensei::stack::{DefaultStack, Stack};
+use corosensei::{Coroutine, CoroutineResult, Yielder};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+// Async functions run on a coroutine of their own, so that `await` can
+// suspend one in the middle of a statement and hand control back to the
+// code that called or resumed it. Calling an async function runs its body up
+// to the first await and returns a promise for its result; each await then
+// resumes the body from a microtask once the awaited promise settles, so
+// the caller always carries on first.
+//
+// Both sides of a switch keep their own environment and script; the rest of
+// the interpreter is shared. Suspended frames still refer to the interpreter
+// they ran on, so a function is not resumed if its interpreter has moved
+// since, and its promise is rejected instead. Time slicing (see
+// resumable.rs) does not pause inside async functions.
+//
+// Each side hands the interpreter to the other when it switches, and takes
+// it back when it continues, so that the compiler knows the other side may
+// have changed it in between.
+
+// Each call gets a stack of its own, so they are kept smaller than the
+// main thread's: recursion inside an async function stops sooner, with
+// "Maximum call stack size exceeded" (see native_stack.rs), after about
+// 150 calls in debug builds. Release builds reach the depth limit first.
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+type Body = Coroutine<Switch, *mut Interpreter, Result<Value, JsEngineError>, DefaultStack>;
+
+// What a suspended body continues with: the awaited outcome, and the
+// interpreter
+type Switch = (Outcome, *mut Interpreter);
+
+// One call of an async function
+pub(crate) struct AsyncCall {
+    coroutine: RefCell<Option<Body>>, // None once the body has finished
+    yielder: Cell<*const Yielder<Switch, *mut Interpreter>>,
+    interpreter: *const Interpreter,
+    stack_limit: usize, // The coroutine's, for the interpreter to use while it runs
+    function: Rc<JsFunction>,
+    promise: Rc<Promise>,
+}
+
+impl Interpreter {
+    // Starts a call whose environment is already set up, returning its
+    // promise once the body first awaits or finishes
+    pub(crate) fn start_async(&mut self, function: &Rc<JsFunction>) -> Result<Value, JsEngineError> {
+        let stack = DefaultStack::new(STACK_SIZE).map_err(|error| JsEngineError::RuntimeError {
+            message: format!("Cannot allocate a stack for the async function: {}", error),
+        })?;
+        
+        let stack_limit = native_stack::limit_for(stack.limit().get());
+        let body = Rc::clone(&function.body);
+        let coroutine = Coroutine::with_stack(stack, move |yielder: &Yielder<Switch, *mut Interpreter>, (_, pointer): Switch| {
+            // SAFETY: the coroutine only runs inside `resume`, which hands it
+            // the interpreter it was called with
+            let interpreter = unsafe { &mut *pointer };
+            if let Some(call) = &interpreter.current_async {
+                call.yielder.set(yielder);
+            }
+            
+            let result = interpreter.execute(&body);
+            match interpreter.settle_tail_call(result)? {
+                ExecutionResult::Return(value) => Ok(value),
+                _ => Ok(Value::Undefined),
+            }
+        });
+        
+        let promise = Promise::new(Rc::downgrade(&self.tasks()));
+        let call = Rc::new(AsyncCall {
+            coroutine: RefCell::new(Some(coroutine)),
+            yielder: Cell::new(std::ptr::null()),
+            interpreter: self,
+            stack_limit,
+            function: Rc::clone(function),
+            promise: Rc::clone(&promise),
+        });
+        call.resume(self, Ok(Value::Undefined));
+        Ok(promise.into_value())
+    }
+    
+    // Suspends the running async function until `value` settles, giving
+    // back its value or throwing its rejection reason
+    pub(crate) fn await_value(&mut self, value: Value) -> Result<Value, JsEngineError> {
+        let Some(call) = self.current_async.take() else {
+            return Err(JsEngineError::SyntaxError {
+                message: "await is only valid in async functions".to_string(),
+            });
+        };
+        let yielder = call.yielder.get();
+        
+        // The reaction holds the call rather than this stack, so a call
+        // waiting on a promise that never settles is freed with it
+        let promise = Promise::resolved(value, Rc::downgrade(&self.tasks()));
+        promise.react(Box::new(move |interpreter, outcome| {
+            interpreter.call_stack().borrow_mut().enter(call.function.name.clone(), call.function.source.clone(), call.function.span.start);
+            call.resume(interpreter, outcome);
+            interpreter.call_stack().borrow_mut().exit();
+            Ok(())
+        }));
+        drop(promise);
+        
+        let environment = Rc::clone(&self.environment);
+        let source = self.source.clone();
+        // SAFETY: the yielder belongs to the coroutine running this code,
+        // and `resume` hands back the interpreter this call started on
+        let (outcome, pointer) = unsafe { &*yielder }.suspend(self);
+        let interpreter = unsafe { &mut *pointer };
+        interpreter.environment = environment;
+        interpreter.source = source;
+        
+        outcome.map_err(JsEngineError::thrown)
+    }
+}
+
+impl AsyncCall {
+    // Runs the body until it next awaits or finishes, and settles the call's
+    // promise when it finishes
+    fn resume(self: &Rc<Self>, interpreter: &mut Interpreter, outcome: Outcome) {
+        if !std::ptr::eq(interpreter, self.interpreter) {
+            let coroutine = self.coroutine.borrow_mut().take();
+            drop(coroutine);
+            let error = JsEngineError::RuntimeError {
+                message: "Cannot resume an async function after its interpreter has moved".to_string(),
+            };
+            self.promise.reject(interpreter.exception_value(error));
+            return;
+        }
+        
+        let environment = Rc::clone(&interpreter.environment);
+        let source = interpreter.source.clone();
+        let outer = interpreter.current_async.replace(Rc::clone(self));
+        let outer_limit = std::mem::replace(&mut interpreter.stack_limit, self.stack_limit);
+        #[cfg(feature = "resumable")]
+        let budget = interpreter.budget.take();
+        
+        let pointer: *mut Interpreter = interpreter;
+        let result = self.coroutine.borrow_mut().as_mut().map(|coroutine| coroutine.resume((outcome, pointer)));
+        
+        interpreter.environment = environment;
+        interpreter.source = source;
+        interpreter.current_async = outer;
+        interpreter.stack_limit = outer_limit;
+        #[cfg(feature = "resumable")]
+        {
+            interpreter.budget = budget;
+        }
+        
+        if let Some(CoroutineResult::Return(result)) = result {
+            let coroutine = self.coroutine.borrow_mut().take();
+            drop(coroutine);
+            match result {
+                Ok(value) => self.promise.resolve(value),
+                Err(error) => self.promise.reject(interpreter.exception_value(error)),
+            }
+        }
+    }
+}
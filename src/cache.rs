@@ -0,0 +1,131 @@
+// src/cache.rs
+use crate::ast::Stmt;
+use crate::error::JsEngineError;
+use crate::{lexer, parser};
+use std::collections::HashMap;
+#[cfg(feature = "cache")]
+use std::path::PathBuf;
+
+// Parse results keyed by a hash of the source, so unchanged scripts are
+// lexed and parsed only once. Entries keep their source and are only used
+// when it matches exactly, which makes hash collisions harmless. Scripts
+// that fail to parse are not cached.
+//
+// With the cache feature, entries can also be written to a directory and
+// picked up by later processes. Files that cannot be read or were written
+// by another version of the engine are treated as misses and replaced.
+#[derive(Default)]
+pub struct ParseCache {
+    entries: HashMap<u64, Entry>,
+    #[cfg(feature = "cache")]
+    directory: Option<PathBuf>,
+}
+
+#[cfg_attr(feature = "cache", derive(serde::Serialize, serde::Deserialize))]
+struct Entry {
+    typescript: bool,
+    source: String,
+    statements: Vec<Stmt>,
+}
+
+impl ParseCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // Also stores entries as files in `directory`, which is created on
+    // first write
+    #[cfg(feature = "cache")]
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+    
+    // Lexes and parses `source`, or returns a copy of the statements from an
+    // earlier parse of the same source
+    pub fn parse(&mut self, source: &str, typescript: bool) -> Result<Vec<Stmt>, JsEngineError> {
+        let key = hash(source, typescript);
+        
+        if let Some(entry) = self.entries.get(&key) && entry.matches(source, typescript) {
+            return Ok(entry.statements.clone());
+        }
+        
+        #[cfg(feature = "cache")]
+        if let Some(entry) = self.load(key) && entry.matches(source, typescript) {
+            let statements = entry.statements.clone();
+            self.entries.insert(key, entry);
+            return Ok(statements);
+        }
+        
+        let tokens = lexer::Lexer::new(source).scan_tokens()?;
+        let statements = parser::Parser::new(tokens).with_typescript(typescript).parse()?;
+        let entry = Entry {
+            typescript,
+            source: source.to_string(),
+            statements: statements.clone(),
+        };
+        
+        #[cfg(feature = "cache")]
+        self.store(key, &entry);
+        
+        self.entries.insert(key, entry);
+        Ok(statements)
+    }
+    
+    // Number of scripts held in memory
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    // Forgets the in-memory entries; files on disk are kept
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+    
+    #[cfg(feature = "cache")]
+    fn path(&self, key: u64) -> Option<PathBuf> {
+        self.directory.as_ref().map(|directory| directory.join(format!("{:016x}.jsc", key)))
+    }
+    
+    // Files start with the version that wrote them, since the AST's layout
+    // changes between versions
+    #[cfg(feature = "cache")]
+    fn load(&self, key: u64) -> Option<Entry> {
+        let bytes = std::fs::read(self.path(key)?).ok()?;
+        let (version, entry): (String, Entry) = bincode::deserialize(&bytes).ok()?;
+        (version == env!("CARGO_PKG_VERSION")).then_some(entry)
+    }
+    
+    // Failing to write only costs a re-parse next time, so errors are ignored
+    #[cfg(feature = "cache")]
+    fn store(&self, key: u64, entry: &Entry) {
+        let Some(path) = self.path(key) else {
+            return;
+        };
+        
+        if let Ok(bytes) = bincode::serialize(&(env!("CARGO_PKG_VERSION"), entry)) && let Some(directory) = path.parent() {
+            let _ = std::fs::create_dir_all(directory).and_then(|_| std::fs::write(&path, bytes));
+        }
+    }
+}
+
+impl Entry {
+    fn matches(&self, source: &str, typescript: bool) -> bool {
+        self.typescript == typescript && self.source == source
+    }
+}
+
+// 64-bit FNV-1a, which unlike the standard library's hasher is stable across
+// builds and so can name files on disk
+fn hash(source: &str, typescript: bool) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in [typescript as u8].iter().chain(source.as_bytes()) {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
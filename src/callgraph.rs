@@ -0,0 +1,261 @@
+// src/callgraph.rs
+use crate::ast::{walk_expr, walk_stmt, Expr, Stmt, Visitor};
+use crate::heap::json_string;
+use crate::lexer::{line_column, Span};
+use crate::resolver::{self, SymbolKind, SymbolTable};
+use std::collections::HashMap;
+
+// Static call graph of a program: which functions call which. Callees are
+// resolved through the resolver's symbol table, so a call is attributed to a
+// function when the callee is a name bound to a function declaration or to a
+// variable initialized with a function expression. Calls to names declared
+// nowhere (host globals such as setTimeout) and methods of undeclared
+// globals (console.log) become external nodes; calls through anything else,
+// such as parameters or call results, are not resolvable statically and are
+// left out.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CallNodeKind {
+    TopLevel, // Code outside any function
+    Function,
+    External,
+}
+
+#[derive(Debug, Clone)]
+pub struct CallNode {
+    pub name: String,
+    pub kind: CallNodeKind,
+    pub span: Option<Span>, // Source text of the function, if it has any
+}
+
+#[derive(Debug, Clone)]
+pub struct CallEdge {
+    pub from: usize,
+    pub to: usize,
+    pub calls: usize, // Number of call sites
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    pub nodes: Vec<CallNode>,
+    pub edges: Vec<CallEdge>,
+}
+
+// A call site whose callee is looked up once every function is known
+enum Callee {
+    Symbol(usize),
+    External(String),
+}
+
+struct Collector<'a> {
+    table: &'a SymbolTable,
+    // Symbol referenced at each reference span start
+    references: HashMap<usize, usize>,
+    graph: CallGraph,
+    // Function node for each function's span start, and for the span start
+    // of the variable declarations that hold a function expression
+    functions: HashMap<usize, usize>,
+    stack: Vec<usize>, // Enclosing function nodes, innermost last
+    calls: Vec<(usize, Callee)>,
+    externals: HashMap<String, usize>,
+}
+
+impl CallGraph {
+    pub fn collect(statements: &[Stmt]) -> CallGraph {
+        let table = resolver::analyze(statements);
+        
+        let mut references = HashMap::new();
+        for (id, symbol) in table.symbols.iter().enumerate() {
+            for reference in &symbol.references {
+                references.insert(reference.span.start, id);
+            }
+        }
+        
+        let mut collector = Collector {
+            table: &table,
+            references,
+            graph: CallGraph::default(),
+            functions: HashMap::new(),
+            stack: Vec::new(),
+            calls: Vec::new(),
+            externals: HashMap::new(),
+        };
+        
+        let top_level = collector.add_node("(top level)".to_string(), CallNodeKind::TopLevel, None);
+        collector.stack.push(top_level);
+        
+        for statement in statements {
+            collector.visit_stmt(statement);
+        }
+        
+        collector.finish()
+    }
+    
+    // Positions are reported as 1-based lines and columns of `source`
+    pub fn to_json(&self, source: &str) -> String {
+        let nodes: Vec<String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(id, node)| {
+                let position = match node.span {
+                    Some(span) => {
+                        let (line, column) = line_column(source, span.start);
+                        format!("\"line\":{},\"column\":{}", line, column)
+                    },
+                    None => "\"line\":null,\"column\":null".to_string(),
+                };
+                
+                format!(
+                    "{{\"id\":{},\"name\":{},\"kind\":{},{}}}",
+                    id,
+                    json_string(&node.name),
+                    json_string(node_kind_name(node.kind)),
+                    position,
+                )
+            })
+            .collect();
+        
+        let edges: Vec<String> = self
+            .edges
+            .iter()
+            .map(|edge| format!("{{\"from\":{},\"to\":{},\"calls\":{}}}", edge.from, edge.to, edge.calls))
+            .collect();
+        
+        format!("{{\"nodes\":[{}],\"edges\":[{}]}}", nodes.join(","), edges.join(","))
+    }
+    
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph calls {\n");
+        
+        for (id, node) in self.nodes.iter().enumerate() {
+            let shape = match node.kind {
+                CallNodeKind::TopLevel => "box",
+                CallNodeKind::Function => "ellipse",
+                CallNodeKind::External => "plaintext",
+            };
+            out.push_str(&format!("  n{} [label={} shape={}];\n", id, json_string(&node.name), shape));
+        }
+        
+        for edge in &self.edges {
+            if edge.calls > 1 {
+                out.push_str(&format!("  n{} -> n{} [label=\"{}\"];\n", edge.from, edge.to, edge.calls));
+            } else {
+                out.push_str(&format!("  n{} -> n{};\n", edge.from, edge.to));
+            }
+        }
+        
+        out.push('}');
+        out
+    }
+}
+
+fn node_kind_name(kind: CallNodeKind) -> &'static str {
+    match kind {
+        CallNodeKind::TopLevel => "top-level",
+        CallNodeKind::Function => "function",
+        CallNodeKind::External => "external",
+    }
+}
+
+impl Visitor for Collector<'_> {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Function(name, _, body, span, _) => {
+                self.function(name.clone(), body, *span);
+            },
+            Stmt::Declaration(name, Some(Expr::Function(_, body, span, _)), name_span, _) => {
+                let node = self.function(name.clone(), body, *span);
+                self.functions.insert(name_span.start, node);
+            },
+            _ => walk_stmt(self, stmt),
+        }
+    }
+    
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Function(_, body, span, _) => {
+                self.function("(anonymous)".to_string(), body, *span);
+            },
+            Expr::Call(callee, ..) | Expr::New(callee, ..) => {
+                if let Expr::Variable(name, span) | Expr::Global(name, span, _) = callee.as_ref() {
+                    let caller = *self.stack.last().expect("top-level node");
+                    let callee = match self.references.get(&span.start) {
+                        Some(&symbol) => Callee::Symbol(symbol),
+                        None => Callee::External(name.clone()),
+                    };
+                    self.calls.push((caller, callee));
+                } else if let Expr::Get(object, method, _) = callee.as_ref()
+                    && let Expr::Variable(name, span) | Expr::Global(name, span, _) = object.as_ref()
+                    && !self.references.contains_key(&span.start)
+                {
+                    let caller = *self.stack.last().expect("top-level node");
+                    self.calls.push((caller, Callee::External(format!("{}.{}", name, method))));
+                }
+                
+                walk_expr(self, expr);
+            },
+            _ => walk_expr(self, expr),
+        }
+    }
+}
+
+impl Collector<'_> {
+    fn add_node(&mut self, name: String, kind: CallNodeKind, span: Option<Span>) -> usize {
+        self.graph.nodes.push(CallNode { name, kind, span });
+        self.graph.nodes.len() - 1
+    }
+    
+    fn function(&mut self, name: String, body: &Stmt, span: Span) -> usize {
+        let node = self.add_node(name, CallNodeKind::Function, Some(span));
+        self.functions.insert(span.start, node);
+        
+        self.stack.push(node);
+        self.visit_stmt(body);
+        self.stack.pop();
+        
+        node
+    }
+    
+    fn finish(mut self) -> CallGraph {
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+        
+        for (caller, callee) in std::mem::take(&mut self.calls) {
+            let target = match callee {
+                Callee::Symbol(symbol) => {
+                    let symbol = &self.table.symbols[symbol];
+                    
+                    // Parameters share their function's span, so only
+                    // declarations can name a function
+                    if symbol.kind == SymbolKind::Parameter {
+                        continue;
+                    }
+                    
+                    match self.functions.get(&symbol.span.start) {
+                        Some(&node) => node,
+                        None => continue,
+                    }
+                },
+                Callee::External(name) => match self.externals.get(&name) {
+                    Some(&node) => node,
+                    None => {
+                        let node = self.add_node(name.clone(), CallNodeKind::External, None);
+                        self.externals.insert(name, node);
+                        node
+                    },
+                },
+            };
+            
+            *edges.entry((caller, target)).or_insert(0) += 1;
+        }
+        
+        let mut edges: Vec<CallEdge> = edges
+            .into_iter()
+            .map(|((from, to), calls)| CallEdge { from, to, calls })
+            .collect();
+        edges.sort_by_key(|edge| (edge.from, edge.to));
+        
+        self.graph.edges = edges;
+        self.graph
+    }
+}
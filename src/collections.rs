@@ -0,0 +1,294 @@
+// src/collections.rs
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, NativeFunction, Value};
+use crate::iterator::{BuiltinIterator, Part, ValueIterator};
+use crate::object::{InternalSlot, PropertyMap};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Map and Set. Keys are compared with SameValueZero: primitives by value,
+// with NaN equal to itself and -0 equal to 0, and objects and functions by
+// identity. Entries are kept in insertion order.
+//
+// Both are iterated live, as for-of does with arrays: entries added during a
+// loop are visited and deleted ones are skipped. Deleting leaves a hole that
+// keeps the positions of loops in progress valid; the holes are squeezed
+// out once no loop is walking the collection.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CollectionKind {
+    Map,
+    Set,
+}
+
+pub struct Collection {
+    kind: CollectionKind,
+    entries: RefCell<Vec<Option<(Value, Value)>>>, // None for deleted entries; a set's values are its keys
+    positions: RefCell<HashMap<Key, usize>>,
+    cursors: Cell<usize>, // Loops walking the collection
+}
+
+// A key as SameValueZero compares it
+#[derive(PartialEq, Eq, Hash)]
+enum Key {
+    Number(u64), // Bits of the number, with every NaN and both zeros made the same
+    String(Rc<str>),
+    Boolean(bool),
+    Null,
+    Undefined,
+    Identity(*const ()),
+}
+
+impl Key {
+    fn new(value: &Value) -> Key {
+        match value {
+            Value::Number(n) if n.is_nan() => Key::Number(f64::NAN.to_bits()),
+            Value::Number(n) if *n == 0.0 => Key::Number(0),
+            Value::Number(n) => Key::Number(n.to_bits()),
+            Value::String(s) => Key::String(Rc::from(s.as_str())),
+            Value::Boolean(b) => Key::Boolean(*b),
+            Value::Null => Key::Null,
+            Value::Undefined => Key::Undefined,
+            Value::Function(function) => Key::Identity(Rc::as_ptr(function) as *const ()),
+            Value::NativeFunction(function) => Key::Identity(Rc::as_ptr(function) as *const ()),
+            Value::Object(object) => Key::Identity(Rc::as_ptr(object) as *const ()),
+        }
+    }
+}
+
+impl Collection {
+    pub fn new(kind: CollectionKind) -> Rc<Collection> {
+        Rc::new(Collection {
+            kind,
+            entries: RefCell::new(Vec::new()),
+            positions: RefCell::new(HashMap::new()),
+            cursors: Cell::new(0),
+        })
+    }
+    
+    // The collection behind a Map or Set object
+    pub fn of(value: &Value) -> Option<Rc<Collection>> {
+        match value {
+            Value::Object(object) => match object.borrow().slot() {
+                Some(InternalSlot::Collection(collection)) => Some(Rc::clone(collection)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    
+    pub fn into_value(self: Rc<Self>) -> Value {
+        Value::Object(Rc::new(RefCell::new(PropertyMap::with_slot(InternalSlot::Collection(self)))))
+    }
+    
+    pub fn kind(&self) -> CollectionKind {
+        self.kind
+    }
+    
+    pub fn len(&self) -> usize {
+        self.positions.borrow().len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    pub fn get(&self, key: &Value) -> Option<Value> {
+        let position = *self.positions.borrow().get(&Key::new(key))?;
+        self.entries.borrow()[position].as_ref().map(|(_, value)| value.clone())
+    }
+    
+    pub fn has(&self, key: &Value) -> bool {
+        self.positions.borrow().contains_key(&Key::new(key))
+    }
+    
+    // Replaces the value of an existing key in place, or adds the key at
+    // the end. -0 is stored as 0. Sets are given their values as keys.
+    pub fn set(&self, key: Value, value: Value) {
+        // Adding 0 turns -0 into 0 and leaves every other number as it is
+        let key = match key {
+            Value::Number(n) => Value::Number(n + 0.0),
+            key => key,
+        };
+        let value = match self.kind {
+            CollectionKind::Map => value,
+            CollectionKind::Set => key.clone(),
+        };
+        let mut positions = self.positions.borrow_mut();
+        let mut entries = self.entries.borrow_mut();
+        match positions.get(&Key::new(&key)) {
+            Some(&position) => {
+                if let Some((_, old)) = &mut entries[position] {
+                    *old = value;
+                }
+            },
+            None => {
+                positions.insert(Key::new(&key), entries.len());
+                entries.push(Some((key, value)));
+            },
+        }
+    }
+    
+    pub fn delete(&self, key: &Value) -> bool {
+        let Some(position) = self.positions.borrow_mut().remove(&Key::new(key)) else {
+            return false;
+        };
+        self.entries.borrow_mut()[position] = None;
+        self.compact();
+        true
+    }
+    
+    pub fn clear(&self) {
+        self.positions.borrow_mut().clear();
+        self.entries.borrow_mut().iter_mut().for_each(|entry| *entry = None);
+        self.compact();
+    }
+    
+    // The entries in order, as key and value
+    pub fn entries(&self) -> Vec<(Value, Value)> {
+        self.entries.borrow().iter().flatten().cloned().collect()
+    }
+    
+    // Squeezes out the holes left by deletions once they make up most of
+    // the entries, unless a loop is walking the collection
+    fn compact(&self) {
+        let mut entries = self.entries.borrow_mut();
+        if self.cursors.get() > 0 || entries.len() < 2 * self.len() {
+            return;
+        }
+        entries.retain(Option::is_some);
+        let mut positions = self.positions.borrow_mut();
+        for (position, (key, _)) in entries.iter().flatten().enumerate() {
+            positions.insert(Key::new(key), position);
+        }
+    }
+}
+
+// A position in a collection, for walking it live. While one exists the
+// collection keeps its holes.
+pub struct Cursor {
+    collection: Rc<Collection>,
+    position: usize,
+}
+
+impl Cursor {
+    pub fn new(collection: &Rc<Collection>) -> Self {
+        collection.cursors.set(collection.cursors.get() + 1);
+        Cursor {
+            collection: Rc::clone(collection),
+            position: 0,
+        }
+    }
+    
+    pub fn kind(&self) -> CollectionKind {
+        self.collection.kind
+    }
+}
+
+impl Iterator for Cursor {
+    type Item = (Value, Value);
+    
+    fn next(&mut self) -> Option<(Value, Value)> {
+        let entries = self.collection.entries.borrow();
+        while let Some(entry) = entries.get(self.position) {
+            self.position += 1;
+            if let Some(entry) = entry {
+                return Some(entry.clone());
+            }
+        }
+        None
+    }
+}
+
+impl Drop for Cursor {
+    fn drop(&mut self) {
+        self.collection.cursors.set(self.collection.cursors.get() - 1);
+        self.collection.compact();
+    }
+}
+
+// The methods of a Map or Set object, and its size. Methods that give back
+// the collection itself, so that calls can be chained, return `this`.
+pub(crate) fn method(collection: &Rc<Collection>, name: &str, this: &Value) -> Option<Value> {
+    let (collection, this) = (Rc::clone(collection), this.clone());
+    let kind = collection.kind;
+    let method: Rc<NativeFunction> = match (kind, name) {
+        (_, "size") => return Some(Value::Number(collection.len() as f64)),
+        (CollectionKind::Map, "get") => Rc::new(move |arguments| Ok(collection.get(&argument(arguments, 0)).unwrap_or(Value::Undefined))),
+        (CollectionKind::Map, "set") => Rc::new(move |arguments| {
+            collection.set(argument(arguments, 0), argument(arguments, 1));
+            Ok(this.clone())
+        }),
+        (CollectionKind::Set, "add") => Rc::new(move |arguments| {
+            collection.set(argument(arguments, 0), Value::Undefined);
+            Ok(this.clone())
+        }),
+        (_, "has") => Rc::new(move |arguments| Ok(Value::Boolean(collection.has(&argument(arguments, 0))))),
+        (_, "delete") => Rc::new(move |arguments| Ok(Value::Boolean(collection.delete(&argument(arguments, 0))))),
+        (_, "clear") => Rc::new(move |_| {
+            collection.clear();
+            Ok(Value::Undefined)
+        }),
+        (_, "keys" | "values" | "entries") => {
+            let part = match name {
+                "keys" => Part::Keys,
+                "values" => Part::Values,
+                _ => Part::Entries,
+            };
+            Rc::new(move |_| Ok(BuiltinIterator::Collection(Cursor::new(&collection), part).into_value()))
+        },
+        (_, "forEach") => {
+            let for_each = PropertyMap::callable(Rc::new(move |interpreter: &mut Interpreter, arguments: &[Value]| {
+                let callback = argument(arguments, 0);
+                if !callback.is_callable() {
+                    return Err(JsEngineError::TypeError {
+                        message: format!("{:?} is not a function", callback),
+                    });
+                }
+                for (key, value) in Cursor::new(&collection) {
+                    interpreter.call_function(&callback, &[value, key, this.clone()])?;
+                }
+                Ok(Value::Undefined)
+            }));
+            return Some(Value::Object(Rc::new(RefCell::new(for_each))));
+        },
+        _ => return None,
+    };
+    Some(Value::NativeFunction(method))
+}
+
+// The globals `Map(iterable)` and `Set(iterable)`, which do what `new Map`
+// and `new Set` do. A map is filled from [key, value] pairs.
+pub(crate) fn constructor(kind: CollectionKind) -> Value {
+    let constructor = PropertyMap::callable(Rc::new(move |interpreter, arguments| {
+        let collection = Collection::new(kind);
+        let iterable = argument(arguments, 0);
+        if matches!(iterable, Value::Undefined | Value::Null) {
+            return Ok(collection.into_value());
+        }
+        
+        let mut items = ValueIterator::new(interpreter, &iterable)?;
+        while let Some(item) = items.next_value(interpreter)? {
+            match kind {
+                CollectionKind::Set => collection.set(item, Value::Undefined),
+                CollectionKind::Map => {
+                    let Value::Object(pair) = &item else {
+                        return Err(JsEngineError::TypeError {
+                            message: format!("Iterator value {} is not an entry object", item),
+                        });
+                    };
+                    let pair = pair.borrow();
+                    let element = |index: &str| pair.get(index).cloned().unwrap_or(Value::Undefined);
+                    collection.set(element("0"), element("1"));
+                },
+            }
+        }
+        Ok(collection.into_value())
+    }));
+    Value::Object(Rc::new(RefCell::new(constructor)))
+}
+
+fn argument(arguments: &[Value], index: usize) -> Value {
+    arguments.get(index).cloned().unwrap_or(Value::Undefined)
+}
@@ -0,0 +1,212 @@
+// src/console.rs
+use crate::error::JsEngineError;
+use crate::heap;
+use crate::inspect::inspect;
+use crate::interpreter::{Environment, Interpreter, Output, Value};
+use crate::object::{InternalSlot, PropertyMap};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Write;
+use std::rc::{Rc, Weak};
+
+// The console object. log, info and debug write to the interpreter's
+// output, stdout by default, and error and warn to its error output, stderr
+// by default. Each prints its arguments inspected and separated by spaces,
+// as one line. table, the timers and the counters write to the output too,
+// and warn on the error output about labels they do not know.
+//
+// console.memory reports heap statistics for everything reachable from
+// `globals`, gathered again each time it is read.
+pub(crate) fn console(output: &Output, error_output: &Output, globals: &Rc<RefCell<Environment>>) -> Value {
+    let timers = Rc::new(RefCell::new(HashMap::new()));
+    let counts = Rc::new(RefCell::new(HashMap::new()));
+    let mut console = PropertyMap::with_slot(InternalSlot::Console(Rc::new(ConsoleState {
+        globals: Rc::downgrade(globals),
+    })));
+    for (name, method) in [
+        ("log", printer("log", output)),
+        ("info", printer("info", output)),
+        ("debug", printer("debug", output)),
+        ("error", printer("error", error_output)),
+        ("warn", printer("warn", error_output)),
+        ("table", table(output)),
+        ("time", timer("time", output, error_output, &timers)),
+        ("timeLog", timer("timeLog", output, error_output, &timers)),
+        ("timeEnd", timer("timeEnd", output, error_output, &timers)),
+        ("count", counter("count", output, error_output, &counts)),
+        ("countReset", counter("countReset", output, error_output, &counts)),
+    ] {
+        console.insert(name.to_string(), method);
+    }
+    Value::Object(Rc::new(RefCell::new(console)))
+}
+
+// Held weakly, since the console is itself stored in the globals
+pub struct ConsoleState {
+    globals: Weak<RefCell<Environment>>,
+}
+
+impl ConsoleState {
+    pub(crate) fn property(&self, name: &str) -> Option<Value> {
+        match name {
+            "memory" => self.globals.upgrade().map(|globals| heap::collect_stats(&[&globals]).to_value()),
+            _ => None,
+        }
+    }
+}
+
+fn printer(name: &'static str, output: &Output) -> Value {
+    let output = Rc::clone(output);
+    Value::NativeFunction(Rc::new(move |args: &[Value]| -> Result<Value, JsEngineError> {
+        let line = args.iter().map(inspect).collect::<Vec<_>>().join(" ");
+        write(&output, name, &line)
+    }))
+}
+
+// console.table(data, columns). There is a row for each property of `data`,
+// and a column for each property of the rows that are objects, in the order
+// they are first seen; rows that are not objects fill a Values column. An
+// array of names as the second argument picks and orders the columns.
+// Anything other than an object is printed as console.log would print it.
+fn table(output: &Output) -> Value {
+    let output = Rc::clone(output);
+    Value::NativeFunction(Rc::new(move |args: &[Value]| -> Result<Value, JsEngineError> {
+        let data = args.first().cloned().unwrap_or(Value::Undefined);
+        let Value::Object(data) = &data else {
+            return write(&output, "table", &inspect(&data));
+        };
+        let rows: Vec<(String, Value)> = data.borrow().iter().map(|(key, value)| (key.clone(), value.clone())).collect();
+        
+        let mut columns: Vec<String> = Vec::new();
+        let mut has_values = false;
+        for (_, row) in &rows {
+            match row {
+                Value::Object(row) => {
+                    for key in row.borrow().keys() {
+                        if !columns.contains(key) {
+                            columns.push(key.clone());
+                        }
+                    }
+                },
+                _ => has_values = true,
+            }
+        }
+        if let Some(Value::Object(chosen)) = args.get(1)
+            && chosen.borrow().is_array()
+        {
+            columns = chosen.borrow().values().map(Value::to_string).collect();
+        }
+        
+        let mut header = vec!["(index)".to_string()];
+        header.extend(columns.iter().cloned());
+        if has_values {
+            header.push("Values".to_string());
+        }
+        let mut lines = vec![header];
+        for (key, row) in &rows {
+            let mut line = vec![key.clone()];
+            match row {
+                Value::Object(row) => {
+                    let row = row.borrow();
+                    line.extend(columns.iter().map(|column| row.get(column).map(inspect).unwrap_or_default()));
+                    if has_values {
+                        line.push(String::new());
+                    }
+                },
+                _ => {
+                    line.extend(columns.iter().map(|_| String::new()));
+                    line.push(inspect(row));
+                },
+            }
+            lines.push(line);
+        }
+        
+        let mut widths = vec![0; lines[0].len()];
+        for line in &lines {
+            for (width, cell) in widths.iter_mut().zip(line) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let border = format!("+{}+", widths.iter().map(|width| "-".repeat(width + 2)).collect::<Vec<_>>().join("+"));
+        let mut text = vec![border.clone()];
+        for (index, line) in lines.iter().enumerate() {
+            let cells: Vec<String> = line.iter().zip(&widths).map(|(cell, &width)| format!("{:<width$}", cell)).collect();
+            text.push(format!("| {} |", cells.join(" | ")));
+            if index == 0 {
+                text.push(border.clone());
+            }
+        }
+        text.push(border);
+        write(&output, "table", &text.join("\n"))
+    }))
+}
+
+// console.time(label) starts a timer, read by the interpreter's clock.
+// console.timeLog(label, ...data) prints the milliseconds since, followed by
+// any data, and console.timeEnd(label) prints them and stops the timer.
+fn timer(name: &'static str, output: &Output, error_output: &Output, timers: &Rc<RefCell<HashMap<String, f64>>>) -> Value {
+    let (output, error_output, timers) = (Rc::clone(output), Rc::clone(error_output), Rc::clone(timers));
+    let timer = PropertyMap::callable(Rc::new(move |interpreter: &mut Interpreter, args: &[Value]| {
+        let label = label(args);
+        let now = interpreter.clock().now();
+        let mut timers = timers.borrow_mut();
+        match (name, timers.get(&label).copied()) {
+            ("time", Some(_)) => write(&error_output, name, &format!("Warning: Label '{}' already exists for console.time()", label)),
+            ("time", None) => {
+                timers.insert(label, now);
+                Ok(Value::Undefined)
+            },
+            (_, None) => write(&error_output, name, &format!("Warning: No such label '{}' for console.{}()", label, name)),
+            (_, Some(start)) => {
+                let mut line = format!("{}: {:.3}ms", label, now - start);
+                if name == "timeEnd" {
+                    timers.remove(&label);
+                } else {
+                    for data in args.iter().skip(1) {
+                        line.push(' ');
+                        line.push_str(&inspect(data));
+                    }
+                }
+                write(&output, name, &line)
+            },
+        }
+    }));
+    Value::Object(Rc::new(RefCell::new(timer)))
+}
+
+// console.count(label) prints how many times it has been called with the
+// label, and console.countReset(label) starts that count again
+fn counter(name: &'static str, output: &Output, error_output: &Output, counts: &Rc<RefCell<HashMap<String, u64>>>) -> Value {
+    let (output, error_output, counts) = (Rc::clone(output), Rc::clone(error_output), Rc::clone(counts));
+    Value::NativeFunction(Rc::new(move |args: &[Value]| -> Result<Value, JsEngineError> {
+        let label = label(args);
+        let mut counts = counts.borrow_mut();
+        if name == "count" {
+            let count = counts.entry(label.clone()).or_insert(0);
+            *count += 1;
+            return write(&output, name, &format!("{}: {}", label, count));
+        }
+        match counts.get_mut(&label) {
+            Some(count) => {
+                *count = 0;
+                Ok(Value::Undefined)
+            },
+            None => write(&error_output, name, &format!("Warning: Count for '{}' does not exist", label)),
+        }
+    }))
+}
+
+// The label argument of the timers and counters, "default" when missing
+fn label(args: &[Value]) -> String {
+    match args.first() {
+        None | Some(Value::Undefined) => "default".to_string(),
+        Some(label) => label.to_string(),
+    }
+}
+
+fn write(output: &Output, name: &str, text: &str) -> Result<Value, JsEngineError> {
+    writeln!(output.borrow_mut(), "{}", text).map_err(|error| JsEngineError::RuntimeError {
+        message: format!("console.{} failed: {}", name, error),
+    })?;
+    Ok(Value::Undefined)
+}
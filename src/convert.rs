@@ -0,0 +1,228 @@
+// src/convert.rs
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, Value};
+use crate::object::PropertyMap;
+use crate::string::JsString;
+use crate::typed_array::{self, TypedArray};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Conversions between Rust and script values for native bindings. The
+// `#[js_function]` and `#[derive(JsClass)]` macros from the derive feature
+// generate code on top of these.
+
+pub trait IntoValue {
+    fn into_value(self) -> Value;
+}
+
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, JsEngineError>;
+}
+
+// A Rust struct exposed to scripts as a plain object, with a global
+// constructor function taking its fields in declaration order
+pub trait JsClass: IntoValue + FromValue {
+    const NAME: &'static str;
+    
+    fn constructor() -> Value;
+    
+    fn register(interpreter: &mut Interpreter) {
+        interpreter.define_global(Self::NAME, Self::constructor());
+    }
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Value {
+        self
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Result<Self, JsEngineError> {
+        Ok(value.clone())
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self) -> Value {
+        Value::Undefined
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self) -> Value {
+        Value::Boolean(self)
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self, JsEngineError> {
+        match value {
+            Value::Boolean(b) => Ok(*b),
+            _ => Err(expected("a boolean", value)),
+        }
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(self) -> Value {
+        Value::Number(self)
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Result<Self, JsEngineError> {
+        match value {
+            Value::Number(n) => Ok(*n),
+            _ => Err(expected("a number", value)),
+        }
+    }
+}
+
+// Integers convert from numbers that are whole and in range
+macro_rules! integer_conversions {
+    ($($type:ty),*) => {
+        $(
+            impl IntoValue for $type {
+                fn into_value(self) -> Value {
+                    Value::Number(self as f64)
+                }
+            }
+            
+            impl FromValue for $type {
+                fn from_value(value: &Value) -> Result<Self, JsEngineError> {
+                    match value {
+                        Value::Number(n) if n.fract() == 0.0 && *n >= <$type>::MIN as f64 && *n <= <$type>::MAX as f64 => Ok(*n as $type),
+                        _ => Err(expected(concat!("an integer in the range of ", stringify!($type)), value)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integer_conversions!(i32, i64, u32, u64, usize);
+
+impl IntoValue for String {
+    fn into_value(self) -> Value {
+        Value::String(JsString::from(self))
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> Value {
+        Value::String(JsString::from(self))
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, JsEngineError> {
+        match value {
+            Value::String(s) => Ok(s.to_string()),
+            _ => Err(expected("a string", value)),
+        }
+    }
+}
+
+// Byte buffers are Uint8Arrays, and any ArrayBuffer or view onto one gives
+// its bytes back
+impl IntoValue for Vec<u8> {
+    fn into_value(self) -> Value {
+        TypedArray::from_bytes(self).into_value()
+    }
+}
+
+impl FromValue for Vec<u8> {
+    fn from_value(value: &Value) -> Result<Self, JsEngineError> {
+        typed_array::bytes_of(value).ok_or_else(|| expected("binary data", value))
+    }
+}
+
+// None is undefined; both null and undefined convert to None
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Value {
+        self.map_or(Value::Undefined, IntoValue::into_value)
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Result<Self, JsEngineError> {
+        match value {
+            Value::Null | Value::Undefined => Ok(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+pub fn native(function: impl Fn(&[Value]) -> Result<Value, JsEngineError> + 'static) -> Value {
+    Value::NativeFunction(Rc::new(function))
+}
+
+// A native function that keeps mutable state between calls, such as a
+// counter or a connection:
+//
+//     let mut count = 0;
+//     interpreter.define_global("next", native_mut(move |_| {
+//         count += 1;
+//         Ok(Value::Number(count as f64))
+//     }));
+//
+// A call that reaches the same function again before returning, through a
+// host that calls back into the script, fails instead of aliasing the state.
+pub fn native_mut(function: impl FnMut(&[Value]) -> Result<Value, JsEngineError> + 'static) -> Value {
+    let function = RefCell::new(function);
+    Value::NativeFunction(Rc::new(move |arguments: &[Value]| {
+        let mut function = function.try_borrow_mut().map_err(|_| JsEngineError::RuntimeError {
+            message: "a stateful native function cannot be called while it is running".to_string(),
+        })?;
+        function(arguments)
+    }))
+}
+
+pub fn object(properties: Vec<(&str, Value)>) -> Value {
+    let properties: PropertyMap = properties.into_iter().collect();
+    Value::Object(Rc::new(RefCell::new(properties)))
+}
+
+// Reads and converts a property; missing properties are undefined
+pub fn property<T: FromValue>(value: &Value, name: &str, class: &str) -> Result<T, JsEngineError> {
+    let Value::Object(properties) = value else {
+        return Err(expected(&format!("a {} object", class), value));
+    };
+    
+    let property = properties.borrow().get(name).cloned().unwrap_or(Value::Undefined);
+    T::from_value(&property).map_err(|error| JsEngineError::TypeError {
+        message: format!("{}.{}: {}", class, name, message(error)),
+    })
+}
+
+// Converts an argument; missing arguments are undefined
+pub fn argument<T: FromValue>(arguments: &[Value], index: usize, function: &str) -> Result<T, JsEngineError> {
+    T::from_value(arguments.get(index).unwrap_or(&Value::Undefined)).map_err(|error| JsEngineError::TypeError {
+        message: format!("{}: argument {}: {}", function, index + 1, message(error)),
+    })
+}
+
+// Extra arguments are ignored, as they are for script functions
+pub fn check_arity(arguments: &[Value], required: usize, function: &str) -> Result<(), JsEngineError> {
+    if arguments.len() < required {
+        return Err(JsEngineError::TypeError {
+            message: format!("{} expects {} argument{}, got {}", function, required, if required == 1 { "" } else { "s" }, arguments.len()),
+        });
+    }
+    
+    Ok(())
+}
+
+fn expected(what: &str, value: &Value) -> JsEngineError {
+    JsEngineError::TypeError {
+        message: format!("expected {}, got {:?}", what, value),
+    }
+}
+
+fn message(error: JsEngineError) -> String {
+    match error {
+        JsEngineError::TypeError { message } => message,
+        error => error.to_string(),
+    }
+}
@@ -1,7 +1,13 @@
 // src/error.rs
+use crate::interpreter::Value;
+use crate::object::PropertyMap;
+use crate::string::JsString;
+use std::cell::RefCell;
+use std::rc::Rc;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
+#[allow(dead_code, clippy::enum_variant_names)]
 pub enum JsEngineError {
     #[error("Lexer error at position {position}: {message}")]
     LexerError { position: usize, message: String },
@@ -20,4 +26,90 @@ pub enum JsEngineError {
     
     #[error("Syntax error: {message}")]
     SyntaxError { message: String },
-}
\ No newline at end of file
+    
+    // A JS value thrown as an exception, such as an error object a native
+    // function raised with `JsEngineError::throw`
+    #[error("Uncaught {}", describe(value))]
+    Thrown { value: Value },
+}
+
+impl JsEngineError {
+    // An error object with the given constructor name, e.g.
+    // `JsEngineError::throw("RangeError", "index out of range")`, for native
+    // functions that fail the way built-ins do
+    pub fn throw(name: &str, message: impl Into<String>) -> Self {
+        JsEngineError::Thrown {
+            value: error_object(name, &message.into()),
+        }
+    }
+    
+    // Throws an arbitrary value, as a script's `throw` statement can
+    pub fn thrown(value: Value) -> Self {
+        JsEngineError::Thrown { value }
+    }
+    
+    // The JS error type an engine error corresponds to; thrown values,
+    // which need not be errors at all, report "Error"
+    pub fn name(&self) -> &str {
+        match self {
+            JsEngineError::LexerError { .. } | JsEngineError::ParserError { .. } | JsEngineError::SyntaxError { .. } => "SyntaxError",
+            JsEngineError::RuntimeError { .. } => "Error",
+            JsEngineError::TypeError { .. } => "TypeError",
+            JsEngineError::ReferenceError { .. } => "ReferenceError",
+            JsEngineError::Thrown { .. } => "Error",
+        }
+    }
+    
+    // The message of an error raised by the engine; None for thrown values
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            JsEngineError::Thrown { .. } => None,
+            JsEngineError::LexerError { message, .. }
+            | JsEngineError::ParserError { message, .. }
+            | JsEngineError::RuntimeError { message }
+            | JsEngineError::TypeError { message }
+            | JsEngineError::ReferenceError { message }
+            | JsEngineError::SyntaxError { message } => Some(message),
+        }
+    }
+    
+    // The error as it leaves a script that did not catch it: errors raised
+    // by the engine become the error object a catch block would have seen,
+    // so that they print like thrown ones, e.g. "Uncaught TypeError: x is
+    // not a function"
+    pub fn uncaught(self) -> Self {
+        match self {
+            JsEngineError::Thrown { .. } => self,
+            error => JsEngineError::thrown(error.to_value()),
+        }
+    }
+    
+    // The exception as scripts see it: the thrown value itself, or an error
+    // object with `name` and `message` for errors raised by the engine
+    pub fn to_value(&self) -> Value {
+        match (self, self.message()) {
+            (JsEngineError::Thrown { value }, _) => value.clone(),
+            (_, message) => error_object(self.name(), message.unwrap_or_default()),
+        }
+    }
+}
+
+fn error_object(name: &str, message: &str) -> Value {
+    let properties = PropertyMap::from([
+        ("name", Value::String(JsString::from(name))),
+        ("message", Value::String(JsString::from(message))),
+    ]);
+    Value::Object(Rc::new(RefCell::new(properties)))
+}
+
+// "Name: message" for error objects, otherwise the value as console.log
+// prints it
+fn describe(value: &Value) -> String {
+    if let Value::Object(properties) = value {
+        let properties = properties.borrow();
+        if let (Some(Value::String(name)), Some(Value::String(message))) = (properties.lookup("name"), properties.lookup("message")) {
+            return if message.is_empty() { name.to_string() } else { format!("{}: {}", name, message) };
+        }
+    }
+    value.to_string()
+}
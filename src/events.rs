@@ -0,0 +1,111 @@
+// src/events.rs
+use crate::error::JsEngineError;
+use crate::interpreter::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+// Script handlers for named events raised by the host, e.g. a game's "tick".
+// Scripts subscribe with the on, once and off globals and the host raises
+// events with Interpreter::emit.
+#[derive(Default)]
+pub struct EventHandlers {
+    handlers: HashMap<String, Vec<Handler>>,
+}
+
+#[derive(Clone)]
+pub struct Handler {
+    pub callback: Value,
+    pub once: bool,
+}
+
+impl EventHandlers {
+    pub fn add(&mut self, event: &str, callback: Value, once: bool) -> Result<(), JsEngineError> {
+        if !callback.is_callable() {
+            return Err(JsEngineError::TypeError {
+                message: format!("Handler for '{}' must be a function, got {:?}", event, callback),
+            });
+        }
+        
+        self.handlers.entry(event.to_string()).or_default().push(Handler { callback, once });
+        Ok(())
+    }
+    
+    // Removes every registration of `callback` for the event; without a
+    // callback, removes all of the event's handlers
+    pub fn remove(&mut self, event: &str, callback: Option<&Value>) {
+        match callback {
+            Some(callback) => {
+                if let Some(handlers) = self.handlers.get_mut(event) {
+                    handlers.retain(|handler| !same_function(&handler.callback, callback));
+                }
+            },
+            None => {
+                self.handlers.remove(event);
+            },
+        }
+    }
+    
+    // The handlers to call for one emit, in registration order. Once-handlers
+    // are unregistered as they are taken.
+    pub fn take(&mut self, event: &str) -> Vec<Handler> {
+        let Some(handlers) = self.handlers.get_mut(event) else {
+            return Vec::new();
+        };
+        
+        let taken = handlers.clone();
+        handlers.retain(|handler| !handler.once);
+        taken
+    }
+    
+    pub fn count(&self, event: &str) -> usize {
+        self.handlers.get(event).map_or(0, Vec::len)
+    }
+}
+
+// The on, once and off globals. The handler table is held weakly, since
+// registered handlers can reach these functions.
+pub fn globals(handlers: &Rc<RefCell<EventHandlers>>) -> Vec<(&'static str, Value)> {
+    vec![
+        ("on", subscribe(Rc::downgrade(handlers), false)),
+        ("once", subscribe(Rc::downgrade(handlers), true)),
+        ("off", {
+            let handlers = Rc::downgrade(handlers);
+            Value::NativeFunction(Rc::new(move |arguments: &[Value]| -> Result<Value, JsEngineError> {
+                let event = event_name(arguments, "off")?;
+                if let Some(handlers) = handlers.upgrade() {
+                    handlers.borrow_mut().remove(&event, arguments.get(1));
+                }
+                Ok(Value::Undefined)
+            }))
+        }),
+    ]
+}
+
+fn subscribe(handlers: Weak<RefCell<EventHandlers>>, once: bool) -> Value {
+    Value::NativeFunction(Rc::new(move |arguments: &[Value]| -> Result<Value, JsEngineError> {
+        let event = event_name(arguments, if once { "once" } else { "on" })?;
+        let callback = arguments.get(1).cloned().unwrap_or(Value::Undefined);
+        if let Some(handlers) = handlers.upgrade() {
+            handlers.borrow_mut().add(&event, callback, once)?;
+        }
+        Ok(Value::Undefined)
+    }))
+}
+
+fn event_name(arguments: &[Value], function: &str) -> Result<String, JsEngineError> {
+    match arguments.first() {
+        Some(Value::String(name)) => Ok(name.to_string()),
+        _ => Err(JsEngineError::TypeError {
+            message: format!("{}: event name must be a string", function),
+        }),
+    }
+}
+
+fn same_function(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+        (Value::NativeFunction(a), Value::NativeFunction(b)) => std::ptr::addr_eq(Rc::as_ptr(a), Rc::as_ptr(b)),
+        _ => false,
+    }
+}
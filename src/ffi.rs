@@ -0,0 +1,226 @@
+// src/ffi.rs
+#![allow(clippy::missing_safety_doc)]
+
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, Value};
+use crate::string::JsString;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::ptr;
+use std::rc::Rc;
+
+// C interface for embedding the engine in C, C++, Go and other hosts that
+// can call a C library. The declarations are in include/jays.h.
+//
+// Engines and values are opaque heap objects owned by the caller and must be
+// released with jays_engine_free and jays_value_free. Strings returned to the
+// caller are released with jays_string_free. An engine may only be used from
+// the thread that created it.
+
+pub struct JaysEngine {
+    interpreter: Interpreter,
+    last_error: Option<CString>,
+}
+
+pub struct JaysValue(Value);
+
+// Value kinds reported by jays_value_type
+pub const JAYS_UNDEFINED: c_int = 0;
+pub const JAYS_NULL: c_int = 1;
+pub const JAYS_BOOLEAN: c_int = 2;
+pub const JAYS_NUMBER: c_int = 3;
+pub const JAYS_STRING: c_int = 4;
+pub const JAYS_FUNCTION: c_int = 5;
+pub const JAYS_OBJECT: c_int = 6;
+
+// Host function: receives the registered user data and the arguments, which
+// are only valid for the duration of the call, and returns a new value owned
+// by the engine, or NULL for undefined
+pub type JaysCallback = unsafe extern "C" fn(user_data: *mut c_void, arguments: *const *const JaysValue, count: usize) -> *mut JaysValue;
+
+#[unsafe(no_mangle)]
+pub extern "C" fn jays_engine_new() -> *mut JaysEngine {
+    Box::into_raw(Box::new(JaysEngine {
+        interpreter: Interpreter::new(),
+        last_error: None,
+    }))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jays_engine_free(engine: *mut JaysEngine) {
+    if !engine.is_null() {
+        drop(unsafe { Box::from_raw(engine) });
+    }
+}
+
+// Runs a script in the engine's global scope. Returns its result, or NULL if
+// it failed, in which case jays_last_error describes why.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jays_eval(engine: *mut JaysEngine, source: *const c_char) -> *mut JaysValue {
+    let engine = unsafe { &mut *engine };
+    let source = unsafe { CStr::from_ptr(source) }.to_string_lossy();
+    
+    let result = engine.interpreter.eval(&source);
+    engine.finish(result)
+}
+
+// Calls a function value with `count` arguments. Returns NULL on failure, like
+// jays_eval.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jays_call(engine: *mut JaysEngine, function: *const JaysValue, arguments: *const *const JaysValue, count: usize) -> *mut JaysValue {
+    let engine = unsafe { &mut *engine };
+    let function = unsafe { &(*function).0 };
+    let arguments = unsafe { arguments_from_raw(arguments, count) };
+    
+    let result = engine.interpreter.call_function(function, &arguments);
+    engine.finish(result)
+}
+
+// Message for the most recent failure, or NULL. The string belongs to the
+// engine and stays valid until its next eval or call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jays_last_error(engine: *const JaysEngine) -> *const c_char {
+    match unsafe { &(*engine).last_error } {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+// Defines a global function that calls back into the host. Returns 0 on
+// success and -1 if `name` is not valid UTF-8.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jays_register_fn(engine: *mut JaysEngine, name: *const c_char, callback: JaysCallback, user_data: *mut c_void) -> c_int {
+    let engine = unsafe { &mut *engine };
+    let Ok(name) = unsafe { CStr::from_ptr(name) }.to_str() else {
+        return -1;
+    };
+    
+    let function = Rc::new(move |arguments: &[Value]| -> Result<Value, JsEngineError> {
+        let arguments: Vec<JaysValue> = arguments.iter().cloned().map(JaysValue).collect();
+        let pointers: Vec<*const JaysValue> = arguments.iter().map(|argument| argument as *const JaysValue).collect();
+        
+        let result = unsafe { callback(user_data, pointers.as_ptr(), pointers.len()) };
+        if result.is_null() {
+            Ok(Value::Undefined)
+        } else {
+            Ok(unsafe { Box::from_raw(result) }.0)
+        }
+    });
+    
+    engine.interpreter.define_global(name, Value::NativeFunction(function));
+    0
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jays_value_free(value: *mut JaysValue) {
+    if !value.is_null() {
+        drop(unsafe { Box::from_raw(value) });
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jays_value_type(value: *const JaysValue) -> c_int {
+    match unsafe { &(*value).0 } {
+        Value::Undefined => JAYS_UNDEFINED,
+        Value::Null => JAYS_NULL,
+        Value::Boolean(_) => JAYS_BOOLEAN,
+        Value::Number(_) => JAYS_NUMBER,
+        Value::String(_) => JAYS_STRING,
+        Value::Function(_) | Value::NativeFunction(_) => JAYS_FUNCTION,
+        Value::Object(_) => JAYS_OBJECT,
+    }
+}
+
+// NaN for values that are not numbers
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jays_value_as_number(value: *const JaysValue) -> f64 {
+    match unsafe { &(*value).0 } {
+        Value::Number(n) => *n,
+        _ => f64::NAN,
+    }
+}
+
+// JavaScript truthiness, as 0 or 1
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jays_value_as_bool(value: *const JaysValue) -> c_int {
+    let truthy = match unsafe { &(*value).0 } {
+        Value::Boolean(b) => *b,
+        Value::Null | Value::Undefined => false,
+        Value::Number(n) => *n != 0.0 && !n.is_nan(),
+        Value::String(s) => !s.is_empty(),
+        _ => true,
+    };
+    truthy as c_int
+}
+
+// The value converted to a string as console.log would print it. Interior NUL
+// characters end the string early.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jays_value_to_string(value: *const JaysValue) -> *mut c_char {
+    let text = unsafe { &(*value).0 }.to_string();
+    let text = text.split('\0').next().unwrap_or_default();
+    CString::new(text).unwrap_or_default().into_raw()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jays_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn jays_value_undefined() -> *mut JaysValue {
+    into_raw(Value::Undefined)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn jays_value_null() -> *mut JaysValue {
+    into_raw(Value::Null)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn jays_value_bool(value: c_int) -> *mut JaysValue {
+    into_raw(Value::Boolean(value != 0))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn jays_value_number(value: f64) -> *mut JaysValue {
+    into_raw(Value::Number(value))
+}
+
+// Copies a NUL-terminated string; invalid UTF-8 is replaced
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jays_value_string(value: *const c_char) -> *mut JaysValue {
+    let value = unsafe { CStr::from_ptr(value) }.to_string_lossy();
+    into_raw(Value::String(JsString::from(value.as_ref())))
+}
+
+impl JaysEngine {
+    fn finish(&mut self, result: Result<Value, JsEngineError>) -> *mut JaysValue {
+        match result {
+            Ok(value) => {
+                self.last_error = None;
+                into_raw(value)
+            },
+            Err(error) => {
+                self.last_error = CString::new(error.to_string().replace('\0', " ")).ok();
+                ptr::null_mut()
+            },
+        }
+    }
+}
+
+fn into_raw(value: Value) -> *mut JaysValue {
+    Box::into_raw(Box::new(JaysValue(value)))
+}
+
+unsafe fn arguments_from_raw(arguments: *const *const JaysValue, count: usize) -> Vec<Value> {
+    if count == 0 {
+        return Vec::new();
+    }
+    
+    unsafe { std::slice::from_raw_parts(arguments, count) }
+        .iter()
+        .map(|&argument| unsafe { (*argument).0.clone() })
+        .collect()
+}
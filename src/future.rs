@@ -0,0 +1,50 @@
+// src/future.rs
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, Value};
+use std::rc::Rc;
+use std::time::Duration;
+
+// Running scripts from async Rust. Interpreters are not Send, so the future
+// has to be polled on the thread that owns the interpreter, e.g. inside a
+// tokio LocalSet or on a current-thread runtime:
+//
+//     let result = interpreter.eval_future("setTimeout(done, 100); 42;").await?;
+//
+// Timers wait on tokio's timer instead of blocking the thread, so other
+// tasks keep running meanwhile, and pausing tokio's clock in tests also
+// skips over timer delays.
+
+impl Interpreter {
+    // Runs a script like eval and resolves with its result once it and
+    // every timer it scheduled have finished
+    pub async fn eval_future(&mut self, source: &str) -> Result<Value, JsEngineError> {
+        let result = self.settle(source).await;
+        
+        if let Err(error) = &result {
+            self.report_error(error);
+        }
+        
+        result
+    }
+    
+    async fn settle(&mut self, source: &str) -> Result<Value, JsEngineError> {
+        let statements = crate::parse_str_with_limits(source, &self.limits)?;
+        let previous = self.source.replace(Rc::from(source));
+        let value = self.run_program(statements);
+        self.source = previous;
+        let value = value?;
+        
+        while let Some(task) = self.next_task() {
+            let delay = task.due - self.clock().now();
+            if delay > 0.0 {
+                tokio::time::sleep(Duration::from_secs_f64(delay / 1000.0)).await;
+            }
+            
+            // Lets a host-provided clock catch up with the time slept
+            self.clock().sleep_until(task.due);
+            self.run_task(task)?;
+        }
+        
+        Ok(value)
+    }
+}
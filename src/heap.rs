@@ -0,0 +1,386 @@
+// src/heap.rs
+use crate::interpreter::{Environment, Value};
+use crate::object::PropertyMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::mem::size_of;
+use std::rc::Rc;
+
+// Counts of everything reachable from the interpreter's environments.
+// Memory is reference counted and freed as soon as the last reference goes
+// away, so there are no collection cycles to time; `bytes` is an estimate
+// of the payload held by the reachable values.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HeapStats {
+    pub environments: usize,
+    pub bindings: usize,
+    pub objects: usize,
+    pub functions: usize,
+    pub native_functions: usize,
+    pub strings: usize,
+    pub string_bytes: usize,
+    pub bytes: usize,
+}
+
+impl HeapStats {
+    // Converts the statistics into a plain script object
+    pub fn to_value(&self) -> Value {
+        let mut fields = PropertyMap::new();
+        
+        for (name, count) in [
+            ("environments", self.environments),
+            ("bindings", self.bindings),
+            ("objects", self.objects),
+            ("functions", self.functions),
+            ("nativeFunctions", self.native_functions),
+            ("strings", self.strings),
+            ("stringBytes", self.string_bytes),
+            ("bytes", self.bytes),
+        ] {
+            fields.insert(name.to_string(), Value::Number(count as f64));
+        }
+        
+        Value::Object(Rc::new(RefCell::new(fields)))
+    }
+}
+
+impl fmt::Display for HeapStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Heap statistics:")?;
+        writeln!(f, "  environments:     {} ({} bindings)", self.environments, self.bindings)?;
+        writeln!(f, "  objects:          {}", self.objects)?;
+        writeln!(f, "  functions:        {}", self.functions)?;
+        writeln!(f, "  native functions: {}", self.native_functions)?;
+        writeln!(f, "  strings:          {} ({} bytes)", self.strings, self.string_bytes)?;
+        write!(f, "  approx. bytes:    {}", self.bytes)
+    }
+}
+
+enum Item {
+    Environment(Rc<RefCell<Environment>>),
+    Value(Value),
+}
+
+// Walks the value graph reachable from `roots`, visiting every environment,
+// object, function and string once
+pub fn collect_stats(roots: &[&Rc<RefCell<Environment>>]) -> HeapStats {
+    let mut stats = HeapStats::default();
+    let mut seen: HashSet<*const ()> = HashSet::new();
+    let mut stack: Vec<Item> = roots.iter().map(|env| Item::Environment(Rc::clone(env))).collect();
+    
+    while let Some(item) = stack.pop() {
+        match item {
+            Item::Environment(env) => {
+                if !seen.insert(Rc::as_ptr(&env) as *const ()) {
+                    continue;
+                }
+                
+                let env = env.borrow();
+                stats.environments += 1;
+                stats.bytes += environment_size(&env);
+                
+                for (_, value) in env.bindings() {
+                    stats.bindings += 1;
+                    stack.push(Item::Value(value.clone()));
+                }
+                
+                if let Some(enclosing) = env.enclosing() {
+                    stack.push(Item::Environment(Rc::clone(enclosing)));
+                }
+            },
+            Item::Value(Value::String(string)) => {
+                if seen.insert(string.id()) {
+                    stats.strings += 1;
+                    stats.string_bytes += string.len();
+                    stats.bytes += string.len();
+                }
+            },
+            Item::Value(Value::Object(object)) => {
+                if !seen.insert(Rc::as_ptr(&object) as *const ()) {
+                    continue;
+                }
+                
+                stats.objects += 1;
+                stats.bytes += value_size(&Value::Object(Rc::clone(&object)));
+                
+                let object = object.borrow();
+                for value in object.values() {
+                    stack.push(Item::Value(value.clone()));
+                }
+                if let Some(prototype) = object.prototype() {
+                    stack.push(Item::Value(Value::Object(Rc::clone(prototype))));
+                }
+            },
+            Item::Value(Value::Function(function)) => {
+                if !seen.insert(Rc::as_ptr(&function) as *const ()) {
+                    continue;
+                }
+                
+                stats.functions += 1;
+                stats.bytes += value_size(&Value::Function(Rc::clone(&function)));
+                stack.push(Item::Environment(Rc::clone(&function.closure)));
+            },
+            Item::Value(Value::NativeFunction(function)) => {
+                if seen.insert(Rc::as_ptr(&function) as *const ()) {
+                    stats.native_functions += 1;
+                }
+            },
+            Item::Value(_) => {},
+        }
+    }
+    
+    stats
+}
+
+// Approximate bytes held directly by an environment, excluding the values
+// its bindings refer to
+fn environment_size(env: &Environment) -> usize {
+    let bindings: usize = env.bindings()
+        .map(|(name, _)| size_of::<Rc<str>>() + size_of::<Value>() + name.len())
+        .sum();
+    
+    size_of::<Environment>() + bindings
+}
+
+// Approximate bytes held directly by a value, excluding anything it refers to
+fn value_size(value: &Value) -> usize {
+    match value {
+        Value::Object(object) => {
+            let entries: usize = object.borrow().keys()
+                .map(|key| size_of::<String>() + size_of::<Value>() + key.len())
+                .sum();
+            
+            size_of::<PropertyMap>() + entries
+        },
+        Value::Function(function) => size_of_val(function.as_ref()) + function.parameters.len() * size_of::<Rc<str>>(),
+        Value::NativeFunction(function) => size_of_val(function),
+        Value::String(string) => string.len(),
+        _ => size_of::<Value>(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeKind {
+    Environment,
+    Object,
+    Function,
+    NativeFunction,
+    String,
+    Primitive,
+}
+
+impl NodeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeKind::Environment => "environment",
+            NodeKind::Object => "object",
+            NodeKind::Function => "function",
+            NodeKind::NativeFunction => "native",
+            NodeKind::String => "string",
+            NodeKind::Primitive => "primitive",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HeapNode {
+    pub id: usize,
+    pub kind: NodeKind,
+    pub label: String,
+    pub size: usize, // Approximate bytes held by this node alone
+}
+
+// A reference from one node to another: a variable binding, an object
+// property, a function's captured scope or a scope's parent
+#[derive(Debug, Clone)]
+pub struct HeapEdge {
+    pub from: usize,
+    pub to: usize,
+    pub name: String,
+}
+
+// The live object graph, for finding leaks and unexpected retention
+#[derive(Debug, Default, Clone)]
+pub struct HeapGraph {
+    pub nodes: Vec<HeapNode>,
+    pub edges: Vec<HeapEdge>,
+    pub roots: Vec<usize>,
+}
+
+impl HeapGraph {
+    // Builds the graph of everything reachable from `roots`. The first root
+    // is labelled as the global scope.
+    pub fn collect(roots: &[&Rc<RefCell<Environment>>]) -> HeapGraph {
+        let mut graph = HeapGraph::default();
+        let mut ids: HashMap<*const (), usize> = HashMap::new();
+        let mut stack: Vec<(usize, Item)> = Vec::new();
+        
+        for (i, root) in roots.iter().enumerate() {
+            let label = if i == 0 { "global scope" } else { "scope" };
+            let id = graph.environment_node(&mut ids, &mut stack, root, label);
+            graph.roots.push(id);
+        }
+        
+        while let Some((id, item)) = stack.pop() {
+            match item {
+                Item::Environment(env) => {
+                    let env = env.borrow();
+                    graph.nodes[id].size = environment_size(&env);
+                    
+                    for (name, value) in env.bindings() {
+                        graph.link(&mut ids, &mut stack, id, name.to_string(), value);
+                    }
+                    
+                    if let Some(enclosing) = env.enclosing() {
+                        let to = graph.environment_node(&mut ids, &mut stack, enclosing, "scope");
+                        graph.edges.push(HeapEdge { from: id, to, name: "(enclosing)".to_string() });
+                    }
+                },
+                Item::Value(Value::Object(object)) => {
+                    let object = object.borrow();
+                    for (key, value) in object.iter() {
+                        graph.link(&mut ids, &mut stack, id, key.clone(), value);
+                    }
+                    if let Some(prototype) = object.prototype() {
+                        graph.link(&mut ids, &mut stack, id, "__proto__".to_string(), &Value::Object(Rc::clone(prototype)));
+                    }
+                },
+                Item::Value(Value::Function(function)) => {
+                    let to = graph.environment_node(&mut ids, &mut stack, &function.closure, "scope");
+                    graph.edges.push(HeapEdge { from: id, to, name: "(closure)".to_string() });
+                },
+                Item::Value(_) => {},
+            }
+        }
+        
+        graph
+    }
+    
+    // Adds a node for `ptr` unless it already has one. Returns the new id,
+    // or None if the node was seen before.
+    fn intern(&mut self, ids: &mut HashMap<*const (), usize>, ptr: *const (), kind: NodeKind, label: String) -> Option<usize> {
+        if ids.contains_key(&ptr) {
+            return None;
+        }
+        
+        let id = self.add_node(kind, label);
+        ids.insert(ptr, id);
+        Some(id)
+    }
+    
+    // Returns the node for an environment, creating and queueing it if new
+    fn environment_node(&mut self, ids: &mut HashMap<*const (), usize>, stack: &mut Vec<(usize, Item)>, env: &Rc<RefCell<Environment>>, label: &str) -> usize {
+        let ptr = Rc::as_ptr(env) as *const ();
+        
+        if let Some(id) = self.intern(ids, ptr, NodeKind::Environment, label.to_string()) {
+            stack.push((id, Item::Environment(Rc::clone(env))));
+        }
+        
+        ids[&ptr]
+    }
+    
+    fn add_node(&mut self, kind: NodeKind, label: String) -> usize {
+        let id = self.nodes.len();
+        self.nodes.push(HeapNode { id, kind, label, size: 0 });
+        id
+    }
+    
+    // Adds an edge named `name` from `from` to the node for `value`, queueing
+    // the value for a visit if it has not been seen yet
+    fn link(&mut self, ids: &mut HashMap<*const (), usize>, stack: &mut Vec<(usize, Item)>, from: usize, name: String, value: &Value) {
+        let (ptr, kind, label) = match value {
+            Value::Object(object) => (Rc::as_ptr(object) as *const (), NodeKind::Object, "Object".to_string()),
+            Value::Function(function) => {
+                let label = format!("function({})", function.parameters.join(", "));
+                (Rc::as_ptr(function) as *const (), NodeKind::Function, label)
+            },
+            Value::NativeFunction(function) => (Rc::as_ptr(function) as *const (), NodeKind::NativeFunction, "native function".to_string()),
+            Value::String(string) => (string.id(), NodeKind::String, format!("{:?}", truncate(string.as_str(), 40))),
+            primitive => {
+                // Primitives are copied by value, so each use gets its own node
+                let to = self.add_node(NodeKind::Primitive, format!("{:?}", primitive));
+                self.nodes[to].size = value_size(primitive);
+                self.edges.push(HeapEdge { from, to, name });
+                return;
+            },
+        };
+        
+        if let Some(id) = self.intern(ids, ptr, kind, label) {
+            self.nodes[id].size = value_size(value);
+            stack.push((id, Item::Value(value.clone())));
+        }
+        
+        self.edges.push(HeapEdge { from, to: ids[&ptr], name });
+    }
+    
+    pub fn to_json(&self) -> String {
+        let nodes: Vec<String> = self.nodes.iter().map(|node| {
+            format!(
+                "{{\"id\":{},\"type\":{},\"label\":{}}}",
+                node.id,
+                json_string(node.kind.name()),
+                json_string(&node.label),
+            )
+        }).collect();
+        
+        let edges: Vec<String> = self.edges.iter().map(|edge| {
+            format!(
+                "{{\"from\":{},\"to\":{},\"name\":{}}}",
+                edge.from,
+                edge.to,
+                json_string(&edge.name),
+            )
+        }).collect();
+        
+        format!("{{\"nodes\":[{}],\"edges\":[{}]}}", nodes.join(","), edges.join(","))
+    }
+    
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph heap {\n");
+        
+        for node in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Environment => "box",
+                NodeKind::Primitive | NodeKind::String => "plaintext",
+                _ => "ellipse",
+            };
+            out.push_str(&format!("  n{} [label={} shape={}];\n", node.id, json_string(&node.label), shape));
+        }
+        
+        for edge in &self.edges {
+            out.push_str(&format!("  n{} -> n{} [label={}];\n", edge.from, edge.to, json_string(&edge.name)));
+        }
+        
+        out.push('}');
+        out
+    }
+}
+
+fn truncate(value: &str, max_chars: usize) -> String {
+    match value.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}...", &value[..end]),
+        None => value.to_string(),
+    }
+}
+
+// Quotes and escapes a string for JSON (also valid as a DOT quoted string)
+pub fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    
+    out.push('"');
+    out
+}
@@ -0,0 +1,32 @@
+// src/hooks.rs
+use crate::error::JsEngineError;
+use crate::interpreter::Value;
+use crate::ast::Stmt;
+
+// Callbacks invoked by the interpreter as it runs. Register implementations
+// with `Interpreter::add_observer` to build profilers, debuggers, coverage
+// tools or audit logs without touching the evaluation loop.
+//
+// Every method has an empty default, so observers only implement the events
+// they care about.
+pub trait ExecutionObserver {
+    // Called before each statement is executed
+    fn on_statement(&mut self, _stmt: &Stmt) {}
+    
+    // Called when a function is entered, after its arguments are evaluated.
+    // Tail calls that reuse the current frame are reported as separate calls.
+    fn on_call_enter(&mut self, _callee: &Value, _arguments: &[Value]) {}
+    
+    // Called when a function returns, whether it succeeded or not
+    fn on_call_exit(&mut self, _callee: &Value, _result: Result<&Value, &JsEngineError>) {}
+    
+    // Called with the error that aborts a run, after any calls it unwound
+    // have been reported as exited
+    fn on_error(&mut self, _error: &JsEngineError) {}
+}
+
+// A pass that rewrites a parsed program before it runs, e.g. to expand
+// macros, inject instrumentation or desugar new syntax. Register with
+// `Interpreter::add_transform`; passes run in registration order, before
+// variables are resolved.
+pub type AstTransform = dyn Fn(Vec<Stmt>) -> Vec<Stmt>;
@@ -0,0 +1,110 @@
+// src/host.rs
+use std::cell::Cell;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Sources of time and randomness for the built-ins. Hosts can swap them on
+// the interpreter builder, e.g. to drive timers with virtual time in a
+// simulation or to replay a fixed random stream in tests.
+
+pub trait Clock {
+    // Milliseconds since the Unix epoch; must never go backwards
+    fn now(&self) -> f64;
+    
+    // Blocks until `now()` reaches `time`. Virtual clocks jump there instead.
+    fn sleep_until(&self, time: f64);
+}
+
+pub trait Rng {
+    // Uniformly distributed in [0, 1)
+    fn next_f64(&mut self) -> f64;
+}
+
+// Wall-clock time, advanced by a monotonic timer so it never jumps back
+pub struct SystemClock {
+    start: Instant,
+    start_time: f64,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock {
+            start: Instant::now(),
+            start_time: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |time| time.as_secs_f64() * 1000.0),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> f64 {
+        self.start_time + self.start.elapsed().as_secs_f64() * 1000.0
+    }
+    
+    fn sleep_until(&self, time: f64) {
+        let remaining = time - self.now();
+        if remaining > 0.0 {
+            std::thread::sleep(Duration::from_secs_f64(remaining / 1000.0));
+        }
+    }
+}
+
+// Time that only moves when the host advances it, or when the interpreter
+// waits for a timer, which skips straight to the timer's due time
+#[derive(Default)]
+pub struct ManualClock {
+    time: Cell<f64>,
+}
+
+impl ManualClock {
+    pub fn new(time: f64) -> Self {
+        ManualClock { time: Cell::new(time) }
+    }
+    
+    pub fn advance(&self, milliseconds: f64) {
+        self.time.set(self.time.get() + milliseconds.max(0.0));
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> f64 {
+        self.time.get()
+    }
+    
+    fn sleep_until(&self, time: f64) {
+        self.time.set(self.time.get().max(time));
+    }
+}
+
+// xorshift64* generator. Not suitable for cryptography, like Math.random.
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    // The same seed always produces the same sequence
+    pub fn new(seed: u64) -> Self {
+        // Spread the seed's bits with a splitmix64 step; the state must not
+        // be zero
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        SeededRng { state: z.max(1) }
+    }
+    
+    // Seeded from the current time
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64);
+        SeededRng::new(nanos)
+    }
+}
+
+impl Rng for SeededRng {
+    fn next_f64(&mut self) -> f64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        let bits = self.state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        // The top 53 bits fill a double's mantissa exactly
+        (bits >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
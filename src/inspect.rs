@@ -0,0 +1,271 @@
+// src/inspect.rs
+use crate::collections::{Collection, CollectionKind};
+use crate::interpreter::Value;
+use crate::number::number_to_string;
+use crate::object::{InternalSlot, PropertyMap};
+use crate::typed_array::TypedArray;
+use std::rc::Rc;
+
+// Objects nested deeper than this print as [Object]
+const MAX_DEPTH: usize = 2;
+// Properties listed per object before the rest are summarised
+const MAX_ITEMS: usize = 100;
+// Bytes of an ArrayBuffer listed before the rest are summarised
+const MAX_BYTES: usize = 50;
+// Objects that would print wider than this are broken over several lines
+const BREAK_LENGTH: usize = 72;
+
+// Formats a value for console.log the way Node's util.inspect does:
+//
+//     { name: 'jay', tags: { admin: true }, greet: [Function: greet] }
+//
+// Strings print as-is at the top level and quoted inside objects, errors
+// as their stack, promises with their state, and maps and sets with their
+// size and entries (`Map(1) { 'a' => 1 }`). Objects deeper than two levels print as [Object],
+// ones that contain themselves print [Circular], and only the first 100
+// properties of an object are shown.
+pub fn inspect(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.to_string(),
+        _ => Inspector::default().value(value, 0),
+    }
+}
+
+#[derive(Default)]
+struct Inspector {
+    // Objects being printed, outermost first
+    stack: Vec<*const ()>,
+}
+
+impl Inspector {
+    fn value(&mut self, value: &Value, depth: usize) -> String {
+        match value {
+            Value::String(s) => quote(s.as_str()),
+            Value::Number(n) if *n == 0.0 && n.is_sign_negative() => "-0".to_string(),
+            Value::Number(n) => number_to_string(*n),
+            Value::Function(function) => match &function.name {
+                Some(name) => format!("[Function: {}]", name),
+                None => "[Function (anonymous)]".to_string(),
+            },
+            Value::NativeFunction(_) => "[Function (native)]".to_string(),
+            Value::Object(object) => {
+                let id = Rc::as_ptr(object) as *const ();
+                if self.stack.contains(&id) {
+                    return "[Circular]".to_string();
+                }
+                
+                // Errors print their stack, which starts with their name
+                // and message
+                let object = object.borrow();
+                if object.prototype().is_some()
+                    && let Some(Value::String(stack)) = object.get("stack")
+                {
+                    return stack.to_string();
+                }
+                
+                if let Some(InternalSlot::Promise(promise)) = object.slot() {
+                    self.stack.push(id);
+                    let state = match promise.outcome() {
+                        None => "<pending>".to_string(),
+                        Some(Ok(value)) => self.value(&value, depth + 1),
+                        Some(Err(reason)) => format!("<rejected> {}", self.value(&reason, depth + 1)),
+                    };
+                    self.stack.pop();
+                    return format!("Promise {{ {} }}", state);
+                }
+                
+                // Proxies print as their targets do
+                if let Some(InternalSlot::Proxy(proxy)) = object.slot() {
+                    self.stack.push(id);
+                    let text = self.value(proxy.target(), depth);
+                    self.stack.pop();
+                    return text;
+                }
+                
+                match object.slot() {
+                    Some(InternalSlot::TypedArray(array)) => return self.typed_array(array),
+                    Some(InternalSlot::ArrayBuffer(buffer)) => return array_buffer(&buffer.bytes()),
+                    Some(InternalSlot::DataView(view)) => {
+                        let buffer = self.value(view.buffer(), depth + 1);
+                        return format!("DataView {{ byteLength: {}, byteOffset: {}, buffer: {} }}", view.len(), view.offset(), buffer);
+                    },
+                    _ => {},
+                }
+                
+                if let Some(InternalSlot::Iterator(state)) = object.slot() {
+                    return format!("Object [{}] {{}}", state.borrow().name());
+                }
+                
+                if let Some(InternalSlot::Collection(collection)) = object.slot() {
+                    return self.collection(collection, id, depth);
+                }
+                
+                #[cfg(feature = "regex")]
+                if let Some(InternalSlot::RegExp(regexp)) = object.slot() {
+                    return regexp.to_string();
+                }
+                
+                // Callable objects are functions with properties, which
+                // follow the function like Node prints them
+                let (prefix, empty, nested) = match (object.call(), object.array_length()) {
+                    (Some(_), _) => ("[Function (native)] ", "[Function (native)]", "[Function (native)]"),
+                    (None, Some(_)) => ("", "[]", "[Array]"),
+                    (None, None) => ("", "{}", "[Object]"),
+                };
+                if object.is_empty() && object.array_length().is_none_or(|length| length == 0) {
+                    return empty.to_string();
+                }
+                if depth > MAX_DEPTH {
+                    return nested.to_string();
+                }
+                
+                self.stack.push(id);
+                let text = self.object(&object, depth);
+                self.stack.pop();
+                format!("{}{}", prefix, text)
+            },
+            other => other.to_string(),
+        }
+    }
+    
+    fn object(&mut self, properties: &PropertyMap, depth: usize) -> String {
+        let mut entries = Vec::new();
+        let mut shown = 0;
+        let mut hidden = 0;
+        
+        // Arrays list their elements bare, with runs of missing ones
+        // summarised, and any other properties after them
+        let length = properties.array_length();
+        if let Some(length) = length {
+            let mut index = 0;
+            while index < length && shown < MAX_ITEMS {
+                match properties.get(&index.to_string()) {
+                    Some(element) => {
+                        entries.push(self.value(element, depth + 1));
+                        index += 1;
+                    },
+                    None => {
+                        let holes = (index..length).take_while(|hole| !properties.contains_key(&hole.to_string())).count();
+                        entries.push(format!("<{} empty item{}>", holes, plural(holes)));
+                        index += holes;
+                    },
+                }
+                shown += 1;
+            }
+            hidden += length - index;
+        }
+        
+        let is_element = |key: &str| length.is_some() && key.parse::<usize>().is_ok_and(|index| index.to_string() == key);
+        for (key, value) in properties.iter().filter(|(key, _)| !is_element(key)) {
+            if shown < MAX_ITEMS {
+                entries.push(format!("{}: {}", key_name(key), self.value(value, depth + 1)));
+                shown += 1;
+            } else {
+                hidden += 1;
+            }
+        }
+        
+        if hidden > 0 {
+            entries.push(format!("... {} more item{}", hidden, plural(hidden)));
+        }
+        
+        let (open, close) = if length.is_some() { ('[', ']') } else { ('{', '}') };
+        let single_line = format!("{} {} {}", open, entries.join(", "), close);
+        if single_line.len() <= BREAK_LENGTH && !single_line.contains('\n') {
+            return single_line;
+        }
+        
+        // Nested lines are indented along with the entry that holds them
+        let indent = "  ".repeat(depth + 1);
+        let lines: Vec<String> = entries.iter().map(|entry| format!("{}{}", indent, entry)).collect();
+        format!("{}\n{}\n{}{}", open, lines.join(",\n"), "  ".repeat(depth), close)
+    }
+}
+
+impl Inspector {
+    fn typed_array(&mut self, array: &TypedArray) -> String {
+        let name = array.kind().name();
+        if array.is_empty() {
+            return format!("{}(0) []", name);
+        }
+        let mut elements: Vec<String> = array.values().into_iter().take(MAX_ITEMS).map(|n| self.value(&Value::Number(n), 0)).collect();
+        let hidden = array.len().saturating_sub(MAX_ITEMS);
+        if hidden > 0 {
+            elements.push(format!("... {} more item{}", hidden, plural(hidden)));
+        }
+        format!("{}({}) [ {} ]", name, array.len(), elements.join(", "))
+    }
+    
+    fn collection(&mut self, collection: &Collection, id: *const (), depth: usize) -> String {
+        let name = match collection.kind() {
+            CollectionKind::Map => "Map",
+            CollectionKind::Set => "Set",
+        };
+        if collection.is_empty() {
+            return format!("{}(0) {{}}", name);
+        }
+        if depth > MAX_DEPTH {
+            return format!("[{}]", name);
+        }
+        
+        self.stack.push(id);
+        let mut entries: Vec<String> = collection
+            .entries()
+            .iter()
+            .take(MAX_ITEMS)
+            .map(|(key, value)| match collection.kind() {
+                CollectionKind::Map => format!("{} => {}", self.value(key, depth + 1), self.value(value, depth + 1)),
+                CollectionKind::Set => self.value(value, depth + 1),
+            })
+            .collect();
+        self.stack.pop();
+        
+        let hidden = collection.len().saturating_sub(MAX_ITEMS);
+        if hidden > 0 {
+            entries.push(format!("... {} more item{}", hidden, plural(hidden)));
+        }
+        format!("{}({}) {{ {} }}", name, collection.len(), entries.join(", "))
+    }
+}
+
+// Like `ArrayBuffer { [Uint8Contents]: <68 69>, byteLength: 2 }`
+fn array_buffer(bytes: &[u8]) -> String {
+    let mut contents: Vec<String> = bytes.iter().take(MAX_BYTES).map(|byte| format!("{:02x}", byte)).collect();
+    let hidden = bytes.len().saturating_sub(MAX_BYTES);
+    if hidden > 0 {
+        contents.push(format!("... {} more byte{}", hidden, plural(hidden)));
+    }
+    format!("ArrayBuffer {{ [Uint8Contents]: <{}>, byteLength: {} }}", contents.join(" "), bytes.len())
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
+
+// Keys print bare when they are valid identifiers
+fn key_name(key: &str) -> String {
+    let mut chars = key.chars();
+    let is_identifier = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    if is_identifier { key.to_string() } else { quote(key) }
+}
+
+// Single quotes unless the string contains them, as Node prefers
+fn quote(text: &str) -> String {
+    let quote = if text.contains('\'') && !text.contains('"') { '"' } else { '\'' };
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push(quote);
+    for c in text.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\\' => out.push_str("\\\\"),
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            },
+            c => out.push(c),
+        }
+    }
+    out.push(quote);
+    out
+}
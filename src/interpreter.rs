@@ -1,21 +1,53 @@
 // src/interpreter.rs
 use crate::error::JsEngineError;
-use crate::parser::{Expr, Stmt, BinaryOp, UnaryOp};
-use std::collections::HashMap;
+use crate::collections::{self, CollectionKind};
+use crate::console;
+use crate::ast::{self, CatchClause, DeclarationKind, Expr, Stmt, BinaryOp, FunctionKind, Pattern, PropertyKey, SwitchClause, UnaryOp};
+use crate::events::{self, EventHandlers};
+use crate::host::{Clock, Rng, SeededRng, SystemClock};
+use crate::heap::{self, HeapGraph, HeapStats};
+use crate::hooks::{AstTransform, ExecutionObserver};
+use crate::iterator::{self, ValueIterator, ITERATOR};
+use crate::lexer::Span;
+use crate::locale::Locale;
+use crate::number::{number_to_string, power};
+use crate::object::{self, InternalSlot, PropertyMap};
+use crate::parser::DEFAULT_MAX_NESTING;
+use crate::performance::Timeline;
+use crate::primitives;
+use crate::promise;
+use crate::proxy::{self, Proxy};
+use crate::realm::RealmTemplate;
+#[cfg(feature = "regex")]
+use crate::regexp::RegExp;
+use crate::resolver;
+use crate::roots::Root;
+use crate::shape::PropertyCache;
+use crate::native_stack;
+use crate::stack::{CallStack, ErrorPrototypes};
+use crate::stdlib::{self, Std};
+use crate::string::JsString;
+use crate::tasks::{Task, TaskQueue};
+use crate::typed_array::{self, ElementKind};
+use indexmap::IndexMap;
+use smallvec::SmallVec;
+use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::fmt;
+use std::io::{self, Write};
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 // JavaScript values
 #[derive(Clone)]
 pub enum Value {
     Number(f64),
-    String(String),
+    String(JsString),
     Boolean(bool),
     Null,
     Function(Rc<JsFunction>),
     NativeFunction(Rc<NativeFunction>),
-    Object(Rc<RefCell<HashMap<String, Value>>>),
+    Object(Rc<RefCell<PropertyMap>>),
     Undefined,
 }
 
@@ -23,12 +55,13 @@ pub enum Value {
 impl fmt::Debug for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Value::Number(n) => write!(f, "{}", n),
+            Value::Number(n) => write!(f, "{}", number_to_string(*n)),
             Value::String(s) => write!(f, "\"{}\"", s),
             Value::Boolean(b) => write!(f, "{}", b),
             Value::Null => write!(f, "null"),
             Value::Function(_) => write!(f, "[Function]"),
             Value::NativeFunction(_) => write!(f, "[Native Function]"),
+            Value::Object(object) if object.borrow().call().is_some() => write!(f, "[Native Function]"),
             Value::Object(_) => write!(f, "[Object]"),
             Value::Undefined => write!(f, "undefined"),
         }
@@ -39,57 +72,164 @@ impl fmt::Debug for Value {
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Value::Number(n) => write!(f, "{}", n),
+            Value::Number(n) => write!(f, "{}", number_to_string(*n)),
             Value::String(s) => write!(f, "{}", s),
             Value::Boolean(b) => write!(f, "{}", b),
             Value::Null => write!(f, "null"),
             Value::Function(_) => write!(f, "[Function]"),
             Value::NativeFunction(_) => write!(f, "[Native Function]"),
+            Value::Object(object) if object.borrow().call().is_some() => write!(f, "[Native Function]"),
             Value::Object(_) => write!(f, "[Object]"),
             Value::Undefined => write!(f, "undefined"),
         }
     }
 }
 
+impl Value {
+    // Functions, native functions and objects with a call behaviour
+    pub fn is_callable(&self) -> bool {
+        match self {
+            Value::Function(_) | Value::NativeFunction(_) => true,
+            Value::Object(object) => object.borrow().call().is_some(),
+            _ => false,
+        }
+    }
+}
+
 // JavaScript function
 pub struct JsFunction {
-    pub parameters: Vec<String>,
-    pub body: Stmt,
-    pub closure: Environment,
+    pub name: Option<Rc<str>>, // None for anonymous function expressions
+    pub parameters: Vec<Rc<str>>,
+    pub body: Rc<Stmt>,
+    pub closure: Rc<RefCell<Environment>>,
+    pub source: Option<Rc<str>>, // The script it was defined in, if known
+    pub span: Span, // Where in `source` its text is
+    pub kind: FunctionKind,
+}
+
+impl JsFunction {
+    // The function's text as written, as Function.prototype.toString
+    // returns it
+    pub fn source_text(&self) -> String {
+        let text = self.source.as_deref().and_then(|source| source.get(self.span.start..self.span.end));
+        match text {
+            Some(text) if !text.is_empty() => text.to_string(),
+            _ => format!(
+                "function {}({}) {{ ... }}",
+                self.name.as_deref().unwrap_or(""),
+                self.parameters.join(", "),
+            ),
+        }
+    }
 }
 
 // Native function type
-pub type NativeFunction = dyn Fn(Vec<Value>) -> Result<Value, JsEngineError>;
+pub type NativeFunction = dyn Fn(&[Value]) -> Result<Value, JsEngineError>;
+
+// Destination for console output, shared with the native functions
+pub type Output = Rc<RefCell<Box<dyn Write>>>;
+
+// What a script printed during Interpreter::eval_captured
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CapturedOutput {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+// Collects written bytes for CapturedOutput
+struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Call arguments; most calls pass only a few, which stay on the stack
+pub type Arguments = SmallVec<[Value; 4]>;
 
 // Environment for storing variables
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct Environment {
-    values: HashMap<String, Value>,
+    values: IndexMap<Rc<str>, Value>,
+    // Changes whenever a binding is added or removed, which is what moves
+    // bindings; see GlobalCache
+    layout: u64,
     enclosing: Option<Rc<RefCell<Environment>>>,
+    read_only: HashSet<Rc<str>>, // Names scripts can neither assign nor redeclare
+    constants: HashSet<Rc<str>>, // Names declared with const
+    uninitialized: HashSet<Rc<str>>, // let and const names whose declaration has not run yet
+    function_scope: bool, // Whether `var` declarations in nested blocks bind here
 }
 
 impl Environment {
     pub fn new() -> Self {
         Environment {
-            values: HashMap::new(),
+            values: IndexMap::new(),
+            layout: 0,
             enclosing: None,
+            read_only: HashSet::new(),
+            constants: HashSet::new(),
+            uninitialized: HashSet::new(),
+            function_scope: true,
         }
     }
     
     pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Self {
         Environment {
-            values: HashMap::new(),
+            values: IndexMap::new(),
+            layout: 0,
             enclosing: Some(enclosing),
+            read_only: HashSet::new(),
+            constants: HashSet::new(),
+            uninitialized: HashSet::new(),
+            function_scope: false,
+        }
+    }
+    
+    // The environment of a function call, which its `var` declarations
+    // bind in
+    pub fn for_function(closure: Rc<RefCell<Environment>>) -> Self {
+        Environment {
+            function_scope: true,
+            ..Environment::with_enclosing(closure)
         }
     }
     
-    pub fn define(&mut self, name: String, value: Value) {
-        self.values.insert(name, value);
+    pub fn define(&mut self, name: Rc<str>, value: Value) {
+        if !self.constants.is_empty() {
+            self.constants.remove(&name);
+        }
+        if !self.uninitialized.is_empty() {
+            self.uninitialized.remove(&name);
+        }
+        self.insert(name, value);
+    }
+    
+    // Binds a name that scripts cannot assign to afterwards
+    pub fn define_constant(&mut self, name: Rc<str>, value: Value) {
+        self.define(Rc::clone(&name), value);
+        self.constants.insert(name);
+    }
+    
+    // Reserves a let or const name for the scope, so that it shadows outer
+    // bindings but cannot be read or assigned before it is defined
+    pub fn define_uninitialized(&mut self, name: &str) {
+        if !self.values.contains_key(name) {
+            self.uninitialized.insert(Rc::from(name));
+        }
     }
     
     pub fn get(&self, name: &str) -> Result<Value, JsEngineError> {
         if let Some(value) = self.values.get(name) {
             Ok(value.clone())
+        } else if self.uninitialized.contains(name) {
+            Err(uninitialized_error(name))
         } else if let Some(enclosing) = &self.enclosing {
             enclosing.borrow().get(name)
         } else {
@@ -99,10 +239,87 @@ impl Environment {
         }
     }
     
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        let value = self.values.shift_remove(name)?;
+        self.layout = next_layout();
+        Some(value)
+    }
+    
+    // Reads a binding of this scope, by where `cache` says it is when the
+    // scope still has the layout it had when the cache was filled
+    pub(crate) fn get_cached(&self, name: &str, cache: &GlobalCache) -> Result<Value, JsEngineError> {
+        if let Some((layout, position)) = cache.entry.get()
+            && layout == self.layout
+            && let Some((_, value)) = self.values.get_index(position)
+        {
+            return Ok(value.clone());
+        }
+        
+        match self.values.get_full(name) {
+            Some((position, _, value)) => {
+                cache.entry.set(Some((self.layout, position)));
+                Ok(value.clone())
+            },
+            None => self.get(name),
+        }
+    }
+    
+    pub fn bindings(&self) -> impl Iterator<Item = (&Rc<str>, &Value)> {
+        self.values.iter()
+    }
+    
+    // A scope with the same declarations and no values, enclosed by
+    // `enclosing`, for copying this one into
+    pub(crate) fn empty_like(&self, enclosing: Option<Rc<RefCell<Environment>>>) -> Self {
+        Environment {
+            values: IndexMap::new(),
+            layout: 0,
+            enclosing,
+            read_only: self.read_only.clone(),
+            constants: self.constants.clone(),
+            uninitialized: self.uninitialized.clone(),
+            function_scope: self.function_scope,
+        }
+    }
+    
+    // Binds a name without changing whether it is constant or read-only,
+    // for filling in a scope made by empty_like
+    pub(crate) fn insert(&mut self, name: Rc<str>, value: Value) {
+        if self.values.insert(name, value).is_none() {
+            self.layout = next_layout();
+        }
+    }
+    
+    pub fn enclosing(&self) -> Option<&Rc<RefCell<Environment>>> {
+        self.enclosing.as_ref()
+    }
+    
+    // Stops scripts from assigning or redeclaring a name bound here; the
+    // host can still replace it with `define`
+    pub fn make_read_only(&mut self, name: &str) {
+        if let Some((name, _)) = self.values.get_key_value(name) {
+            self.read_only.insert(Rc::clone(name));
+        }
+    }
+    
+    pub fn is_read_only(&self, name: &str) -> bool {
+        self.read_only.contains(name)
+    }
+    
     pub fn assign(&mut self, name: &str, value: Value) -> Result<(), JsEngineError> {
-        if self.values.contains_key(name) {
-            self.values.insert(name.to_string(), value);
+        if let Some(slot) = self.values.get_mut(name) {
+            if self.read_only.contains(name) {
+                return Err(read_only_error(name));
+            }
+            if self.constants.contains(name) {
+                return Err(JsEngineError::TypeError {
+                    message: "Assignment to constant variable.".to_string(),
+                });
+            }
+            *slot = value;
             Ok(())
+        } else if self.uninitialized.contains(name) {
+            Err(uninitialized_error(name))
         } else if let Some(enclosing) = &self.enclosing {
             enclosing.borrow_mut().assign(name, value)
         } else {
@@ -113,183 +330,1069 @@ impl Environment {
     }
 }
 
+// A global variable read keeps a GlobalCache of where the binding was in the
+// global scope. Bindings only move when one is added or removed, which gives
+// the scope a new layout, so the position holds for as long as the layout
+// does. Layouts are numbered across all scopes, as a function body, and the
+// caches in it, can be shared by the interpreters a realm template makes.
+#[derive(Clone, Default)]
+pub struct GlobalCache {
+    entry: Cell<Option<(u64, usize)>>, // The layout and the binding's position in it
+}
+
+impl fmt::Debug for GlobalCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GlobalCache")
+    }
+}
+
+thread_local! {
+    static LAYOUTS: Cell<u64> = const { Cell::new(0) };
+}
+
+fn next_layout() -> u64 {
+    LAYOUTS.with(|layouts| {
+        layouts.set(layouts.get() + 1);
+        layouts.get()
+    })
+}
+
+fn read_only_error(name: &str) -> JsEngineError {
+    JsEngineError::TypeError {
+        message: format!("Cannot assign to read only global '{}'", name),
+    }
+}
+
+fn uninitialized_error(name: &str) -> JsEngineError {
+    JsEngineError::ReferenceError {
+        message: format!("Cannot access '{}' before initialization", name),
+    }
+}
+
 // Return type for control flow
 pub enum ExecutionResult {
     Value(Value),
     Return(Value),
+    TailCall(Box<(Value, Value, Arguments)>), // callee, this, arguments; boxed to keep results small
+    Break,
     None,
 }
 
+impl ExecutionResult {
+    // Whether this result ends the enclosing statement list: a return ends
+    // the function body and a break the innermost loop or switch
+    fn is_completion(&self) -> bool {
+        matches!(self, ExecutionResult::Return(_) | ExecutionResult::TailCall(..) | ExecutionResult::Break)
+    }
+    
+    // Whether a loop or switch stops after its body finishes with this
+    // result. A break stops it and is used up there.
+    fn ends_statement(&mut self) -> bool {
+        if matches!(self, ExecutionResult::Break) {
+            *self = ExecutionResult::None;
+            return true;
+        }
+        self.is_completion()
+    }
+}
+
+// Resource limits for running untrusted or generated code. Exceeding one
+// fails the run with an error instead of hanging or overflowing the stack.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    pub max_steps: Option<u64>, // Statements executed per run
+    pub max_call_depth: Option<usize>, // Nested calls, not counting tail calls
+    pub max_nesting: usize, // Syntactic nesting depth accepted by the parser
+}
+
+// Calls also fail once the native stack is nearly used up, which on small
+// stacks, and in debug builds, comes first; see native_stack.rs
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_steps: None,
+            max_call_depth: Some(DEFAULT_MAX_CALL_DEPTH),
+            max_nesting: DEFAULT_MAX_NESTING,
+        }
+    }
+}
+
 // Interpreter
 pub struct Interpreter {
-    environment: Rc<RefCell<Environment>>,
+    pub(crate) environment: Rc<RefCell<Environment>>,
     globals: Rc<RefCell<Environment>>,
+    call_depth: usize,
+    pub(crate) stack_limit: usize, // Address the native stack may not grow past; see native_stack.rs
+    pub(crate) limits: Limits,
+    steps: u64,
+    pub(crate) source: Option<Rc<str>>, // Text of the script whose code is running
+    output: Output,
+    error_output: Output,
+    tasks: Rc<RefCell<TaskQueue>>,
+    timeline: Rc<RefCell<Timeline>>,
+    clock: Rc<dyn Clock>,
+    rng: Rc<RefCell<dyn Rng>>,
+    locale: Locale,
+    events: Rc<RefCell<EventHandlers>>,
+    stack: Rc<RefCell<CallStack>>,
+    error_prototypes: Rc<ErrorPrototypes>,
+    roots: Rc<RefCell<Environment>>, // Values rooted by the host
+    next_root: usize,
+    #[cfg(feature = "resumable")]
+    pub(crate) budget: Option<crate::resumable::Budget>,
+    #[cfg(feature = "async")]
+    pub(crate) current_async: Option<Rc<crate::asynchronous::AsyncCall>>, // The async call whose body is running
+    observers: Vec<Box<dyn ExecutionObserver>>,
+    transforms: Vec<Box<AstTransform>>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Configures an interpreter before it is created. By default every built-in
+// namespace compiled into the crate is installed.
+#[derive(Clone)]
+pub struct InterpreterBuilder {
+    stdlib: Vec<Std>,
+    limits: Limits,
+    clock: Option<Rc<dyn Clock>>,
+    rng: Option<Rc<RefCell<dyn Rng>>>,
+    locale: Locale,
+    hardened: bool,
+}
+
+impl InterpreterBuilder {
+    // Installs only the given namespaces, e.g. to keep fs and net away from
+    // untrusted scripts. console, String, Error, queueMicrotask, Promise and
+    // the event functions are always present.
+    pub fn with_stdlib(mut self, namespaces: &[Std]) -> Self {
+        self.stdlib = namespaces.to_vec();
+        self
+    }
+    
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+    
+    // Time source for timers and other time-based built-ins; the system
+    // clock by default
+    pub fn with_clock(mut self, clock: Rc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+    
+    // Random source for Math.random; seeded from the time by default
+    pub fn with_rng(mut self, rng: impl Rng + 'static) -> Self {
+        self.rng = Some(Rc::new(RefCell::new(rng)));
+        self
+    }
+    
+    // Locale for toLocaleString and localeCompare when a script does not
+    // pass one; en-US by default
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+    
+    // Locks down the built-ins once they are installed; see
+    // Interpreter::lockdown
+    pub fn hardened(mut self) -> Self {
+        self.hardened = true;
+        self
+    }
+    
+    pub fn build(self) -> Interpreter {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        for (name, value) in self.builtins() {
+            globals.borrow_mut().define(name, value);
+        }
+        self.build_in(globals)
+    }
+    
+    // The built-ins that keep no state of their own, which a template makes
+    // once and gives each of its instances copies of
+    pub(crate) fn builtins(&self) -> Vec<(Rc<str>, Value)> {
+        let mut builtins = vec![
+            ("String", primitives::string_namespace()),
+            ("Number", primitives::number_namespace()),
+            ("Object", object::object_namespace()),
+            ("Array", object::array_namespace()),
+            ("Proxy", proxy::proxy_constructor()),
+            ("Reflect", proxy::reflect_namespace()),
+            ("Symbol", iterator::symbol_namespace()),
+            ("ArrayBuffer", typed_array::array_buffer_constructor()),
+            ("DataView", typed_array::data_view_constructor()),
+            ("TextEncoder", typed_array::text_encoder()),
+            ("TextDecoder", typed_array::text_decoder()),
+            ("Map", collections::constructor(CollectionKind::Map)),
+            ("Set", collections::constructor(CollectionKind::Set)),
+        ];
+        builtins.extend(primitives::number_globals());
+        builtins.extend(stdlib::builtins(&self.stdlib));
+        let mut builtins: Vec<(Rc<str>, Value)> = builtins.into_iter().map(|(name, value)| (Rc::from(name), value)).collect();
+        for kind in ElementKind::ALL {
+            builtins.push((Rc::from(kind.name()), typed_array::constructor(kind)));
+        }
+        builtins
+    }
+    
+    // An interpreter whose globals already hold the built-ins from
+    // `builtins`, with the rest of the built-ins added
+    pub(crate) fn build_in(&self, globals: Rc<RefCell<Environment>>) -> Interpreter {
+        let mut interpreter = Interpreter::core(globals);
+        interpreter.limits = self.limits.clone();
+        if let Some(clock) = &self.clock {
+            interpreter.clock = Rc::clone(clock);
+        }
+        if let Some(rng) = &self.rng {
+            interpreter.rng = Rc::clone(rng);
+        }
+        interpreter.locale = self.locale.clone();
+        stdlib::install(&mut interpreter, &self.stdlib);
+        if self.hardened {
+            interpreter.lockdown();
+        }
+        interpreter
+    }
+    
+    // Runs `setup` once on a new interpreter and keeps the resulting globals
+    // as a template for creating further interpreters cheaply
+    pub fn template(self, setup: impl FnOnce(&mut Interpreter) -> Result<(), JsEngineError>) -> Result<RealmTemplate, JsEngineError> {
+        RealmTemplate::new(self, setup)
+    }
 }
 
 impl Interpreter {
     pub fn new() -> Self {
-        let globals = Rc::new(RefCell::new(Environment::new()));
+        Interpreter::builder().build()
+    }
+    
+    pub fn builder() -> InterpreterBuilder {
+        InterpreterBuilder {
+            stdlib: Std::all(),
+            limits: Limits::default(),
+            clock: None,
+            rng: None,
+            locale: Locale::default(),
+            hardened: false,
+        }
+    }
+    
+    // An interpreter with the language core's globals that belong to it,
+    // added to `globals`
+    fn core(globals: Rc<RefCell<Environment>>) -> Self {
+        let output: Output = Rc::new(RefCell::new(Box::new(io::stdout())));
+        let error_output: Output = Rc::new(RefCell::new(Box::new(io::stderr())));
         
         // Define global functions
-        let console_log = Rc::new(|args: Vec<Value>| -> Result<Value, JsEngineError> {
-            for (i, arg) in args.iter().enumerate() {
-                if i > 0 {
-                    print!(" ");
-                }
-                print!("{}", arg);
+        globals.borrow_mut().define(Rc::from("console"), console::console(&output, &error_output, &globals));
+        
+        // Held weakly like the timer functions, since microtasks can reach it
+        let tasks = Rc::new(RefCell::new(TaskQueue::default()));
+        let weak_tasks = Rc::downgrade(&tasks);
+        let queue_microtask = Rc::new(move |args: &[Value]| -> Result<Value, JsEngineError> {
+            let callback = match args.first() {
+                Some(callback) if callback.is_callable() => callback.clone(),
+                _ => {
+                    return Err(JsEngineError::TypeError {
+                        message: "queueMicrotask: callback must be a function".to_string(),
+                    });
+                },
+            };
+            if let Some(tasks) = weak_tasks.upgrade() {
+                tasks.borrow_mut().queue_microtask(callback);
             }
-            println!();
             Ok(Value::Undefined)
         });
         
         globals.borrow_mut().define(
-            "console.log".to_string(),
-            Value::NativeFunction(console_log),
+            Rc::from("queueMicrotask"),
+            Value::NativeFunction(queue_microtask),
         );
+        globals.borrow_mut().define(Rc::from("Promise"), promise::promise_constructor(Rc::downgrade(&tasks)));
+        
+        let stack = Rc::new(RefCell::new(CallStack::default()));
+        let error_prototypes = Rc::new(ErrorPrototypes::new());
+        for (name, constructor) in error_prototypes.constructors() {
+            globals.borrow_mut().define(Rc::from(name), constructor);
+        }
+        
+        let events = Rc::new(RefCell::new(EventHandlers::default()));
+        for (name, function) in events::globals(&events) {
+            globals.borrow_mut().define(Rc::from(name), function);
+        }
         
         Interpreter {
             environment: Rc::clone(&globals),
             globals,
+            call_depth: 0,
+            stack_limit: native_stack::thread_limit(),
+            limits: Limits::default(),
+            steps: 0,
+            source: None,
+            output,
+            error_output,
+            tasks,
+            timeline: Rc::new(RefCell::new(Timeline::default())),
+            clock: Rc::new(SystemClock::default()),
+            rng: Rc::new(RefCell::new(SeededRng::from_time())),
+            locale: Locale::default(),
+            events,
+            stack,
+            error_prototypes,
+            roots: Rc::new(RefCell::new(Environment::new())),
+            next_root: 0,
+            #[cfg(feature = "resumable")]
+            budget: None,
+            #[cfg(feature = "async")]
+            current_async: None,
+            observers: Vec::new(),
+            transforms: Vec::new(),
         }
     }
     
+    // Redirects console.log, info and debug, which write to stdout by default
+    pub fn set_output(&mut self, output: Box<dyn Write>) {
+        *self.output.borrow_mut() = output;
+    }
+    
+    // Redirects console.error and warn, which write to stderr by default
+    pub fn set_error_output(&mut self, output: Box<dyn Write>) {
+        *self.error_output.borrow_mut() = output;
+    }
+    
+    // Lexes, parses and runs a script within the nesting limit
+    pub fn eval(&mut self, source: &str) -> Result<Value, JsEngineError> {
+        let statements = crate::parse_str_with_limits(source, &self.limits)?;
+        self.interpret_with_source(statements, source)
+    }
+    
+    // Runs a script like eval, collecting what it prints instead of passing
+    // it on; the previous output streams are restored afterwards
+    pub fn eval_captured(&mut self, source: &str) -> (Result<Value, JsEngineError>, CapturedOutput) {
+        let stdout = Rc::new(RefCell::new(Vec::new()));
+        let stderr = Rc::new(RefCell::new(Vec::new()));
+        let previous_output = std::mem::replace(&mut *self.output.borrow_mut(), Box::new(Buffer(Rc::clone(&stdout))));
+        let previous_error_output = std::mem::replace(&mut *self.error_output.borrow_mut(), Box::new(Buffer(Rc::clone(&stderr))));
+        
+        let result = self.eval(source);
+        
+        *self.output.borrow_mut() = previous_output;
+        *self.error_output.borrow_mut() = previous_error_output;
+        
+        let captured = CapturedOutput {
+            stdout: String::from_utf8_lossy(&stdout.borrow()).into_owned(),
+            stderr: String::from_utf8_lossy(&stderr.borrow()).into_owned(),
+        };
+        (result, captured)
+    }
+    
+    // Registers an observer that is notified of statements, calls and errors
+    // for every subsequent run
+    pub fn add_observer(&mut self, observer: Box<dyn ExecutionObserver>) {
+        self.observers.push(observer);
+    }
+    
+    // Step and call depth limits apply to every subsequent run; the nesting
+    // limit is enforced by the parser
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+    
+    // Defines or replaces a global binding, such as a host-provided function
+    pub fn define_global(&mut self, name: &str, value: Value) {
+        self.globals.borrow_mut().define(Rc::from(name), value);
+    }
+    
+    // Queue of callbacks run after each script, shared with the built-ins
+    // that schedule them
+    pub fn tasks(&self) -> Rc<RefCell<TaskQueue>> {
+        Rc::clone(&self.tasks)
+    }
+    
+    pub(crate) fn call_stack(&self) -> &Rc<RefCell<CallStack>> {
+        &self.stack
+    }
+    
+    // The marks and measures recorded by performance.mark and
+    // performance.measure
+    pub fn timeline(&self) -> Rc<RefCell<Timeline>> {
+        Rc::clone(&self.timeline)
+    }
+    
+    pub fn clock(&self) -> Rc<dyn Clock> {
+        Rc::clone(&self.clock)
+    }
+    
+    pub fn rng(&self) -> Rc<RefCell<dyn Rng>> {
+        Rc::clone(&self.rng)
+    }
+    
+    pub(crate) fn globals(&self) -> &Rc<RefCell<Environment>> {
+        &self.globals
+    }
+    
+    // Calls a function value from outside the script, e.g. a callback the
+    // script handed to the host
+    pub fn call_function(&mut self, callee: &Value, arguments: &[Value]) -> Result<Value, JsEngineError> {
+        self.call(callee, arguments.iter().cloned().collect())
+    }
+    
+    // Registers a pass applied to every program between parsing and execution
+    pub fn add_transform(&mut self, transform: Box<AstTransform>) {
+        self.transforms.push(transform);
+    }
+    
+    // Calls the script's handlers for a named event in the order they were
+    // registered, stopping at the first that fails. Returns how many ran.
+    pub fn emit(&mut self, event: &str, arguments: &[Value]) -> Result<usize, JsEngineError> {
+        let handlers = self.events.borrow_mut().take(event);
+        
+        for handler in &handlers {
+            self.call(&handler.callback, arguments.iter().cloned().collect())?;
+        }
+        
+        Ok(handlers.len())
+    }
+    
+    // Number of handlers currently registered for an event
+    pub fn listener_count(&self, event: &str) -> usize {
+        self.events.borrow().count(event)
+    }
+    
+    // Keeps a value alive for the host until the returned root is dropped
+    pub fn root(&mut self, value: Value) -> Root {
+        self.next_root += 1;
+        Root::new(&self.roots, Rc::from(format!("(root {})", self.next_root)), value)
+    }
+    
+    // Statistics for everything reachable from the global and current scopes
+    // and the host's roots
+    pub fn heap_stats(&self) -> HeapStats {
+        heap::collect_stats(&[&self.globals, &self.environment, &self.roots])
+    }
+    
+    // The object graph reachable from the global and current scopes and the
+    // host's roots
+    pub fn heap_graph(&self) -> HeapGraph {
+        HeapGraph::collect(&[&self.globals, &self.environment, &self.roots])
+    }
+    
+    // Runs statements parsed from `source`, which functions keep so they
+    // can return their text from toString
+    pub fn interpret_with_source(&mut self, statements: Vec<Stmt>, source: &str) -> Result<Value, JsEngineError> {
+        let previous = self.source.replace(Rc::from(source));
+        let result = self.interpret(statements);
+        self.source = previous;
+        result
+    }
+    
     pub fn interpret(&mut self, statements: Vec<Stmt>) -> Result<Value, JsEngineError> {
+        let result = self.run_program(statements).and_then(|value| {
+            self.run_tasks()?;
+            Ok(value)
+        });
+        
+        if let Err(error) = &result {
+            self.report_error(error);
+        }
+        
+        result
+    }
+    
+    // Transforms, resolves and runs a program, leaving any tasks it
+    // scheduled in the queue
+    pub(crate) fn run_program(&mut self, mut statements: Vec<Stmt>) -> Result<Value, JsEngineError> {
+        for transform in &self.transforms {
+            statements = transform(statements);
+        }
+        
+        {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("resolve").entered();
+            resolver::resolve(&mut statements);
+        }
+        
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("eval", statements = statements.len()).entered();
+        self.steps = 0;
+        self.stack.borrow_mut().enter(None, self.source.clone(), 0);
+        let value = self.run(&statements);
+        self.stack.borrow_mut().exit();
+        let value = value?;
+        self.run_microtasks()?;
+        Ok(value)
+    }
+    
+    pub(crate) fn report_error(&mut self, error: &JsEngineError) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(%error, "script failed");
+        
+        for observer in &mut self.observers {
+            observer.on_error(error);
+        }
+    }
+    
+    fn run(&mut self, statements: &[Stmt]) -> Result<Value, JsEngineError> {
+        self.hoist_var_declarations(statements)?;
+        self.hoist_lexical_declarations(statements);
         let mut last_value = Value::Undefined;
         
         for statement in statements {
-            match self.execute(&statement)? {
+            match self.execute(statement)? {
                 ExecutionResult::Value(value) => last_value = value,
                 ExecutionResult::Return(value) => return Ok(value),
-                ExecutionResult::None => {}
+                ExecutionResult::TailCall(call) => {
+                    let (callee, this, arguments) = *call;
+                    return self.call_method(&callee, this, arguments);
+                },
+                // The parser only accepts break inside a loop or switch
+                ExecutionResult::Break | ExecutionResult::None => {}
             }
         }
         
         Ok(last_value)
     }
     
-    fn execute(&mut self, stmt: &Stmt) -> Result<ExecutionResult, JsEngineError> {
+    // Runs scheduled tasks until none are left, waiting for each to fall due
+    fn run_tasks(&mut self) -> Result<(), JsEngineError> {
+        while let Some(task) = self.next_task() {
+            self.clock.sleep_until(task.due);
+            self.run_task(task)?;
+        }
+        Ok(())
+    }
+    
+    pub(crate) fn next_task(&self) -> Option<Task> {
+        self.tasks.borrow_mut().pop_next()
+    }
+    
+    pub(crate) fn run_task(&mut self, task: Task) -> Result<Value, JsEngineError> {
+        if let Some(interval) = task.interval {
+            self.tasks.borrow_mut().repeat(&task, self.clock.now() + interval);
+        }
+        let value = self.call(&task.callback, task.arguments.into_iter().collect())?;
+        self.run_microtasks()?;
+        Ok(value)
+    }
+    
+    // Runs queued microtasks, including those they queue, until none are left
+    fn run_microtasks(&mut self) -> Result<(), JsEngineError> {
+        loop {
+            let Some(callback) = self.tasks.borrow_mut().pop_microtask() else {
+                return Ok(());
+            };
+            self.call(&callback, Arguments::new())?;
+        }
+    }
+    
+    pub(crate) fn execute(&mut self, stmt: &Stmt) -> Result<ExecutionResult, JsEngineError> {
+        for observer in &mut self.observers {
+            observer.on_statement(stmt);
+        }
+        
+        if let Some(max_steps) = self.limits.max_steps {
+            self.steps += 1;
+            if self.steps > max_steps {
+                return Err(JsEngineError::RuntimeError {
+                    message: format!("Execution exceeded the limit of {} steps", max_steps),
+                });
+            }
+        }
+        
+        #[cfg(feature = "resumable")]
+        if let Some(budget) = &mut self.budget {
+            while budget.remaining == 0 {
+                // SAFETY: see Budget::pause
+                budget.remaining = unsafe { (*budget.pause)() };
+            }
+            budget.remaining -= 1;
+        }
+        
         match stmt {
             Stmt::Expression(expr) => {
                 let value = self.evaluate(expr)?;
                 Ok(ExecutionResult::Value(value))
             },
-            Stmt::Declaration(name, initializer) => {
+            Stmt::Declaration(name, initializer, _, kind) => {
                 let value = if let Some(expr) = initializer {
                     self.evaluate(expr)?
+                } else if *kind == DeclarationKind::Var {
+                    // The name was bound to undefined when the function
+                    // started, and keeps any value assigned since
+                    return Ok(ExecutionResult::None);
                 } else {
                     Value::Undefined
                 };
                 
-                self.environment.borrow_mut().define(name.clone(), value);
+                self.declare(name, value, *kind)?;
+                Ok(ExecutionResult::None)
+            },
+            Stmt::Destructure(pattern, initializer, kind) => {
+                let value = self.evaluate(initializer)?;
+                self.bind_pattern(pattern, value, *kind)?;
                 Ok(ExecutionResult::None)
             },
             Stmt::Block(statements) => {
                 let previous = Rc::clone(&self.environment);
-                self.environment = Rc::new(RefCell::new(Environment::with_enclosing(previous)));
+                self.environment = Rc::new(RefCell::new(Environment::with_enclosing(Rc::clone(&previous))));
+                
+                let result = self.execute_block(statements);
+                
+                // Restore previous environment, also when the block failed
+                self.environment = previous;
+                
+                result
+            },
+            Stmt::If(condition, then_branch, else_branch) => {
+                let condition_value = self.evaluate(condition)?;
                 
+                if self.is_truthy(&condition_value) {
+                    self.execute(then_branch)
+                } else if let Some(else_stmt) = else_branch {
+                    self.execute(else_stmt)
+                } else {
+                    Ok(ExecutionResult::None)
+                }
+            },
+            Stmt::While(condition, body) => {
                 let mut result = ExecutionResult::None;
                 
-                for statement in statements {
-                    result = self.execute(statement)?;
+                loop {
+                    let condition_value = self.evaluate(condition)?;
+                    if !self.is_truthy(&condition_value) {
+                        break;
+                    }
                     
-                    if let ExecutionResult::Return(_) = result {
+                    result = self.execute(body)?;
+                    
+                    if result.ends_statement() {
                         break;
                     }
                 }
                 
-                // Restore previous environment
-                {
-                    let enclosing_env = Rc::clone(&self.environment.borrow().enclosing.as_ref().unwrap());
-                    self.environment = enclosing_env;
+                Ok(result)
+            },
+            Stmt::For(initializer, condition, update, body, per_iteration) => {
+                let previous = Rc::clone(&self.environment);
+                self.environment = Rc::new(RefCell::new(Environment::with_enclosing(Rc::clone(&previous))));
+                
+                let result = self.for_loop(initializer.as_deref(), condition.as_ref(), update.as_ref(), body, per_iteration.then_some(&previous));
+                
+                self.environment = previous;
+                
+                result
+            },
+            Stmt::ForOf(name, iterable, body, _, declared) => {
+                let iterable = self.evaluate(iterable)?;
+                let mut result = ExecutionResult::None;
+                
+                let mut iterator = ValueIterator::new(self, &iterable)?;
+                while let Some(value) = iterator.next_value(self)? {
+                    result = if *declared {
+                        let previous = Rc::clone(&self.environment);
+                        self.environment = Rc::new(RefCell::new(Environment::with_enclosing(Rc::clone(&previous))));
+                        self.environment.borrow_mut().define(Rc::from(name.as_str()), value);
+                        
+                        let result = self.execute(body);
+                        self.environment = previous;
+                        result?
+                    } else {
+                        self.environment.borrow_mut().assign(name, value)?;
+                        self.execute(body)?
+                    };
+                    
+                    if result.ends_statement() {
+                        break;
+                    }
                 }
                 
                 Ok(result)
             },
-            Stmt::If(condition, then_branch, else_branch) => {
-                let condition_value = self.evaluate(condition)?;
+            Stmt::Switch(discriminant, clauses) => {
+                let value = self.evaluate(discriminant)?;
                 
-                if self.is_truthy(&condition_value) {
-                    self.execute(then_branch)
-                } else if let Some(else_stmt) = else_branch {
-                    self.execute(else_stmt)
+                let previous = Rc::clone(&self.environment);
+                self.environment = Rc::new(RefCell::new(Environment::with_enclosing(Rc::clone(&previous))));
+                
+                let result = self.switch(&value, clauses);
+                
+                self.environment = previous;
+                
+                result
+            },
+            Stmt::Break => Ok(ExecutionResult::Break),
+            Stmt::Try(block, handler, finalizer) => {
+                let mut result = self.execute(block);
+                result = self.settle_tail_call(result);
+                if let Some(handler) = handler
+                    && let Err(error) = result
+                {
+                    let exception = self.exception_value(error);
+                    result = self.catch(handler, exception, finalizer.is_some());
+                }
+                
+                let Some(finalizer) = finalizer else {
+                    return result;
+                };
+                
+                // A finally block that returns or breaks overrides how the
+                // rest of the statement ended, even with an exception
+                let finished = self.execute(finalizer)?;
+                if finished.is_completion() {
+                    return Ok(finished);
+                }
+                result
+            },
+            Stmt::Throw(value) => {
+                let value = self.evaluate(value)?;
+                Err(JsEngineError::thrown(value))
+            },
+            Stmt::Return(value) => {
+                if self.call_depth > 0 && let Some(expr) = value {
+                    return self.evaluate_tail(expr);
+                }
+                
+                let return_value = if let Some(expr) = value {
+                    self.evaluate(expr)?
                 } else {
-                    Ok(ExecutionResult::None)
+                    Value::Undefined
+                };
+                
+                Ok(ExecutionResult::Return(return_value))
+            },
+            Stmt::Function(name, parameters, body, span, kind) => {
+                let function = Value::Function(Rc::new(JsFunction {
+                    name: Some(Rc::from(name.as_str())),
+                    parameters: parameters.iter().map(|(name, _)| Rc::clone(name)).collect(),
+                    body: Rc::clone(body),
+                    closure: Rc::clone(&self.environment),
+                    source: self.source.clone(),
+                    span: *span,
+                    kind: *kind,
+                }));
+                
+                self.declare(name, function, DeclarationKind::Let)?;
+                Ok(ExecutionResult::None)
+            },
+        }
+    }
+    
+    // Binds a name declared by the script: `var` in the enclosing function
+    // and anything else in the current scope
+    fn declare(&mut self, name: &str, value: Value, kind: DeclarationKind) -> Result<(), JsEngineError> {
+        let environment = match kind {
+            DeclarationKind::Var => self.function_environment(),
+            DeclarationKind::Let | DeclarationKind::Const => Rc::clone(&self.environment),
+        };
+        let mut environment = environment.borrow_mut();
+        if environment.is_read_only(name) {
+            return Err(read_only_error(name));
+        }
+        
+        if kind == DeclarationKind::Const {
+            environment.define_constant(Rc::from(name), value);
+        } else {
+            environment.define(Rc::from(name), value);
+        }
+        Ok(())
+    }
+    
+    // The innermost function call's environment, or the global one
+    fn function_environment(&self) -> Rc<RefCell<Environment>> {
+        let mut environment = Rc::clone(&self.environment);
+        loop {
+            let enclosing = {
+                let current = environment.borrow();
+                match &current.enclosing {
+                    Some(enclosing) if !current.function_scope => Rc::clone(enclosing),
+                    _ => break,
+                }
+            };
+            environment = enclosing;
+        }
+        environment
+    }
+    
+    // Binds the `var` names of a function body or script to undefined up
+    // front, so they can be used before their declarations run. Names
+    // already bound, such as parameters, keep their values.
+    fn hoist_var_declarations(&mut self, statements: &[Stmt]) -> Result<(), JsEngineError> {
+        for (name, _) in ast::var_bindings(statements) {
+            let mut environment = self.environment.borrow_mut();
+            if !environment.values.contains_key(name) {
+                if environment.is_read_only(name) {
+                    return Err(read_only_error(name));
+                }
+                environment.define(Rc::from(name), Value::Undefined);
+            }
+        }
+        Ok(())
+    }
+    
+    // Reserves the let and const names declared directly in a block, so
+    // that using them before their declaration is an error instead of
+    // reaching an outer binding
+    fn hoist_lexical_declarations(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            match statement {
+                Stmt::Declaration(name, _, _, DeclarationKind::Let | DeclarationKind::Const) => {
+                    self.environment.borrow_mut().define_uninitialized(name);
+                },
+                Stmt::Destructure(pattern, _, DeclarationKind::Let | DeclarationKind::Const) => {
+                    for (name, _) in pattern.bindings() {
+                        self.environment.borrow_mut().define_uninitialized(name);
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+    
+    // Declares the names in a destructuring pattern. Properties are read
+    // like member accesses, so strings and numbers can be destructured too,
+    // and a default is evaluated only when its property is undefined.
+    fn bind_pattern(&mut self, pattern: &Pattern, value: Value, kind: DeclarationKind) -> Result<(), JsEngineError> {
+        let properties = match pattern {
+            Pattern::Identifier(name, _) => return self.declare(name, value, kind),
+            Pattern::Object(properties) => properties,
+        };
+        
+        if matches!(value, Value::Null | Value::Undefined) {
+            let message = match properties.first() {
+                Some((key, ..)) => format!("Cannot destructure property '{}' of '{:?}' as it is {:?}.", key, value, value),
+                None => format!("Cannot destructure '{:?}' as it is {:?}.", value, value),
+            };
+            return Err(JsEngineError::TypeError { message });
+        }
+        
+        for (key, target, default) in properties {
+            let mut property = self.get_property(&value, key)?;
+            if let (Value::Undefined, Some(default)) = (&property, default) {
+                property = self.evaluate(default)?;
+            }
+            self.bind_pattern(target, property, kind)?;
+        }
+        Ok(())
+    }
+    
+    // Runs a catch clause with its parameter bound to the exception. The
+    // clause's tail calls are made here instead when a finally block has
+    // to run after them.
+    fn catch(&mut self, (parameter, body): &CatchClause, exception: Value, before_finally: bool) -> Result<ExecutionResult, JsEngineError> {
+        let previous = Rc::clone(&self.environment);
+        self.environment = Rc::new(RefCell::new(Environment::with_enclosing(Rc::clone(&previous))));
+        
+        let mut result = match parameter {
+            Some(parameter) => self.bind_pattern(parameter, exception, DeclarationKind::Let).and_then(|_| self.execute(body)),
+            None => self.execute(body),
+        };
+        if before_finally {
+            result = self.settle_tail_call(result);
+        }
+        
+        self.environment = previous;
+        result
+    }
+    
+    // A `return f()` inside a try block would otherwise leave the block
+    // before the call is made, so its exceptions would escape the catch
+    // clause and the finally block would run too early. Makes the call
+    // and returns its value instead.
+    pub(crate) fn settle_tail_call(&mut self, result: Result<ExecutionResult, JsEngineError>) -> Result<ExecutionResult, JsEngineError> {
+        match result {
+            Ok(ExecutionResult::TailCall(call)) => {
+                let (callee, this, arguments) = *call;
+                self.call_method(&callee, this, arguments).map(ExecutionResult::Return)
+            },
+            result => result,
+        }
+    }
+    
+    fn execute_block(&mut self, statements: &[Stmt]) -> Result<ExecutionResult, JsEngineError> {
+        self.hoist_lexical_declarations(statements);
+        let mut result = ExecutionResult::None;
+        
+        for statement in statements {
+            result = self.execute(statement)?;
+            
+            if result.is_completion() {
+                break;
+            }
+        }
+        
+        Ok(result)
+    }
+    
+    // Runs in the loop's own environment, which the initializer declares
+    // into. With per-iteration bindings, the variables are copied into a
+    // fresh environment inside `enclosing` before each iteration's condition,
+    // as JS does, so closures created in the body keep the values of their
+    // iteration.
+    fn for_loop(
+        &mut self,
+        initializer: Option<&Stmt>,
+        condition: Option<&Expr>,
+        update: Option<&Expr>,
+        body: &Stmt,
+        enclosing: Option<&Rc<RefCell<Environment>>>,
+    ) -> Result<ExecutionResult, JsEngineError> {
+        if let Some(initializer) = initializer {
+            self.execute(initializer)?;
+        }
+        
+        let mut result = ExecutionResult::None;
+        let mut first = true;
+        
+        loop {
+            if let Some(enclosing) = enclosing {
+                self.environment = self.copy_iteration_environment(enclosing);
+            }
+            
+            if !first && let Some(update) = update {
+                self.evaluate(update)?;
+            }
+            first = false;
+            
+            if let Some(condition) = condition {
+                let condition_value = self.evaluate(condition)?;
+                if !self.is_truthy(&condition_value) {
+                    break;
+                }
+            }
+            
+            result = self.execute(body)?;
+            
+            if result.ends_statement() {
+                break;
+            }
+        }
+        
+        Ok(result)
+    }
+    
+    // Tests the cases in order until one equals `value` (strictly, as
+    // ===), then runs the clauses from there on, falling through from one
+    // to the next until a break. Without a match, runs from the default
+    // clause, wherever it is.
+    fn switch(&mut self, value: &Value, clauses: &[SwitchClause]) -> Result<ExecutionResult, JsEngineError> {
+        let mut start = None;
+        for (index, (test, _)) in clauses.iter().enumerate() {
+            if let Some(test) = test {
+                let test = self.evaluate(test)?;
+                if self.is_equal(value, &test) {
+                    start = Some(index);
+                    break;
                 }
+            }
+        }
+        
+        let Some(start) = start.or_else(|| clauses.iter().position(|(test, _)| test.is_none())) else {
+            return Ok(ExecutionResult::None);
+        };
+        
+        let mut result = ExecutionResult::None;
+        for (_, body) in &clauses[start..] {
+            result = self.execute_block(body)?;
+            
+            if result.ends_statement() {
+                break;
+            }
+        }
+        
+        Ok(result)
+    }
+    
+    fn copy_iteration_environment(&self, enclosing: &Rc<RefCell<Environment>>) -> Rc<RefCell<Environment>> {
+        let current = self.environment.borrow();
+        let mut copy = Environment::with_enclosing(Rc::clone(enclosing));
+        for (name, value) in current.bindings() {
+            copy.define(Rc::clone(name), value.clone());
+        }
+        copy.constants = current.constants.clone();
+        Rc::new(RefCell::new(copy))
+    }
+    
+    // The value of an expression in return position inside a function. A
+    // call there is handed back to `call` so it can reuse the current frame
+    // instead of recursing, and so is one in the part of &&, ||, ?? or ?:
+    // that gives the value.
+    fn evaluate_tail(&mut self, expr: &Expr) -> Result<ExecutionResult, JsEngineError> {
+        match expr {
+            Expr::Call(callee, arguments, _) => {
+                let (callee_value, this) = self.evaluate_callee(callee)?;
+                let arg_values = self.evaluate_arguments(arguments)?;
+                Ok(ExecutionResult::TailCall(Box::new((callee_value, this, arg_values))))
             },
-            Stmt::While(condition, body) => {
-                let condition_value = self.evaluate(condition)?;
-                
-                let mut result = ExecutionResult::None;
-                
-                while self.is_truthy(&condition_value) {
-                    result = self.execute(body)?;
-                    
-                    if let ExecutionResult::Return(_) = result {
-                        break;
-                    }
+            Expr::Binary(left, operator @ (BinaryOp::And | BinaryOp::Or | BinaryOp::Nullish), right) => {
+                let left_value = self.evaluate(left)?;
+                if self.short_circuits(operator, &left_value) {
+                    return Ok(ExecutionResult::Return(left_value));
                 }
-                
-                Ok(result)
-            },
-            Stmt::Return(value) => {
-                let return_value = if let Some(expr) = value {
-                    self.evaluate(expr)?
-                } else {
-                    Value::Undefined
-                };
-                
-                Ok(ExecutionResult::Return(return_value))
+                self.evaluate_tail(right)
             },
-            Stmt::Function(name, parameters, body) => {
-                let function = Value::Function(Rc::new(JsFunction {
-                    parameters: parameters.clone(),
-                    body: (**body).clone(),
-                    closure: self.environment.borrow().clone(),
-                }));
-                
-                self.environment.borrow_mut().define(name.clone(), function);
-                Ok(ExecutionResult::None)
+            Expr::Conditional(condition, then_branch, else_branch) => match self.branch(condition, then_branch, else_branch.as_deref())? {
+                Some(branch) => self.evaluate_tail(branch),
+                None => Ok(ExecutionResult::Return(Value::Undefined)),
             },
+            _ => Ok(ExecutionResult::Return(self.evaluate(expr)?)),
+        }
+    }
+    
+    // Whether the left operand of &&, || or ?? is the result, so that the
+    // right operand does not run
+    fn short_circuits(&self, operator: &BinaryOp, left_value: &Value) -> bool {
+        match operator {
+            BinaryOp::And => !self.is_truthy(left_value),
+            BinaryOp::Or => self.is_truthy(left_value),
+            _ => !matches!(left_value, Value::Null | Value::Undefined),
+        }
+    }
+    
+    // The branch of a conditional that its condition picks, if it has one
+    fn branch<'a>(&mut self, condition: &Expr, then_branch: &'a Expr, else_branch: Option<&'a Expr>) -> Result<Option<&'a Expr>, JsEngineError> {
+        let condition_value = self.evaluate(condition)?;
+        if self.is_truthy(&condition_value) {
+            Ok(Some(then_branch))
+        } else {
+            Ok(else_branch)
         }
     }
     
     fn evaluate(&mut self, expr: &Expr) -> Result<Value, JsEngineError> {
         match expr {
             Expr::Number(value) => Ok(Value::Number(*value)),
-            Expr::String(value) => Ok(Value::String(value.clone())),
+            Expr::String(value) => Ok(Value::String(JsString::from(Rc::clone(value)))),
             Expr::Boolean(value) => Ok(Value::Boolean(*value)),
             Expr::Null => Ok(Value::Null),
-            Expr::Variable(name) => self.environment.borrow().get(name),
+            #[cfg(feature = "regex")]
+            Expr::RegExp(pattern, flags) => Ok(RegExp::new(pattern, flags)?.into_value()),
+            #[cfg(not(feature = "regex"))]
+            Expr::RegExp(..) => Err(JsEngineError::SyntaxError {
+                message: "regular expressions are not supported in this build".to_string(),
+            }),
+            Expr::Variable(name, _) => self.environment.borrow().get(name),
+            Expr::Global(name, _, cache) => self.globals.borrow().get_cached(name, cache),
             Expr::Binary(left, operator, right) => {
                 let left_value = self.evaluate(left)?;
-                let right_value = self.evaluate(right)?;
                 
-                match operator {
-                    BinaryOp::Add => self.add(&left_value, &right_value),
-                    BinaryOp::Subtract => self.subtract(&left_value, &right_value),
-                    BinaryOp::Multiply => self.multiply(&left_value, &right_value),
-                    BinaryOp::Divide => self.divide(&left_value, &right_value),
-                    BinaryOp::Equal => Ok(Value::Boolean(self.is_equal(&left_value, &right_value))),
-                    BinaryOp::NotEqual => Ok(Value::Boolean(!self.is_equal(&left_value, &right_value))),
-                    BinaryOp::Less => self.less_than(&left_value, &right_value),
-                    BinaryOp::LessEqual => self.less_equal(&left_value, &right_value),
-                    BinaryOp::Greater => self.greater_than(&left_value, &right_value),
-                    BinaryOp::GreaterEqual => self.greater_equal(&left_value, &right_value),
-                    BinaryOp::And => {
-                        if !self.is_truthy(&left_value) {
-                            Ok(left_value)
-                        } else {
-                            Ok(right_value)
-                        }
-                    },
-                    BinaryOp::Or => {
-                        if self.is_truthy(&left_value) {
-                            Ok(left_value)
-                        } else {
-                            Ok(right_value)
-                        }
-                    },
+                // The right operand of &&, || and ?? only runs when it
+                // decides the result
+                if matches!(operator, BinaryOp::And | BinaryOp::Or | BinaryOp::Nullish) {
+                    if self.short_circuits(operator, &left_value) {
+                        return Ok(left_value);
+                    }
+                    return self.evaluate(right);
                 }
+                
+                let right_value = self.evaluate(right)?;
+                self.binary_operation(operator, left_value, right_value)
             },
             Expr::Unary(operator, right) => {
                 let right_value = self.evaluate(right)?;
@@ -297,86 +1400,635 @@ impl Interpreter {
                 match operator {
                     UnaryOp::Negate => self.negate(&right_value),
                     UnaryOp::Not => Ok(Value::Boolean(!self.is_truthy(&right_value))),
+                    UnaryOp::Plus => {
+                        let primitive = self.primitive_value(right_value, false)?;
+                        Ok(Value::Number(stdlib::to_number(&primitive)))
+                    },
+                    UnaryOp::Void => Ok(Value::Undefined),
                 }
             },
             Expr::Conditional(condition, then_branch, else_branch) => {
-                let condition_value = self.evaluate(condition)?;
-                
-                if self.is_truthy(&condition_value) {
-                    self.evaluate(then_branch)
-                } else if let Some(else_expr) = else_branch {
-                    self.evaluate(else_expr)
-                } else {
-                    Ok(Value::Undefined)
+                match self.branch(condition, then_branch, else_branch.as_deref())? {
+                    Some(branch) => self.evaluate(branch),
+                    None => Ok(Value::Undefined),
                 }
             },
-            Expr::Assign(name, value) => {
+            Expr::Assign(name, value, _) => {
                 let value = self.evaluate(value)?;
                 self.environment.borrow_mut().assign(name, value.clone())?;
                 Ok(value)
             },
-            Expr::Call(callee, arguments) => {
-                let callee_value = self.evaluate(callee)?;
+            Expr::CompoundAssign(target, operator, value) => match target.as_ref() {
+                Expr::Variable(name, _) | Expr::Global(name, _, _) => {
+                    let current = self.evaluate(target)?;
+                    let value = self.evaluate(value)?;
+                    let result = self.binary_operation(operator, current, value)?;
+                    self.environment.borrow_mut().assign(name, result.clone())?;
+                    Ok(result)
+                },
+                Expr::Get(object, name, cache) => {
+                    let object = self.evaluate(object)?;
+                    let current = self.get_cached_property(&object, name, cache)?;
+                    let value = self.evaluate(value)?;
+                    let result = self.binary_operation(operator, current, value)?;
+                    self.set_cached_property(&object, name, result.clone(), cache)?;
+                    Ok(result)
+                },
+                Expr::Index(object, key) => {
+                    let object = self.evaluate(object)?;
+                    let key = self.evaluate(key)?;
+                    let key = self.property_key(key)?;
+                    let current = self.get_property(&object, &key)?;
+                    let value = self.evaluate(value)?;
+                    let result = self.binary_operation(operator, current, value)?;
+                    self.set_property(&object, &key, result.clone())?;
+                    Ok(result)
+                },
+                _ => Err(JsEngineError::SyntaxError {
+                    message: "Invalid assignment target".to_string(),
+                }),
+            },
+            Expr::Call(callee, arguments, span) => {
+                let (callee_value, this) = self.evaluate_callee(callee)?;
+                let arg_values = self.evaluate_arguments(arguments)?;
                 
-                let mut arg_values = Vec::new();
-                for argument in arguments {
-                    arg_values.push(self.evaluate(argument)?);
-                }
+                self.stack.borrow_mut().set_position(span.start);
+                self.call_method(&callee_value, this, arg_values)
+            },
+            Expr::New(constructor, arguments, span) => {
+                let constructor = self.evaluate(constructor)?;
+                let arg_values = self.evaluate_arguments(arguments)?;
                 
-                self.call(&callee_value, arg_values)
+                self.stack.borrow_mut().set_position(span.start);
+                self.construct(&constructor, arg_values)
+            },
+            Expr::Object(properties) => {
+                let mut object = PropertyMap::new();
+                for (key, value) in properties {
+                    let name = match key {
+                        PropertyKey::Name(name) => name.to_string(),
+                        PropertyKey::Computed(key) => {
+                            let key = self.evaluate(key)?;
+                            self.property_key(key)?
+                        },
+                    };
+                    let value = self.evaluate(value)?;
+                    object.insert(name, value);
+                }
+                Ok(Value::Object(Rc::new(RefCell::new(object))))
             },
-            Expr::Function(parameters, body) => {
+            Expr::Array(elements) => {
+                let mut values = Vec::with_capacity(elements.len());
+                for element in elements {
+                    self.evaluate_spreading(element, &mut values)?;
+                }
+                Ok(Value::Object(Rc::new(RefCell::new(PropertyMap::array(values)))))
+            },
+            Expr::Get(object, name, cache) => {
+                let object = self.evaluate(object)?;
+                self.get_cached_property(&object, name, cache)
+            },
+            Expr::Index(object, key) => {
+                let object = self.evaluate(object)?;
+                let key = self.evaluate(key)?;
+                let key = self.property_key(key)?;
+                self.get_property(&object, &key)
+            },
+            Expr::Set(object, name, value, cache) => {
+                let object = self.evaluate(object)?;
+                let value = self.evaluate(value)?;
+                self.set_cached_property(&object, name, value.clone(), cache)?;
+                Ok(value)
+            },
+            Expr::SetIndex(object, key, value) => {
+                let object = self.evaluate(object)?;
+                let key = self.evaluate(key)?;
+                let key = self.property_key(key)?;
+                let value = self.evaluate(value)?;
+                self.set_property(&object, &key, value.clone())?;
+                Ok(value)
+            },
+            Expr::Chain(chain) => Ok(self.evaluate_chain(chain)?.unwrap_or(Value::Undefined)),
+            // Only reached when a chain is built without Expr::Chain around
+            // it, in which case the base is not optional
+            Expr::Optional(base) => self.evaluate(base),
+            // Only valid as an array element or call argument, which are
+            // evaluated with evaluate_spreading
+            Expr::Spread(_) => Err(JsEngineError::SyntaxError {
+                message: "Spread syntax is only allowed in array literals and call arguments".to_string(),
+            }),
+            Expr::This => Ok(self.environment.borrow().get("this").unwrap_or(Value::Undefined)),
+            Expr::Await(operand) => {
+                let value = self.evaluate(operand)?;
+                self.await_value(value)
+            },
+            Expr::Function(parameters, body, span, kind) => {
                 Ok(Value::Function(Rc::new(JsFunction {
-                    parameters: parameters.clone(),
-                    body: (**body).clone(),
-                    closure: self.environment.borrow().clone(),
+                    name: None,
+                    parameters: parameters.iter().map(|(name, _)| Rc::clone(name)).collect(),
+                    body: Rc::clone(body),
+                    closure: Rc::clone(&self.environment),
+                    source: self.source.clone(),
+                    span: *span,
+                    kind: *kind,
                 })))
             },
+            Expr::NamedFunction(function) => {
+                let Stmt::Function(name, ..) = function.as_ref() else {
+                    unreachable!("named function expressions hold a function declaration")
+                };
+                
+                // Declared in an environment of its own, which the function
+                // closes over
+                let previous = Rc::clone(&self.environment);
+                self.environment = Rc::new(RefCell::new(Environment::with_enclosing(Rc::clone(&previous))));
+                let result = self.execute(function);
+                let value = self.environment.borrow().get(name).unwrap_or(Value::Undefined);
+                self.environment = previous;
+                
+                result.map(|_| value)
+            },
+        }
+    }
+    
+    // The function a call expression calls and the value `this` is bound
+    // to: the object for `object.method()`, undefined for a plain call
+    fn evaluate_callee(&mut self, callee: &Expr) -> Result<(Value, Value), JsEngineError> {
+        match callee {
+            Expr::Get(object, name, cache) => {
+                let object = self.evaluate(object)?;
+                let method = self.get_cached_property(&object, name, cache)?;
+                Ok((method, object))
+            },
+            Expr::Index(object, key) => {
+                let object = self.evaluate(object)?;
+                let key = self.evaluate(key)?;
+                let key = self.property_key(key)?;
+                let method = self.get_property(&object, &key)?;
+                Ok((method, object))
+            },
+            _ => Ok((self.evaluate(callee)?, Value::Undefined)),
+        }
+    }
+    
+    // Applies an operator that needs both operands
+    fn binary_operation(&mut self, operator: &BinaryOp, mut left: Value, mut right: Value) -> Result<Value, JsEngineError> {
+        // Operators that work on primitives give objects a chance to convert
+        // themselves first
+        if matches!(operator, BinaryOp::Add | BinaryOp::Less | BinaryOp::LessEqual | BinaryOp::Greater | BinaryOp::GreaterEqual) {
+            left = self.primitive_value(left, false)?;
+            right = self.primitive_value(right, false)?;
+        }
+        
+        match operator {
+            BinaryOp::Add => self.add(&left, &right),
+            BinaryOp::Subtract => self.subtract(&left, &right),
+            BinaryOp::Multiply => self.multiply(&left, &right),
+            BinaryOp::Divide => self.divide(&left, &right),
+            BinaryOp::Remainder => self.remainder(&left, &right),
+            BinaryOp::Exponent => self.exponentiate(&left, &right),
+            BinaryOp::Equal => Ok(Value::Boolean(self.is_equal(&left, &right))),
+            BinaryOp::NotEqual => Ok(Value::Boolean(!self.is_equal(&left, &right))),
+            BinaryOp::Less => Ok(Value::Boolean(self.compare(&left, &right).is_some_and(Ordering::is_lt))),
+            BinaryOp::LessEqual => Ok(Value::Boolean(self.compare(&left, &right).is_some_and(Ordering::is_le))),
+            BinaryOp::Greater => Ok(Value::Boolean(self.compare(&left, &right).is_some_and(Ordering::is_gt))),
+            BinaryOp::GreaterEqual => Ok(Value::Boolean(self.compare(&left, &right).is_some_and(Ordering::is_ge))),
+            BinaryOp::And | BinaryOp::Or | BinaryOp::Nullish => unreachable!("handled before evaluating the right operand"),
+        }
+    }
+    
+    // Evaluates the inside of an optional chain, or None when an optional
+    // base in it is null or undefined
+    fn evaluate_chain(&mut self, expr: &Expr) -> Result<Option<Value>, JsEngineError> {
+        match expr {
+            Expr::Optional(base) => Ok(self.evaluate_chain(base)?.filter(|base| !matches!(base, Value::Null | Value::Undefined))),
+            Expr::Get(..) | Expr::Index(..) => Ok(self.chain_callee(expr)?.map(|(value, _)| value)),
+            Expr::Call(callee, arguments, span) => {
+                let Some((callee_value, this)) = self.chain_callee(callee)? else {
+                    return Ok(None);
+                };
+                let arg_values = self.evaluate_arguments(arguments)?;
+                
+                self.stack.borrow_mut().set_position(span.start);
+                self.call_method(&callee_value, this, arg_values).map(Some)
+            },
+            _ => self.evaluate(expr).map(Some),
+        }
+    }
+    
+    // evaluate_callee for a callee inside an optional chain
+    fn chain_callee(&mut self, callee: &Expr) -> Result<Option<(Value, Value)>, JsEngineError> {
+        match callee {
+            Expr::Optional(base) => {
+                let callee = self.chain_callee(base)?;
+                Ok(callee.filter(|(function, _)| !matches!(function, Value::Null | Value::Undefined)))
+            },
+            Expr::Get(object, name, cache) => {
+                let Some(object) = self.evaluate_chain(object)? else {
+                    return Ok(None);
+                };
+                let method = self.get_cached_property(&object, name, cache)?;
+                Ok(Some((method, object)))
+            },
+            Expr::Index(object, key) => {
+                let Some(object) = self.evaluate_chain(object)? else {
+                    return Ok(None);
+                };
+                let key = self.evaluate(key)?;
+                let key = self.property_key(key)?;
+                let method = self.get_property(&object, &key)?;
+                Ok(Some((method, object)))
+            },
+            _ => Ok(self.evaluate_chain(callee)?.map(|function| (function, Value::Undefined))),
+        }
+    }
+    
+    // The four operations on properties. Proxies are given them first; see
+    // proxy.rs.
+    
+    pub(crate) fn get_property(&mut self, object: &Value, name: &str) -> Result<Value, JsEngineError> {
+        if let Some(proxy) = Proxy::of(object) {
+            return proxy.get(self, object, name);
+        }
+        match object {
+            Value::Object(properties) => {
+                let properties = properties.borrow();
+                match properties.array_length() {
+                    Some(length) if name == "length" => Ok(Value::Number(length as f64)),
+                    _ => Ok(properties
+                        .lookup(name)
+                        .or_else(|| properties.slot().and_then(|slot| slot.method(name, object)))
+                        .or_else(|| (name == ITERATOR).then(|| iterator::iterator_method(object)).flatten())
+                        .unwrap_or(Value::Undefined)),
+                }
+            },
+            Value::Null | Value::Undefined => Err(JsEngineError::TypeError {
+                message: format!("Cannot read properties of {:?} (reading '{}')", object, name),
+            }),
+            Value::Number(n) => Ok(primitives::number_property(*n, name, &self.locale)),
+            Value::String(s) => Ok(primitives::string_property(s, name, &self.locale)),
+            // Function.prototype.toString, the only method functions have
+            Value::Function(_) | Value::NativeFunction(_) if name == "toString" => {
+                let text = match object {
+                    Value::Function(function) => function.source_text(),
+                    _ => "function () { [native code] }".to_string(),
+                };
+                Ok(Value::NativeFunction(Rc::new(move |_| Ok(Value::String(JsString::from(text.as_str()))))))
+            },
+            _ => Ok(Value::Undefined),
+        }
+    }
+    
+    // Assigning to a property of a primitive does nothing, as in sloppy mode;
+    // frozen objects refuse, as in strict mode, so hardened built-ins cannot
+    // be patched without the script noticing
+    pub(crate) fn set_property(&mut self, object: &Value, name: &str, value: Value) -> Result<(), JsEngineError> {
+        if let Some(proxy) = Proxy::of(object) {
+            if !proxy.set(self, object, name, value)? {
+                return Err(JsEngineError::TypeError {
+                    message: format!("'set' on proxy: trap returned falsish for property '{}'", name),
+                });
+            }
+            return Ok(());
+        }
+        match object {
+            Value::Object(properties) => {
+                if properties.borrow().is_frozen() {
+                    return Err(JsEngineError::TypeError {
+                        message: format!("Cannot assign to read only property '{}' of object", name),
+                    });
+                }
+                if let Some(slot) = properties.borrow().slot()
+                    && slot.set_property(name, &value)
+                {
+                    return Ok(());
+                }
+                if name == "length" && properties.borrow().is_array() {
+                    let length = stdlib::to_number(&value);
+                    if length.fract() != 0.0 || !(0.0..=u32::MAX as f64).contains(&length) {
+                        return Err(JsEngineError::throw("RangeError", "Invalid array length"));
+                    }
+                    properties.borrow_mut().set_array_length(length as usize);
+                    return Ok(());
+                }
+                properties.borrow_mut().insert(name.to_string(), value);
+                Ok(())
+            },
+            Value::Null | Value::Undefined => Err(JsEngineError::TypeError {
+                message: format!("Cannot set properties of {:?} (setting '{}')", object, name),
+            }),
+            _ => Ok(()),
+        }
+    }
+    
+    // get_property and set_property for `object.name` at a site with an
+    // inline cache. An own property of an object with the shape the site saw
+    // last is read or written by position; see shape.rs. Proxies and, for
+    // writes, objects whose slot or length may claim the name take the
+    // general path.
+    
+    fn get_cached_property(&mut self, object: &Value, name: &str, cache: &PropertyCache) -> Result<Value, JsEngineError> {
+        if let Value::Object(properties) = object {
+            let properties = properties.borrow();
+            if let Some(shape) = properties.shape()
+                && !matches!(properties.slot(), Some(InternalSlot::Proxy(_)))
+            {
+                if let Some(value) = cache.position(shape).and_then(|position| properties.value_at(position)) {
+                    return Ok(value.clone());
+                }
+                cache.remember(shape, name);
+            }
+        }
+        self.get_property(object, name)
+    }
+    
+    fn set_cached_property(&mut self, object: &Value, name: &str, value: Value, cache: &PropertyCache) -> Result<(), JsEngineError> {
+        let Value::Object(properties) = object else {
+            return self.set_property(object, name, value);
+        };
+        let cacheable = |properties: &PropertyMap| properties.slot().is_none() && !properties.is_array() && !properties.is_frozen();
+        
+        {
+            let mut properties = properties.borrow_mut();
+            if cacheable(&properties)
+                && let Some(position) = properties.shape().and_then(|shape| cache.position(shape))
+                && let Some(slot) = properties.value_at_mut(position)
+            {
+                *slot = value;
+                return Ok(());
+            }
+        }
+        
+        self.set_property(object, name, value)?;
+        let properties = properties.borrow();
+        if cacheable(&properties)
+            && let Some(shape) = properties.shape()
+        {
+            cache.remember(shape, name);
+        }
+        Ok(())
+    }
+    
+    // Whether an object has a property of that name, itself or through its
+    // prototypes, as `in` tells
+    pub(crate) fn has_property(&mut self, object: &Value, name: &str) -> Result<bool, JsEngineError> {
+        if let Some(proxy) = Proxy::of(object) {
+            return proxy.has(self, name);
+        }
+        match object {
+            Value::Object(properties) => {
+                let properties = properties.borrow();
+                Ok(properties.lookup(name).is_some() || (name == "length" && properties.is_array()))
+            },
+            Value::Function(_) | Value::NativeFunction(_) => Ok(false),
+            _ => Err(JsEngineError::TypeError {
+                message: format!("Cannot use 'in' operator to search for '{}' in {}", name, object),
+            }),
+        }
+    }
+    
+    // Removes an object's own property, as `delete` would. Frozen objects
+    // refuse, giving false; the length of an array stays.
+    pub(crate) fn delete_property(&mut self, object: &Value, name: &str) -> Result<bool, JsEngineError> {
+        if let Some(proxy) = Proxy::of(object) {
+            return proxy.delete(self, name);
+        }
+        match object {
+            Value::Object(properties) => {
+                let mut properties = properties.borrow_mut();
+                if properties.is_frozen() {
+                    return Ok(!properties.contains_key(name));
+                }
+                properties.remove(name);
+                Ok(true)
+            },
+            Value::Null | Value::Undefined => Err(JsEngineError::TypeError {
+                message: format!("Cannot convert {:?} to object", object),
+            }),
+            _ => Ok(true),
+        }
+    }
+    
+    // ToPropertyKey: the string a computed key names, so that o[1] and
+    // o["1"] are the same property
+    pub(crate) fn property_key(&mut self, key: Value) -> Result<String, JsEngineError> {
+        Ok(self.primitive_value(key, true)?.to_string())
+    }
+    
+    fn evaluate_arguments(&mut self, arguments: &[Expr]) -> Result<Arguments, JsEngineError> {
+        let mut arg_values = Arguments::with_capacity(arguments.len());
+        for argument in arguments {
+            self.evaluate_spreading(argument, &mut arg_values)?;
+        }
+        
+        Ok(arg_values)
+    }
+    
+    // Adds the value of an array element or call argument to `values`, or
+    // each value it iterates over when it is spread
+    fn evaluate_spreading(&mut self, expr: &Expr, values: &mut impl Extend<Value>) -> Result<(), JsEngineError> {
+        let Expr::Spread(iterable) = expr else {
+            values.extend([self.evaluate(expr)?]);
+            return Ok(());
+        };
+        let iterable = self.evaluate(iterable)?;
+        let mut iterator = ValueIterator::new(self, &iterable)?;
+        while let Some(value) = iterator.next_value(self)? {
+            values.extend([value]);
+        }
+        Ok(())
+    }
+    
+    fn call(&mut self, callee: &Value, arguments: Arguments) -> Result<Value, JsEngineError> {
+        self.call_method(callee, Value::Undefined, arguments)
+    }
+    
+    // Calls a function with `this` bound to `this`
+    pub(crate) fn call_method(&mut self, callee: &Value, this: Value, arguments: Arguments) -> Result<Value, JsEngineError> {
+        let previous = Rc::clone(&self.environment);
+        let previous_source = self.source.clone();
+        let result = self.call_with_tail_calls(callee.clone(), this, arguments);
+        
+        // Restore previous environment
+        self.environment = previous;
+        self.source = previous_source;
+        
+        result
+    }
+    
+    // `new constructor(...arguments)`. A script function runs with `this`
+    // bound to a new object, which is the result unless the function
+    // returns an object of its own. The built-in constructors make their
+    // object whether or not they are called with `new`, so they are just
+    // called.
+    pub(crate) fn construct(&mut self, constructor: &Value, arguments: Arguments) -> Result<Value, JsEngineError> {
+        match constructor {
+            Value::Function(function) if function.kind == FunctionKind::Function => {
+                let this = Value::Object(Rc::new(RefCell::new(PropertyMap::new())));
+                let result = self.call_method(constructor, this.clone(), arguments)?;
+                Ok(if matches!(result, Value::Object(_)) { result } else { this })
+            },
+            Value::NativeFunction(_) | Value::Object(_) if constructor.is_callable() => self.call(constructor, arguments),
+            _ => Err(JsEngineError::TypeError {
+                message: format!("{:?} is not a constructor", constructor),
+            }),
+        }
+    }
+    
+    // Runs a call to completion, looping instead of recursing whenever the
+    // callee finishes with a tail call
+    fn call_with_tail_calls(&mut self, mut callee: Value, mut this: Value, mut arguments: Arguments) -> Result<Value, JsEngineError> {
+        // Callees entered so far, including frames replaced by tail calls.
+        // Only tracked while observers are registered; their exits are
+        // reported innermost first once the chain finishes, so observers
+        // still see properly nested calls.
+        let mut frames = Vec::new();
+        
+        let result = loop {
+            if !callee.is_callable() {
+                break Err(JsEngineError::TypeError {
+                    message: format!("{:?} is not a function", callee),
+                });
+            }
+            
+            if !self.observers.is_empty() {
+                for observer in &mut self.observers {
+                    observer.on_call_enter(&callee, &arguments);
+                }
+                frames.push(callee.clone());
+            }
+            
+            #[cfg(feature = "tracing")]
+            let _span = match &callee {
+                Value::Function(function) => tracing::trace_span!(
+                    "call",
+                    function = function.name.as_deref().unwrap_or("(anonymous)"),
+                    arguments = arguments.len(),
+                ),
+                _ => tracing::trace_span!("call", function = "(native)", arguments = arguments.len()),
+            }
+            .entered();
+            
+            match self.call_frame(&callee, this, arguments) {
+                Ok(ExecutionResult::TailCall(call)) => (callee, this, arguments) = *call,
+                Ok(ExecutionResult::Return(value)) => break Ok(value),
+                Ok(ExecutionResult::Value(value)) => break Ok(value),
+                Ok(ExecutionResult::None | ExecutionResult::Break) => break Ok(Value::Undefined),
+                Err(error) => break Err(error),
+            }
+        };
+        
+        while let Some(frame) = frames.pop() {
+            for observer in &mut self.observers {
+                observer.on_call_exit(&frame, result.as_ref());
+            }
         }
+        
+        result
     }
     
-    fn call(&mut self, callee: &Value, arguments: Vec<Value>) -> Result<Value, JsEngineError> {
+    // Executes a single function body, which may end in a tail call
+    fn call_frame(&mut self, callee: &Value, this: Value, arguments: Arguments) -> Result<ExecutionResult, JsEngineError> {
         match callee {
             Value::Function(function) => {
+                if self.limits.max_call_depth.is_some_and(|max_call_depth| self.call_depth >= max_call_depth)
+                    || native_stack::position() < self.stack_limit
+                {
+                    return Err(JsEngineError::RuntimeError {
+                        message: "Maximum call stack size exceeded".to_string(),
+                    });
+                }
+                
                 // Create a new environment for the function call
-                let mut environment = Environment::with_enclosing(Rc::new(RefCell::new(function.closure.clone())));
+                let mut environment = Environment::for_function(Rc::clone(&function.closure));
                 
-                // Bind arguments to parameters
-                for (i, param) in function.parameters.iter().enumerate() {
-                    let value = if i < arguments.len() {
-                        arguments[i].clone()
-                    } else {
-                        Value::Undefined
-                    };
-                    
-                    environment.define(param.clone(), value);
+                // Bind arguments to parameters, moving each value into place
+                let mut values = arguments.into_iter();
+                for param in function.parameters.iter() {
+                    let value = values.next().unwrap_or(Value::Undefined);
+                    environment.define(Rc::clone(param), value);
+                }
+                
+                // Arrow functions see the `this` of the scope they were
+                // created in
+                if !function.kind.is_arrow() {
+                    environment.define(Rc::from("this"), this);
                 }
                 
-                let previous = Rc::clone(&self.environment);
                 self.environment = Rc::new(RefCell::new(environment));
+                self.hoist_var_declarations(std::slice::from_ref(&*function.body))?;
+                
+                // Functions created by this one come from the same script
+                self.source = function.source.clone();
                 
                 // Execute function body
-                let result = match self.execute(&function.body)? {
-                    ExecutionResult::Return(value) => value,
-                    ExecutionResult::Value(value) => value,
-                    ExecutionResult::None => Value::Undefined,
+                self.call_depth += 1;
+                self.stack.borrow_mut().enter(function.name.clone(), function.source.clone(), function.span.start);
+                let result = if function.kind.is_async() {
+                    self.start_async(function).map(ExecutionResult::Return)
+                } else {
+                    self.execute(&function.body)
                 };
+                self.stack.borrow_mut().exit();
+                self.call_depth -= 1;
                 
-                // Restore previous environment
-                self.environment = previous;
-                
-                Ok(result)
+                result
+            },
+            Value::NativeFunction(function) => match function(&arguments) {
+                Ok(value) => Ok(ExecutionResult::Value(value)),
+                Err(error) => {
+                    // The stack is still that of the native's caller here
+                    self.link_error(&error);
+                    self.stack.borrow().attach(&error);
+                    Err(error)
+                },
+            },
+            Value::Object(object) if let Some(call) = object.borrow().call().cloned() => match call(self, &arguments) {
+                Ok(value) => Ok(ExecutionResult::Value(value)),
+                Err(error) => {
+                    self.link_error(&error);
+                    self.stack.borrow().attach(&error);
+                    Err(error)
+                },
             },
-            Value::NativeFunction(function) => function(arguments),
             _ => Err(JsEngineError::TypeError {
                 message: format!("{:?} is not a function", callee),
             }),
         }
     }
     
+    // Natives raise errors with JsEngineError::throw, which cannot reach the
+    // error prototypes, so they are linked up here: a RangeError gets
+    // RangeError's prototype, and errors of other types Error's
+    fn link_error(&self, error: &JsEngineError) {
+        if let JsEngineError::Thrown { value: Value::Object(object) } = error {
+            let mut object = object.borrow_mut();
+            if object.prototype().is_none()
+                && let Some(Value::String(name)) = object.get("name")
+            {
+                let prototype = Rc::clone(self.error_prototypes.get(name.as_str()));
+                object.set_prototype(Some(prototype));
+            }
+        }
+    }
+    
+    // The value a catch clause receives: what was thrown, or an error
+    // object of the matching type for an error raised by the engine. The
+    // frames the error came from have been left by then, so its stack
+    // starts at the code that caught it.
+    pub(crate) fn exception_value(&self, error: JsEngineError) -> Value {
+        match error {
+            JsEngineError::Thrown { value } => value,
+            error => {
+                let prototype = self.error_prototypes.get(error.name());
+                ErrorPrototypes::create(prototype, error.message().map(str::to_string), &self.stack.borrow())
+            },
+        }
+    }
+    
     // Helper methods for evaluating expressions
     
-    fn is_truthy(&self, value: &Value) -> bool {
+    pub(crate) fn is_truthy(&self, value: &Value) -> bool {
         match value {
             Value::Boolean(b) => *b,
             Value::Null => false,
@@ -387,8 +2039,12 @@ impl Interpreter {
         }
     }
     
+    // Objects and functions are equal only to themselves
     fn is_equal(&self, a: &Value, b: &Value) -> bool {
         match (a, b) {
+            (Value::Object(a), Value::Object(b)) => Rc::ptr_eq(a, b),
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            (Value::NativeFunction(a), Value::NativeFunction(b)) => std::ptr::addr_eq(Rc::as_ptr(a), Rc::as_ptr(b)),
             (Value::Number(a), Value::Number(b)) => a == b,
             (Value::String(a), Value::String(b)) => a == b,
             (Value::Boolean(a), Value::Boolean(b)) => a == b,
@@ -398,23 +2054,83 @@ impl Interpreter {
         }
     }
     
+    // Converts an object to a primitive with its valueOf and toString
+    // methods (ToPrimitive): the first one that returns a primitive wins,
+    // trying toString first when a string is preferred. Objects with neither
+    // become "[object Object]", like those inheriting Object.prototype's.
+    // Functions convert to their source text.
+    pub(crate) fn primitive_value(&mut self, value: Value, prefer_string: bool) -> Result<Value, JsEngineError> {
+        let object = match &value {
+            Value::Object(object) => object,
+            Value::Function(function) => return Ok(Value::String(JsString::from(function.source_text()))),
+            Value::NativeFunction(_) => return Ok(Value::String(JsString::from("function () { [native code] }"))),
+            _ => return Ok(value),
+        };
+        
+        let methods = if prefer_string { ["toString", "valueOf"] } else { ["valueOf", "toString"] };
+        let mut found = false;
+        
+        for name in methods {
+            let method = {
+                let object = object.borrow();
+                object.lookup(name).or_else(|| object.slot().and_then(|slot| slot.method(name, &value)))
+            };
+            if let Some(method) = method.filter(Value::is_callable) {
+                found = true;
+                let result = self.call_method(&method, value.clone(), Arguments::new())?;
+                if !matches!(result, Value::Object(_)) {
+                    return Ok(result);
+                }
+            }
+        }
+        
+        if found {
+            Err(JsEngineError::TypeError {
+                message: "Cannot convert object to primitive value".to_string(),
+            })
+        } else if object.borrow().is_array() {
+            Ok(Value::String(JsString::from(self.join_array(object, &mut Vec::new())?)))
+        } else {
+            Ok(Value::String(JsString::from("[object Object]")))
+        }
+    }
+    
+    // Array.prototype.toString: the elements joined with commas, null and
+    // undefined as empty strings. An array nested in itself also joins as
+    // an empty string, as in other engines.
+    fn join_array(&mut self, array: &Rc<RefCell<PropertyMap>>, visiting: &mut Vec<*const ()>) -> Result<String, JsEngineError> {
+        let id = Rc::as_ptr(array) as *const ();
+        if visiting.contains(&id) {
+            return Ok(String::new());
+        }
+        visiting.push(id);
+        
+        let length = array.borrow().array_length().unwrap_or(0);
+        let mut parts = Vec::with_capacity(length);
+        for index in 0..length {
+            let element = array.borrow().get(&index.to_string()).cloned();
+            parts.push(match element {
+                None | Some(Value::Null | Value::Undefined) => String::new(),
+                Some(Value::Object(nested)) if nested.borrow().is_array() && nested.borrow().call().is_none() => self.join_array(&nested, visiting)?,
+                Some(element) => self.primitive_value(element, true)?.to_string(),
+            });
+        }
+        
+        visiting.pop();
+        Ok(parts.join(","))
+    }
+    
     fn add(&self, a: &Value, b: &Value) -> Result<Value, JsEngineError> {
         match (a, b) {
             (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a + b)),
-            (Value::String(a), Value::String(b)) => {
-                let mut result = a.clone();
-                result.push_str(b);
-                Ok(Value::String(result))
-            },
+            (Value::String(a), Value::String(b)) => Ok(Value::String(a.concat(b))),
             (Value::String(a), b) => {
-                let mut result = a.clone();
-                result.push_str(&format!("{}", b));
-                Ok(Value::String(result))
+                let b = JsString::from(format!("{}", b));
+                Ok(Value::String(a.concat(&b)))
             },
             (a, Value::String(b)) => {
-                let mut result = format!("{}", a);
-                result.push_str(b);
-                Ok(Value::String(result))
+                let a = JsString::from(format!("{}", a));
+                Ok(Value::String(a.concat(b)))
             },
             _ => Err(JsEngineError::TypeError {
                 message: format!("Cannot add {:?} and {:?}", a, b),
@@ -442,62 +2158,67 @@ impl Interpreter {
     
     fn divide(&self, a: &Value, b: &Value) -> Result<Value, JsEngineError> {
         match (a, b) {
-            (Value::Number(a), Value::Number(b)) => {
-                if *b == 0.0 {
-                    // In JavaScript, division by zero returns Infinity
-                    Ok(Value::Number(f64::INFINITY))
-                } else {
-                    Ok(Value::Number(a / b))
-                }
-            },
+            // IEEE 754 division is what JavaScript specifies: x / 0 is
+            // Infinity with the sign of x (and of the zero), 0 / 0 is NaN
+            (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a / b)),
             _ => Err(JsEngineError::TypeError {
                 message: format!("Cannot divide {:?} by {:?}", a, b),
             }),
         }
     }
     
-    fn negate(&self, value: &Value) -> Result<Value, JsEngineError> {
-        match value {
-            Value::Number(n) => Ok(Value::Number(-n)),
+    fn remainder(&self, a: &Value, b: &Value) -> Result<Value, JsEngineError> {
+        match (a, b) {
+            // Rust's % truncates like JavaScript's: the result takes the sign
+            // of the dividend, and x % 0 and Infinity % x are NaN
+            (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a % b)),
             _ => Err(JsEngineError::TypeError {
-                message: format!("Cannot negate {:?}", value),
+                message: format!("Cannot take the remainder of {:?} by {:?}", a, b),
             }),
         }
     }
     
-    fn less_than(&self, a: &Value, b: &Value) -> Result<Value, JsEngineError> {
+    fn exponentiate(&self, a: &Value, b: &Value) -> Result<Value, JsEngineError> {
         match (a, b) {
-            (Value::Number(a), Value::Number(b)) => Ok(Value::Boolean(a < b)),
+            (Value::Number(a), Value::Number(b)) => Ok(Value::Number(power(*a, *b))),
             _ => Err(JsEngineError::TypeError {
-                message: format!("Cannot compare {:?} < {:?}", a, b),
+                message: format!("Cannot raise {:?} to the power of {:?}", a, b),
             }),
         }
     }
     
-    fn less_equal(&self, a: &Value, b: &Value) -> Result<Value, JsEngineError> {
-        match (a, b) {
-            (Value::Number(a), Value::Number(b)) => Ok(Value::Boolean(a <= b)),
+    fn negate(&self, value: &Value) -> Result<Value, JsEngineError> {
+        match value {
+            Value::Number(n) => Ok(Value::Number(-n)),
             _ => Err(JsEngineError::TypeError {
-                message: format!("Cannot compare {:?} <= {:?}", a, b),
+                message: format!("Cannot negate {:?}", value),
             }),
         }
     }
     
-    fn greater_than(&self, a: &Value, b: &Value) -> Result<Value, JsEngineError> {
+    // The abstract relational comparison of two primitives: strings by
+    // their UTF-16 code units, anything else converted to numbers. None when
+    // either number is NaN, which makes every comparison false.
+    fn compare(&self, a: &Value, b: &Value) -> Option<Ordering> {
         match (a, b) {
-            (Value::Number(a), Value::Number(b)) => Ok(Value::Boolean(a > b)),
-            _ => Err(JsEngineError::TypeError {
-                message: format!("Cannot compare {:?} > {:?}", a, b),
-            }),
+            (Value::String(a), Value::String(b)) => Some(a.as_str().encode_utf16().cmp(b.as_str().encode_utf16())),
+            _ => stdlib::to_number(a).partial_cmp(&stdlib::to_number(b)),
         }
     }
+}
+// Without the async feature there are no coroutines for async functions to
+// run on; the parser still accepts them so the same scripts parse either way
+#[cfg(not(feature = "async"))]
+impl Interpreter {
+    fn start_async(&mut self, _function: &Rc<JsFunction>) -> Result<Value, JsEngineError> {
+        Err(JsEngineError::RuntimeError {
+            message: "async functions are not supported in this build".to_string(),
+        })
+    }
     
-    fn greater_equal(&self, a: &Value, b: &Value) -> Result<Value, JsEngineError> {
-        match (a, b) {
-            (Value::Number(a), Value::Number(b)) => Ok(Value::Boolean(a >= b)),
-            _ => Err(JsEngineError::TypeError {
-                message: format!("Cannot compare {:?} >= {:?}", a, b),
-            }),
-        }
+    fn await_value(&mut self, _value: Value) -> Result<Value, JsEngineError> {
+        Err(JsEngineError::RuntimeError {
+            message: "async functions are not supported in this build".to_string(),
+        })
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,241 @@
+// src/iterator.rs
+use crate::collections::{Collection, CollectionKind, Cursor};
+use crate::error::JsEngineError;
+use crate::interpreter::{Arguments, Interpreter, Value};
+use crate::object::{InternalSlot, PropertyMap};
+use crate::string::JsString;
+use crate::typed_array::TypedArray;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// The iteration protocol. A value is iterable when it has a method under
+// the key Symbol.iterator that returns an iterator: an object whose next
+// method gives results of the form { value, done }. for-of, Array.from and
+// the Map and Set constructors all walk values this way.
+//
+// There are no symbols yet, so Symbol.iterator is the string "@@iterator",
+// used as a computed key. Strings, arrays, typed arrays, maps, sets and the
+// iterators made from them have the method built in, and are walked without
+// calling it unless a script has given them a method of its own.
+
+pub const ITERATOR: &str = "@@iterator";
+
+// Which of a collection's keys and values its iterator gives
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Part {
+    Keys,
+    Values,
+    Entries, // [key, value] arrays
+}
+
+// A walk over a built-in iterable: the code points of a string, the
+// elements of an array or typed array, or the entries of a map or set. Arrays are read as
+// the walk reaches each index, so elements added on the way are visited too.
+pub enum BuiltinIterator {
+    String(JsString, usize), // Byte offset of the next code point
+    Array(Rc<RefCell<PropertyMap>>, usize), // Next index
+    Collection(Cursor, Part),
+    TypedArray(Rc<TypedArray>, usize), // Next index
+}
+
+impl BuiltinIterator {
+    // None when `value` is not a built-in iterable. Maps give their
+    // entries and sets their values.
+    pub(crate) fn new(value: &Value) -> Option<Self> {
+        if let Some(collection) = Collection::of(value) {
+            let part = match collection.kind() {
+                CollectionKind::Map => Part::Entries,
+                CollectionKind::Set => Part::Values,
+            };
+            return Some(BuiltinIterator::Collection(Cursor::new(&collection), part));
+        }
+        if let Some(array) = TypedArray::of(value) {
+            return Some(BuiltinIterator::TypedArray(array, 0));
+        }
+        match value {
+            Value::String(s) => Some(BuiltinIterator::String(s.clone(), 0)),
+            Value::Object(object) if object.borrow().is_array() => Some(BuiltinIterator::Array(Rc::clone(object), 0)),
+            _ => None,
+        }
+    }
+    
+    // A new iterator object that walks this
+    pub(crate) fn into_value(self) -> Value {
+        let slot = InternalSlot::Iterator(Rc::new(RefCell::new(self)));
+        Value::Object(Rc::new(RefCell::new(PropertyMap::with_slot(slot))))
+    }
+    
+    // What the iterator is named when printed
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            BuiltinIterator::String(..) => "String Iterator",
+            BuiltinIterator::Array(..) | BuiltinIterator::TypedArray(..) => "Array Iterator",
+            BuiltinIterator::Collection(cursor, _) => match cursor.kind() {
+                CollectionKind::Map => "Map Iterator",
+                CollectionKind::Set => "Set Iterator",
+            },
+        }
+    }
+}
+
+impl Iterator for BuiltinIterator {
+    type Item = Value;
+    
+    fn next(&mut self) -> Option<Value> {
+        match self {
+            BuiltinIterator::String(s, position) => {
+                let c = s.as_str()[*position..].chars().next()?;
+                *position += c.len_utf8();
+                Some(Value::String(JsString::from(c.to_string())))
+            },
+            BuiltinIterator::Array(array, index) => {
+                let array = array.borrow();
+                if *index >= array.array_length().unwrap_or(0) {
+                    return None;
+                }
+                let element = array.get(&index.to_string()).cloned().unwrap_or(Value::Undefined);
+                *index += 1;
+                Some(element)
+            },
+            BuiltinIterator::Collection(cursor, part) => {
+                let (key, value) = cursor.next()?;
+                match part {
+                    Part::Keys => Some(key),
+                    Part::Values => Some(value),
+                    Part::Entries => Some(array(vec![key, value])),
+                }
+            },
+            BuiltinIterator::TypedArray(array, index) => {
+                let element = array.get(*index)?;
+                *index += 1;
+                Some(Value::Number(element))
+            },
+        }
+    }
+}
+
+// The values for-of walks over
+pub(crate) enum ValueIterator {
+    Builtin(BuiltinIterator),
+    Shared(Rc<RefCell<BuiltinIterator>>), // An iterator object of a built-in iterable
+    Protocol {
+        iterator: Value,
+        next: Value,
+    },
+}
+
+impl ValueIterator {
+    // Gets the iterator of `value` from its Symbol.iterator method
+    pub(crate) fn new(interpreter: &mut Interpreter, value: &Value) -> Result<Self, JsEngineError> {
+        let own_method = match value {
+            Value::Object(object) => object.borrow().lookup(ITERATOR).is_some(),
+            _ => false,
+        };
+        if !own_method {
+            if let Some(state) = state(value) {
+                return Ok(ValueIterator::Shared(state));
+            }
+            if let Some(builtin) = BuiltinIterator::new(value) {
+                return Ok(ValueIterator::Builtin(builtin));
+            }
+        }
+        
+        let not_iterable = || JsEngineError::TypeError {
+            message: format!("{} is not iterable", value),
+        };
+        if matches!(value, Value::Null | Value::Undefined) {
+            return Err(not_iterable());
+        }
+        let method = interpreter.get_property(value, ITERATOR)?;
+        if !method.is_callable() {
+            return Err(not_iterable());
+        }
+        let iterator = interpreter.call_method(&method, value.clone(), Arguments::new())?;
+        if !matches!(iterator, Value::Object(_)) {
+            return Err(JsEngineError::TypeError {
+                message: "Result of the Symbol.iterator method is not an object".to_string(),
+            });
+        }
+        let next = interpreter.get_property(&iterator, "next")?;
+        Ok(ValueIterator::Protocol { iterator, next })
+    }
+    
+    // The next value, or None once the iterator is done
+    pub(crate) fn next_value(&mut self, interpreter: &mut Interpreter) -> Result<Option<Value>, JsEngineError> {
+        match self {
+            ValueIterator::Builtin(builtin) => Ok(builtin.next()),
+            ValueIterator::Shared(state) => Ok(state.borrow_mut().next()),
+            ValueIterator::Protocol { iterator, next } => {
+                let result = interpreter.call_method(next, iterator.clone(), Arguments::new())?;
+                if !matches!(result, Value::Object(_)) {
+                    return Err(JsEngineError::TypeError {
+                        message: format!("Iterator result {} is not an object", result),
+                    });
+                }
+                let done = interpreter.get_property(&result, "done")?;
+                if interpreter.is_truthy(&done) {
+                    return Ok(None);
+                }
+                Ok(Some(interpreter.get_property(&result, "value")?))
+            },
+        }
+    }
+    
+    // Every remaining value
+    pub(crate) fn collect_values(mut self, interpreter: &mut Interpreter) -> Result<Vec<Value>, JsEngineError> {
+        let mut values = Vec::new();
+        while let Some(value) = self.next_value(interpreter)? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+// The Symbol.iterator method of a built-in iterable, which makes a new
+// iterator object for it, or of such an iterator object, which gives back
+// the iterator itself
+pub(crate) fn iterator_method(value: &Value) -> Option<Value> {
+    let value = value.clone();
+    if state(&value).is_some() {
+        return Some(Value::NativeFunction(Rc::new(move |_| Ok(value.clone()))));
+    }
+    BuiltinIterator::new(&value)?;
+    Some(Value::NativeFunction(Rc::new(move |_| Ok(BuiltinIterator::new(&value).map_or(Value::Undefined, BuiltinIterator::into_value)))))
+}
+
+// next for an iterator object
+pub(crate) fn method(state: &Rc<RefCell<BuiltinIterator>>, name: &str) -> Option<Value> {
+    if name != "next" {
+        return None;
+    }
+    let state = Rc::clone(state);
+    Some(Value::NativeFunction(Rc::new(move |_| {
+        let value = state.borrow_mut().next();
+        let result = PropertyMap::from([
+            ("value", value.clone().unwrap_or(Value::Undefined)),
+            ("done", Value::Boolean(value.is_none())),
+        ]);
+        Ok(Value::Object(Rc::new(RefCell::new(result))))
+    })))
+}
+
+// The global `Symbol`, which for now only holds Symbol.iterator
+pub(crate) fn symbol_namespace() -> Value {
+    let symbol = PropertyMap::from([("iterator", Value::String(JsString::from(ITERATOR)))]);
+    Value::Object(Rc::new(RefCell::new(symbol)))
+}
+
+// The walk behind an iterator object
+fn state(value: &Value) -> Option<Rc<RefCell<BuiltinIterator>>> {
+    match value {
+        Value::Object(object) => match object.borrow().slot() {
+            Some(InternalSlot::Iterator(state)) => Some(Rc::clone(state)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn array(elements: Vec<Value>) -> Value {
+    Value::Object(Rc::new(RefCell::new(PropertyMap::array(elements))))
+}
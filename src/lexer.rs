@@ -1,5 +1,6 @@
 // src/lexer.rs
 use crate::error::JsEngineError;
+use crate::syntax::SyntaxExtensions;
 use std::collections::HashMap;
 use std::iter::Peekable;
 use std::str::Chars;
@@ -8,12 +9,15 @@ use std::str::Chars;
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenType {
     // Single character tokens
-    LeftParen, RightParen, LeftBrace, RightBrace,
-    Comma, Dot, Minus, Plus, Semicolon, Slash, Star,
+    LeftParen, RightParen, LeftBrace, RightBrace, LeftBracket, RightBracket,
+    Comma, Dot, Minus, Plus, Semicolon, Slash, Star, Percent,
+    Colon, Question, Pipe, Ampersand,
     
     // One or two character tokens
     Bang, BangEqual,
-    Equal, EqualEqual,
+    Equal, EqualEqual, Arrow,
+    QuestionDot, QuestionQuestion, DotDotDot,
+    StarStar, StarStarEqual,
     Greater, GreaterEqual,
     Less, LessEqual,
     
@@ -21,30 +25,70 @@ pub enum TokenType {
     Identifier(String),
     String(String),
     Number(f64),
+    RegExp(String, String), // pattern, flags
+    
+    // Template literals, holding the text of each part with its escapes
+    // processed. `a${x}b${y}c` lexes as TemplateHead("a"), the tokens of x,
+    // TemplateMiddle("b"), the tokens of y and TemplateTail("c"); a template
+    // without substitutions is a single Template token.
+    Template(String),
+    TemplateHead(String),
+    TemplateMiddle(String),
+    TemplateTail(String),
     
     // Keywords
     And, Else, False, Function, If, Null,
-    Or, Return, True, Var, While, Let, Const,
+    Or, Return, True, Var, While, For, Let, Const, This,
+    Switch, Case, Default, Break,
+    Try, Catch, Finally, Throw,
+    
+    // Operator registered by a syntax extension
+    Operator(String),
     
     // Special tokens
-    EOF
+    Eof
+}
+
+// Byte offsets into the source, `end` exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "cache", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
 }
 
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
+    #[allow(dead_code)]
     pub lexeme: String,
     pub line: usize,
     pub column: usize,
+    #[allow(dead_code)]
+    pub span: Span,
 }
 
 pub struct Lexer<'a> {
+    text: &'a str,
     source: Peekable<Chars<'a>>,
-    tokens: Vec<Token>,
-    current: usize,
+    finished: bool,
+    current: usize, // Byte offset of the next character
     line: usize,
     column: usize,
+    // Position of the first character of the token being scanned
+    start: usize,
+    start_line: usize,
+    start_column: usize,
     keywords: HashMap<String, TokenType>,
+    operators: Vec<String>, // Extension operators, longest first
+    // Whether a `/` here starts a regular expression rather than being a
+    // division, which it is unless the previous token ends an operand
+    regexp_allowed: bool,
+    // For each template substitution being scanned, innermost last, the
+    // number of braces opened inside it and not yet closed; the `}` that
+    // finds no open brace ends the substitution
+    substitutions: Vec<usize>,
 }
 
 impl<'a> Lexer<'a> {
@@ -61,76 +105,181 @@ impl<'a> Lexer<'a> {
         keywords.insert("true".to_string(), TokenType::True);
         keywords.insert("var".to_string(), TokenType::Var);
         keywords.insert("while".to_string(), TokenType::While);
+        keywords.insert("for".to_string(), TokenType::For);
         keywords.insert("let".to_string(), TokenType::Let);
         keywords.insert("const".to_string(), TokenType::Const);
+        keywords.insert("this".to_string(), TokenType::This);
+        keywords.insert("switch".to_string(), TokenType::Switch);
+        keywords.insert("case".to_string(), TokenType::Case);
+        keywords.insert("default".to_string(), TokenType::Default);
+        keywords.insert("break".to_string(), TokenType::Break);
+        keywords.insert("try".to_string(), TokenType::Try);
+        keywords.insert("catch".to_string(), TokenType::Catch);
+        keywords.insert("finally".to_string(), TokenType::Finally);
+        keywords.insert("throw".to_string(), TokenType::Throw);
         
         Lexer {
+            text: source,
             source: source.chars().peekable(),
-            tokens: Vec::new(),
+            finished: false,
             current: 0,
             line: 1,
             column: 1,
+            start: 0,
+            start_line: 1,
+            start_column: 1,
             keywords,
+            operators: Vec::new(),
+            regexp_allowed: true,
+            substitutions: Vec::new(),
         }
     }
     
+    // Recognizes the operators registered by syntax extensions
+    pub fn with_extensions(mut self, extensions: &SyntaxExtensions) -> Self {
+        self.operators = extensions.operators().to_vec();
+        self
+    }
+    
     pub fn scan_tokens(&mut self) -> Result<Vec<Token>, JsEngineError> {
-        while let Some(c) = self.advance() {
-            self.scan_token(c)?;
-        }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("lex", bytes = self.text.len()).entered();
         
-        // Add EOF token
-        self.tokens.push(Token {
-            token_type: TokenType::EOF,
-            lexeme: "".to_string(),
-            line: self.line,
-            column: self.column,
-        });
+        self.collect()
+    }
+    
+    // Scans the next token, skipping whitespace and comments. Returns the
+    // EOF token once the source is exhausted.
+    pub fn next_token(&mut self) -> Result<Token, JsEngineError> {
+        loop {
+            self.start = self.current;
+            self.start_line = self.line;
+            self.start_column = self.column;
+            
+            if let Some(token) = self.extension_operator() {
+                self.regexp_allowed = true;
+                return Ok(token);
+            }
+            
+            let Some(c) = self.advance() else {
+                break;
+            };
+            
+            if let Some(token) = self.scan_token(c)? {
+                self.regexp_allowed = !matches!(
+                    token.token_type,
+                    TokenType::Identifier(_) | TokenType::String(_) | TokenType::Number(_) | TokenType::RegExp(..)
+                        | TokenType::Template(_) | TokenType::TemplateTail(_) | TokenType::RightParen | TokenType::RightBracket | TokenType::True | TokenType::False
+                        | TokenType::Null | TokenType::This
+                );
+                return Ok(token);
+            }
+        }
         
-        Ok(self.tokens.clone())
+        Ok(self.make_token(TokenType::Eof, "".to_string()))
     }
     
-    fn scan_token(&mut self, c: char) -> Result<(), JsEngineError> {
-        match c {
+    fn scan_token(&mut self, c: char) -> Result<Option<Token>, JsEngineError> {
+        let token = match c {
             // Single character tokens
-            '(' => self.add_token(TokenType::LeftParen, String::from("(")),
-            ')' => self.add_token(TokenType::RightParen, String::from(")")),
-            '{' => self.add_token(TokenType::LeftBrace, String::from("{")),
-            '}' => self.add_token(TokenType::RightBrace, String::from("}")),
-            ',' => self.add_token(TokenType::Comma, String::from(",")),
-            '.' => self.add_token(TokenType::Dot, String::from(".")),
-            '-' => self.add_token(TokenType::Minus, String::from("-")),
-            '+' => self.add_token(TokenType::Plus, String::from("+")),
-            ';' => self.add_token(TokenType::Semicolon, String::from(";")),
-            '*' => self.add_token(TokenType::Star, String::from("*")),
+            '(' => self.make_token(TokenType::LeftParen, String::from("(")),
+            ')' => self.make_token(TokenType::RightParen, String::from(")")),
+            '{' => {
+                if let Some(open) = self.substitutions.last_mut() {
+                    *open += 1;
+                }
+                self.make_token(TokenType::LeftBrace, String::from("{"))
+            },
+            '}' => match self.substitutions.last_mut() {
+                Some(0) => self.template(false)?,
+                Some(open) => {
+                    *open -= 1;
+                    self.make_token(TokenType::RightBrace, String::from("}"))
+                },
+                None => self.make_token(TokenType::RightBrace, String::from("}")),
+            },
+            '[' => self.make_token(TokenType::LeftBracket, String::from("[")),
+            ']' => self.make_token(TokenType::RightBracket, String::from("]")),
+            ',' => self.make_token(TokenType::Comma, String::from(",")),
+            '.' => {
+                if self.text[self.current..].starts_with("..") {
+                    self.advance();
+                    self.advance();
+                    self.make_token(TokenType::DotDotDot, String::from("..."))
+                } else {
+                    self.make_token(TokenType::Dot, String::from("."))
+                }
+            },
+            '-' => self.make_token(TokenType::Minus, String::from("-")),
+            '+' => self.make_token(TokenType::Plus, String::from("+")),
+            ';' => self.make_token(TokenType::Semicolon, String::from(";")),
+            '%' => self.make_token(TokenType::Percent, String::from("%")),
+            ':' => self.make_token(TokenType::Colon, String::from(":")),
             
             // One or two character tokens
+            '*' => {
+                if !self.match_next('*') {
+                    self.make_token(TokenType::Star, String::from("*"))
+                } else if self.match_next('=') {
+                    self.make_token(TokenType::StarStarEqual, String::from("**="))
+                } else {
+                    self.make_token(TokenType::StarStar, String::from("**"))
+                }
+            },
+            '?' => {
+                // `a?.5:1` is a conditional with a decimal, not optional chaining
+                let rest = &self.text[self.current..];
+                if self.match_next('?') {
+                    self.make_token(TokenType::QuestionQuestion, String::from("??"))
+                } else if rest.starts_with('.') && !rest[1..].starts_with(|c: char| c.is_ascii_digit()) {
+                    self.advance();
+                    self.make_token(TokenType::QuestionDot, String::from("?."))
+                } else {
+                    self.make_token(TokenType::Question, String::from("?"))
+                }
+            },
+            '|' => {
+                if self.match_next('|') {
+                    self.make_token(TokenType::Or, String::from("||"))
+                } else {
+                    self.make_token(TokenType::Pipe, String::from("|"))
+                }
+            },
+            '&' => {
+                if self.match_next('&') {
+                    self.make_token(TokenType::And, String::from("&&"))
+                } else {
+                    self.make_token(TokenType::Ampersand, String::from("&"))
+                }
+            },
             '!' => {
                 if self.match_next('=') {
-                    self.add_token(TokenType::BangEqual, String::from("!="));
+                    self.make_token(TokenType::BangEqual, String::from("!="))
                 } else {
-                    self.add_token(TokenType::Bang, String::from("!"));
+                    self.make_token(TokenType::Bang, String::from("!"))
                 }
             },
             '=' => {
                 if self.match_next('=') {
-                    self.add_token(TokenType::EqualEqual, String::from("=="));
+                    self.make_token(TokenType::EqualEqual, String::from("=="))
+                } else if self.match_next('>') {
+                    self.make_token(TokenType::Arrow, String::from("=>"))
                 } else {
-                    self.add_token(TokenType::Equal, String::from("="));
+                    self.make_token(TokenType::Equal, String::from("="))
                 }
             },
             '<' => {
                 if self.match_next('=') {
-                    self.add_token(TokenType::LessEqual, String::from("<="));
+                    self.make_token(TokenType::LessEqual, String::from("<="))
                 } else {
-                    self.add_token(TokenType::Less, String::from("<"));
+                    self.make_token(TokenType::Less, String::from("<"))
                 }
             },
             '>' => {
                 if self.match_next('=') {
-                    self.add_token(TokenType::GreaterEqual, String::from(">="));
+                    self.make_token(TokenType::GreaterEqual, String::from(">="))
                 } else {
-                    self.add_token(TokenType::Greater, String::from(">"));
+                    self.make_token(TokenType::Greater, String::from(">"))
                 }
             },
             
@@ -144,25 +293,26 @@ impl<'a> Lexer<'a> {
                         }
                         self.advance();
                     }
+                    return Ok(None);
+                } else if self.regexp_allowed {
+                    self.regexp()?
                 } else {
-                    self.add_token(TokenType::Slash, String::from("/"));
+                    self.make_token(TokenType::Slash, String::from("/"))
                 }
             },
             
             // String literals
             '"' => self.string()?,
+            '`' => self.template(true)?,
             
             // Whitespace
-            ' ' | '\r' | '\t' => {
+            ' ' | '\r' | '\t' | '\n' => {
                 // Skip whitespace
-            },
-            '\n' => {
-                self.line += 1;
-                self.column = 1;
+                return Ok(None);
             },
             
             // Number literals
-            c if c.is_digit(10) => self.number(c)?,
+            c if c.is_ascii_digit() => self.number(c)?,
             
             // Identifiers
             c if self.is_alpha(c) => self.identifier(c)?,
@@ -170,25 +320,43 @@ impl<'a> Lexer<'a> {
             // Unknown character
             _ => {
                 return Err(JsEngineError::LexerError {
-                    position: self.current,
+                    position: self.start,
                     message: format!("Unexpected character: {}", c),
                 });
             },
-        }
+        };
         
-        Ok(())
+        Ok(Some(token))
     }
     
     fn advance(&mut self) -> Option<char> {
         if let Some(c) = self.source.next() {
-            self.current += 1;
-            self.column += 1;
+            self.current += c.len_utf8();
+            
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+            
             Some(c)
         } else {
             None
         }
     }
     
+    fn extension_operator(&mut self) -> Option<Token> {
+        let rest = &self.text[self.current..];
+        let operator = self.operators.iter().find(|operator| rest.starts_with(operator.as_str()))?.clone();
+        
+        for _ in operator.chars() {
+            self.advance();
+        }
+        
+        Some(self.make_token(TokenType::Operator(operator.clone()), operator))
+    }
+    
     fn match_next(&mut self, expected: char) -> bool {
         if let Some(&next_char) = self.source.peek() {
             if next_char != expected {
@@ -196,27 +364,29 @@ impl<'a> Lexer<'a> {
             }
             
             // Consume the character
-            self.source.next();
-            self.current += 1;
-            self.column += 1;
+            self.advance();
             true
         } else {
             false
         }
     }
     
-    fn add_token(&mut self, token_type: TokenType, lexeme: String) {
-        self.tokens.push(Token {
+    // Builds a token starting at the recorded token start and ending at the
+    // current position
+    fn make_token(&self, token_type: TokenType, lexeme: String) -> Token {
+        Token {
             token_type,
             lexeme,
-            line: self.line,
-            column: self.column,
-        });
+            line: self.start_line,
+            column: self.start_column,
+            span: Span {
+                start: self.start,
+                end: self.current,
+            },
+        }
     }
     
-    fn string(&mut self) -> Result<(), JsEngineError> {
-        let start_line = self.line;
-        let start_column = self.column - 1; // Because we already consumed the opening quote
+    fn string(&mut self) -> Result<Token, JsEngineError> {
         let mut value = String::new();
         
         while let Some(&c) = self.source.peek() {
@@ -224,12 +394,8 @@ impl<'a> Lexer<'a> {
                 break;
             }
             
-            if c == '\n' {
-                self.line += 1;
-                self.column = 1;
-            }
-            
-            value.push(self.advance().unwrap());
+            value.push(c);
+            self.advance();
         }
         
         // Check if we reached the closing quote
@@ -243,27 +409,149 @@ impl<'a> Lexer<'a> {
         // Consume the closing quote
         self.advance();
         
-        // Add the token with the string value
-        self.tokens.push(Token {
-            token_type: TokenType::String(value.clone()),
-            lexeme: format!("\"{}\"", value),
-            line: start_line,
-            column: start_column,
-        });
+        let lexeme = format!("\"{}\"", value);
+        Ok(self.make_token(TokenType::String(value), lexeme))
+    }
+    
+    // The part of a template literal after its opening backtick, or after
+    // the `}` closing a substitution, up to the closing backtick or the next
+    // `${`
+    fn template(&mut self, opening: bool) -> Result<Token, JsEngineError> {
+        let mut value = String::new();
+        
+        let substitution = loop {
+            match self.advance() {
+                None => {
+                    return Err(JsEngineError::LexerError {
+                        position: self.start,
+                        message: "Unterminated template literal.".to_string(),
+                    });
+                },
+                Some('`') => break false,
+                Some('$') if self.match_next('{') => break true,
+                Some('\\') => match self.advance() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    Some('0') => value.push('\0'),
+                    // A backslash before a line break continues the line
+                    Some('\n') => {},
+                    Some(c) => value.push(c),
+                    None => {},
+                },
+                Some(c) => value.push(c),
+            }
+        };
         
-        Ok(())
+        let token_type = match (opening, substitution) {
+            (true, false) => TokenType::Template(value),
+            (true, true) => TokenType::TemplateHead(value),
+            (false, true) => TokenType::TemplateMiddle(value),
+            (false, false) => TokenType::TemplateTail(value),
+        };
+        match (opening, substitution) {
+            (true, true) => self.substitutions.push(0),
+            (false, false) => {
+                self.substitutions.pop();
+            },
+            _ => {},
+        }
+        
+        let lexeme = self.text[self.start..self.current].to_string();
+        Ok(self.make_token(token_type, lexeme))
     }
     
-    fn number(&mut self, first_digit: char) -> Result<(), JsEngineError> {
-        let start_column = self.column - 1; // Because we already consumed the first digit
-        let mut value = first_digit.to_string();
+    // A regular expression literal after its opening slash. A slash inside
+    // a character class does not end it.
+    fn regexp(&mut self) -> Result<Token, JsEngineError> {
+        let mut pattern = String::new();
+        let mut in_class = false;
         
-        // Consume digits
+        loop {
+            let c = match self.advance() {
+                Some('\n') | None => {
+                    return Err(JsEngineError::LexerError {
+                        position: self.start,
+                        message: "Unterminated regular expression.".to_string(),
+                    });
+                },
+                Some(c) => c,
+            };
+            match c {
+                '/' if !in_class => break,
+                '[' => in_class = true,
+                ']' => in_class = false,
+                '\\' => {
+                    pattern.push(c);
+                    match self.advance() {
+                        Some(escaped) if escaped != '\n' => pattern.push(escaped),
+                        _ => {
+                            return Err(JsEngineError::LexerError {
+                                position: self.start,
+                                message: "Unterminated regular expression.".to_string(),
+                            });
+                        },
+                    }
+                    continue;
+                },
+                _ => {},
+            }
+            pattern.push(c);
+        }
+        
+        let mut flags = String::new();
         while let Some(&c) = self.source.peek() {
-            if !c.is_digit(10) && c != '.' {
+            if !self.is_alphanumeric(c) {
                 break;
             }
+            flags.push(c);
+            self.advance();
+        }
+        
+        let lexeme = format!("/{}/{}", pattern, flags);
+        Ok(self.make_token(TokenType::RegExp(pattern, flags), lexeme))
+    }
+    
+    // Decimal literals with an optional fraction and exponent, and 0x, 0o
+    // and 0b integer literals. Underscores may separate digits.
+    fn number(&mut self, first_digit: char) -> Result<Token, JsEngineError> {
+        let radix = match (first_digit, self.source.peek()) {
+            ('0', Some('x' | 'X')) => 16,
+            ('0', Some('o' | 'O')) => 8,
+            ('0', Some('b' | 'B')) => 2,
+            _ => 10,
+        };
+        
+        if radix != 10 {
+            self.advance();
+            let digits = self.digits(radix, String::new())?;
+            if digits.is_empty() {
+                return Err(self.number_error("Missing digits after the radix prefix"));
+            }
+            
+            // Accumulate in f64 so that literals past 2^64 still round
+            let value = digits.chars().fold(0.0, |value, digit| value * radix as f64 + digit.to_digit(radix).unwrap() as f64);
+            return Ok(self.make_token(TokenType::Number(value), self.text[self.start..self.current].to_string()));
+        }
+        
+        let mut value = self.digits(10, first_digit.to_string())?;
+        
+        if self.source.peek() == Some(&'.') {
+            self.advance();
+            value.push('.');
+            value = self.digits(10, value)?;
+        }
+        
+        // An exponent needs digits; otherwise the e starts an identifier
+        let rest = &self.text[self.current..];
+        let exponent = rest.strip_prefix(['e', 'E']).map(|rest| rest.strip_prefix(['+', '-']).unwrap_or(rest));
+        if exponent.is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit())) {
             value.push(self.advance().unwrap());
+            if let Some(&sign @ ('+' | '-')) = self.source.peek() {
+                self.advance();
+                value.push(sign);
+            }
+            value = self.digits(10, value)?;
         }
         
         // Parse the number
@@ -277,19 +565,38 @@ impl<'a> Lexer<'a> {
             }
         };
         
-        // Add the token
-        self.tokens.push(Token {
-            token_type: TokenType::Number(num_value),
-            lexeme: value,
-            line: self.line,
-            column: start_column,
-        });
-        
-        Ok(())
+        Ok(self.make_token(TokenType::Number(num_value), self.text[self.start..self.current].to_string()))
+    }
+    
+    // Appends the digits that follow to `digits`, dropping separators, which
+    // are only allowed between two digits
+    fn digits(&mut self, radix: u32, mut digits: String) -> Result<String, JsEngineError> {
+        while let Some(&c) = self.source.peek() {
+            if c == '_' {
+                self.advance();
+                let after_digit = digits.ends_with(|c: char| c.is_digit(radix));
+                if !after_digit || !self.source.peek().is_some_and(|c| c.is_digit(radix)) {
+                    return Err(self.number_error("Numeric separators are only allowed between digits"));
+                }
+                continue;
+            }
+            if !c.is_digit(radix) {
+                break;
+            }
+            digits.push(c);
+            self.advance();
+        }
+        Ok(digits)
+    }
+    
+    fn number_error(&self, message: &str) -> JsEngineError {
+        JsEngineError::LexerError {
+            position: self.current,
+            message: format!("{}: {}", message, &self.text[self.start..self.current]),
+        }
     }
     
-    fn identifier(&mut self, first_char: char) -> Result<(), JsEngineError> {
-        let start_column = self.column - 1; // Because we already consumed the first character
+    fn identifier(&mut self, first_char: char) -> Result<Token, JsEngineError> {
         let mut name = first_char.to_string();
         
         // Consume identifier characters
@@ -297,7 +604,8 @@ impl<'a> Lexer<'a> {
             if !self.is_alphanumeric(c) {
                 break;
             }
-            name.push(self.advance().unwrap());
+            name.push(c);
+            self.advance();
         }
         
         // Check if it's a keyword
@@ -307,15 +615,7 @@ impl<'a> Lexer<'a> {
             TokenType::Identifier(name.clone())
         };
         
-        // Add the token
-        self.tokens.push(Token {
-            token_type,
-            lexeme: name,
-            line: self.line,
-            column: start_column,
-        });
-        
-        Ok(())
+        Ok(self.make_token(token_type, name))
     }
     
     fn is_alpha(&self, c: char) -> bool {
@@ -323,6 +623,36 @@ impl<'a> Lexer<'a> {
     }
     
     fn is_alphanumeric(&self, c: char) -> bool {
-        self.is_alpha(c) || c.is_digit(10)
+        self.is_alpha(c) || c.is_ascii_digit()
+    }
+}
+
+// Yields tokens on demand, ending with a single EOF token. Iteration stops
+// after the EOF token or the first error.
+impl Iterator for Lexer<'_> {
+    type Item = Result<Token, JsEngineError>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        
+        let result = self.next_token();
+        self.finished = match &result {
+            Ok(token) => token.token_type == TokenType::Eof,
+            Err(_) => true,
+        };
+        
+        Some(result)
     }
-}
\ No newline at end of file
+}
+
+// 1-based line and column of a byte offset into `source`
+pub fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    let column = before[line_start..].chars().count() + 1;
+    
+    (line, column)
+}
@@ -0,0 +1,77 @@
+// src/lib.rs
+pub mod lexer;
+pub mod ast;
+pub mod parser;
+pub mod interpreter;
+pub mod resolver;
+pub mod error;
+pub mod string;
+pub mod object;
+pub mod shape;
+pub mod number;
+pub mod inspect;
+pub mod locale;
+pub mod primitives;
+pub mod heap;
+pub mod snapshot;
+pub mod hooks;
+pub mod syntax;
+pub mod typecheck;
+pub mod callgraph;
+pub mod metrics;
+pub mod unused;
+pub mod stdlib;
+pub mod tasks;
+pub mod promise;
+pub mod proxy;
+pub mod collections;
+pub mod iterator;
+pub mod typed_array;
+pub mod console;
+pub mod performance;
+pub mod stack;
+pub mod native_stack;
+pub mod roots;
+pub mod convert;
+pub mod events;
+pub mod host;
+pub mod realm;
+pub mod lockdown;
+pub mod cache;
+
+#[cfg(feature = "derive")]
+pub use jays_derive::{js_function, JsClass};
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "resumable")]
+pub mod resumable;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+#[cfg(feature = "regex")]
+pub mod regexp;
+#[cfg(feature = "tokio")]
+pub mod future;
+
+// Entry points for fuzzing and other hosts that must never panic: every
+// failure, including input nested too deeply to handle, comes back as an
+// error.
+
+// Lexes and parses `source` with the default nesting limit
+pub fn parse_str(source: &str) -> Result<Vec<ast::Stmt>, error::JsEngineError> {
+    parse_str_with_limits(source, &interpreter::Limits::default())
+}
+
+pub(crate) fn parse_str_with_limits(source: &str, limits: &interpreter::Limits) -> Result<Vec<ast::Stmt>, error::JsEngineError> {
+    let tokens = lexer::Lexer::new(source).scan_tokens()?;
+    parser::Parser::new(tokens).with_max_nesting(limits.max_nesting).parse()
+}
+
+// Runs `source` in a fresh interpreter. Recursion stops at `max_call_depth`,
+// or earlier if the thread's stack is running out; set `max_steps` to bound
+// running time as well.
+pub fn eval_str_with_limits(source: &str, limits: &interpreter::Limits) -> Result<interpreter::Value, error::JsEngineError> {
+    let mut interpreter = interpreter::Interpreter::builder().with_limits(limits.clone()).build();
+    interpreter.eval(source)
+}
@@ -0,0 +1,108 @@
+// src/locale.rs
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+// Formatting conventions for the locale-aware built-ins, such as
+// Number.prototype.toLocaleString. Only the separators are modelled: digits
+// are grouped in threes with the language's group separator, and numbers
+// are rounded to at most three fraction digits as JS does by default.
+//
+//     let interpreter = Interpreter::builder().with_locale(Locale::new("de-DE")).build();
+//
+// Locales are chosen by their language subtag; unknown languages format
+// like English.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Locale {
+    tag: Rc<str>,
+    group: &'static str,
+    decimal: char,
+}
+
+impl Locale {
+    pub fn new(tag: &str) -> Self {
+        let language = tag.split(['-', '_']).next().unwrap_or("").to_ascii_lowercase();
+        let (group, decimal) = match language.as_str() {
+            "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" => (".", ','),
+            "fr" => ("\u{202f}", ','),
+            "ru" | "uk" | "pl" | "cs" | "sv" | "fi" | "nb" => ("\u{a0}", ','),
+            _ => (",", '.'),
+        };
+        
+        Locale {
+            tag: Rc::from(tag),
+            group,
+            decimal,
+        }
+    }
+    
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+    
+    // e.g. 1234567.891 as "1,234,567.891" in English or "1.234.567,891" in
+    // German
+    pub fn format_number(&self, n: f64) -> String {
+        if n.is_nan() {
+            return "NaN".to_string();
+        }
+        if n.is_infinite() {
+            return if n < 0.0 { "-∞" } else { "∞" }.to_string();
+        }
+        
+        let fixed = format!("{:.3}", n.abs());
+        let (integer, fraction) = fixed.split_once('.').unwrap_or((&fixed, ""));
+        let fraction = fraction.trim_end_matches('0');
+        
+        let mut result = String::new();
+        if n.is_sign_negative() {
+            result.push('-');
+        }
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                result.push_str(self.group);
+            }
+            result.push(digit);
+        }
+        if !fraction.is_empty() {
+            result.push(self.decimal);
+            result.push_str(fraction);
+        }
+        result
+    }
+    
+    // Orders strings as a reader would rather than by code point: letters
+    // compare without regard to case or accents first, then unaccented
+    // before accented, then lowercase before uppercase. The same order is
+    // used for every locale.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        let primary = |text: &str| text.chars().flat_map(char::to_lowercase).map(base_letter).collect::<Vec<_>>();
+        let secondary = |text: &str| text.chars().flat_map(char::to_lowercase).collect::<Vec<_>>();
+        let tertiary = |text: &str| text.chars().map(char::is_uppercase).collect::<Vec<_>>();
+        
+        primary(a)
+            .cmp(&primary(b))
+            .then_with(|| secondary(a).cmp(&secondary(b)))
+            .then_with(|| tertiary(a).cmp(&tertiary(b)))
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::new("en-US")
+    }
+}
+
+// Strips the accent from the Latin-1 letters; other characters are kept
+fn base_letter(c: char) -> char {
+    match c {
+        'à'..='å' => 'a',
+        'ç' => 'c',
+        'è'..='ë' => 'e',
+        'ì'..='ï' => 'i',
+        'ñ' => 'n',
+        'ò'..='ö' | 'ø' => 'o',
+        'ù'..='ü' => 'u',
+        'ý' | 'ÿ' => 'y',
+        other => other,
+    }
+}
@@ -0,0 +1,42 @@
+// src/lockdown.rs
+use crate::interpreter::{Interpreter, Value};
+use std::collections::HashSet;
+use std::rc::Rc;
+
+impl Interpreter {
+    // Hardens the built-ins against tampering, in the spirit of SES's
+    // lockdown(), so a script cannot replace or patch `Math`, `JSON` and the
+    // like to attack other scripts run by the same interpreter. Every global
+    // bound when this is called becomes read-only, so assigning or
+    // redeclaring it throws a TypeError, and every object reachable from
+    // those globals is frozen. The methods of strings and numbers are built
+    // in and cannot be changed by scripts in the first place.
+    //
+    // Call it before running untrusted code; globals the host defines
+    // afterwards are left alone. InterpreterBuilder::hardened does this as
+    // part of building.
+    pub fn lockdown(&mut self) {
+        let mut pending = Vec::new();
+        {
+            let mut globals = self.globals().borrow_mut();
+            let names: Vec<Rc<str>> = globals.bindings().map(|(name, _)| Rc::clone(name)).collect();
+            for name in names {
+                globals.make_read_only(&name);
+            }
+            pending.extend(globals.bindings().map(|(_, value)| value.clone()));
+        }
+        
+        // Objects can refer to each other in cycles, so each is visited once
+        let mut seen = HashSet::new();
+        while let Some(value) = pending.pop() {
+            if let Value::Object(object) = value
+                && seen.insert(Rc::as_ptr(&object))
+            {
+                object.borrow_mut().freeze();
+                let object = object.borrow();
+                pending.extend(object.values().cloned());
+                pending.extend(object.prototype().map(|prototype| Value::Object(Rc::clone(prototype))));
+            }
+        }
+    }
+}
@@ -1,41 +1,108 @@
 // src/main.rs
-mod lexer;
-mod parser;
-mod interpreter;
-mod error;
-
+use jays::ast::Stmt;
+use jays::cache::ParseCache;
+use jays::callgraph::CallGraph;
+use jays::error::JsEngineError;
+use jays::{interpreter, lexer, metrics, parser, resolver, snapshot, typecheck, unused};
 use std::env;
 use std::fs;
 use std::io::{self, Write};
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+// Output format of the graphs --heap-dump and callgraph print
+enum GraphFormat {
+    Json,
+    Dot,
+}
+
+// Command-line flags
+#[derive(Default)]
+struct Options {
+    heap_stats: bool, // --heap-stats: print heap statistics after each run
+    heap_dump: Option<GraphFormat>, // --heap-dump=json|dot: print the object graph after each run
+    heap_snapshot: Option<String>, // --heap-snapshot=FILE: write a V8 .heapsnapshot after each run
+    typescript: bool, // --ts, or a .ts file: strip TypeScript type syntax
+    cache_dir: Option<String>, // --cache-dir=DIR: reuse parse results stored in DIR
+    hardened: bool, // --hardened: make the built-in globals read-only and freeze their objects
+}
+
+// Room for the default call depth limit even in debug builds, whose calls
+// take far more stack than the main thread has
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+fn main() {
+    let result = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(|| run().map_err(|error| format!("{:?}", error)))
+        .expect("failed to start the interpreter thread")
+        .join()
+        .expect("interpreter thread panicked");
+    
+    if let Err(error) = result {
+        eprintln!("Error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = env::args().collect();
     
-    if args.len() > 1 {
+    // Subcommands
+    match args.get(1).map(String::as_str) {
+        Some("check") => return check(&args[2..]),
+        Some("analyze") => return analyze(&args[2..]),
+        Some("callgraph") => return callgraph(&args[2..]),
+        Some("metrics") => return report_metrics(&args[2..]),
+        Some("unused") => return report_unused(&args[2..]),
+        _ => {}
+    }
+    
+    let mut options = Options::default();
+    let mut file_path = None;
+    
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "--heap-stats" => options.heap_stats = true,
+            "--ts" => options.typescript = true,
+            "--hardened" => options.hardened = true,
+            "--heap-dump" | "--heap-dump=json" => options.heap_dump = Some(GraphFormat::Json),
+            "--heap-dump=dot" => options.heap_dump = Some(GraphFormat::Dot),
+            _ if arg.starts_with("--heap-snapshot=") => {
+                options.heap_snapshot = Some(arg["--heap-snapshot=".len()..].to_string());
+            },
+            _ if arg.starts_with("--cache-dir=") => {
+                options.cache_dir = Some(arg["--cache-dir=".len()..].to_string());
+            },
+            _ => file_path = Some(arg),
+        }
+    }
+    
+    if let Some(file_path) = file_path {
+        if file_path.ends_with(".ts") {
+            options.typescript = true;
+        }
+        
         // Execute JavaScript from a file
-        let file_path = &args[1];
         let source = fs::read_to_string(file_path)?;
-        execute_js(&source)?;
+        // Uncaught exceptions print as "Uncaught TypeError: message"
+        if let Err(error) = execute_js(&source, &options) {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
     } else {
         // Interactive REPL mode
-        repl()?;
+        repl(&options)?;
     }
     
     Ok(())
 }
 
-fn execute_js(source: &str) -> Result<(), Box<dyn std::error::Error>> {
-    // Create a lexer and scan tokens
-    let mut lexer = lexer::Lexer::new(source);
-    let tokens = lexer.scan_tokens()?;
-    
-    // Create a parser and parse the tokens into an AST
-    let mut parser = parser::Parser::new(tokens);
-    let statements = parser.parse()?;
+fn execute_js(source: &str, options: &Options) -> Result<(), Box<dyn std::error::Error>> {
+    // Parse the source into an AST, or load it from the cache directory
+    let statements = parse_cache(options)?.parse(source, options.typescript)?;
     
     // Create an interpreter and execute the AST
-    let mut interpreter = interpreter::Interpreter::new();
-    let result = interpreter.interpret(statements)?;
+    let mut interpreter = new_interpreter(options);
+    let result = interpreter.interpret_with_source(statements, source).map_err(JsEngineError::uncaught)?;
     
     // Print the result if we're not in a block or if the result is not undefined
     if let interpreter::Value::Undefined = result {
@@ -44,10 +111,215 @@ fn execute_js(source: &str) -> Result<(), Box<dyn std::error::Error>> {
         println!("=> {:?}", result);
     }
     
+    if options.heap_stats {
+        println!("{}", interpreter.heap_stats());
+    }
+    
+    match options.heap_dump {
+        Some(GraphFormat::Json) => println!("{}", interpreter.heap_graph().to_json()),
+        Some(GraphFormat::Dot) => println!("{}", interpreter.heap_graph().to_dot()),
+        None => {}
+    }
+    
+    if let Some(path) = &options.heap_snapshot {
+        fs::write(path, snapshot::heap_snapshot(&interpreter.heap_graph()))?;
+    }
+    
+    Ok(())
+}
+
+fn new_interpreter(options: &Options) -> interpreter::Interpreter {
+    let builder = interpreter::Interpreter::builder();
+    if options.hardened { builder.hardened() } else { builder }.build()
+}
+
+#[cfg(feature = "cache")]
+fn parse_cache(options: &Options) -> Result<ParseCache, Box<dyn std::error::Error>> {
+    let cache = ParseCache::new();
+    Ok(match &options.cache_dir {
+        Some(directory) => cache.with_directory(directory),
+        None => cache,
+    })
+}
+
+#[cfg(not(feature = "cache"))]
+fn parse_cache(options: &Options) -> Result<ParseCache, Box<dyn std::error::Error>> {
+    if options.cache_dir.is_some() {
+        return Err("--cache-dir requires building with the cache feature".into());
+    }
+    Ok(ParseCache::new())
+}
+
+// Lexes and parses a file without running it, returning its source and
+// statements; .ts files are parsed in TypeScript mode
+fn parse_file(file_path: &str) -> Result<(String, Vec<Stmt>), Box<dyn std::error::Error>> {
+    let source = fs::read_to_string(file_path)?;
+    let tokens = lexer::Lexer::new(&source).scan_tokens()?;
+    let mut parser = parser::Parser::new(tokens).with_typescript(file_path.ends_with(".ts"));
+    let statements = parser.parse()?;
+    
+    Ok((source, statements))
+}
+
+// jays check [--types] FILE: reports syntax errors, and with --types probable
+// type errors, without running the script
+fn check(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut types = false;
+    let mut file_path = None;
+    
+    for arg in args {
+        match arg.as_str() {
+            "--types" => types = true,
+            _ => file_path = Some(arg),
+        }
+    }
+    
+    let Some(file_path) = file_path else {
+        return Err("usage: jays check [--types] FILE".into());
+    };
+    
+    let (source, statements) = parse_file(file_path)?;
+    
+    let diagnostics = if types { typecheck::check_types(&statements) } else { Vec::new() };
+    for diagnostic in &diagnostics {
+        let (line, column) = lexer::line_column(&source, diagnostic.span.start);
+        println!("{}:{}:{}: {}", file_path, line, column, diagnostic);
+    }
+    
+    if !diagnostics.is_empty() {
+        std::process::exit(1);
+    }
+    
+    Ok(())
+}
+
+// jays analyze FILE: prints every scope, declaration and reference as JSON
+fn analyze(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let [file_path] = args else {
+        return Err("usage: jays analyze FILE".into());
+    };
+    
+    let (source, statements) = parse_file(file_path)?;
+    println!("{}", resolver::analyze(&statements).to_json(&source));
+    
+    Ok(())
+}
+
+// jays callgraph [--format=json|dot] FILE: prints which functions call which
+fn callgraph(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let usage = "usage: jays callgraph [--format=json|dot] FILE";
+    let mut format = GraphFormat::Json;
+    let mut file_path = None;
+    
+    for arg in args {
+        match arg.as_str() {
+            "--format=json" => format = GraphFormat::Json,
+            "--format=dot" => format = GraphFormat::Dot,
+            _ if arg.starts_with("--") => return Err(format!("unknown option '{}' ({})", arg, usage).into()),
+            _ => file_path = Some(arg),
+        }
+    }
+    
+    let Some(file_path) = file_path else {
+        return Err(usage.into());
+    };
+    
+    let (source, statements) = parse_file(file_path)?;
+    let graph = CallGraph::collect(&statements);
+    
+    match format {
+        GraphFormat::Json => println!("{}", graph.to_json(&source)),
+        GraphFormat::Dot => println!("{}", graph.to_dot()),
+    }
+    
+    Ok(())
+}
+
+// jays metrics [--json] [--max-complexity=N] FILE: prints per-function code
+// metrics, failing if any function is more complex than the limit
+fn report_metrics(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut json = false;
+    let mut max_complexity = None;
+    let mut file_path = None;
+    
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            _ if arg.starts_with("--max-complexity=") => {
+                max_complexity = Some(arg["--max-complexity=".len()..].parse::<usize>()?);
+            },
+            _ => file_path = Some(arg),
+        }
+    }
+    
+    let Some(file_path) = file_path else {
+        return Err("usage: jays metrics [--json] [--max-complexity=N] FILE".into());
+    };
+    
+    let (source, statements) = parse_file(file_path)?;
+    let metrics = metrics::collect_metrics(&statements, &source);
+    
+    if json {
+        println!("{}", metrics::to_json(&metrics, &source));
+    } else {
+        print!("{}", metrics::format_table(&metrics, &source));
+    }
+    
+    if let Some(limit) = max_complexity {
+        let over: Vec<_> = metrics.iter().filter(|metrics| metrics.complexity > limit).collect();
+        
+        for metrics in &over {
+            eprintln!("{}: '{}' has complexity {} (limit {})", file_path, metrics.name, metrics.complexity, limit);
+        }
+        
+        if !over.is_empty() {
+            std::process::exit(1);
+        }
+    }
+    
+    Ok(())
+}
+
+// jays unused [--json] FILE...: reports unused declarations, failing if
+// there are any. Each file is analyzed on its own.
+fn report_unused(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let json = args.iter().any(|arg| arg == "--json");
+    let files: Vec<&String> = args.iter().filter(|arg| *arg != "--json").collect();
+    
+    if files.is_empty() {
+        return Err("usage: jays unused [--json] FILE...".into());
+    }
+    
+    let mut found = false;
+    let mut entries = Vec::new();
+    
+    for file_path in files {
+        let (source, statements) = parse_file(file_path)?;
+        let unused = unused::find_unused(&statements);
+        found |= !unused.is_empty();
+        
+        if json {
+            entries.extend(unused::json_entries(&unused, file_path, &source));
+        } else {
+            for binding in &unused {
+                let (line, column) = lexer::line_column(&source, binding.span.start);
+                println!("{}:{}:{}: {}", file_path, line, column, binding.message());
+            }
+        }
+    }
+    
+    if json {
+        println!("{}", unused::to_json(&entries));
+    }
+    
+    if found {
+        std::process::exit(1);
+    }
+    
     Ok(())
 }
 
-fn repl() -> Result<(), Box<dyn std::error::Error>> {
+fn repl(options: &Options) -> Result<(), Box<dyn std::error::Error>> {
     println!("MiniJS Engine REPL (press Ctrl+C to exit)");
     
     loop {
@@ -61,7 +333,7 @@ fn repl() -> Result<(), Box<dyn std::error::Error>> {
             continue;
         }
         
-        match execute_js(&input) {
+        match execute_js(&input, options) {
             Ok(_) => {},
             Err(e) => println!("Error: {}", e),
         }
@@ -0,0 +1,184 @@
+// src/metrics.rs
+use crate::ast::{walk_expr, walk_stmt, BinaryOp, Expr, Stmt, Visitor};
+use crate::heap::json_string;
+use crate::lexer::{line_column, Span};
+
+// Per-function code metrics. Nested functions are measured on their own and
+// do not count towards the function that contains them; code outside any
+// function is reported as "(top level)".
+#[derive(Debug, Clone)]
+pub struct FunctionMetrics {
+    pub name: String,
+    pub span: Option<Span>, // None for the top level
+    pub parameters: usize,
+    // 1 plus the number of branch points: if, while, catch, ?: and short-circuit
+    // && / || / ??
+    pub complexity: usize,
+    // Deepest nesting of if / while statements
+    pub nesting: usize,
+    pub lines: usize,
+}
+
+pub fn collect_metrics(statements: &[Stmt], source: &str) -> Vec<FunctionMetrics> {
+    let mut collector = Collector {
+        source,
+        done: Vec::new(),
+        stack: Vec::new(),
+    };
+    
+    collector.begin("(top level)".to_string(), None, 0);
+    for statement in statements {
+        collector.visit_stmt(statement);
+    }
+    collector.end();
+    
+    // Report functions in source order, top level first
+    collector.done.sort_by_key(|metrics| metrics.span.map(|span| span.start));
+    collector.done
+}
+
+// Renders metrics as an aligned text table
+pub fn format_table(metrics: &[FunctionMetrics], source: &str) -> String {
+    let names: Vec<String> = metrics
+        .iter()
+        .map(|metrics| match metrics.span {
+            Some(span) => {
+                let (line, _) = line_column(source, span.start);
+                format!("{} (line {})", metrics.name, line)
+            },
+            None => metrics.name.clone(),
+        })
+        .collect();
+    let width = names.iter().map(|name| name.chars().count()).max().unwrap_or(0).max("function".len());
+    
+    let mut out = format!("{:<width$}  complexity  nesting  params  lines\n", "function", width = width);
+    for (name, metrics) in names.iter().zip(metrics) {
+        out.push_str(&format!(
+            "{:<width$}  {:>10}  {:>7}  {:>6}  {:>5}\n",
+            name,
+            metrics.complexity,
+            metrics.nesting,
+            metrics.parameters,
+            metrics.lines,
+            width = width,
+        ));
+    }
+    
+    out
+}
+
+pub fn to_json(metrics: &[FunctionMetrics], source: &str) -> String {
+    let entries: Vec<String> = metrics
+        .iter()
+        .map(|metrics| {
+            let line = metrics.span.map_or("null".to_string(), |span| line_column(source, span.start).0.to_string());
+            format!(
+                "{{\"name\":{},\"line\":{},\"complexity\":{},\"nesting\":{},\"parameters\":{},\"lines\":{}}}",
+                json_string(&metrics.name),
+                line,
+                metrics.complexity,
+                metrics.nesting,
+                metrics.parameters,
+                metrics.lines,
+            )
+        })
+        .collect();
+    
+    format!("[{}]", entries.join(","))
+}
+
+struct Collector<'a> {
+    source: &'a str,
+    done: Vec<FunctionMetrics>,
+    // Functions being measured, innermost last, with their current depth
+    stack: Vec<(FunctionMetrics, usize)>,
+}
+
+impl Visitor for Collector<'_> {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Function(name, parameters, body, span, _) => self.function(name.clone(), parameters.len(), body, *span),
+            Stmt::If(..) | Stmt::While(..) | Stmt::For(..) | Stmt::ForOf(..) => {
+                self.branch();
+                self.nested(|collector| walk_stmt(collector, stmt));
+            },
+            // Each case is a way through; the default clause is the
+            // fall-back path rather than a decision of its own
+            Stmt::Try(_, Some(_), _) => {
+                self.branch();
+                self.nested(|collector| walk_stmt(collector, stmt));
+            },
+            Stmt::Switch(_, clauses) => {
+                for _ in clauses.iter().filter(|(test, _)| test.is_some()) {
+                    self.branch();
+                }
+                self.nested(|collector| walk_stmt(collector, stmt));
+            },
+            _ => walk_stmt(self, stmt),
+        }
+    }
+    
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Function(parameters, body, span, _) => {
+                self.function("(anonymous)".to_string(), parameters.len(), body, *span);
+            },
+            Expr::Conditional(..) | Expr::Binary(_, BinaryOp::And | BinaryOp::Or | BinaryOp::Nullish, _) => {
+                self.branch();
+                walk_expr(self, expr);
+            },
+            _ => walk_expr(self, expr),
+        }
+    }
+}
+
+impl Collector<'_> {
+    fn function(&mut self, name: String, parameters: usize, body: &Stmt, span: Span) {
+        self.begin(name, Some(span), parameters);
+        self.visit_stmt(body);
+        self.end();
+    }
+    
+    fn begin(&mut self, name: String, span: Option<Span>, parameters: usize) {
+        let lines = match span {
+            Some(span) => self.source.get(span.start..span.end).map_or(0, |text| text.lines().count()),
+            None => self.source.lines().count(),
+        };
+        
+        self.stack.push((
+            FunctionMetrics {
+                name,
+                span,
+                parameters,
+                complexity: 1,
+                nesting: 0,
+                lines,
+            },
+            0,
+        ));
+    }
+    
+    fn end(&mut self) {
+        let (metrics, _) = self.stack.pop().expect("function being measured");
+        self.done.push(metrics);
+    }
+    
+    fn branch(&mut self) {
+        if let Some((metrics, _)) = self.stack.last_mut() {
+            metrics.complexity += 1;
+        }
+    }
+    
+    fn nested(&mut self, walk: impl FnOnce(&mut Self)) {
+        if let Some((metrics, depth)) = self.stack.last_mut() {
+            *depth += 1;
+            metrics.nesting = metrics.nesting.max(*depth);
+        }
+        
+        walk(self);
+        
+        if let Some((_, depth)) = self.stack.last_mut() {
+            *depth -= 1;
+        }
+    }
+}
@@ -0,0 +1,76 @@
+// src/native_stack.rs
+use std::cell::OnceCell;
+
+// How far the native stack can grow. Every call checks the stack pointer
+// against a limit near the end of the stack it runs on, so that recursion
+// too deep for the stack fails with "Maximum call stack size exceeded"
+// instead of overflowing it. How deep that is depends on the build, as a
+// call takes several kilobytes of stack in release builds and tens of
+// kilobytes in debug builds, and on the size of the stack: a thread's, or a
+// coroutine's for resumable executions and async functions.
+
+// Room kept below the limit for what runs between two checks: the rest of
+// a call, and the natives and host code it calls
+const RESERVE: usize = 256 * 1024;
+
+// Stack assumed when the platform cannot tell how big a thread's is
+const FALLBACK_SIZE: usize = 1024 * 1024;
+
+thread_local! {
+    static THREAD_LIMIT: OnceCell<usize> = const { OnceCell::new() };
+}
+
+// An address in the caller's frame, standing in for the stack pointer
+#[inline(always)]
+pub(crate) fn position() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+// The limit for the current thread's stack. Stacks grow downwards on every
+// platform the engine runs on.
+pub(crate) fn thread_limit() -> usize {
+    THREAD_LIMIT.with(|limit| {
+        *limit.get_or_init(|| match thread_stack_end() {
+            Some(end) => limit_for(end),
+            None => position().saturating_sub(FALLBACK_SIZE - RESERVE),
+        })
+    })
+}
+
+// The limit for a stack whose lowest usable address is `end`
+pub(crate) fn limit_for(end: usize) -> usize {
+    end + RESERVE
+}
+
+#[cfg(target_os = "linux")]
+fn thread_stack_end() -> Option<usize> {
+    // SAFETY: the attributes are initialized by pthread_getattr_np before
+    // they are read, and destroyed once the stack has been read from them
+    unsafe {
+        let mut attributes = std::mem::MaybeUninit::<libc::pthread_attr_t>::uninit();
+        if libc::pthread_getattr_np(libc::pthread_self(), attributes.as_mut_ptr()) != 0 {
+            return None;
+        }
+        let mut address = std::ptr::null_mut();
+        let mut size = 0;
+        let result = libc::pthread_attr_getstack(attributes.as_ptr(), &mut address, &mut size);
+        libc::pthread_attr_destroy(attributes.as_mut_ptr());
+        (result == 0).then_some(address as usize)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn thread_stack_end() -> Option<usize> {
+    // SAFETY: both functions only read the attributes of the calling thread
+    unsafe {
+        let thread = libc::pthread_self();
+        let start = libc::pthread_get_stackaddr_np(thread) as usize;
+        Some(start - libc::pthread_get_stacksize_np(thread))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn thread_stack_end() -> Option<usize> {
+    None
+}
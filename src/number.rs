@@ -0,0 +1,369 @@
+// src/number.rs
+
+// Exponentiation as JavaScript specifies it for ** and Math.pow, which
+// differs from powf where the result is ambiguous: 1 ** Infinity and
+// 1 ** NaN are NaN rather than 1
+pub fn power(base: f64, exponent: f64) -> f64 {
+    if exponent.is_nan() || (exponent.is_infinite() && base.abs() == 1.0) {
+        return f64::NAN;
+    }
+    base.powf(exponent)
+}
+
+// Number-to-string conversion as JavaScript specifies it (ToString applied
+// to a Number): the shortest digits that read back as the same number,
+// written out in full from 1e-6 up to 1e21 and in exponent form outside that
+// range. -0 prints as "0".
+pub fn number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        return "NaN".to_string();
+    }
+    if n == 0.0 {
+        return "0".to_string();
+    }
+    if n < 0.0 {
+        return format!("-{}", number_to_string(-n));
+    }
+    if n.is_infinite() {
+        return "Infinity".to_string();
+    }
+    
+    // Rust's exponent form also uses the shortest round-tripping digits,
+    // e.g. "1.2345e3"
+    let scientific = format!("{:e}", n);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let point = exponent.parse::<i32>().unwrap_or(0) + 1; // Position of the decimal point
+    
+    if k <= point && point <= 21 {
+        format!("{}{}", digits, "0".repeat((point - k) as usize))
+    } else if 0 < point && point <= 21 {
+        format!("{}.{}", &digits[..point as usize], &digits[point as usize..])
+    } else if -6 < point && point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else {
+        let exponent = point - 1;
+        let sign = if exponent < 0 { '-' } else { '+' };
+        if k == 1 {
+            format!("{}e{}{}", digits, sign, exponent.abs())
+        } else {
+            format!("{}.{}e{}{}", &digits[..1], &digits[1..], sign, exponent.abs())
+        }
+    }
+}
+
+// Enough significant digits to write any f64 out exactly, which the
+// methods below round from so that halfway cases round up as the spec
+// requires rather than to even as Rust's formatting does
+const EXACT_DIGITS: usize = 800;
+
+// Number.prototype.toFixed for 0 <= digits <= 100: `digits` places after
+// the point, or ToString for numbers of 1e21 and above
+pub fn to_fixed(n: f64, digits: usize) -> String {
+    if !n.is_finite() || n.abs() >= 1e21 {
+        return number_to_string(n);
+    }
+    if n < 0.0 {
+        return format!("-{}", to_fixed(-n, digits));
+    }
+    
+    // The integer nearest n * 10^digits, which is 0 when n is too small to
+    // reach the last place
+    let (significand, point) = exact_digits(n);
+    let keep = point + digits as i32;
+    let mut integer = if keep < 0 { Vec::new() } else { round_digits(&significand, keep as usize).0 };
+    if integer.is_empty() {
+        integer.push(b'0');
+    }
+    
+    if digits == 0 {
+        return String::from_utf8(integer).unwrap_or_default();
+    }
+    if integer.len() <= digits {
+        let mut padded = vec![b'0'; digits + 1 - integer.len()];
+        padded.extend(integer);
+        integer = padded;
+    }
+    let (whole, fraction) = integer.split_at(integer.len() - digits);
+    format!("{}.{}", String::from_utf8_lossy(whole), String::from_utf8_lossy(fraction))
+}
+
+// Number.prototype.toExponential: one digit before the point and
+// `digits` after it, or as many as it takes to identify the number when
+// `digits` is None
+pub fn to_exponential(n: f64, digits: Option<usize>) -> String {
+    if !n.is_finite() {
+        return number_to_string(n);
+    }
+    if n < 0.0 {
+        return format!("-{}", to_exponential(-n, digits));
+    }
+    
+    let (significand, exponent) = match digits {
+        None if n == 0.0 => (b"0".to_vec(), 0),
+        None => {
+            let scientific = format!("{:e}", n);
+            let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+            (mantissa.bytes().filter(|c| *c != b'.').collect(), exponent.parse().unwrap_or(0))
+        },
+        Some(digits) => significant_digits(n, digits + 1),
+    };
+    
+    let mut out = String::from_utf8_lossy(&significand[..1]).into_owned();
+    if significand.len() > 1 {
+        out.push('.');
+        out.push_str(&String::from_utf8_lossy(&significand[1..]));
+    }
+    format!("{}e{}{}", out, if exponent < 0 { '-' } else { '+' }, exponent.abs())
+}
+
+// Number.prototype.toPrecision for 1 <= precision <= 100: `precision`
+// significant digits, in exponent form when the number is too large or too
+// small to write them out plainly
+pub fn to_precision(n: f64, precision: usize) -> String {
+    if !n.is_finite() {
+        return number_to_string(n);
+    }
+    if n < 0.0 {
+        return format!("-{}", to_precision(-n, precision));
+    }
+    
+    let (significand, exponent) = significant_digits(n, precision);
+    if exponent < -6 || exponent >= precision as i32 {
+        return to_exponential(n, Some(precision - 1));
+    }
+    
+    let digits = String::from_utf8_lossy(&significand).into_owned();
+    if exponent < 0 {
+        format!("0.{}{}", "0".repeat((-exponent - 1) as usize), digits)
+    } else if exponent as usize == precision - 1 {
+        digits
+    } else {
+        let (whole, fraction) = digits.split_at(exponent as usize + 1);
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+// Number.prototype.toString with a radix from 2 to 36. As in V8, the
+// fraction gets as many digits as it takes to tell the number apart from
+// its neighbours, e.g. (0.1).toString(2) has 55 of them.
+pub fn to_radix_string(n: f64, radix: u32) -> String {
+    if radix == 10 || !n.is_finite() || n == 0.0 {
+        return number_to_string(n);
+    }
+    if n < 0.0 {
+        return format!("-{}", to_radix_string(-n, radix));
+    }
+    
+    let base = radix as f64;
+    let mut integer = n.floor();
+    let mut fraction = n - integer;
+    let mut fraction_digits = Vec::new();
+    
+    // Half the gap to the next double, below which digits say nothing more
+    let mut delta = (0.5 * (f64::from_bits(n.to_bits() + 1) - n)).max(f64::from_bits(1));
+    if fraction >= delta {
+        loop {
+            fraction *= base;
+            delta *= base;
+            let digit = fraction as u32;
+            fraction_digits.push(digit);
+            fraction -= digit as f64;
+            
+            if (fraction > 0.5 || (fraction == 0.5 && digit & 1 == 1)) && fraction + delta > 1.0 {
+                // Round up, carrying into the integer part if every digit
+                // was the largest one
+                while let Some(last) = fraction_digits.pop() {
+                    if last + 1 < radix {
+                        fraction_digits.push(last + 1);
+                        break;
+                    }
+                }
+                if fraction_digits.is_empty() {
+                    integer += 1.0;
+                }
+                break;
+            }
+            if fraction < delta {
+                break;
+            }
+        }
+    }
+    
+    // Digits below the precision of huge integers are zeros
+    let mut integer_digits = Vec::new();
+    while integer / base >= 9_007_199_254_740_992.0 {
+        integer /= base;
+        integer_digits.push(0);
+    }
+    loop {
+        let remainder = integer % base;
+        integer_digits.push(remainder as u32);
+        integer = (integer - remainder) / base;
+        if integer <= 0.0 {
+            break;
+        }
+    }
+    
+    let mut out: String = integer_digits.iter().rev().map(|digit| radix_digit(*digit, radix)).collect();
+    if !fraction_digits.is_empty() {
+        out.push('.');
+        out.extend(fraction_digits.iter().map(|digit| radix_digit(*digit, radix)));
+    }
+    out
+}
+
+fn radix_digit(digit: u32, radix: u32) -> char {
+    char::from_digit(digit, radix).unwrap_or('0')
+}
+
+// The decimal digits of a positive number, exactly, and the position of
+// the decimal point relative to the first of them
+fn exact_digits(n: f64) -> (Vec<u8>, i32) {
+    let scientific = format!("{:.*e}", EXACT_DIGITS, n);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let mut digits: Vec<u8> = mantissa.bytes().filter(|c| *c != b'.').collect();
+    while digits.len() > 1 && digits.last() == Some(&b'0') {
+        digits.pop();
+    }
+    (digits, exponent.parse::<i32>().unwrap_or(0) + 1)
+}
+
+// The first `count` digits, rounded half up on the rest; the bool is true
+// when rounding carried into a new leading digit, making the result one
+// digit longer
+fn round_digits(digits: &[u8], count: usize) -> (Vec<u8>, bool) {
+    let mut kept: Vec<u8> = digits.iter().copied().chain(std::iter::repeat(b'0')).take(count).collect();
+    if digits.get(count).is_none_or(|digit| *digit < b'5') {
+        return (kept, false);
+    }
+    
+    for digit in kept.iter_mut().rev() {
+        if *digit == b'9' {
+            *digit = b'0';
+        } else {
+            *digit += 1;
+            return (kept, false);
+        }
+    }
+    kept.insert(0, b'1');
+    (kept, true)
+}
+
+// A positive number rounded to `count` significant digits, with the power
+// of ten of the first
+fn significant_digits(n: f64, count: usize) -> (Vec<u8>, i32) {
+    if n == 0.0 {
+        return (vec![b'0'; count], 0);
+    }
+    let (digits, point) = exact_digits(n);
+    let (mut rounded, carried) = round_digits(&digits, count);
+    rounded.truncate(count);
+    (rounded, point - 1 + carried as i32)
+}
+
+// String-to-number conversion as JavaScript specifies it (ToNumber applied
+// to a String): surrounding whitespace is ignored, an empty string is 0, and
+// besides decimal literals the text may be Infinity with an optional sign or
+// an unsigned 0x, 0o or 0b integer. Anything else is NaN.
+pub fn string_to_number(text: &str) -> f64 {
+    let text = text.trim();
+    if text.is_empty() {
+        return 0.0;
+    }
+    
+    let prefixed = |prefixes: [&str; 2], radix| prefixes.iter().find_map(|prefix| text.strip_prefix(prefix)).map(|digits| (digits, radix));
+    let radix_digits = prefixed(["0x", "0X"], 16).or_else(|| prefixed(["0o", "0O"], 8)).or_else(|| prefixed(["0b", "0B"], 2));
+    if let Some((digits, radix)) = radix_digits {
+        return match integer_prefix(digits, radix) {
+            (value, length) if length > 0 && length == digits.len() => value,
+            _ => f64::NAN,
+        };
+    }
+    
+    match decimal_prefix(text) {
+        Some((value, length)) if length == text.len() => value,
+        _ => f64::NAN,
+    }
+}
+
+// parseFloat: the longest decimal literal, or Infinity, at the start of the
+// text after any whitespace; NaN if there is none
+pub fn parse_float(text: &str) -> f64 {
+    decimal_prefix(text.trim_start()).map_or(f64::NAN, |(value, _)| value)
+}
+
+// parseInt: the integer in base `radix` at the start of the text after any
+// whitespace and sign. A radix of 0 means 10, or 16 for text starting with
+// 0x; a 0x prefix is also skipped in base 16. NaN if there are no digits or
+// the radix is outside 2 to 36.
+pub fn parse_int(text: &str, radix: u32) -> f64 {
+    let text = text.trim_start();
+    let (sign, text) = match text.as_bytes().first() {
+        Some(b'-') => (-1.0, &text[1..]),
+        Some(b'+') => (1.0, &text[1..]),
+        _ => (1.0, text),
+    };
+    
+    let hex = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X"));
+    let (text, radix) = match (radix, hex) {
+        (0 | 16, Some(digits)) => (digits, 16),
+        (0, None) => (text, 10),
+        _ => (text, radix),
+    };
+    if !(2..=36).contains(&radix) {
+        return f64::NAN;
+    }
+    
+    match integer_prefix(text, radix) {
+        (_, 0) => f64::NAN,
+        (value, _) => sign * value,
+    }
+}
+
+// The value of the digits in base `radix` at the start of `text`, and how
+// many bytes they take. Accumulates in f64, so that long inputs round
+// instead of overflowing.
+fn integer_prefix(text: &str, radix: u32) -> (f64, usize) {
+    let length = text.find(|c: char| !c.is_digit(radix)).unwrap_or(text.len());
+    let value = text[..length].chars().fold(0.0, |value, digit| value * radix as f64 + digit.to_digit(radix).unwrap_or(0) as f64);
+    (value, length)
+}
+
+// The value of the StrDecimalLiteral at the start of `text`, and how many
+// bytes it takes: an optional sign, then Infinity or digits with an
+// optional fraction and exponent
+fn decimal_prefix(text: &str) -> Option<(f64, usize)> {
+    let bytes = text.as_bytes();
+    let mut end = usize::from(matches!(bytes.first(), Some(b'+' | b'-')));
+    if text[end..].starts_with("Infinity") {
+        let value = if bytes[0] == b'-' { f64::NEG_INFINITY } else { f64::INFINITY };
+        return Some((value, end + "Infinity".len()));
+    }
+    
+    let digits = |from: usize| bytes[from..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+    let integer_digits = digits(end);
+    end += integer_digits;
+    let mut fraction_digits = 0;
+    if bytes.get(end) == Some(&b'.') {
+        fraction_digits = digits(end + 1);
+        if integer_digits + fraction_digits > 0 {
+            end += 1 + fraction_digits;
+        }
+    }
+    if integer_digits + fraction_digits == 0 {
+        return None;
+    }
+    
+    // An exponent only counts with digits after it
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let sign = usize::from(matches!(bytes.get(end + 1), Some(b'+' | b'-')));
+        let exponent_digits = digits(end + 1 + sign);
+        if exponent_digits > 0 {
+            end += 1 + sign + exponent_digits;
+        }
+    }
+    
+    text[..end].parse().ok().map(|value| (value, end))
+}
@@ -0,0 +1,562 @@
+// src/object.rs
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, Value};
+use crate::collections::{self, Collection};
+use crate::console::ConsoleState;
+use crate::iterator::{self, BuiltinIterator, ValueIterator, ITERATOR};
+use crate::promise::{self, Promise};
+use crate::proxy::Proxy;
+#[cfg(feature = "regex")]
+use crate::regexp::RegExp;
+use crate::shape::Shape;
+use crate::string::JsString;
+use crate::stdlib::to_number;
+use crate::typed_array::{ArrayBuffer, DataView, TypedArray};
+use indexmap::IndexMap;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// What calling an object with a call behaviour does. Such objects are
+// functions that carry properties of their own, like `assert` with
+// `assert.equal`, and unlike native functions they are given the
+// interpreter, so they can call back into the script.
+pub type CallBehaviour = dyn Fn(&mut Interpreter, &[Value]) -> Result<Value, JsEngineError>;
+
+// The properties of an object, enumerated in the order JavaScript specifies:
+// keys that are array indices ("0", "1", ...) first in ascending numeric
+// order, then every other key in the order it was first added. Assigning to
+// an existing key keeps its place; removing and re-adding one moves it to
+// the end.
+//
+// Arrays are property maps with a length, which is kept one past the
+// highest index as elements are added and is not itself a stored property.
+//
+// Reading a key the map does not have falls back to its prototype, and to
+// that object's prototype in turn; writes always go to the map itself.
+//
+// A frozen map rejects changes made by scripts; the methods here are for
+// the engine and the host and do not check it.
+#[derive(Clone, Default)]
+pub struct PropertyMap {
+    entries: Entries,
+    frozen: bool,
+    call: Option<Rc<CallBehaviour>>,
+    array_length: Option<usize>, // Some for arrays
+    prototype: Option<Rc<RefCell<PropertyMap>>>,
+    slot: Option<InternalSlot>,
+}
+
+// Where an object keeps its properties: in the positions its shape gives
+// them (see shape.rs), or in a map of its own
+#[derive(Clone)]
+enum Entries {
+    Shaped(Rc<Shape>, Vec<Value>),
+    Map(IndexMap<String, Value>),
+}
+
+impl Default for Entries {
+    fn default() -> Self {
+        Entries::Shaped(Shape::empty(), Vec::new())
+    }
+}
+
+impl Entries {
+    fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Entries::Shaped(shape, values) => shape.position(key).map(|position| &values[position]),
+            Entries::Map(map) => map.get(key),
+        }
+    }
+    
+    fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        match self {
+            Entries::Shaped(shape, values) => shape.position(key).map(|position| &mut values[position]),
+            Entries::Map(map) => map.get_mut(key),
+        }
+    }
+    
+    // The key and value at `position` in the order keys were added
+    fn get_index(&self, position: usize) -> Option<(&String, &Value)> {
+        match self {
+            Entries::Shaped(shape, values) => shape.key(position).map(|key| (key, &values[position])),
+            Entries::Map(map) => map.get_index(position),
+        }
+    }
+    
+    fn keys(&self) -> impl Iterator<Item = &String> {
+        (0..self.len()).filter_map(|position| self.get_index(position)).map(|(key, _)| key)
+    }
+    
+    fn len(&self) -> usize {
+        match self {
+            Entries::Shaped(_, values) => values.len(),
+            Entries::Map(map) => map.len(),
+        }
+    }
+    
+    fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+        if let Some(existing) = self.get_mut(&key) {
+            return Some(std::mem::replace(existing, value));
+        }
+        if let Entries::Shaped(shape, values) = self
+            && array_index(&key).is_none()
+            && let Some(child) = shape.with_key(&key)
+        {
+            *shape = child;
+            values.push(value);
+            return None;
+        }
+        self.map_mut().insert(key, value)
+    }
+    
+    fn remove(&mut self, key: &str) -> Option<Value> {
+        self.get(key)?;
+        self.map_mut().shift_remove(key)
+    }
+    
+    // The entries as a map, which they become for good
+    fn map_mut(&mut self) -> &mut IndexMap<String, Value> {
+        if let Entries::Shaped(shape, values) = self {
+            let keys = (0..shape.len()).filter_map(|position| shape.key(position).cloned());
+            *self = Entries::Map(keys.zip(std::mem::take(values)).collect());
+        }
+        match self {
+            Entries::Map(map) => map,
+            Entries::Shaped(..) => unreachable!("entries were just made a map"),
+        }
+    }
+}
+
+// Engine state behind objects that are more than their properties. The
+// methods that work on the state, and properties that reflect it, are found
+// through the slot when the object has no property of that name.
+#[derive(Clone)]
+pub enum InternalSlot {
+    Promise(Rc<Promise>),
+    Collection(Rc<Collection>), // Map or Set
+    Proxy(Rc<Proxy>),
+    Iterator(Rc<RefCell<BuiltinIterator>>), // An iterator of a built-in iterable
+    ArrayBuffer(Rc<ArrayBuffer>),
+    TypedArray(Rc<TypedArray>),
+    DataView(Rc<DataView>),
+    #[cfg(feature = "regex")]
+    RegExp(Rc<RegExp>),
+    Console(Rc<ConsoleState>), // The console object, for console.memory
+}
+
+impl InternalSlot {
+    // `this` is the object holding the slot
+    pub(crate) fn method(&self, name: &str, this: &Value) -> Option<Value> {
+        match self {
+            InternalSlot::Promise(state) => promise::method(state, name),
+            InternalSlot::Collection(state) => collections::method(state, name, this),
+            InternalSlot::Proxy(_) => None, // The interpreter asks the proxy first
+            InternalSlot::Iterator(state) => iterator::method(state, name),
+            InternalSlot::ArrayBuffer(state) => state.property(name),
+            InternalSlot::TypedArray(state) => state.property(name),
+            InternalSlot::DataView(state) => state.property(name),
+            #[cfg(feature = "regex")]
+            InternalSlot::RegExp(state) => state.property(name),
+            InternalSlot::Console(state) => state.property(name),
+        }
+    }
+    
+    // Assigns a property that reflects the state, returning false when
+    // `name` is not one
+    pub(crate) fn set_property(&self, name: &str, value: &Value) -> bool {
+        match self {
+            InternalSlot::Promise(_) | InternalSlot::Collection(_) | InternalSlot::Proxy(_) | InternalSlot::Iterator(_) => false,
+            InternalSlot::ArrayBuffer(_) | InternalSlot::DataView(_) | InternalSlot::Console(_) => false,
+            InternalSlot::TypedArray(state) => state.set_property(name, value),
+            #[cfg(feature = "regex")]
+            InternalSlot::RegExp(state) => state.set_property(name, value),
+        }
+    }
+}
+
+impl PropertyMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // The properties of a callable object
+    pub fn callable(call: Rc<CallBehaviour>) -> Self {
+        PropertyMap {
+            call: Some(call),
+            ..Self::default()
+        }
+    }
+    
+    pub fn call(&self) -> Option<&Rc<CallBehaviour>> {
+        self.call.as_ref()
+    }
+    
+    // The properties of an object backed by engine state
+    pub fn with_slot(slot: InternalSlot) -> Self {
+        PropertyMap {
+            slot: Some(slot),
+            ..Self::default()
+        }
+    }
+    
+    pub fn slot(&self) -> Option<&InternalSlot> {
+        self.slot.as_ref()
+    }
+    
+    pub fn set_slot(&mut self, slot: Option<InternalSlot>) {
+        self.slot = slot;
+    }
+    
+    // An array holding `elements` at indices 0, 1, ...
+    pub fn array(elements: Vec<Value>) -> Self {
+        let length = elements.len();
+        PropertyMap {
+            entries: Entries::Map(elements.into_iter().enumerate().map(|(index, element)| (index.to_string(), element)).collect()),
+            array_length: Some(length),
+            ..Self::default()
+        }
+    }
+    
+    pub fn is_array(&self) -> bool {
+        self.array_length.is_some()
+    }
+    
+    pub fn array_length(&self) -> Option<usize> {
+        self.array_length
+    }
+    
+    // Shortening an array removes the elements past its new end. Does
+    // nothing to objects that are not arrays.
+    pub fn set_array_length(&mut self, length: usize) {
+        if self.array_length.is_none() {
+            return;
+        }
+        // Only maps hold array indices
+        if let Entries::Map(map) = &mut self.entries {
+            map.retain(|key, _| array_index(key).is_none_or(|index| (index as usize) < length));
+        }
+        self.array_length = Some(length);
+    }
+    
+    // A map without properties but of the same kind as this one: callable
+    // if this is, and an array of the same length if this is an array. For
+    // copying objects property by property.
+    pub fn empty_like(&self) -> Self {
+        PropertyMap {
+            call: self.call.clone(),
+            array_length: self.array_length,
+            ..Self::default()
+        }
+    }
+    
+    pub fn prototype(&self) -> Option<&Rc<RefCell<PropertyMap>>> {
+        self.prototype.as_ref()
+    }
+    
+    pub fn set_prototype(&mut self, prototype: Option<Rc<RefCell<PropertyMap>>>) {
+        self.prototype = prototype;
+    }
+    
+    // The value of `key` here or on the nearest prototype that has it
+    pub fn lookup(&self, key: &str) -> Option<Value> {
+        if let Some(value) = self.entries.get(key) {
+            return Some(value.clone());
+        }
+        let mut prototype = self.prototype.clone();
+        while let Some(object) = prototype {
+            let object = object.borrow();
+            if let Some(value) = object.entries.get(key) {
+                return Some(value.clone());
+            }
+            prototype = object.prototype.clone();
+        }
+        None
+    }
+    
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries.get(key)
+    }
+    
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.entries.get_mut(key)
+    }
+    
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entries.get(key).is_some()
+    }
+    
+    pub fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+        if let Some(length) = &mut self.array_length
+            && let Some(index) = array_index(&key)
+        {
+            *length = (*length).max(index as usize + 1);
+        }
+        self.entries.insert(key, value)
+    }
+    
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.entries.remove(key)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.len() == 0
+    }
+    
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+    
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+    
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.order().into_iter().filter_map(|index| self.entries.get_index(index))
+    }
+    
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.iter().map(|(key, _)| key)
+    }
+    
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.iter().map(|(_, value)| value)
+    }
+    
+    // Every property value, in no particular order
+    pub(crate) fn values_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_> {
+        match &mut self.entries {
+            Entries::Shaped(_, values) => Box::new(values.iter_mut()),
+            Entries::Map(map) => Box::new(map.values_mut()),
+        }
+    }
+    
+    // The shape of this object if it has one, for inline caches
+    pub(crate) fn shape(&self) -> Option<&Rc<Shape>> {
+        match &self.entries {
+            Entries::Shaped(shape, _) => Some(shape),
+            Entries::Map(_) => None,
+        }
+    }
+    
+    // The value at `position` in the shape of this object, for inline
+    // caches that found it has the shape they expect
+    pub(crate) fn value_at(&self, position: usize) -> Option<&Value> {
+        match &self.entries {
+            Entries::Shaped(_, values) => values.get(position),
+            Entries::Map(_) => None,
+        }
+    }
+    
+    pub(crate) fn value_at_mut(&mut self, position: usize) -> Option<&mut Value> {
+        match &mut self.entries {
+            Entries::Shaped(_, values) => values.get_mut(position),
+            Entries::Map(_) => None,
+        }
+    }
+    
+    // Positions in `entries` in enumeration order
+    fn order(&self) -> Vec<usize> {
+        let mut indices = Vec::new();
+        let mut others = Vec::with_capacity(self.entries.len());
+        for (position, key) in self.entries.keys().enumerate() {
+            match array_index(key) {
+                Some(index) => indices.push((index, position)),
+                None => others.push(position),
+            }
+        }
+        
+        if indices.is_empty() {
+            return others;
+        }
+        indices.sort_unstable();
+        indices.into_iter().map(|(_, position)| position).chain(others).collect()
+    }
+}
+
+// The global `Object`, with the functions for reading and setting
+// prototypes. Object literals start without a prototype, as if made with
+// Object.create(null), so Object.getPrototypeOf gives null for them and for
+// values that are not objects.
+pub(crate) fn object_namespace() -> Value {
+    let create = Rc::new(|arguments: &[Value]| -> Result<Value, JsEngineError> {
+        let prototype = prototype_argument(arguments.first())?;
+        let mut properties = PropertyMap::new();
+        properties.set_prototype(prototype);
+        Ok(Value::Object(Rc::new(RefCell::new(properties))))
+    });
+    
+    let get_prototype_of = Rc::new(|arguments: &[Value]| -> Result<Value, JsEngineError> {
+        match arguments.first() {
+            Some(Value::Object(object)) => Ok(object.borrow().prototype().map_or(Value::Null, |prototype| Value::Object(Rc::clone(prototype)))),
+            None | Some(Value::Null | Value::Undefined) => Err(JsEngineError::TypeError {
+                message: "Cannot convert undefined or null to object".to_string(),
+            }),
+            Some(_) => Ok(Value::Null),
+        }
+    });
+    
+    let set_prototype_of = Rc::new(|arguments: &[Value]| -> Result<Value, JsEngineError> {
+        let target = arguments.first().cloned().unwrap_or(Value::Undefined);
+        let prototype = prototype_argument(arguments.get(1))?;
+        let Value::Object(object) = &target else {
+            return match target {
+                Value::Null | Value::Undefined => Err(JsEngineError::TypeError {
+                    message: "Object.setPrototypeOf called on null or undefined".to_string(),
+                }),
+                _ => Ok(target),
+            };
+        };
+        
+        // A chain leading back to the object would make lookups loop
+        let mut link = prototype.clone();
+        while let Some(ancestor) = link {
+            if Rc::ptr_eq(&ancestor, object) {
+                return Err(JsEngineError::TypeError {
+                    message: "Cyclic __proto__ value".to_string(),
+                });
+            }
+            link = ancestor.borrow().prototype().cloned();
+        }
+        if object.borrow().is_frozen() {
+            return Err(JsEngineError::TypeError {
+                message: "#<Object> is not extensible".to_string(),
+            });
+        }
+        object.borrow_mut().set_prototype(prototype);
+        Ok(target)
+    });
+    
+    // The object's own keys in enumeration order; see PropertyMap
+    let keys = Rc::new(|arguments: &[Value]| -> Result<Value, JsEngineError> {
+        match arguments.first() {
+            Some(Value::Object(object)) => Ok(array_value(object.borrow().keys().map(|key| Value::String(JsString::from(key.as_str()))).collect())),
+            None | Some(Value::Null | Value::Undefined) => Err(JsEngineError::TypeError {
+                message: "Cannot convert undefined or null to object".to_string(),
+            }),
+            Some(_) => Ok(array_value(Vec::new())),
+        }
+    });
+    
+    let properties = PropertyMap::from([
+        ("create", Value::NativeFunction(create)),
+        ("keys", Value::NativeFunction(keys)),
+        ("getPrototypeOf", Value::NativeFunction(get_prototype_of)),
+        ("setPrototypeOf", Value::NativeFunction(set_prototype_of)),
+    ]);
+    Value::Object(Rc::new(RefCell::new(properties)))
+}
+
+// The global `Array`. Called with a single number it makes an array with
+// that many holes, as `new Array(n)` does, and otherwise an array of its
+// arguments, like Array.of. Array.from copies an iterable, or an object with
+// a length, passing each element and its index through the mapping function
+// when there is one.
+pub(crate) fn array_namespace() -> Value {
+    let mut array = PropertyMap::callable(Rc::new(|_, arguments| {
+        let [Value::Number(length)] = arguments else {
+            return Ok(array_value(arguments.to_vec()));
+        };
+        if length.fract() != 0.0 || !(0.0..4_294_967_296.0).contains(length) {
+            return Err(JsEngineError::throw("RangeError", "Invalid array length"));
+        }
+        let mut array = PropertyMap::array(Vec::new());
+        array.set_array_length(*length as usize);
+        Ok(Value::Object(Rc::new(RefCell::new(array))))
+    }));
+    
+    let is_array = Rc::new(|arguments: &[Value]| -> Result<Value, JsEngineError> {
+        Ok(Value::Boolean(matches!(arguments.first(), Some(Value::Object(object)) if object.borrow().is_array())))
+    });
+    
+    let of = Rc::new(|arguments: &[Value]| -> Result<Value, JsEngineError> { Ok(array_value(arguments.to_vec())) });
+    
+    let from = PropertyMap::callable(Rc::new(|interpreter, arguments| {
+        let items = arguments.first().cloned().unwrap_or(Value::Undefined);
+        let map = arguments.get(1).cloned().unwrap_or(Value::Undefined);
+        if !matches!(map, Value::Undefined) && !map.is_callable() {
+            return Err(JsEngineError::TypeError {
+                message: format!("{:?} is not a function", map),
+            });
+        }
+        
+        let iterable = match &items {
+            Value::Null | Value::Undefined => true,
+            _ => !matches!(interpreter.get_property(&items, ITERATOR)?, Value::Undefined),
+        };
+        let elements: Vec<Value> = match &items {
+            _ if iterable => ValueIterator::new(interpreter, &items)?.collect_values(interpreter)?,
+            Value::Object(object) => {
+                // An array-like: elements at the indices below its length
+                let object = object.borrow();
+                let length = to_number(&object.lookup("length").unwrap_or(Value::Undefined));
+                let length = if length.is_nan() { 0.0 } else { length.trunc().clamp(0.0, 4_294_967_295.0) };
+                (0..length as usize).map(|index| object.lookup(&index.to_string()).unwrap_or(Value::Undefined)).collect()
+            },
+            _ => Vec::new(),
+        };
+        if matches!(map, Value::Undefined) {
+            return Ok(array_value(elements));
+        }
+        
+        let mut mapped = Vec::with_capacity(elements.len());
+        for (index, element) in elements.into_iter().enumerate() {
+            mapped.push(interpreter.call_function(&map, &[element, Value::Number(index as f64)])?);
+        }
+        Ok(array_value(mapped))
+    }));
+    
+    let properties = [
+        ("isArray", Value::NativeFunction(is_array)),
+        ("of", Value::NativeFunction(of)),
+        ("from", Value::Object(Rc::new(RefCell::new(from)))),
+    ];
+    for (name, value) in properties {
+        array.insert(name.to_string(), value);
+    }
+    Value::Object(Rc::new(RefCell::new(array)))
+}
+
+fn array_value(elements: Vec<Value>) -> Value {
+    Value::Object(Rc::new(RefCell::new(PropertyMap::array(elements))))
+}
+
+// A prototype given to Object.create or Object.setPrototypeOf, which must
+// be an object or null
+fn prototype_argument(argument: Option<&Value>) -> Result<Option<Rc<RefCell<PropertyMap>>>, JsEngineError> {
+    match argument {
+        Some(Value::Object(object)) => Ok(Some(Rc::clone(object))),
+        Some(Value::Null) => Ok(None),
+        other => Err(JsEngineError::TypeError {
+            message: format!("Object prototype may only be an Object or null: {}", other.unwrap_or(&Value::Undefined)),
+        }),
+    }
+}
+
+// The number a key names if it is an array index: a canonical decimal
+// integer below 2^32 - 1, so "01" and "-1" are ordinary keys
+fn array_index(key: &str) -> Option<u32> {
+    if key.is_empty() || (key.len() > 1 && key.starts_with('0')) || !key.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    key.parse::<u32>().ok().filter(|index| *index != u32::MAX)
+}
+
+impl<K: Into<String>> FromIterator<(K, Value)> for PropertyMap {
+    fn from_iter<I: IntoIterator<Item = (K, Value)>>(properties: I) -> Self {
+        let mut map = PropertyMap::new();
+        for (key, value) in properties {
+            map.insert(key.into(), value);
+        }
+        map
+    }
+}
+
+impl<K: Into<String>, const N: usize> From<[(K, Value); N]> for PropertyMap {
+    fn from(properties: [(K, Value); N]) -> Self {
+        properties.into_iter().collect()
+    }
+}
@@ -1,72 +1,129 @@
 // src/parser.rs
+use crate::ast::{BinaryOp, CatchClause, DeclarationKind, Expr, FunctionKind, Parameter, Pattern, PropertyKey, Stmt, SwitchClause, UnaryOp};
 use crate::error::JsEngineError;
-use crate::lexer::{Token, TokenType};
-// use std::rc::Rc;
+use crate::lexer::{Span, Token, TokenType};
+use crate::number::number_to_string;
+use crate::shape::PropertyCache;
+use crate::syntax::SyntaxExtensions;
+use std::rc::Rc;
 
-// Define our AST nodes
-#[derive(Debug, Clone)]
-pub enum Expr {
-    // Literal values
-    Number(f64),
-    String(String),
-    Boolean(bool),
-    Null,
-    
-    // Variables
-    Variable(String),
-    
-    // Operations
-    Binary(Box<Expr>, BinaryOp, Box<Expr>),
-    Unary(UnaryOp, Box<Expr>),
-    
-    // Control flow
-    Conditional(Box<Expr>, Box<Expr>, Option<Box<Expr>>), // condition, then-branch, else-branch
-    
-    // Variables and functions
-    Assign(String, Box<Expr>),
-    Call(Box<Expr>, Vec<Expr>),
-    
-    // Function definition
-    Function(Vec<String>, Box<Stmt>),
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    Left, Right,
 }
 
-#[derive(Debug, Clone)]
-pub enum Stmt {
-    Expression(Expr),
-    Declaration(String, Option<Expr>), // var name = expr
-    Block(Vec<Stmt>),
-    If(Expr, Box<Stmt>, Option<Box<Stmt>>),
-    While(Expr, Box<Stmt>),
-    Return(Option<Expr>),
-    Function(String, Vec<String>, Box<Stmt>), // name, params, body
+// Binary operator table: the operator, precedence (higher binds tighter) and
+// associativity for each infix token, or None if the token is not one
+pub fn binary_operator(token_type: &TokenType) -> Option<(BinaryOp, u8, Associativity)> {
+    let entry = match token_type {
+        // Below || and &&, which it cannot be mixed with unparenthesized
+        TokenType::QuestionQuestion => (BinaryOp::Nullish, 0, Associativity::Left),
+        TokenType::Or => (BinaryOp::Or, 1, Associativity::Left),
+        TokenType::And => (BinaryOp::And, 2, Associativity::Left),
+        TokenType::EqualEqual => (BinaryOp::Equal, 3, Associativity::Left),
+        TokenType::BangEqual => (BinaryOp::NotEqual, 3, Associativity::Left),
+        TokenType::Less => (BinaryOp::Less, 4, Associativity::Left),
+        TokenType::LessEqual => (BinaryOp::LessEqual, 4, Associativity::Left),
+        TokenType::Greater => (BinaryOp::Greater, 4, Associativity::Left),
+        TokenType::GreaterEqual => (BinaryOp::GreaterEqual, 4, Associativity::Left),
+        TokenType::Plus => (BinaryOp::Add, 5, Associativity::Left),
+        TokenType::Minus => (BinaryOp::Subtract, 5, Associativity::Left),
+        TokenType::Star => (BinaryOp::Multiply, 6, Associativity::Left),
+        TokenType::Slash => (BinaryOp::Divide, 6, Associativity::Left),
+        TokenType::Percent => (BinaryOp::Remainder, 6, Associativity::Left),
+        TokenType::StarStar => (BinaryOp::Exponent, 7, Associativity::Right),
+        _ => return None,
+    };
+    
+    Some(entry)
 }
 
-#[derive(Debug, Clone)]
-pub enum BinaryOp {
-    Add, Subtract, Multiply, Divide,
-    Equal, NotEqual, Less, LessEqual, Greater, GreaterEqual,
-    And, Or,
+// The two families of short-circuit operators. An operand of one cannot be
+// an unparenthesized expression built from the other.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ShortCircuit {
+    Nullish, AndOr,
 }
 
-#[derive(Debug, Clone)]
-pub enum UnaryOp {
-    Negate, Not,
+impl ShortCircuit {
+    fn of(operator: &BinaryOp) -> Option<ShortCircuit> {
+        match operator {
+            BinaryOp::Nullish => Some(ShortCircuit::Nullish),
+            BinaryOp::And | BinaryOp::Or => Some(ShortCircuit::AndOr),
+            _ => None,
+        }
+    }
 }
 
+const MIXED_SHORT_CIRCUIT: &str = "'??' cannot be mixed with '&&' or '||' without parentheses.";
+
+// Precedence of the TypeScript `as` operator, the same as comparisons
+const AS_PRECEDENCE: u8 = 4;
+
+// Default limit on syntactic nesting: nested statements, parenthesized and
+// unary expressions, and chains of operators or calls. Deeper input is
+// rejected so that parsing, resolving and running it cannot overflow the stack.
+pub const DEFAULT_MAX_NESTING: usize = 512;
+
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    typescript: bool, // Accept and discard TypeScript type syntax
+    extensions: SyntaxExtensions,
+    depth: usize,
+    max_nesting: usize,
+    breakable: usize, // Loops and switches around the current statement in this function
+    in_async: bool, // Whether `await` is an operator here, as it is in async functions
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
+    pub fn new(mut tokens: Vec<Token>) -> Self {
+        // The parser relies on the token stream ending with Eof
+        if !matches!(tokens.last(), Some(token) if token.token_type == TokenType::Eof) {
+            let (line, column, end) = tokens.last().map_or((1, 1, 0), |token| (token.line, token.column, token.span.end));
+            tokens.push(Token {
+                token_type: TokenType::Eof,
+                lexeme: String::new(),
+                line,
+                column,
+                span: Span { start: end, end },
+            });
+        }
+        
         Parser {
             tokens,
             current: 0,
+            typescript: false,
+            extensions: SyntaxExtensions::default(),
+            depth: 0,
+            max_nesting: DEFAULT_MAX_NESTING,
+            breakable: 0,
+            in_async: false,
         }
     }
     
+    pub fn with_max_nesting(mut self, max_nesting: usize) -> Self {
+        self.max_nesting = max_nesting;
+        self
+    }
+    
+    // Enables TypeScript mode: type annotations, `as` casts, interfaces and
+    // type aliases are parsed and thrown away, so simple .ts files run as-is
+    pub fn with_typescript(mut self, enabled: bool) -> Self {
+        self.typescript = enabled;
+        self
+    }
+    
+    // Consults the given syntax extensions before the built-in grammar
+    pub fn with_extensions(mut self, extensions: &SyntaxExtensions) -> Self {
+        self.extensions = extensions.clone();
+        self
+    }
+    
     pub fn parse(&mut self) -> Result<Vec<Stmt>, JsEngineError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("parse", tokens = self.tokens.len()).entered();
+        
         let mut statements = Vec::new();
         
         while !self.is_at_end() {
@@ -76,18 +133,54 @@ impl Parser {
         Ok(statements)
     }
     
-    fn declaration(&mut self) -> Result<Stmt, JsEngineError> {
-        if self.match_token(&[TokenType::Var, TokenType::Let, TokenType::Const]) {
-            return self.var_declaration();
+    // The parsing entry points and helpers below are public so that syntax
+    // extension handlers can parse the constructs they are embedded in
+    
+    pub fn declaration(&mut self) -> Result<Stmt, JsEngineError> {
+        if let Some(kind) = self.declaration_kind() {
+            return self.var_declaration(kind);
         } else if self.match_token(&[TokenType::Function]) {
-            return self.function_declaration();
+            let start = self.previous().span.start;
+            return self.function_declaration(start, FunctionKind::Function);
+        } else if self.at_async_function() {
+            let start = self.advance().span.start;
+            self.advance(); // Consume 'function'
+            return self.function_declaration(start, FunctionKind::AsyncFunction);
+        } else if self.typescript && self.check_next_identifier() {
+            if self.check_identifier("interface") {
+                return self.interface_declaration();
+            } else if self.check_identifier("type") {
+                return self.type_alias_declaration();
+            }
         }
         
         self.statement()
     }
     
-    fn var_declaration(&mut self) -> Result<Stmt, JsEngineError> {
+    // Consumes `var`, `let` or `const`
+    fn declaration_kind(&mut self) -> Option<DeclarationKind> {
+        let kind = match self.peek().token_type {
+            TokenType::Var => DeclarationKind::Var,
+            TokenType::Let => DeclarationKind::Let,
+            TokenType::Const => DeclarationKind::Const,
+            _ => return None,
+        };
+        self.advance();
+        Some(kind)
+    }
+    
+    fn var_declaration(&mut self, kind: DeclarationKind) -> Result<Stmt, JsEngineError> {
+        if self.match_token(&[TokenType::LeftBrace]) {
+            let pattern = self.object_pattern()?;
+            self.type_annotation()?;
+            self.consume(TokenType::Equal, "Missing initializer in destructuring declaration.")?;
+            let initializer = self.expression()?;
+            self.consume(TokenType::Semicolon, "Expected ';' after variable declaration.")?;
+            return Ok(Stmt::Destructure(pattern, initializer, kind));
+        }
+        
         // Expect an identifier
+        let span = self.peek().span;
         let name = match &self.peek().token_type {
             TokenType::Identifier(name) => name.clone(),
             _ => {
@@ -96,10 +189,13 @@ impl Parser {
         };
         
         self.advance(); // Consume the identifier
+        self.type_annotation()?;
         
         // Check for initialization
         let initializer = if self.match_token(&[TokenType::Equal]) {
             Some(self.expression()?)
+        } else if kind == DeclarationKind::Const {
+            return Err(self.error("Missing initializer in const declaration."));
         } else {
             None
         };
@@ -107,10 +203,63 @@ impl Parser {
         // Expect semicolon
         self.consume(TokenType::Semicolon, "Expected ';' after variable declaration.")?;
         
-        Ok(Stmt::Declaration(name, initializer))
+        Ok(Stmt::Declaration(name, initializer, span, kind))
     }
     
-    fn function_declaration(&mut self) -> Result<Stmt, JsEngineError> {
+    // { a, b: renamed, c = 1, d: { e } } after the opening brace
+    fn object_pattern(&mut self) -> Result<Pattern, JsEngineError> {
+        let mut properties = Vec::new();
+        
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            let span = self.peek().span;
+            let shorthand = match &self.peek().token_type {
+                TokenType::Identifier(name) => Some(name.clone()),
+                _ => None,
+            };
+            let key = self.literal_key("Expected property name in object pattern.")?;
+            
+            let target = if self.match_token(&[TokenType::Colon]) {
+                self.binding_target()?
+            } else if let Some(name) = shorthand {
+                Pattern::Identifier(name, span)
+            } else {
+                return Err(self.error("Expected ':' after property name."));
+            };
+            let default = if self.match_token(&[TokenType::Equal]) {
+                Some(self.assignment()?)
+            } else {
+                None
+            };
+            properties.push((key, target, default));
+            
+            if !self.match_token(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        
+        self.consume(TokenType::RightBrace, "Expected '}' after object pattern.")?;
+        Ok(Pattern::Object(properties))
+    }
+    
+    // A name or a nested object pattern
+    fn binding_target(&mut self) -> Result<Pattern, JsEngineError> {
+        if self.match_token(&[TokenType::LeftBrace]) {
+            return self.nested(Self::object_pattern);
+        }
+        
+        let span = self.peek().span;
+        match &self.peek().token_type {
+            TokenType::Identifier(name) => {
+                let name = name.clone();
+                self.advance();
+                Ok(Pattern::Identifier(name, span))
+            },
+            _ => Err(self.error("Expected variable name.")),
+        }
+    }
+    
+    // After the 'function' keyword; `start` is where the declaration begins
+    fn function_declaration(&mut self, start: usize, kind: FunctionKind) -> Result<Stmt, JsEngineError> {
         // Expect a function name
         let name = match &self.peek().token_type {
             TokenType::Identifier(name) => name.clone(),
@@ -120,11 +269,29 @@ impl Parser {
         };
         
         self.advance(); // Consume the function name
+        self.type_parameters()?;
         
         // Parse parameters
         self.consume(TokenType::LeftParen, "Expected '(' after function name.")?;
+        let (parameters, prologue) = self.parameters()?;
         
+        // Parse function body
+        self.consume(TokenType::LeftBrace, "Expected '{' before function body.")?;
+        let body = Rc::new(with_prologue(prologue, self.function_body(kind)?));
+        let span = Span { start, end: self.previous().span.end };
+        
+        Ok(Stmt::Function(name, parameters, body, span, kind))
+    }
+    
+    // Parses a parameter list up to and including the closing parenthesis,
+    // followed by the return type annotation if there is one.
+    //
+    // A destructured parameter is received under a name no script can use
+    // and unpacked by a declaration that goes at the start of the body,
+    // which is returned alongside the names.
+    fn parameters(&mut self) -> Result<(Vec<Parameter>, Vec<Stmt>), JsEngineError> {
         let mut parameters = Vec::new();
+        let mut prologue = Vec::new();
         
         if !self.check(&TokenType::RightParen) {
             loop {
@@ -134,14 +301,28 @@ impl Parser {
                 
                 match &self.peek().token_type {
                     TokenType::Identifier(name) => {
-                        parameters.push(name.clone());
+                        parameters.push((Rc::from(name.as_str()), self.peek().span));
                         self.advance();
                     }
+                    TokenType::LeftBrace => {
+                        let span = self.advance().span;
+                        let name = format!("{{parameter {}}}", parameters.len());
+                        let pattern = self.object_pattern()?;
+                        // Bound like the other parameters, in the function's scope
+                        prologue.push(Stmt::Destructure(pattern, Expr::Variable(name.clone(), span), DeclarationKind::Var));
+                        parameters.push((Rc::from(name), span));
+                    }
                     _ => {
                         return Err(self.error("Expected parameter name."));
                     }
                 }
                 
+                // Optional parameters are undefined when omitted anyway
+                if self.typescript {
+                    self.match_token(&[TokenType::Question]);
+                }
+                self.type_annotation()?;
+                
                 if !self.match_token(&[TokenType::Comma]) {
                     break;
                 }
@@ -149,19 +330,64 @@ impl Parser {
         }
         
         self.consume(TokenType::RightParen, "Expected ')' after parameters.")?;
+        self.type_annotation()?;
         
-        // Parse function body
-        self.consume(TokenType::LeftBrace, "Expected '{' before function body.")?;
-        let body = Box::new(self.block()?);
+        Ok((parameters, prologue))
+    }
+    
+    // TypeScript declarations that only exist at compile time. They leave an
+    // empty block behind, which has no effect when run.
+    
+    fn interface_declaration(&mut self) -> Result<Stmt, JsEngineError> {
+        self.advance(); // Consume 'interface'
         
-        Ok(Stmt::Function(name, parameters, body))
+        // Skip the name, type parameters and any `extends` clause
+        while !self.check(&TokenType::LeftBrace) && !self.is_at_end() {
+            self.advance();
+        }
+        
+        self.skip_balanced(TokenType::LeftBrace, TokenType::RightBrace)?;
+        Ok(Stmt::Block(Vec::new()))
     }
     
-    fn statement(&mut self) -> Result<Stmt, JsEngineError> {
+    fn type_alias_declaration(&mut self) -> Result<Stmt, JsEngineError> {
+        self.advance(); // Consume 'type'
+        self.advance(); // Consume the alias name
+        self.type_parameters()?;
+        
+        self.consume(TokenType::Equal, "Expected '=' after type alias name.")?;
+        self.skip_type()?;
+        self.consume(TokenType::Semicolon, "Expected ';' after type alias.")?;
+        
+        Ok(Stmt::Block(Vec::new()))
+    }
+    
+    pub fn statement(&mut self) -> Result<Stmt, JsEngineError> {
+        self.nested(Parser::statement_inner)
+    }
+    
+    fn statement_inner(&mut self) -> Result<Stmt, JsEngineError> {
+        if let TokenType::Identifier(name) = &self.peek().token_type
+            && let Some(handler) = self.extensions.statement(name)
+        {
+            self.advance(); // Consume the keyword
+            return handler(self);
+        }
+        
         if self.match_token(&[TokenType::If]) {
             self.if_statement()
         } else if self.match_token(&[TokenType::While]) {
             self.while_statement()
+        } else if self.match_token(&[TokenType::For]) {
+            self.for_statement()
+        } else if self.match_token(&[TokenType::Switch]) {
+            self.switch_statement()
+        } else if self.match_token(&[TokenType::Break]) {
+            self.break_statement()
+        } else if self.match_token(&[TokenType::Try]) {
+            self.try_statement()
+        } else if self.match_token(&[TokenType::Throw]) {
+            self.throw_statement()
         } else if self.match_token(&[TokenType::Return]) {
             self.return_statement()
         } else if self.match_token(&[TokenType::LeftBrace]) {
@@ -192,11 +418,190 @@ impl Parser {
         let condition = self.expression()?;
         self.consume(TokenType::RightParen, "Expected ')' after while condition.")?;
         
-        let body = Box::new(self.statement()?);
+        let body = Box::new(self.loop_body()?);
         
         Ok(Stmt::While(condition, body))
     }
     
+    fn for_statement(&mut self) -> Result<Stmt, JsEngineError> {
+        self.consume(TokenType::LeftParen, "Expected '(' after 'for'.")?;
+        
+        if let Some(declared) = self.for_of_head() {
+            return self.for_of_statement(declared);
+        }
+        
+        // The initializer consumes its own semicolon
+        let mut per_iteration = false;
+        let initializer = if self.match_token(&[TokenType::Semicolon]) {
+            None
+        } else if let Some(kind) = self.declaration_kind() {
+            per_iteration = kind != DeclarationKind::Var;
+            Some(Box::new(self.var_declaration(kind)?))
+        } else {
+            Some(Box::new(self.expression_statement()?))
+        };
+        
+        let condition = if !self.check(&TokenType::Semicolon) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.consume(TokenType::Semicolon, "Expected ';' after loop condition.")?;
+        
+        let update = if !self.check(&TokenType::RightParen) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.consume(TokenType::RightParen, "Expected ')' after for clauses.")?;
+        
+        let body = Box::new(self.loop_body()?);
+        
+        Ok(Stmt::For(initializer, condition, update, body, per_iteration))
+    }
+    
+    // Whether the loop head reads `[let|const|var] name of`, and if so
+    // whether it declares the name
+    fn for_of_head(&self) -> Option<bool> {
+        let token_type = |offset: usize| self.tokens.get(self.current + offset).map(|token| &token.token_type);
+        let is_of = |offset: usize| matches!(token_type(offset), Some(TokenType::Identifier(name)) if name == "of");
+        
+        match token_type(0) {
+            Some(TokenType::Let | TokenType::Const | TokenType::Var) => (self.check_next_identifier() && is_of(2)).then_some(true),
+            Some(TokenType::Identifier(_)) => is_of(1).then_some(false),
+            _ => None,
+        }
+    }
+    
+    fn for_of_statement(&mut self, declared: bool) -> Result<Stmt, JsEngineError> {
+        if declared {
+            self.advance(); // Consume let, const or var
+        }
+        
+        let span = self.peek().span;
+        let name = self.advance().lexeme.clone();
+        self.advance(); // Consume 'of'
+        
+        let iterable = self.assignment()?;
+        self.consume(TokenType::RightParen, "Expected ')' after for-of head.")?;
+        let body = Box::new(self.loop_body()?);
+        
+        Ok(Stmt::ForOf(name, iterable, body, span, declared))
+    }
+    
+    fn loop_body(&mut self) -> Result<Stmt, JsEngineError> {
+        self.breakable += 1;
+        let body = self.statement();
+        self.breakable -= 1;
+        body
+    }
+    
+    // Clauses run from the first case that matches, or from the default
+    // clause if none does, until a break
+    fn switch_statement(&mut self) -> Result<Stmt, JsEngineError> {
+        self.consume(TokenType::LeftParen, "Expected '(' after 'switch'.")?;
+        let discriminant = self.expression()?;
+        self.consume(TokenType::RightParen, "Expected ')' after switch value.")?;
+        self.consume(TokenType::LeftBrace, "Expected '{' before switch cases.")?;
+        
+        self.breakable += 1;
+        let clauses = self.switch_clauses();
+        self.breakable -= 1;
+        
+        Ok(Stmt::Switch(discriminant, clauses?))
+    }
+    
+    fn switch_clauses(&mut self) -> Result<Vec<SwitchClause>, JsEngineError> {
+        let mut clauses = Vec::new();
+        let mut has_default = false;
+        
+        while !self.match_token(&[TokenType::RightBrace]) {
+            let test = if self.match_token(&[TokenType::Case]) {
+                Some(self.expression()?)
+            } else if self.match_token(&[TokenType::Default]) {
+                if has_default {
+                    return Err(self.error("More than one default clause in switch statement."));
+                }
+                has_default = true;
+                None
+            } else {
+                return Err(self.error("Expected 'case' or 'default'."));
+            };
+            self.consume(TokenType::Colon, "Expected ':' after case.")?;
+            
+            let mut body = Vec::new();
+            while !self.check(&TokenType::Case) && !self.check(&TokenType::Default) && !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+                body.push(self.declaration()?);
+            }
+            clauses.push((test, body));
+        }
+        
+        Ok(clauses)
+    }
+    
+    fn break_statement(&mut self) -> Result<Stmt, JsEngineError> {
+        if self.breakable == 0 {
+            return Err(self.error("Illegal break statement."));
+        }
+        self.consume(TokenType::Semicolon, "Expected ';' after 'break'.")?;
+        Ok(Stmt::Break)
+    }
+    
+    fn try_statement(&mut self) -> Result<Stmt, JsEngineError> {
+        self.consume(TokenType::LeftBrace, "Expected '{' after 'try'.")?;
+        let block = Box::new(self.block()?);
+        
+        let handler = if self.match_token(&[TokenType::Catch]) {
+            Some(self.catch_clause()?)
+        } else {
+            None
+        };
+        let finalizer = if self.match_token(&[TokenType::Finally]) {
+            self.consume(TokenType::LeftBrace, "Expected '{' after 'finally'.")?;
+            Some(Box::new(self.block()?))
+        } else {
+            None
+        };
+        
+        if handler.is_none() && finalizer.is_none() {
+            return Err(self.error("Missing catch or finally after try."));
+        }
+        Ok(Stmt::Try(block, handler, finalizer))
+    }
+    
+    // `(name) { ... }`, `({ message }) { ... }` or just `{ ... }` after
+    // 'catch'
+    fn catch_clause(&mut self) -> Result<CatchClause, JsEngineError> {
+        let parameter = if self.match_token(&[TokenType::LeftParen]) {
+            let parameter = self.binding_target()?;
+            self.type_annotation()?;
+            self.consume(TokenType::RightParen, "Expected ')' after catch parameter.")?;
+            Some(parameter)
+        } else {
+            None
+        };
+        
+        self.consume(TokenType::LeftBrace, "Expected '{' before catch block.")?;
+        Ok((parameter, Box::new(self.block()?)))
+    }
+    
+    fn throw_statement(&mut self) -> Result<Stmt, JsEngineError> {
+        let value = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expected ';' after thrown value.")?;
+        Ok(Stmt::Throw(value))
+    }
+    
+    // A function's body starts outside any loop, whatever encloses the
+    // function itself
+    fn function_body(&mut self, kind: FunctionKind) -> Result<Stmt, JsEngineError> {
+        let breakable = std::mem::replace(&mut self.breakable, 0);
+        let in_async = std::mem::replace(&mut self.in_async, kind.is_async());
+        let body = self.block();
+        self.breakable = breakable;
+        self.in_async = in_async;
+        body
+    }
+    
     fn return_statement(&mut self) -> Result<Stmt, JsEngineError> {
         let value = if !self.check(&TokenType::Semicolon) {
             Some(self.expression()?)
@@ -209,7 +614,8 @@ impl Parser {
         Ok(Stmt::Return(value))
     }
     
-    fn block(&mut self) -> Result<Stmt, JsEngineError> {
+    // Parses the statements of a block whose '{' has been consumed
+    pub fn block(&mut self) -> Result<Stmt, JsEngineError> {
         let mut statements = Vec::new();
         
         while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
@@ -228,151 +634,371 @@ impl Parser {
         Ok(Stmt::Expression(expr))
     }
     
-    fn expression(&mut self) -> Result<Expr, JsEngineError> {
+    pub fn expression(&mut self) -> Result<Expr, JsEngineError> {
         self.assignment()
     }
     
     fn assignment(&mut self) -> Result<Expr, JsEngineError> {
-        let expr = self.or()?;
+        self.nested(Parser::assignment_inner)
+    }
+    
+    fn assignment_inner(&mut self) -> Result<Expr, JsEngineError> {
+        let expr = self.conditional()?;
         
         if self.match_token(&[TokenType::Equal]) {
             let value = self.assignment()?;
             
-            if let Expr::Variable(name) = expr {
-                return Ok(Expr::Assign(name, Box::new(value)));
+            return match expr {
+                Expr::Variable(name, span) => Ok(Expr::Assign(name, Box::new(value), span)),
+                Expr::Get(object, name, cache) => Ok(Expr::Set(object, name, Box::new(value), cache)),
+                Expr::Index(object, key) => Ok(Expr::SetIndex(object, key, Box::new(value))),
+                _ => Err(self.error("Invalid assignment target.")),
+            };
+        }
+        
+        if self.match_token(&[TokenType::StarStarEqual]) {
+            if !matches!(expr, Expr::Variable(..) | Expr::Get(..) | Expr::Index(..)) {
+                return Err(self.error("Invalid assignment target."));
             }
-            
-            return Err(self.error("Invalid assignment target."));
+            let value = self.assignment()?;
+            return Ok(Expr::CompoundAssign(Box::new(expr), BinaryOp::Exponent, Box::new(value)));
         }
         
         Ok(expr)
     }
     
-    fn or(&mut self) -> Result<Expr, JsEngineError> {
-        let mut expr = self.and()?;
-        
-        while self.match_token(&[TokenType::Or]) {
-            let right = self.and()?;
-            expr = Expr::Binary(Box::new(expr), BinaryOp::Or, Box::new(right));
+    // condition ? then : else, binding more loosely than any binary operator
+    fn conditional(&mut self) -> Result<Expr, JsEngineError> {
+        let condition = self.binary(0)?;
+        if !self.match_token(&[TokenType::Question]) {
+            return Ok(condition);
         }
         
-        Ok(expr)
+        let then_branch = self.assignment()?;
+        self.consume(TokenType::Colon, "Expected ':' in conditional expression.")?;
+        let else_branch = self.assignment()?;
+        Ok(Expr::Conditional(Box::new(condition), Box::new(then_branch), Some(Box::new(else_branch))))
     }
     
-    fn and(&mut self) -> Result<Expr, JsEngineError> {
-        let mut expr = self.equality()?;
-        
-        while self.match_token(&[TokenType::And]) {
-            let right = self.equality()?;
-            expr = Expr::Binary(Box::new(expr), BinaryOp::And, Box::new(right));
-        }
-        
-        Ok(expr)
+    // Precedence climbing over the binary operator table: parses a unary
+    // operand, then keeps folding in operators that bind at least as tightly
+    // as `min_precedence`
+    pub fn binary(&mut self, min_precedence: u8) -> Result<Expr, JsEngineError> {
+        self.binary_inner(min_precedence, None)
     }
     
-    fn equality(&mut self) -> Result<Expr, JsEngineError> {
-        let mut expr = self.comparison()?;
+    // `short_circuit` is the kind of short-circuit operator the expression
+    // is the right operand of, if any
+    fn binary_inner(&mut self, min_precedence: u8, mut short_circuit: Option<ShortCircuit>) -> Result<Expr, JsEngineError> {
+        let negated = self.check(&TokenType::Minus) || self.check(&TokenType::Bang);
+        let mut expr = self.unary()?;
         
-        while self.match_token(&[TokenType::BangEqual, TokenType::EqualEqual]) {
-            let operator = match self.previous().token_type {
-                TokenType::BangEqual => BinaryOp::NotEqual,
-                TokenType::EqualEqual => BinaryOp::Equal,
-                _ => unreachable!(),
+        // Each operator folded in nests the expression parsed so far one
+        // level deeper
+        let mut folded = 0;
+        
+        loop {
+            if let Some((precedence, handler)) = self.extensions.infix(&self.peek().token_type) {
+                if precedence < min_precedence {
+                    break;
+                }
+                
+                self.fold(&mut folded)?;
+                self.advance(); // Consume the operator
+                expr = handler(self, expr)?;
+                continue;
+            }
+            
+            // `expr as Type` binds like a comparison and leaves the value as is
+            if self.typescript && self.check_identifier("as") {
+                if AS_PRECEDENCE < min_precedence {
+                    break;
+                }
+                
+                self.advance(); // Consume 'as'
+                self.skip_type()?;
+                continue;
+            }
+            
+            let Some((operator, precedence, associativity)) = binary_operator(&self.peek().token_type) else {
+                break;
             };
             
-            let right = self.comparison()?;
+            if precedence < min_precedence {
+                break;
+            }
+            
+            // -2 ** 2 could mean either (-2) ** 2 or -(2 ** 2)
+            if matches!(operator, BinaryOp::Exponent) && negated && folded == 0 {
+                return Err(self.error("Unary operator used immediately before exponentiation expression. Parenthesis must be used to disambiguate operator precedence."));
+            }
+            
+            // a ?? b || c could mean either (a ?? b) || c or a ?? (b || c)
+            let kind = ShortCircuit::of(&operator);
+            if kind.is_some() && short_circuit.is_some() && kind != short_circuit {
+                return Err(self.error(MIXED_SHORT_CIRCUIT));
+            }
+            
+            self.fold(&mut folded)?;
+            self.advance(); // Consume the operator
+            
+            let next_precedence = match associativity {
+                Associativity::Left => precedence + 1,
+                Associativity::Right => precedence,
+            };
+            
+            let right = self.binary_inner(next_precedence, kind)?;
             expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
+            short_circuit = kind.or(short_circuit);
         }
         
         Ok(expr)
     }
     
-    fn comparison(&mut self) -> Result<Expr, JsEngineError> {
-        let mut expr = self.term()?;
+    fn unary(&mut self) -> Result<Expr, JsEngineError> {
+        self.nested(Parser::unary_inner)
+    }
+    
+    fn unary_inner(&mut self) -> Result<Expr, JsEngineError> {
+        // Outside async functions `await` is an ordinary name
+        if self.in_async && self.check_identifier("await") {
+            self.advance();
+            let operand = self.unary()?;
+            return Ok(Expr::Await(Box::new(operand)));
+        }
         
-        while self.match_token(&[
-            TokenType::Greater, TokenType::GreaterEqual,
-            TokenType::Less, TokenType::LessEqual,
-        ]) {
+        // `void` is reserved in JavaScript, so it is always the operator
+        if self.check_identifier("void") {
+            self.advance();
+            let operand = self.unary()?;
+            return Ok(Expr::Unary(UnaryOp::Void, Box::new(operand)));
+        }
+        
+        if self.match_token(&[TokenType::Bang, TokenType::Minus, TokenType::Plus]) {
             let operator = match self.previous().token_type {
-                TokenType::Greater => BinaryOp::Greater,
-                TokenType::GreaterEqual => BinaryOp::GreaterEqual,
-                TokenType::Less => BinaryOp::Less,
-                TokenType::LessEqual => BinaryOp::LessEqual,
+                TokenType::Bang => UnaryOp::Not,
+                TokenType::Minus => UnaryOp::Negate,
+                TokenType::Plus => UnaryOp::Plus,
                 _ => unreachable!(),
             };
             
-            let right = self.term()?;
-            expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
+            let right = self.unary()?;
+            return Ok(Expr::Unary(operator, Box::new(right)));
         }
         
-        Ok(expr)
+        self.call()
     }
     
-    fn term(&mut self) -> Result<Expr, JsEngineError> {
-        let mut expr = self.factor()?;
+    fn call(&mut self) -> Result<Expr, JsEngineError> {
+        let start = self.peek().span.start;
+        let mut calls = 0;
+        let mut expr = if self.check_identifier("new") {
+            self.new_expression(&mut calls)?
+        } else {
+            self.primary()?
+        };
+        let mut optional = false;
         
-        while self.match_token(&[TokenType::Minus, TokenType::Plus]) {
-            let operator = match self.previous().token_type {
-                TokenType::Minus => BinaryOp::Subtract,
-                TokenType::Plus => BinaryOp::Add,
-                _ => unreachable!(),
-            };
-            
-            let right = self.factor()?;
-            expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
+        loop {
+            // `?.` is followed by a name, `[key]` or `(arguments)`, and the
+            // rest of the chain is skipped when the base is null or undefined
+            if self.match_token(&[TokenType::QuestionDot]) {
+                optional = true;
+                expr = Expr::Optional(Box::new(expr));
+                if self.check(&TokenType::LeftParen) || self.check(&TokenType::LeftBracket) {
+                    continue;
+                }
+                self.fold(&mut calls)?;
+                let name = self.property_name()?;
+                expr = Expr::Get(Box::new(expr), name, PropertyCache::default());
+            } else if self.match_token(&[TokenType::LeftParen]) {
+                self.fold(&mut calls)?;
+                expr = self.finish_call(expr, start)?;
+            } else if self.match_token(&[TokenType::Dot]) {
+                self.fold(&mut calls)?;
+                let name = self.property_name()?;
+                expr = Expr::Get(Box::new(expr), name, PropertyCache::default());
+            } else if self.match_token(&[TokenType::LeftBracket]) {
+                self.fold(&mut calls)?;
+                let key = self.expression()?;
+                self.consume(TokenType::RightBracket, "Expected ']' after property key.")?;
+                expr = Expr::Index(Box::new(expr), Box::new(key));
+            } else {
+                break;
+            }
         }
         
+        if optional {
+            expr = Expr::Chain(Box::new(expr));
+        }
         Ok(expr)
     }
     
-    fn factor(&mut self) -> Result<Expr, JsEngineError> {
-        let mut expr = self.unary()?;
+    // `new` followed by the constructor, a name with any property accesses
+    // after it, and its arguments, which may be left out: `new Map`. As in
+    // JavaScript, `new` is reserved and so always the operator.
+    fn new_expression(&mut self, calls: &mut usize) -> Result<Expr, JsEngineError> {
+        let start = self.advance().span.start; // Consume 'new'
+        let mut constructor = self.primary()?;
+        loop {
+            if self.match_token(&[TokenType::Dot]) {
+                self.fold(calls)?;
+                let name = self.property_name()?;
+                constructor = Expr::Get(Box::new(constructor), name, PropertyCache::default());
+            } else if self.match_token(&[TokenType::LeftBracket]) {
+                self.fold(calls)?;
+                let key = self.expression()?;
+                self.consume(TokenType::RightBracket, "Expected ']' after property key.")?;
+                constructor = Expr::Index(Box::new(constructor), Box::new(key));
+            } else {
+                break;
+            }
+        }
         
-        while self.match_token(&[TokenType::Slash, TokenType::Star]) {
-            let operator = match self.previous().token_type {
-                TokenType::Slash => BinaryOp::Divide,
-                TokenType::Star => BinaryOp::Multiply,
-                _ => unreachable!(),
-            };
-            
-            let right = self.unary()?;
-            expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
+        self.fold(calls)?;
+        let (arguments, end) = if self.match_token(&[TokenType::LeftParen]) {
+            self.arguments()?
+        } else {
+            (Vec::new(), self.previous().span.end)
+        };
+        Ok(Expr::New(Box::new(constructor), arguments, Span { start, end }))
+    }
+    
+    // The name after a dot, which may also be a keyword (`promise.catch`)
+    fn property_name(&mut self) -> Result<Rc<str>, JsEngineError> {
+        if !self.at_name() {
+            return Err(self.error("Expected property name after '.'."));
         }
         
-        Ok(expr)
+        Ok(Rc::from(self.advance().lexeme.as_str()))
     }
     
-    fn unary(&mut self) -> Result<Expr, JsEngineError> {
-        if self.match_token(&[TokenType::Bang, TokenType::Minus]) {
-            let operator = match self.previous().token_type {
-                TokenType::Bang => UnaryOp::Not,
-                TokenType::Minus => UnaryOp::Negate,
-                _ => unreachable!(),
-            };
+    fn at_name(&self) -> bool {
+        let token = self.peek();
+        match token.token_type {
+            TokenType::Identifier(_) => true,
+            TokenType::String(_) | TokenType::Number(_) | TokenType::RegExp(..) | TokenType::Operator(_) => false,
+            _ => token.lexeme.starts_with(|c: char| c.is_alphabetic()),
+        }
+    }
+    
+    // [element, ...] after the opening bracket
+    fn array_literal(&mut self) -> Result<Expr, JsEngineError> {
+        let mut elements = Vec::new();
+        
+        while !self.check(&TokenType::RightBracket) && !self.is_at_end() {
+            elements.push(self.spread_or(Self::assignment)?);
             
-            let right = self.unary()?;
-            return Ok(Expr::Unary(operator, Box::new(right)));
+            if !self.match_token(&[TokenType::Comma]) {
+                break;
+            }
         }
         
-        self.call()
+        self.consume(TokenType::RightBracket, "Expected ']' after array elements.")?;
+        Ok(Expr::Array(elements))
     }
     
-    fn call(&mut self) -> Result<Expr, JsEngineError> {
-        let mut expr = self.primary()?;
+    // { name: value, "quoted": value, 1: value, } after the opening brace.
+    // Numeric keys name the property their number prints as.
+    fn object_literal(&mut self) -> Result<Expr, JsEngineError> {
+        let mut properties = Vec::new();
         
-        loop {
-            if self.match_token(&[TokenType::LeftParen]) {
-                expr = self.finish_call(expr)?;
-            } else {
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            properties.push(self.object_property()?);
+            
+            if !self.match_token(&[TokenType::Comma]) {
                 break;
             }
         }
         
-        Ok(expr)
+        self.consume(TokenType::RightBrace, "Expected '}' after object literal.")?;
+        Ok(Expr::Object(properties))
+    }
+    
+    // `key: value`, the shorthand `name` for `name: name`, or a method
+    // `key(parameters) { body }`, optionally async. Keys may be computed:
+    // `[expr]: value`.
+    fn object_property(&mut self) -> Result<(PropertyKey, Expr), JsEngineError> {
+        let start = self.peek().span.start;
+        let is_async = self.check_identifier("async")
+            && !matches!(
+                self.tokens.get(self.current + 1).map(|token| &token.token_type),
+                Some(TokenType::Colon | TokenType::LeftParen | TokenType::Comma | TokenType::RightBrace)
+            );
+        if is_async {
+            self.advance();
+        }
+        
+        let shorthand = match &self.peek().token_type {
+            TokenType::Identifier(name) => Some((name.clone(), self.peek().span)),
+            _ => None,
+        };
+        let key = if self.match_token(&[TokenType::LeftBracket]) {
+            let key = self.assignment()?;
+            self.consume(TokenType::RightBracket, "Expected ']' after computed property name.")?;
+            PropertyKey::Computed(key)
+        } else {
+            PropertyKey::Name(self.literal_key("Expected property name in object literal.")?)
+        };
+        
+        if is_async || self.check(&TokenType::LeftParen) {
+            let kind = if is_async { FunctionKind::AsyncFunction } else { FunctionKind::Function };
+            return Ok((key, self.method(start, kind)?));
+        }
+        
+        if let Some((name, span)) = shorthand
+            && !self.check(&TokenType::Colon)
+        {
+            return Ok((key, Expr::Variable(name, span)));
+        }
+        
+        self.consume(TokenType::Colon, "Expected ':' after property name.")?;
+        Ok((key, self.assignment()?))
+    }
+    
+    // The parameters and body of a method in an object literal, which is an
+    // ordinary function
+    fn method(&mut self, start: usize, kind: FunctionKind) -> Result<Expr, JsEngineError> {
+        self.type_parameters()?;
+        self.consume(TokenType::LeftParen, "Expected '(' after method name.")?;
+        let (parameters, prologue) = self.parameters()?;
+        
+        self.consume(TokenType::LeftBrace, "Expected '{' before method body.")?;
+        let body = Rc::new(with_prologue(prologue, self.function_body(kind)?));
+        let span = Span { start, end: self.previous().span.end };
+        
+        Ok(Expr::Function(parameters, body, span, kind))
+    }
+    
+    // A property name in an object literal or pattern: a name, a string, or
+    // a number, which names the property its number prints as
+    fn literal_key(&mut self, message: &str) -> Result<Rc<str>, JsEngineError> {
+        let key: Rc<str> = match &self.peek().token_type {
+            TokenType::String(value) => Rc::from(value.as_str()),
+            TokenType::Number(value) => Rc::from(number_to_string(*value)),
+            _ if self.at_name() => Rc::from(self.peek().lexeme.as_str()),
+            _ => return Err(self.error(message)),
+        };
+        self.advance();
+        Ok(key)
     }
     
-    fn finish_call(&mut self, callee: Expr) -> Result<Expr, JsEngineError> {
+    // An array element or call argument, which may be spread
+    fn spread_or(&mut self, element: fn(&mut Self) -> Result<Expr, JsEngineError>) -> Result<Expr, JsEngineError> {
+        if self.match_token(&[TokenType::DotDotDot]) {
+            return Ok(Expr::Spread(Box::new(self.assignment()?)));
+        }
+        element(self)
+    }
+    
+    fn finish_call(&mut self, callee: Expr, start: usize) -> Result<Expr, JsEngineError> {
+        let (arguments, end) = self.arguments()?;
+        Ok(Expr::Call(Box::new(callee), arguments, Span { start, end }))
+    }
+    
+    // The arguments of a call after the opening parenthesis, and where the
+    // closing one ends
+    fn arguments(&mut self) -> Result<(Vec<Expr>, usize), JsEngineError> {
         let mut arguments = Vec::new();
         
         if !self.check(&TokenType::RightParen) {
@@ -381,7 +1007,7 @@ impl Parser {
                     return Err(self.error("Cannot have more than 255 arguments."));
                 }
                 
-                arguments.push(self.expression()?);
+                arguments.push(self.spread_or(Self::expression)?);
                 
                 if !self.match_token(&[TokenType::Comma]) {
                     break;
@@ -389,18 +1015,32 @@ impl Parser {
             }
         }
         
-        self.consume(TokenType::RightParen, "Expected ')' after arguments.")?;
+        let end = self.consume(TokenType::RightParen, "Expected ')' after arguments.")?.span.end;
         
-        Ok(Expr::Call(Box::new(callee), arguments))
+        Ok((arguments, end))
     }
     
     fn primary(&mut self) -> Result<Expr, JsEngineError> {
+        if let Some(handler) = self.extensions.prefix(&self.peek().token_type) {
+            self.advance(); // Consume the leading token
+            return handler(self);
+        }
+        
+        if self.at_arrow_function(0) {
+            return self.arrow_function(FunctionKind::Arrow);
+        }
+        if self.check_identifier("async") && self.at_arrow_function(1) {
+            return self.arrow_function(FunctionKind::AsyncArrow);
+        }
+        
         if self.match_token(&[TokenType::False]) {
             Ok(Expr::Boolean(false))
         } else if self.match_token(&[TokenType::True]) {
             Ok(Expr::Boolean(true))
         } else if self.match_token(&[TokenType::Null]) {
             Ok(Expr::Null)
+        } else if self.match_token(&[TokenType::This]) {
+            Ok(Expr::This)
         } else if self.match_token(&[TokenType::Number(0.0)]) {
             // Get the actual number from the previous token
             if let TokenType::Number(value) = &self.previous().token_type {
@@ -411,63 +1051,283 @@ impl Parser {
         } else if self.match_token(&[TokenType::String("".to_string())]) {
             // Get the actual string from the previous token
             if let TokenType::String(value) = &self.previous().token_type {
-                Ok(Expr::String(value.clone()))
+                Ok(Expr::String(Rc::from(value.as_str())))
             } else {
                 unreachable!()
             }
+        } else if let TokenType::RegExp(pattern, flags) = &self.peek().token_type {
+            let regexp = Expr::regexp(pattern, flags);
+            self.advance();
+            Ok(regexp)
         } else if self.match_token(&[TokenType::Identifier("".to_string())]) {
             // Get the identifier name from the previous token
             if let TokenType::Identifier(name) = &self.previous().token_type {
-                Ok(Expr::Variable(name.clone()))
+                Ok(Expr::Variable(name.clone(), self.previous().span))
             } else {
                 unreachable!()
             }
+        } else if self.match_token(&[TokenType::LeftBrace]) {
+            self.object_literal()
+        } else if self.match_token(&[TokenType::LeftBracket]) {
+            self.array_literal()
         } else if self.match_token(&[TokenType::LeftParen]) {
             let expr = self.expression()?;
             self.consume(TokenType::RightParen, "Expected ')' after expression.")?;
             Ok(expr)
         } else if self.match_token(&[TokenType::Function]) {
-            // Anonymous function
-            self.consume(TokenType::LeftParen, "Expected '(' after 'function'.")?;
-            
-            let mut parameters = Vec::new();
-            
-            if !self.check(&TokenType::RightParen) {
-                loop {
-                    if parameters.len() >= 255 {
-                        return Err(self.error("Cannot have more than 255 parameters."));
-                    }
-                    
-                    match &self.peek().token_type {
-                        TokenType::Identifier(name) => {
-                            parameters.push(name.clone());
-                            self.advance();
-                        }
-                        _ => {
-                            return Err(self.error("Expected parameter name."));
-                        }
-                    }
-                    
-                    if !self.match_token(&[TokenType::Comma]) {
-                        break;
+            let start = self.previous().span.start;
+            self.function_expression(start, FunctionKind::Function)
+        } else if self.at_async_function() {
+            let start = self.advance().span.start;
+            self.advance(); // Consume 'function'
+            self.function_expression(start, FunctionKind::AsyncFunction)
+        } else {
+            Err(self.error("Expected expression."))
+        }
+    }
+    
+    // A function expression, after the 'function' keyword. A named one is
+    // parsed like a declaration.
+    fn function_expression(&mut self, start: usize, kind: FunctionKind) -> Result<Expr, JsEngineError> {
+        if let TokenType::Identifier(_) = &self.peek().token_type {
+            let function = self.function_declaration(start, kind)?;
+            return Ok(Expr::NamedFunction(Box::new(function)));
+        }
+        
+        self.type_parameters()?;
+        self.consume(TokenType::LeftParen, "Expected '(' after 'function'.")?;
+        let (parameters, prologue) = self.parameters()?;
+        
+        self.consume(TokenType::LeftBrace, "Expected '{' before function body.")?;
+        let body = Rc::new(with_prologue(prologue, self.function_body(kind)?));
+        let span = Span { start, end: self.previous().span.end };
+        
+        Ok(Expr::Function(parameters, body, span, kind))
+    }
+    
+    // `async function`; `async` is only a keyword in front of a function
+    fn at_async_function(&self) -> bool {
+        self.check_identifier("async") && matches!(self.tokens.get(self.current + 1).map(|token| &token.token_type), Some(TokenType::Function))
+    }
+    
+    // Whether the tokens from `start` on begin an arrow function: `x =>` or
+    // a parenthesized list whose closing parenthesis is followed by `=>`,
+    // or in TypeScript by a return type and `=>`
+    fn at_arrow_function(&mut self, start: usize) -> bool {
+        let token_type = |offset: usize| self.tokens.get(self.current + start + offset).map(|token| &token.token_type);
+        
+        match token_type(0) {
+            Some(TokenType::Identifier(_)) => matches!(token_type(1), Some(TokenType::Arrow)),
+            Some(TokenType::LeftParen) => {
+                let mut depth = 0;
+                for offset in 0.. {
+                    match token_type(offset) {
+                        Some(TokenType::LeftParen) => depth += 1,
+                        Some(TokenType::RightParen) => {
+                            depth -= 1;
+                            if depth == 0 {
+                                return match token_type(offset + 1) {
+                                    Some(TokenType::Arrow) => true,
+                                    Some(TokenType::Colon) if self.typescript => self.at_return_type_and_arrow(start + offset + 2),
+                                    _ => false,
+                                };
+                            }
+                        },
+                        Some(TokenType::Eof) | None => return false,
+                        _ => {},
                     }
                 }
+                false
+            },
+            _ => false,
+        }
+    }
+    
+    // Whether the tokens from `start` on are a type followed by `=>`. The
+    // type is skipped to find out, then the parser is put back.
+    fn at_return_type_and_arrow(&mut self, start: usize) -> bool {
+        let current = self.current;
+        self.current += start;
+        let arrow = self.skip_type().is_ok() && self.check(&TokenType::Arrow);
+        self.current = current;
+        
+        arrow
+    }
+    
+    // `(a, b) => expression`, `x => expression` or the same with a block
+    // body, each optionally preceded by `async`
+    fn arrow_function(&mut self, kind: FunctionKind) -> Result<Expr, JsEngineError> {
+        let start = self.peek().span.start;
+        if kind.is_async() {
+            self.advance(); // Consume 'async'
+        }
+        
+        let (parameters, prologue) = if self.match_token(&[TokenType::LeftParen]) {
+            self.parameters()?
+        } else {
+            let token = self.advance();
+            let TokenType::Identifier(name) = &token.token_type else {
+                return Err(self.error("Expected parameter name."));
+            };
+            (vec![(Rc::from(name.as_str()), token.span)], Vec::new())
+        };
+        
+        self.consume(TokenType::Arrow, "Expected '=>' after parameters.")?;
+        
+        let body = if self.match_token(&[TokenType::LeftBrace]) {
+            self.function_body(kind)?
+        } else {
+            // A concise body returns its value
+            let in_async = std::mem::replace(&mut self.in_async, kind.is_async());
+            let value = self.assignment()?;
+            self.in_async = in_async;
+            Stmt::Block(vec![Stmt::Return(Some(value))])
+        };
+        let span = Span { start, end: self.previous().span.end };
+        
+        Ok(Expr::Function(parameters, Rc::new(with_prologue(prologue, body)), span, kind))
+    }
+    
+    // Skipping TypeScript type syntax. Types are only checked for being
+    // well-formed enough to find where they end.
+    
+    // `: Type` after a declaration, parameter or parameter list
+    fn type_annotation(&mut self) -> Result<(), JsEngineError> {
+        if self.typescript && self.match_token(&[TokenType::Colon]) {
+            self.skip_type()?;
+        }
+        
+        Ok(())
+    }
+    
+    // `<T, U extends V>` after a function or type alias name
+    fn type_parameters(&mut self) -> Result<(), JsEngineError> {
+        if self.typescript && self.check(&TokenType::Less) {
+            self.skip_balanced(TokenType::Less, TokenType::Greater)?;
+        }
+        
+        Ok(())
+    }
+    
+    // A union or intersection of type operands
+    fn skip_type(&mut self) -> Result<(), JsEngineError> {
+        self.nested(Parser::skip_type_inner)
+    }
+    
+    fn skip_type_inner(&mut self) -> Result<(), JsEngineError> {
+        self.match_token(&[TokenType::Pipe, TokenType::Ampersand]);
+        
+        loop {
+            self.skip_type_operand()?;
+            
+            if !self.match_token(&[TokenType::Pipe, TokenType::Ampersand]) {
+                break;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    fn skip_type_operand(&mut self) -> Result<(), JsEngineError> {
+        match &self.peek().token_type {
+            TokenType::Identifier(name) => {
+                let is_operator = matches!(name.as_str(), "keyof" | "typeof" | "readonly");
+                self.advance();
+                
+                if is_operator {
+                    return self.skip_type_operand();
+                }
+                
+                // Qualified names and generic arguments: `ns.Name<T>`
+                while self.match_token(&[TokenType::Dot]) {
+                    self.consume(TokenType::Identifier("".to_string()), "Expected type name after '.'.")?;
+                }
+                self.type_parameters()?;
+            },
+            TokenType::Null | TokenType::True | TokenType::False | TokenType::String(_) | TokenType::Number(_) => {
+                self.advance();
+            },
+            TokenType::LeftBrace => self.skip_balanced(TokenType::LeftBrace, TokenType::RightBrace)?,
+            TokenType::LeftBracket => self.skip_balanced(TokenType::LeftBracket, TokenType::RightBracket)?,
+            TokenType::LeftParen => {
+                // Parenthesized type or function type `(a: A) => B`
+                self.skip_balanced(TokenType::LeftParen, TokenType::RightParen)?;
+                
+                if self.match_token(&[TokenType::Arrow]) {
+                    self.skip_type()?;
+                }
+            },
+            TokenType::Less => {
+                // Generic function type `<T>(a: T) => T`
+                self.skip_balanced(TokenType::Less, TokenType::Greater)?;
+                return self.skip_type_operand();
+            },
+            _ => return Err(self.error("Expected type.")),
+        }
+        
+        // Array types: `T[]`
+        while self.check(&TokenType::LeftBracket)
+            && matches!(self.tokens.get(self.current + 1).map(|token| &token.token_type), Some(TokenType::RightBracket))
+        {
+            self.advance();
+            self.advance();
+        }
+        
+        Ok(())
+    }
+    
+    // Skips from an opening token to its matching closing token
+    fn skip_balanced(&mut self, open: TokenType, close: TokenType) -> Result<(), JsEngineError> {
+        let mut depth = 0;
+        
+        loop {
+            if self.is_at_end() {
+                return Err(self.error("Unterminated type."));
             }
             
-            self.consume(TokenType::RightParen, "Expected ')' after parameters.")?;
+            if self.check(&open) {
+                depth += 1;
+            } else if self.check(&close) {
+                depth -= 1;
+            }
             
-            self.consume(TokenType::LeftBrace, "Expected '{' before function body.")?;
-            let body = Box::new(self.block()?);
+            self.advance();
             
-            Ok(Expr::Function(parameters, body))
-        } else {
-            Err(self.error("Expected expression."))
+            if depth == 0 {
+                return Ok(());
+            }
         }
     }
     
     // Helper methods for the parser
     
-    fn match_token(&mut self, types: &[TokenType]) -> bool {
+    // Runs `parse` one nesting level deeper, failing once the nesting limit
+    // is reached
+    fn nested<T>(&mut self, parse: fn(&mut Self) -> Result<T, JsEngineError>) -> Result<T, JsEngineError> {
+        if self.depth >= self.max_nesting {
+            return Err(self.error("Code is nested too deeply."));
+        }
+        
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        
+        result
+    }
+    
+    // Counts one more node wrapped around the left operand of an operator or
+    // call chain, which nests it a level deeper without recursing
+    fn fold(&self, folded: &mut usize) -> Result<(), JsEngineError> {
+        *folded += 1;
+        
+        if self.depth + *folded > self.max_nesting {
+            return Err(self.error("Expression is nested too deeply."));
+        }
+        
+        Ok(())
+    }
+    
+    pub fn match_token(&mut self, types: &[TokenType]) -> bool {
         for token_type in types {
             if self.check(token_type) {
                 self.advance();
@@ -478,7 +1338,7 @@ impl Parser {
         false
     }
     
-    fn check(&self, token_type: &TokenType) -> bool {
+    pub fn check(&self, token_type: &TokenType) -> bool {
         if self.is_at_end() {
             return false;
         }
@@ -492,7 +1352,7 @@ impl Parser {
         }
     }
     
-    fn advance(&mut self) -> &Token {
+    pub fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
             self.current += 1;
         }
@@ -500,19 +1360,29 @@ impl Parser {
         self.previous()
     }
     
-    fn is_at_end(&self) -> bool {
-        matches!(self.peek().token_type, TokenType::EOF)
+    // Whether the current token is the identifier `name`; used for
+    // contextual keywords that are ordinary identifiers elsewhere
+    fn check_identifier(&self, name: &str) -> bool {
+        matches!(&self.peek().token_type, TokenType::Identifier(identifier) if identifier == name)
+    }
+    
+    fn check_next_identifier(&self) -> bool {
+        matches!(self.tokens.get(self.current + 1).map(|token| &token.token_type), Some(TokenType::Identifier(_)))
+    }
+    
+    pub fn is_at_end(&self) -> bool {
+        matches!(self.peek().token_type, TokenType::Eof)
     }
     
-    fn peek(&self) -> &Token {
+    pub fn peek(&self) -> &Token {
         &self.tokens[self.current]
     }
     
-    fn previous(&self) -> &Token {
-        &self.tokens[self.current - 1]
+    pub fn previous(&self) -> &Token {
+        &self.tokens[self.current.saturating_sub(1)]
     }
     
-    fn consume(&mut self, token_type: TokenType, message: &str) -> Result<&Token, JsEngineError> {
+    pub fn consume(&mut self, token_type: TokenType, message: &str) -> Result<&Token, JsEngineError> {
         if self.check(&token_type) {
             Ok(self.advance())
         } else {
@@ -520,7 +1390,7 @@ impl Parser {
         }
     }
     
-    fn error(&self, message: &str) -> JsEngineError {
+    pub fn error(&self, message: &str) -> JsEngineError {
         let token = self.peek();
         
         JsEngineError::ParserError {
@@ -529,4 +1399,22 @@ impl Parser {
             message: message.to_string(),
         }
     }
-}
\ No newline at end of file
+}
+
+// Puts the declarations unpacking destructured parameters ahead of the
+// statements of a function body
+fn with_prologue(mut prologue: Vec<Stmt>, body: Stmt) -> Stmt {
+    if prologue.is_empty() {
+        return body;
+    }
+    match body {
+        Stmt::Block(statements) => {
+            prologue.extend(statements);
+            Stmt::Block(prologue)
+        },
+        body => {
+            prologue.push(body);
+            Stmt::Block(prologue)
+        },
+    }
+}
@@ -0,0 +1,66 @@
+// src/performance.rs
+
+// Marks and measures a script records with performance.mark and
+// performance.measure (the User Timing API). The interpreter keeps them so
+// the host can report how long the phases of a script took once it has
+// run. Times are milliseconds since the interpreter was created.
+#[derive(Default)]
+pub struct Timeline {
+    entries: Vec<PerformanceEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerformanceEntry {
+    pub name: String,
+    pub entry_type: EntryType,
+    pub start_time: f64,
+    pub duration: f64, // Always 0 for marks
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryType {
+    Mark,
+    Measure,
+}
+
+impl EntryType {
+    // The entryType string scripts see
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntryType::Mark => "mark",
+            EntryType::Measure => "measure",
+        }
+    }
+    
+    pub fn from_name(name: &str) -> Option<EntryType> {
+        match name {
+            "mark" => Some(EntryType::Mark),
+            "measure" => Some(EntryType::Measure),
+            _ => None,
+        }
+    }
+}
+
+impl Timeline {
+    pub fn record(&mut self, entry: PerformanceEntry) {
+        self.entries.push(entry);
+    }
+    
+    // Every entry in the order recorded
+    pub fn entries(&self) -> &[PerformanceEntry] {
+        &self.entries
+    }
+    
+    pub fn entries_by_type(&self, entry_type: EntryType) -> impl DoubleEndedIterator<Item = &PerformanceEntry> {
+        self.entries.iter().filter(move |entry| entry.entry_type == entry_type)
+    }
+    
+    // The most recent mark with the given name
+    pub fn mark(&self, name: &str) -> Option<&PerformanceEntry> {
+        self.entries_by_type(EntryType::Mark).rev().find(|entry| entry.name == name)
+    }
+    
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
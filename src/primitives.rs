@@ -0,0 +1,244 @@
+// src/primitives.rs
+use crate::error::JsEngineError;
+use crate::interpreter::Value;
+use crate::iterator::{self, ITERATOR};
+use crate::locale::Locale;
+use crate::number::{number_to_string, parse_float, parse_int, to_exponential, to_fixed, to_precision, to_radix_string};
+use crate::object::PropertyMap;
+#[cfg(feature = "regex")]
+use crate::regexp;
+use crate::stdlib::to_number;
+use crate::string::JsString;
+use crate::typed_array::canonical_index;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+// 2^53 - 1, the largest integer above which some integers have no f64
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+// Properties read from numbers and strings, such as `n.toLocaleString`.
+// Native functions have no `this`, so each method is returned bound to the
+// value it was read from. Unknown names are undefined.
+//
+// Strings are measured and indexed in UTF-16 code units, as in other
+// engines, although they are stored as UTF-8, and `s[i]` reads the unit at
+// index i. match and replace come with regular expressions (see
+// regexp.rs); replace takes a callable object, since a replacement function
+// needs the interpreter.
+
+pub(crate) fn number_property(n: f64, name: &str, locale: &Locale) -> Value {
+    match name {
+        "toLocaleString" => {
+            let locale = locale.clone();
+            method(move |arguments| Ok(Value::String(JsString::from(locale_argument(arguments, 0, &locale).format_number(n)))))
+        },
+        "toFixed" => method(move |arguments| {
+            let digits = digits_argument(arguments, 0..=100, "toFixed() digits argument must be between 0 and 100")?;
+            Ok(Value::String(JsString::from(to_fixed(n, digits.unwrap_or(0)))))
+        }),
+        "toExponential" => method(move |arguments| {
+            if !n.is_finite() {
+                return Ok(Value::String(JsString::from(number_to_string(n))));
+            }
+            let digits = digits_argument(arguments, 0..=100, "toExponential() argument must be between 0 and 100")?;
+            Ok(Value::String(JsString::from(to_exponential(n, digits))))
+        }),
+        "toPrecision" => method(move |arguments| {
+            if !n.is_finite() || matches!(arguments.first(), None | Some(Value::Undefined)) {
+                return Ok(Value::String(JsString::from(number_to_string(n))));
+            }
+            let precision = digits_argument(arguments, 1..=100, "toPrecision() argument must be between 1 and 100")?;
+            Ok(Value::String(JsString::from(to_precision(n, precision.unwrap_or(1)))))
+        }),
+        "toString" => method(move |arguments| {
+            let radix = digits_argument(arguments, 2..=36, "toString() radix must be between 2 and 36")?;
+            Ok(Value::String(JsString::from(to_radix_string(n, radix.unwrap_or(10) as u32))))
+        }),
+        _ => Value::Undefined,
+    }
+}
+
+pub(crate) fn string_property(s: &JsString, name: &str, locale: &Locale) -> Value {
+    let s = s.clone();
+    if let Some(index) = canonical_index(name) {
+        return s.code_unit_at(index).map_or(Value::Undefined, |unit| Value::String(JsString::from_utf16_lossy(&[unit])));
+    }
+    match name {
+        "length" => Value::Number(s.utf16_len() as f64),
+        "charAt" => method(move |arguments| {
+            let unit = index_argument(arguments).and_then(|index| s.code_unit_at(index));
+            Ok(Value::String(JsString::from_utf16_lossy(unit.as_slice())))
+        }),
+        "at" => method(move |arguments| {
+            // Negative indices count back from the end
+            let index = integer(arguments.first().map_or(0.0, to_number));
+            let index = if index < 0.0 { index + s.utf16_len() as f64 } else { index };
+            let unit = (index >= 0.0).then(|| s.code_unit_at(index as usize)).flatten();
+            Ok(unit.map_or(Value::Undefined, |unit| Value::String(JsString::from_utf16_lossy(&[unit]))))
+        }),
+        "charCodeAt" => method(move |arguments| {
+            let unit = index_argument(arguments).and_then(|index| s.code_unit_at(index));
+            Ok(Value::Number(unit.map_or(f64::NAN, |unit| unit as f64)))
+        }),
+        "codePointAt" => method(move |arguments| {
+            let code_point = index_argument(arguments).and_then(|index| s.code_point_at(index));
+            Ok(code_point.map_or(Value::Undefined, |code_point| Value::Number(code_point as f64)))
+        }),
+        "localeCompare" => {
+            let locale = locale.clone();
+            method(move |arguments| {
+                let that = arguments.first().unwrap_or(&Value::Undefined).to_string();
+                let order = match locale_argument(arguments, 1, &locale).compare(s.as_str(), &that) {
+                    Ordering::Less => -1.0,
+                    Ordering::Equal => 0.0,
+                    Ordering::Greater => 1.0,
+                };
+                Ok(Value::Number(order))
+            })
+        },
+        ITERATOR => iterator::iterator_method(&Value::String(s)).unwrap_or(Value::Undefined),
+        #[cfg(feature = "regex")]
+        "match" => method(move |arguments| regexp::string_match(&s, arguments.first().unwrap_or(&Value::Undefined))),
+        #[cfg(feature = "regex")]
+        "replace" => {
+            let replace = PropertyMap::callable(Rc::new(move |interpreter, arguments| {
+                let argument = |index| arguments.get(index).unwrap_or(&Value::Undefined);
+                regexp::string_replace(interpreter, &s, argument(0), argument(1))
+            }));
+            Value::Object(Rc::new(RefCell::new(replace)))
+        },
+        _ => Value::Undefined,
+    }
+}
+
+// The global `String`, holding the functions that build strings from
+// character codes
+pub(crate) fn string_namespace() -> Value {
+    let from_char_code = method(|arguments| {
+        // ToUint16: wraps modulo 2^16
+        let units: Vec<u16> = arguments.iter().map(|argument| integer(to_number(argument)).rem_euclid(65536.0) as u16).collect();
+        Ok(Value::String(JsString::from_utf16_lossy(&units)))
+    });
+    
+    let from_code_point = method(|arguments| {
+        let mut text = String::new();
+        for argument in arguments {
+            let n = to_number(argument);
+            let code_point = (n.fract() == 0.0 && (0.0..=1_114_111.0).contains(&n)).then_some(n as u32);
+            match code_point {
+                Some(code_point) => text.push(char::from_u32(code_point).unwrap_or(char::REPLACEMENT_CHARACTER)),
+                None => return Err(JsEngineError::throw("RangeError", format!("Invalid code point {}", argument))),
+            }
+        }
+        Ok(Value::String(JsString::from(text)))
+    });
+    
+    let properties = PropertyMap::from([
+        ("fromCharCode", from_char_code),
+        ("fromCodePoint", from_code_point),
+    ]);
+    Value::Object(Rc::new(RefCell::new(properties)))
+}
+
+// The global `Number`, which converts its argument to a number, holding the
+// numeric constants and the functions that test numbers without converting
+// them
+pub(crate) fn number_namespace() -> Value {
+    let mut number = PropertyMap::callable(Rc::new(|interpreter, arguments| {
+        let primitive = match arguments.first() {
+            Some(value) => interpreter.primitive_value(value.clone(), false)?,
+            None => return Ok(Value::Number(0.0)),
+        };
+        Ok(Value::Number(to_number(&primitive)))
+    }));
+    
+    let test = |test: fn(f64) -> bool| method(move |arguments| Ok(Value::Boolean(matches!(arguments.first(), Some(Value::Number(n)) if test(*n)))));
+    let properties = [
+        ("isNaN", test(f64::is_nan)),
+        ("isFinite", test(f64::is_finite)),
+        ("isInteger", test(|n| n.is_finite() && n.fract() == 0.0)),
+        ("isSafeInteger", test(|n| n.fract() == 0.0 && n.abs() <= MAX_SAFE_INTEGER)),
+        ("parseFloat", parse_float_function()),
+        ("parseInt", parse_int_function()),
+        ("MAX_SAFE_INTEGER", Value::Number(MAX_SAFE_INTEGER)),
+        ("MIN_SAFE_INTEGER", Value::Number(-MAX_SAFE_INTEGER)),
+        ("MAX_VALUE", Value::Number(f64::MAX)),
+        ("MIN_VALUE", Value::Number(f64::from_bits(1))),
+        ("EPSILON", Value::Number(f64::EPSILON)),
+        ("POSITIVE_INFINITY", Value::Number(f64::INFINITY)),
+        ("NEGATIVE_INFINITY", Value::Number(f64::NEG_INFINITY)),
+        ("NaN", Value::Number(f64::NAN)),
+    ];
+    for (name, value) in properties {
+        number.insert(name.to_string(), value);
+    }
+    Value::Object(Rc::new(RefCell::new(number)))
+}
+
+// The global number functions and constants, and undefined. isNaN and
+// isFinite convert their argument first, unlike Number.isNaN and
+// Number.isFinite.
+pub(crate) fn number_globals() -> Vec<(&'static str, Value)> {
+    vec![
+        ("parseFloat", parse_float_function()),
+        ("parseInt", parse_int_function()),
+        ("isNaN", method(|arguments| Ok(Value::Boolean(arguments.first().map_or(f64::NAN, to_number).is_nan())))),
+        ("isFinite", method(|arguments| Ok(Value::Boolean(arguments.first().map_or(f64::NAN, to_number).is_finite())))),
+        ("NaN", Value::Number(f64::NAN)),
+        ("Infinity", Value::Number(f64::INFINITY)),
+        ("undefined", Value::Undefined),
+    ]
+}
+
+fn parse_float_function() -> Value {
+    method(|arguments| Ok(Value::Number(parse_float(&arguments.first().unwrap_or(&Value::Undefined).to_string()))))
+}
+
+fn parse_int_function() -> Value {
+    method(|arguments| {
+        let text = arguments.first().unwrap_or(&Value::Undefined).to_string();
+        // ToInt32 of the radix, so NaN and undefined are 0
+        let radix = integer(arguments.get(1).map_or(0.0, to_number));
+        let radix = if radix.is_finite() { radix.rem_euclid(4_294_967_296.0) as u32 } else { 0 };
+        Ok(Value::Number(parse_int(&text, radix)))
+    })
+}
+
+fn method(function: impl Fn(&[Value]) -> Result<Value, JsEngineError> + 'static) -> Value {
+    Value::NativeFunction(Rc::new(function))
+}
+
+// ToIntegerOrInfinity: NaN is 0 and fractions are dropped
+pub(crate) fn integer(n: f64) -> f64 {
+    if n.is_nan() { 0.0 } else { n.trunc() }
+}
+
+// The position argument of the string methods, which defaults to 0; None
+// when it is negative
+fn index_argument(arguments: &[Value]) -> Option<usize> {
+    let index = integer(arguments.first().map_or(0.0, to_number));
+    (index >= 0.0).then_some(index as usize)
+}
+
+// The integer argument of the number formatting methods, None when it is
+// undefined; RangeError outside `range`
+fn digits_argument(arguments: &[Value], range: RangeInclusive<usize>, message: &str) -> Result<Option<usize>, JsEngineError> {
+    let digits = match arguments.first() {
+        None | Some(Value::Undefined) => return Ok(None),
+        Some(argument) => integer(to_number(argument)),
+    };
+    if digits < *range.start() as f64 || digits > *range.end() as f64 {
+        return Err(JsEngineError::throw("RangeError", message));
+    }
+    Ok(Some(digits as usize))
+}
+
+// A locale tag passed to the method overrides the interpreter's locale
+fn locale_argument(arguments: &[Value], index: usize, default: &Locale) -> Locale {
+    match arguments.get(index) {
+        Some(Value::String(tag)) => Locale::new(tag.as_str()),
+        _ => default.clone(),
+    }
+}
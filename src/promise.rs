@@ -0,0 +1,291 @@
+// src/promise.rs
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, NativeFunction, Value};
+use crate::object::{InternalSlot, PropertyMap};
+use crate::tasks::TaskQueue;
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+// Promises, settled through the interpreter's microtask queue. Once a
+// promise settles, each of its reactions (then callbacks, and async
+// functions awaiting it) runs as a microtask of its own, in the order they
+// were added, and never during the call that added it or settled the
+// promise.
+//
+// Resolving a promise with another promise, or with any object that has a
+// `then` method, makes it settle the way that one does. Promise objects
+// keep their state in an internal slot; then, catch and finally are found
+// through the slot instead of being stored as properties.
+
+// How a promise settled: fulfilled with a value or rejected with a reason
+pub type Outcome = Result<Value, Value>;
+
+pub(crate) type Reaction = Box<dyn FnOnce(&mut Interpreter, Outcome) -> Result<(), JsEngineError>>;
+
+enum State {
+    Pending(Vec<Reaction>),
+    Settled(Outcome),
+}
+
+pub struct Promise {
+    state: RefCell<State>,
+    tasks: Weak<RefCell<TaskQueue>>, // Weak since queued reactions can reach the promise
+}
+
+impl Promise {
+    pub fn new(tasks: Weak<RefCell<TaskQueue>>) -> Rc<Promise> {
+        Rc::new(Promise {
+            state: RefCell::new(State::Pending(Vec::new())),
+            tasks,
+        })
+    }
+    
+    // The promise behind a promise object
+    pub fn of(value: &Value) -> Option<Rc<Promise>> {
+        match value {
+            Value::Object(object) => match object.borrow().slot() {
+                Some(InternalSlot::Promise(promise)) => Some(Rc::clone(promise)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    
+    // A promise already resolved with `value`, or `value` itself when it is
+    // a promise, as Promise.resolve and await take their operand
+    pub fn resolved(value: Value, tasks: Weak<RefCell<TaskQueue>>) -> Rc<Promise> {
+        if let Some(promise) = Promise::of(&value) {
+            return promise;
+        }
+        let promise = Promise::new(tasks);
+        promise.resolve(value);
+        promise
+    }
+    
+    // A new object for the promise. Each promise is given one, which scripts
+    // then pass around.
+    pub fn into_value(self: Rc<Self>) -> Value {
+        Value::Object(Rc::new(RefCell::new(PropertyMap::with_slot(InternalSlot::Promise(self)))))
+    }
+    
+    // None while the promise is pending
+    pub fn outcome(&self) -> Option<Outcome> {
+        match &*self.state.borrow() {
+            State::Pending(_) => None,
+            State::Settled(outcome) => Some(outcome.clone()),
+        }
+    }
+    
+    // Fulfills the promise, or follows `value` if it is a promise or another
+    // thenable. Does nothing once the promise has settled.
+    pub fn resolve(self: &Rc<Self>, value: Value) {
+        if let Some(other) = Promise::of(&value) {
+            let promise = Rc::clone(self);
+            other.react(Box::new(move |_, outcome| {
+                promise.settle(outcome);
+                Ok(())
+            }));
+            return;
+        }
+        
+        let then = match &value {
+            Value::Object(object) => object.borrow().lookup("then").filter(Value::is_callable),
+            _ => None,
+        };
+        let Some(then) = then else {
+            self.settle(Ok(value));
+            return;
+        };
+        
+        // A thenable's then method is called from a microtask, with fresh
+        // resolving functions for this promise
+        let promise = Rc::clone(self);
+        self.queue(
+            Box::new(move |interpreter, _| {
+                let (resolve, reject) = promise.resolving_functions();
+                if let Err(error) = interpreter.call_method(&then, value, [resolve, reject.clone()].into_iter().collect()) {
+                    let reason = interpreter.exception_value(error);
+                    interpreter.call_function(&reject, &[reason])?;
+                }
+                Ok(())
+            }),
+            Ok(Value::Undefined),
+        );
+    }
+    
+    pub fn reject(&self, reason: Value) {
+        self.settle(Err(reason));
+    }
+    
+    fn settle(&self, outcome: Outcome) {
+        let reactions = {
+            let mut state = self.state.borrow_mut();
+            if matches!(*state, State::Settled(_)) {
+                return;
+            }
+            match std::mem::replace(&mut *state, State::Settled(outcome.clone())) {
+                State::Pending(reactions) => reactions,
+                State::Settled(_) => unreachable!(),
+            }
+        };
+        
+        for reaction in reactions {
+            self.queue(reaction, outcome.clone());
+        }
+    }
+    
+    // Runs `reaction` as a microtask once the promise has settled, or soon
+    // if it already has
+    pub(crate) fn react(&self, reaction: Reaction) {
+        let outcome = match &mut *self.state.borrow_mut() {
+            State::Pending(reactions) => {
+                reactions.push(reaction);
+                return;
+            },
+            State::Settled(outcome) => outcome.clone(),
+        };
+        self.queue(reaction, outcome);
+    }
+    
+    // Microtasks are called without arguments, so the reaction and its
+    // outcome travel in a callable object that runs the reaction once
+    fn queue(&self, reaction: Reaction, outcome: Outcome) {
+        let Some(tasks) = self.tasks.upgrade() else {
+            return;
+        };
+        
+        let job = RefCell::new(Some((reaction, outcome)));
+        let callback = PropertyMap::callable(Rc::new(move |interpreter, _| {
+            let job = job.borrow_mut().take();
+            if let Some((reaction, outcome)) = job {
+                reaction(interpreter, outcome)?;
+            }
+            Ok(Value::Undefined)
+        }));
+        tasks.borrow_mut().queue_microtask(Value::Object(Rc::new(RefCell::new(callback))));
+    }
+    
+    // The resolve and reject functions handed to scripts. Only the first
+    // call to either of them has an effect.
+    pub fn resolving_functions(self: &Rc<Self>) -> (Value, Value) {
+        let called = Rc::new(Cell::new(false));
+        
+        let (promise, resolve_called) = (Rc::clone(self), Rc::clone(&called));
+        let resolve = Rc::new(move |arguments: &[Value]| -> Result<Value, JsEngineError> {
+            if !resolve_called.replace(true) {
+                promise.resolve(argument(arguments, 0));
+            }
+            Ok(Value::Undefined)
+        });
+        
+        let promise = Rc::clone(self);
+        let reject = Rc::new(move |arguments: &[Value]| -> Result<Value, JsEngineError> {
+            if !called.replace(true) {
+                promise.reject(argument(arguments, 0));
+            }
+            Ok(Value::Undefined)
+        });
+        
+        (Value::NativeFunction(resolve), Value::NativeFunction(reject))
+    }
+    
+    // A promise settled by whichever handler matches how this one settles,
+    // or the same way as this one when that handler is not a function
+    fn then(self: &Rc<Self>, on_fulfilled: Value, on_rejected: Value) -> Value {
+        let derived = Promise::new(self.tasks.clone());
+        let result = Rc::clone(&derived);
+        self.react(Box::new(move |interpreter, outcome| {
+            let (handler, argument) = match &outcome {
+                Ok(value) => (on_fulfilled, value.clone()),
+                Err(reason) => (on_rejected, reason.clone()),
+            };
+            if !handler.is_callable() {
+                result.settle(outcome);
+                return Ok(());
+            }
+            
+            match interpreter.call_function(&handler, &[argument]) {
+                Ok(value) => result.resolve(value),
+                Err(error) => result.reject(interpreter.exception_value(error)),
+            }
+            Ok(())
+        }));
+        derived.into_value()
+    }
+    
+    // Calls `callback` without arguments once this promise settles, and
+    // settles the same way unless the callback throws
+    fn finally(self: &Rc<Self>, callback: Value) -> Value {
+        let derived = Promise::new(self.tasks.clone());
+        let result = Rc::clone(&derived);
+        self.react(Box::new(move |interpreter, outcome| {
+            if callback.is_callable()
+                && let Err(error) = interpreter.call_function(&callback, &[])
+            {
+                result.reject(interpreter.exception_value(error));
+                return Ok(());
+            }
+            result.settle(outcome);
+            Ok(())
+        }));
+        derived.into_value()
+    }
+}
+
+// then, catch and finally for a promise object
+pub(crate) fn method(promise: &Rc<Promise>, name: &str) -> Option<Value> {
+    let promise = Rc::clone(promise);
+    let method: Rc<NativeFunction> = match name {
+        "then" => Rc::new(move |arguments| Ok(promise.then(argument(arguments, 0), argument(arguments, 1)))),
+        "catch" => Rc::new(move |arguments| Ok(promise.then(Value::Undefined, argument(arguments, 0)))),
+        "finally" => Rc::new(move |arguments| Ok(promise.finally(argument(arguments, 0)))),
+        _ => return None,
+    };
+    Some(Value::NativeFunction(method))
+}
+
+// The global `Promise`. Calling it with an executor does what
+// `new Promise(executor)` does: the executor is called right away with the
+// resolving functions, and a throw from it rejects the promise.
+pub(crate) fn promise_constructor(tasks: Weak<RefCell<TaskQueue>>) -> Value {
+    let constructor_tasks = tasks.clone();
+    let mut constructor = PropertyMap::callable(Rc::new(move |interpreter, arguments| {
+        let executor = argument(arguments, 0);
+        if !executor.is_callable() {
+            return Err(JsEngineError::TypeError {
+                message: format!("Promise resolver {:?} is not a function", executor),
+            });
+        }
+        
+        let promise = Promise::new(constructor_tasks.clone());
+        let (resolve, reject) = promise.resolving_functions();
+        if let Err(error) = interpreter.call_function(&executor, &[resolve, reject.clone()]) {
+            let reason = interpreter.exception_value(error);
+            interpreter.call_function(&reject, &[reason])?;
+        }
+        Ok(promise.into_value())
+    }));
+    
+    let resolve_tasks = tasks.clone();
+    let resolve = Rc::new(move |arguments: &[Value]| -> Result<Value, JsEngineError> {
+        let value = argument(arguments, 0);
+        if Promise::of(&value).is_some() {
+            return Ok(value);
+        }
+        Ok(Promise::resolved(value, resolve_tasks.clone()).into_value())
+    });
+    
+    let reject = Rc::new(move |arguments: &[Value]| -> Result<Value, JsEngineError> {
+        let promise = Promise::new(tasks.clone());
+        promise.reject(argument(arguments, 0));
+        Ok(promise.into_value())
+    });
+    
+    constructor.insert("resolve".to_string(), Value::NativeFunction(resolve));
+    constructor.insert("reject".to_string(), Value::NativeFunction(reject));
+    Value::Object(Rc::new(RefCell::new(constructor)))
+}
+
+fn argument(arguments: &[Value], index: usize) -> Value {
+    arguments.get(index).cloned().unwrap_or(Value::Undefined)
+}
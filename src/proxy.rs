@@ -0,0 +1,173 @@
+// src/proxy.rs
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, Value};
+use crate::object::{InternalSlot, PropertyMap};
+use crate::string::JsString;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Proxies and Reflect. A proxy object stands in for a target object and
+// hands reading, writing, testing for and deleting its properties to the
+// functions ("traps") of a handler object, falling back to doing the same to
+// the target for each trap the handler leaves out.
+//
+// The interpreter reads and writes properties through get_property,
+// set_property, has_property and delete_property, which turn to the proxy
+// when an object has one in its internal slot. Reflect offers those four
+// operations to scripts, so a trap can pass an operation on to its target.
+// Engine code that walks an object's own properties directly, like
+// JSON.stringify, sees the proxy's and not the target's.
+
+pub struct Proxy {
+    target: Value,
+    handler: Value,
+}
+
+impl Proxy {
+    // The proxy behind a proxy object
+    pub fn of(value: &Value) -> Option<Rc<Proxy>> {
+        match value {
+            Value::Object(object) => match object.borrow().slot() {
+                Some(InternalSlot::Proxy(proxy)) => Some(Rc::clone(proxy)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    
+    pub fn into_value(self: Rc<Self>) -> Value {
+        Value::Object(Rc::new(RefCell::new(PropertyMap::with_slot(InternalSlot::Proxy(self)))))
+    }
+    
+    pub fn target(&self) -> &Value {
+        &self.target
+    }
+    
+    // The handler's function for `name`, None when it has none
+    fn trap(&self, interpreter: &mut Interpreter, name: &str) -> Result<Option<Value>, JsEngineError> {
+        match interpreter.get_property(&self.handler, name)? {
+            Value::Undefined | Value::Null => Ok(None),
+            trap if trap.is_callable() => Ok(Some(trap)),
+            trap => Err(JsEngineError::TypeError {
+                message: format!("'{}' on proxy: trap {:?} is not a function", name, trap),
+            }),
+        }
+    }
+    
+    // Calls a trap with the handler as `this`
+    fn call_trap(&self, interpreter: &mut Interpreter, trap: &Value, arguments: Vec<Value>) -> Result<Value, JsEngineError> {
+        interpreter.call_method(trap, self.handler.clone(), arguments.into_iter().collect())
+    }
+    
+    // `proxy` is the object holding this proxy, passed to get and set traps
+    // as the receiver
+    pub(crate) fn get(&self, interpreter: &mut Interpreter, proxy: &Value, key: &str) -> Result<Value, JsEngineError> {
+        match self.trap(interpreter, "get")? {
+            Some(trap) => self.call_trap(interpreter, &trap, vec![self.target.clone(), Value::String(JsString::from(key)), proxy.clone()]),
+            None => interpreter.get_property(&self.target, key),
+        }
+    }
+    
+    // False when the trap refuses the assignment
+    pub(crate) fn set(&self, interpreter: &mut Interpreter, proxy: &Value, key: &str, value: Value) -> Result<bool, JsEngineError> {
+        match self.trap(interpreter, "set")? {
+            Some(trap) => {
+                let result = self.call_trap(interpreter, &trap, vec![self.target.clone(), Value::String(JsString::from(key)), value, proxy.clone()])?;
+                Ok(interpreter.is_truthy(&result))
+            },
+            None => {
+                interpreter.set_property(&self.target, key, value)?;
+                Ok(true)
+            },
+        }
+    }
+    
+    pub(crate) fn has(&self, interpreter: &mut Interpreter, key: &str) -> Result<bool, JsEngineError> {
+        match self.trap(interpreter, "has")? {
+            Some(trap) => {
+                let result = self.call_trap(interpreter, &trap, vec![self.target.clone(), Value::String(JsString::from(key))])?;
+                Ok(interpreter.is_truthy(&result))
+            },
+            None => interpreter.has_property(&self.target, key),
+        }
+    }
+    
+    pub(crate) fn delete(&self, interpreter: &mut Interpreter, key: &str) -> Result<bool, JsEngineError> {
+        match self.trap(interpreter, "deleteProperty")? {
+            Some(trap) => {
+                let result = self.call_trap(interpreter, &trap, vec![self.target.clone(), Value::String(JsString::from(key))])?;
+                Ok(interpreter.is_truthy(&result))
+            },
+            None => interpreter.delete_property(&self.target, key),
+        }
+    }
+}
+
+// The global `Proxy(target, handler)`, which does what
+// `new Proxy(target, handler)` does
+pub(crate) fn proxy_constructor() -> Value {
+    Value::NativeFunction(Rc::new(|arguments| {
+        let (target, handler) = (argument(arguments, 0), argument(arguments, 1));
+        if !is_object(&target) || !is_object(&handler) {
+            return Err(JsEngineError::TypeError {
+                message: "Cannot create proxy with a non-object as target or handler".to_string(),
+            });
+        }
+        Ok(Rc::new(Proxy { target, handler }).into_value())
+    }))
+}
+
+// The global `Reflect`: get, set, has and deleteProperty, each doing what
+// the operator or assignment it is named after does, or would do, to its
+// target. set and deleteProperty report whether they succeeded instead of
+// throwing.
+pub(crate) fn reflect_namespace() -> Value {
+    let get = reflect("get", |interpreter, target, key, _| interpreter.get_property(target, &key));
+    let set = reflect("set", |interpreter, target, key, arguments| {
+        let value = argument(arguments, 2);
+        if let Some(proxy) = Proxy::of(target) {
+            return Ok(Value::Boolean(proxy.set(interpreter, target, &key, value)?));
+        }
+        if let Value::Object(object) = target
+            && object.borrow().is_frozen()
+        {
+            return Ok(Value::Boolean(false));
+        }
+        interpreter.set_property(target, &key, value)?;
+        Ok(Value::Boolean(true))
+    });
+    let has = reflect("has", |interpreter, target, key, _| Ok(Value::Boolean(interpreter.has_property(target, &key)?)));
+    let delete_property = reflect("deleteProperty", |interpreter, target, key, _| Ok(Value::Boolean(interpreter.delete_property(target, &key)?)));
+    
+    let properties = PropertyMap::from([
+        ("get", get),
+        ("set", set),
+        ("has", has),
+        ("deleteProperty", delete_property),
+    ]);
+    Value::Object(Rc::new(RefCell::new(properties)))
+}
+
+// A Reflect function, given its target and the key converted to a string.
+// The target must be an object.
+fn reflect(name: &'static str, operation: fn(&mut Interpreter, &Value, String, &[Value]) -> Result<Value, JsEngineError>) -> Value {
+    let function = PropertyMap::callable(Rc::new(move |interpreter, arguments| {
+        let target = argument(arguments, 0);
+        if !is_object(&target) {
+            return Err(JsEngineError::TypeError {
+                message: format!("Reflect.{} called on non-object", name),
+            });
+        }
+        let key = interpreter.property_key(argument(arguments, 1))?;
+        operation(interpreter, &target, key, arguments)
+    }));
+    Value::Object(Rc::new(RefCell::new(function)))
+}
+
+fn is_object(value: &Value) -> bool {
+    matches!(value, Value::Object(_) | Value::Function(_) | Value::NativeFunction(_))
+}
+
+fn argument(arguments: &[Value], index: usize) -> Value {
+    arguments.get(index).cloned().unwrap_or(Value::Undefined)
+}
@@ -0,0 +1,191 @@
+// src/python.rs
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, Value};
+use crate::object::PropertyMap;
+use crate::string::JsString;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyRuntimeError, PyTypeError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyString, PyTuple};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Python module exposing the engine, built into the cdylib with the python
+// feature (e.g. `maturin develop --features python,pyo3/extension-module`).
+//
+//     import jays
+//     engine = jays.Engine()
+//     engine.register("greet", lambda name: "Hello, " + name)
+//     engine.eval('greet("world");')
+//
+// Numbers, strings, booleans and null convert to their Python counterparts
+// and back; undefined becomes None. Objects are copied into dicts, and JS
+// functions are returned as callables that run in the engine they came from.
+
+create_exception!(jays, JsError, PyException, "Raised when a script fails");
+
+#[pyclass(unsendable)]
+pub struct Engine {
+    interpreter: Rc<RefCell<Interpreter>>,
+}
+
+#[pymethods]
+impl Engine {
+    #[new]
+    fn new() -> Self {
+        Engine {
+            interpreter: Rc::new(RefCell::new(Interpreter::new())),
+        }
+    }
+
+    // Runs a script in the engine's global scope and returns its result
+    fn eval(&self, py: Python<'_>, source: &str) -> PyResult<Py<PyAny>> {
+        let result = busy(&self.interpreter)?.eval(source).map_err(to_py_error)?;
+        to_python(py, &self.interpreter, &result)
+    }
+
+    // Defines a global JS function that calls a Python callable
+    fn register(&self, name: &str, function: Py<PyAny>) -> PyResult<()> {
+        let function = from_python_callable(function, &self.interpreter);
+        busy(&self.interpreter)?.define_global(name, function);
+        Ok(())
+    }
+
+    // Defines a global variable from a Python value
+    fn set(&self, name: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        let value = from_python(value, &self.interpreter)?;
+        busy(&self.interpreter)?.define_global(name, value);
+        Ok(())
+    }
+}
+
+// A JS function handed to Python
+#[pyclass(unsendable)]
+pub struct Function {
+    function: Value,
+    interpreter: Rc<RefCell<Interpreter>>,
+}
+
+#[pymethods]
+impl Function {
+    #[pyo3(signature = (*arguments))]
+    fn __call__(&self, py: Python<'_>, arguments: &Bound<'_, PyTuple>) -> PyResult<Py<PyAny>> {
+        let arguments = arguments
+            .iter()
+            .map(|argument| from_python(&argument, &self.interpreter))
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let result = busy(&self.interpreter)?.call_function(&self.function, &arguments).map_err(to_py_error)?;
+        to_python(py, &self.interpreter, &result)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<jays function {:?}>", self.function)
+    }
+}
+
+// Runs a script in a fresh engine
+#[pyfunction]
+fn eval(py: Python<'_>, source: &str) -> PyResult<Py<PyAny>> {
+    Engine::new().eval(py, source)
+}
+
+#[pymodule]
+fn jays(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Engine>()?;
+    module.add_class::<Function>()?;
+    module.add_function(wrap_pyfunction!(eval, module)?)?;
+    module.add("JsError", module.py().get_type::<JsError>())?;
+    Ok(())
+}
+
+// The engine cannot be reentered, e.g. by a Python callback that calls back
+// into the script that is calling it
+fn busy(interpreter: &Rc<RefCell<Interpreter>>) -> PyResult<std::cell::RefMut<'_, Interpreter>> {
+    interpreter
+        .try_borrow_mut()
+        .map_err(|_| PyRuntimeError::new_err("the engine is already running a script"))
+}
+
+fn to_py_error(error: JsEngineError) -> PyErr {
+    JsError::new_err(error.to_string())
+}
+
+fn to_python(py: Python<'_>, interpreter: &Rc<RefCell<Interpreter>>, value: &Value) -> PyResult<Py<PyAny>> {
+    Ok(match value {
+        Value::Number(n) => n.into_pyobject(py)?.into_any().unbind(),
+        Value::String(s) => s.as_str().into_pyobject(py)?.into_any().unbind(),
+        Value::Boolean(b) => b.into_pyobject(py)?.to_owned().into_any().unbind(),
+        Value::Null | Value::Undefined => py.None(),
+        Value::Function(_) | Value::NativeFunction(_) => Py::new(
+            py,
+            Function {
+                function: value.clone(),
+                interpreter: Rc::clone(interpreter),
+            },
+        )?
+        .into_any(),
+        Value::Object(properties) => {
+            let dict = PyDict::new(py);
+            for (key, value) in properties.borrow().iter() {
+                dict.set_item(key, to_python(py, interpreter, value)?)?;
+            }
+            dict.into_any().unbind()
+        },
+    })
+}
+
+fn from_python(value: &Bound<'_, PyAny>, interpreter: &Rc<RefCell<Interpreter>>) -> PyResult<Value> {
+    if value.is_none() {
+        Ok(Value::Null)
+    } else if let Ok(b) = value.cast::<PyBool>() {
+        Ok(Value::Boolean(b.is_true()))
+    } else if value.is_instance_of::<PyInt>() || value.is_instance_of::<PyFloat>() {
+        Ok(Value::Number(value.extract()?))
+    } else if let Ok(s) = value.cast::<PyString>() {
+        Ok(Value::String(JsString::from(s.to_str()?)))
+    } else if let Ok(function) = value.cast::<Function>() {
+        Ok(function.borrow().function.clone())
+    } else if let Ok(dict) = value.cast::<PyDict>() {
+        let mut properties = PropertyMap::new();
+        for (key, value) in dict.iter() {
+            properties.insert(key.str()?.to_str()?.to_string(), from_python(&value, interpreter)?);
+        }
+        Ok(Value::Object(Rc::new(RefCell::new(properties))))
+    } else if value.is_callable() {
+        Ok(from_python_callable(value.clone().unbind(), interpreter))
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "cannot convert {} to a JavaScript value",
+            value.get_type().name()?
+        )))
+    }
+}
+
+// Wraps a Python callable as a native function. Python exceptions surface in
+// the script as runtime errors.
+fn from_python_callable(function: Py<PyAny>, interpreter: &Rc<RefCell<Interpreter>>) -> Value {
+    // Held weakly: the function is stored in the interpreter's globals
+    let interpreter = Rc::downgrade(interpreter);
+
+    Value::NativeFunction(Rc::new(move |arguments: &[Value]| -> Result<Value, JsEngineError> {
+        let Some(interpreter) = interpreter.upgrade() else {
+            return Ok(Value::Undefined);
+        };
+
+        Python::attach(|py| {
+            let call = || -> PyResult<Value> {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| to_python(py, &interpreter, argument))
+                    .collect::<PyResult<Vec<_>>>()?;
+                let result = function.call1(py, PyTuple::new(py, arguments)?)?;
+                from_python(result.bind(py), &interpreter)
+            };
+
+            call().map_err(|error| JsEngineError::RuntimeError {
+                message: format!("Python error: {}", error),
+            })
+        })
+    }))
+}
@@ -0,0 +1,366 @@
+// src/realm.rs
+use crate::collections::Collection;
+use crate::error::JsEngineError;
+use crate::interpreter::{Environment, Interpreter, InterpreterBuilder, JsFunction, Value};
+use crate::object::{InternalSlot, PropertyMap};
+use crate::typed_array::ArrayBuffer;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// A global environment prepared once and stamped out into many interpreters,
+// e.g. one per request in a server. The setup (prelude scripts, host
+// bindings) runs a single time; each instance starts from a copy of the
+// globals it left behind instead of redoing that work.
+//
+//     let template = Interpreter::builder()
+//         .with_stdlib(&[Std::Math, Std::Json])
+//         .template(|interpreter| {
+//             interpreter.define_global("version", Value::Number(2.0));
+//             interpreter.eval("function greet(name) { return \"Hello, \" + name; }")?;
+//             Ok(())
+//         })?;
+//
+//     let mut context = template.instantiate();
+//
+// Instances are isolated from the template and from each other: objects,
+// functions and the scopes of closures the setup left reachable from the
+// globals are copied into each instance when it is created, and reassigning
+// a global only affects that instance. The built-ins that keep no state,
+// such as Object, JSON and the typed array constructors, are made once for
+// the template; each instance gets copies of their objects and shares their
+// functions. Its console, timers, events and other built-ins with state are
+// its own. Setup that leaves objects in the globals that cannot be copied,
+// such as promises, fails; see Interpreter::transfer for which.
+pub struct RealmTemplate {
+    builder: InterpreterBuilder,
+    globals: Rc<RefCell<Environment>>,
+    builtins: Vec<(Rc<str>, Value)>,
+    bindings: Vec<(Rc<str>, Value)>,
+}
+
+impl RealmTemplate {
+    pub(crate) fn new(
+        builder: InterpreterBuilder,
+        setup: impl FnOnce(&mut Interpreter) -> Result<(), JsEngineError>,
+    ) -> Result<Self, JsEngineError> {
+        let builtins = builder.builtins();
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        for (name, value) in &builtins {
+            globals.borrow_mut().define(Rc::clone(name), value.clone());
+        }
+        let mut interpreter = builder.build_in(globals);
+        let initial: HashMap<Rc<str>, Value> = interpreter
+            .globals()
+            .borrow()
+            .bindings()
+            .map(|(name, value)| (Rc::clone(name), value.clone()))
+            .collect();
+        
+        setup(&mut interpreter)?;
+        
+        let globals = Rc::clone(interpreter.globals());
+        let bindings = globals
+            .borrow()
+            .bindings()
+            .filter(|(name, value)| !initial.get(*name).is_some_and(|builtin| same_value(builtin, value)))
+            .map(|(name, value)| (Rc::clone(name), value.clone()))
+            .collect();
+        
+        let template = RealmTemplate {
+            builder,
+            globals,
+            builtins,
+            bindings,
+        };
+        // A template holding globals that cannot be copied, such as
+        // promises, is refused now rather than each instance sharing them
+        if let (_, Some(error)) = template.copy_globals() {
+            return Err(error);
+        }
+        Ok(template)
+    }
+    
+    // A fresh interpreter with the template's globals
+    pub fn instantiate(&self) -> Interpreter {
+        self.copy_globals().0
+    }
+    
+    // Also gives the first error from copying a global. Setup has been
+    // checked, so that happens only when a function from it has since
+    // stored something that cannot be copied in one of the globals' objects;
+    // the instance then shares that global with the template.
+    fn copy_globals(&self) -> (Interpreter, Option<JsEngineError>) {
+        let target = Rc::new(RefCell::new(Environment::new()));
+        let mut copies = Copies::default();
+        let mut first_error = None;
+        let mut copy_into = |target: &Rc<RefCell<Environment>>, bindings: &[(Rc<str>, Value)]| {
+            for (name, value) in bindings {
+                let value = copy(value, &self.globals, target, &mut copies).unwrap_or_else(|error| {
+                    first_error.get_or_insert(error);
+                    value.clone()
+                });
+                target.borrow_mut().define(Rc::clone(name), value);
+            }
+        };
+        
+        // The built-ins are in place before the interpreter adds its own, and
+        // what the setup defined replaces both
+        copy_into(&target, &self.builtins);
+        let interpreter = self.builder.build_in(target);
+        copy_into(interpreter.globals(), &self.bindings);
+        
+        (interpreter, first_error)
+    }
+}
+
+// Whether a binding still holds the built-in it started with
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::NativeFunction(a), Value::NativeFunction(b)) => std::ptr::addr_eq(Rc::as_ptr(a), Rc::as_ptr(b)),
+        (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+        (Value::Object(a), Value::Object(b)) => Rc::ptr_eq(a, b),
+        _ => false,
+    }
+}
+
+// What has been copied for an instance so far, by the address of the
+// original, so that shared references and cycles are preserved
+#[derive(Default)]
+struct Copies {
+    values: HashMap<*const (), Value>,
+    scopes: HashMap<*const (), Rc<RefCell<Environment>>>,
+}
+
+// Copies objects, and functions along with the scopes they closed over; the
+// template's globals stand for the instance's
+fn copy(
+    value: &Value,
+    from: &Rc<RefCell<Environment>>,
+    to: &Rc<RefCell<Environment>>,
+    copies: &mut Copies,
+) -> Result<Value, JsEngineError> {
+    match value {
+        Value::Object(object) => {
+            let key = Rc::as_ptr(object) as *const ();
+            if let Some(copied) = copies.values.get(&key) {
+                return Ok(copied.clone());
+            }
+            
+            // The properties are cloned at once, then the objects and
+            // functions among them replaced with their copies. Callable
+            // objects are built in, so their behaviour is shared.
+            let copied = Rc::new(RefCell::new(object.borrow().empty_like()));
+            copies.values.insert(key, Value::Object(Rc::clone(&copied)));
+            let slot = object.borrow().slot().cloned();
+            if let Some(slot) = slot {
+                let slot = copy_slot(&slot, |value| copy(value, from, to, copies))?;
+                copied.borrow_mut().set_slot(Some(slot));
+            }
+            
+            let mut contents = object.borrow().clone();
+            contents.set_slot(copied.borrow().slot().cloned());
+            for value in contents.values_mut() {
+                if matches!(value, Value::Object(_) | Value::Function(_)) {
+                    *value = copy(value, from, to, copies)?;
+                }
+            }
+            if let Some(prototype) = contents.prototype().cloned()
+                && let Value::Object(prototype) = copy(&Value::Object(prototype), from, to, copies)?
+            {
+                contents.set_prototype(Some(prototype));
+            }
+            *copied.borrow_mut() = contents;
+            copy_entries(object, &copied, |value| copy(value, from, to, copies))?;
+            
+            Ok(Value::Object(copied))
+        },
+        Value::Function(function) => {
+            let key = Rc::as_ptr(function) as *const ();
+            if let Some(copied) = copies.values.get(&key) {
+                return Ok(copied.clone());
+            }
+            
+            // A function's scope often holds the function itself, which is
+            // then copied along with it
+            let closure = copy_scope(&function.closure, from, to, copies)?;
+            let copied = copies
+                .values
+                .entry(key)
+                .or_insert_with(|| {
+                    Value::Function(Rc::new(JsFunction {
+                        name: function.name.clone(),
+                        parameters: function.parameters.clone(),
+                        body: Rc::clone(&function.body),
+                        closure,
+                        source: function.source.clone(),
+                        span: function.span,
+                        kind: function.kind,
+                    }))
+                })
+                .clone();
+            Ok(copied)
+        },
+        other => Ok(other.clone()),
+    }
+}
+
+// Copies a scope a function closed over, and the scopes enclosing it up to
+// the template's globals
+fn copy_scope(
+    scope: &Rc<RefCell<Environment>>,
+    from: &Rc<RefCell<Environment>>,
+    to: &Rc<RefCell<Environment>>,
+    copies: &mut Copies,
+) -> Result<Rc<RefCell<Environment>>, JsEngineError> {
+    if Rc::ptr_eq(scope, from) {
+        return Ok(Rc::clone(to));
+    }
+    let key = Rc::as_ptr(scope) as *const ();
+    if let Some(copied) = copies.scopes.get(&key) {
+        return Ok(Rc::clone(copied));
+    }
+    
+    let enclosing = scope.borrow().enclosing().cloned();
+    let enclosing = match enclosing {
+        Some(enclosing) => Some(copy_scope(&enclosing, from, to, copies)?),
+        None => None,
+    };
+    let copied = Rc::new(RefCell::new(scope.borrow().empty_like(enclosing)));
+    copies.scopes.insert(key, Rc::clone(&copied));
+    
+    let bindings: Vec<(Rc<str>, Value)> = scope
+        .borrow()
+        .bindings()
+        .map(|(name, value)| (Rc::clone(name), value.clone()))
+        .collect();
+    for (name, value) in bindings {
+        let value = copy(&value, from, to, copies)?;
+        copied.borrow_mut().insert(name, value);
+    }
+    Ok(copied)
+}
+
+impl Interpreter {
+    // Deep-copies a value for use in another interpreter, as structured
+    // clone does for postMessage: nested objects are copied rather than
+    // shared, and shared references and cycles are preserved within the
+    // copy. As with structured clone, copies have no prototype. Functions
+    // belong to the interpreter that created them and cannot be
+    // transferred, and neither can promises, proxies, iterators and regular
+    // expressions, which fail with a DataCloneError. Values carry no
+    // reference to their interpreter, so the copy can be used in `_target`
+    // as-is.
+    pub fn transfer(&self, value: &Value, _target: &Interpreter) -> Result<Value, JsEngineError> {
+        let mut copies = HashMap::new();
+        let mut pending = Vec::new();
+        let copied = clone_shallow(value, &mut copies, &mut pending)?;
+        
+        // Objects are filled in from a work list rather than by recursion, so
+        // deeply nested data cannot overflow the stack
+        while let Some((source, destination)) = pending.pop() {
+            copy_contents(&source, &destination, |value| clone_shallow(value, &mut copies, &mut pending))?;
+        }
+        
+        Ok(copied)
+    }
+}
+
+type Object = Rc<RefCell<PropertyMap>>;
+
+// Copies a primitive, or allocates the copy of an object and queues its
+// properties to be copied
+fn clone_shallow(value: &Value, copies: &mut HashMap<*const (), Object>, pending: &mut Vec<(Object, Object)>) -> Result<Value, JsEngineError> {
+    match value {
+        _ if value.is_callable() => Err(JsEngineError::TypeError {
+            message: "functions cannot be transferred between interpreters".to_string(),
+        }),
+        Value::Object(object) => {
+            let key = Rc::as_ptr(object) as *const ();
+            if let Some(copy) = copies.get(&key) {
+                return Ok(Value::Object(Rc::clone(copy)));
+            }
+            let copy = Rc::new(RefCell::new(object.borrow().empty_like()));
+            copies.insert(key, Rc::clone(&copy));
+            pending.push((Rc::clone(object), Rc::clone(&copy)));
+            let slot = object.borrow().slot().cloned();
+            if let Some(slot) = slot {
+                let slot = copy_slot(&slot, |value| clone_shallow(value, copies, pending))?;
+                copy.borrow_mut().set_slot(Some(slot));
+            }
+            Ok(Value::Object(copy))
+        },
+        other => Ok(other.clone()),
+    }
+}
+
+// The internal slot of the copy of an object, for transfer and templates
+// alike. `copy_object` copies the objects the slot refers to. Maps and sets
+// start out empty and are filled by copy_contents. Buffers are copied with
+// their bytes; a view is copied along with its buffer's object, so that
+// views of one buffer are views of one buffer in the copy too. Promises,
+// proxies, iterators and regular expressions hold state that belongs to the
+// interpreter or to work in progress, and are not copied.
+fn copy_slot(
+    slot: &InternalSlot,
+    mut copy_object: impl FnMut(&Value) -> Result<Value, JsEngineError>,
+) -> Result<InternalSlot, JsEngineError> {
+    match slot {
+        InternalSlot::Promise(_) => Err(clone_error("Promise")),
+        InternalSlot::Collection(collection) => Ok(InternalSlot::Collection(Collection::new(collection.kind()))),
+        InternalSlot::Proxy(_) => Err(clone_error("Proxy")),
+        InternalSlot::Iterator(state) => Err(clone_error(state.borrow().name())),
+        InternalSlot::ArrayBuffer(buffer) => Ok(InternalSlot::ArrayBuffer(ArrayBuffer::new(buffer.bytes()))),
+        InternalSlot::TypedArray(array) => {
+            let buffer = copy_object(&array.buffer_value())?;
+            array.with_buffer(buffer).map(InternalSlot::TypedArray).ok_or_else(|| clone_error(&array.kind().name()))
+        },
+        InternalSlot::DataView(view) => {
+            let buffer = copy_object(view.buffer())?;
+            view.with_buffer(buffer).map(InternalSlot::DataView).ok_or_else(|| clone_error("DataView"))
+        },
+        #[cfg(feature = "regex")]
+        InternalSlot::RegExp(_) => Err(clone_error("RegExp")),
+        InternalSlot::Console(_) => Err(clone_error("console")),
+    }
+}
+
+fn clone_error(name: &str) -> JsEngineError {
+    JsEngineError::throw("DataCloneError", format!("#<{}> could not be cloned", name))
+}
+
+// Copies the properties of `source`, and its entries if it is a map or set,
+// into its copy `destination`
+fn copy_contents(
+    source: &Object,
+    destination: &Object,
+    mut copy_value: impl FnMut(&Value) -> Result<Value, JsEngineError>,
+) -> Result<(), JsEngineError> {
+    let properties: Vec<(String, Value)> = source
+        .borrow()
+        .iter()
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    for (name, value) in properties {
+        let value = copy_value(&value)?;
+        destination.borrow_mut().insert(name, value);
+    }
+    copy_entries(source, destination, copy_value)
+}
+
+// Copies the entries of `source` into its copy `destination`, if they are
+// maps or sets
+fn copy_entries(
+    source: &Object,
+    destination: &Object,
+    mut copy_value: impl FnMut(&Value) -> Result<Value, JsEngineError>,
+) -> Result<(), JsEngineError> {
+    if let Some(entries) = Collection::of(&Value::Object(Rc::clone(source))).map(|collection| collection.entries())
+        && let Some(collection) = Collection::of(&Value::Object(Rc::clone(destination)))
+    {
+        for (key, value) in entries {
+            collection.set(copy_value(&key)?, copy_value(&value)?);
+        }
+    }
+    Ok(())
+}
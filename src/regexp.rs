@@ -0,0 +1,458 @@
+// src/regexp.rs
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, Value};
+use crate::object::{InternalSlot, PropertyMap};
+use crate::string::JsString;
+use regex::{Captures, Regex};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+// Regular expression objects, made by `/pattern/flags` literals and the
+// RegExp global and run by the regex crate. Patterns are translated from
+// JavaScript syntax to the crate's: \d, \w and \b only know ASCII, as in
+// JavaScript, `[^]` matches any character, and escaped punctuation that the
+// crate does not accept escaped is taken literally. Backreferences and
+// lookaround have no translation and are syntax errors.
+//
+// The flags are g, i, m, s, u and y. Positions that scripts see (`index`
+// and `lastIndex`) are in UTF-16 code units. Regular expression objects
+// keep their state in an internal slot, through which their properties and
+// methods are found.
+
+pub struct RegExp {
+    source: String,
+    flags: String,
+    regex: Regex,
+    last_index: Cell<usize>,
+}
+
+impl RegExp {
+    pub fn new(source: &str, flags: &str) -> Result<Rc<RegExp>, JsEngineError> {
+        let invalid_flags = || JsEngineError::SyntaxError {
+            message: format!("Invalid flags supplied to RegExp constructor '{}'", flags),
+        };
+        let mut modifiers = String::new();
+        for (index, flag) in flags.char_indices() {
+            if !"gimsuy".contains(flag) || flags[..index].contains(flag) {
+                return Err(invalid_flags());
+            }
+            if "ims".contains(flag) {
+                modifiers.push(flag);
+            }
+        }
+        
+        let invalid = |reason: String| JsEngineError::SyntaxError {
+            message: format!("Invalid regular expression: /{}/{}: {}", source, flags, reason),
+        };
+        let mut pattern = translate(source).map_err(|reason| invalid(reason.to_string()))?;
+        if !modifiers.is_empty() {
+            pattern = format!("(?{}){}", modifiers, pattern);
+        }
+        let regex = Regex::new(&pattern).map_err(|error| invalid(error.to_string()))?;
+        
+        Ok(Rc::new(RegExp {
+            source: source.to_string(),
+            flags: flags.to_string(),
+            regex,
+            last_index: Cell::new(0),
+        }))
+    }
+    
+    // The regular expression behind a RegExp object
+    pub fn of(value: &Value) -> Option<Rc<RegExp>> {
+        match value {
+            Value::Object(object) => match object.borrow().slot() {
+                Some(InternalSlot::RegExp(regexp)) => Some(Rc::clone(regexp)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    
+    pub fn into_value(self: Rc<Self>) -> Value {
+        Value::Object(Rc::new(RefCell::new(PropertyMap::with_slot(InternalSlot::RegExp(self)))))
+    }
+    
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    
+    pub fn flags(&self) -> &str {
+        &self.flags
+    }
+    
+    fn has_flag(&self, flag: char) -> bool {
+        self.flags.contains(flag)
+    }
+    
+    // The next match, as RegExp.prototype.exec finds it: global and sticky
+    // expressions start at lastIndex and move it past the match, or back to
+    // 0 when there is none, and sticky ones only match right at lastIndex
+    fn next_match<'t>(&self, text: &'t str) -> Option<Captures<'t>> {
+        let (global, sticky) = (self.has_flag('g'), self.has_flag('y'));
+        if !global && !sticky {
+            return self.regex.captures(text);
+        }
+        
+        let found = byte_offset(text, self.last_index.get())
+            .and_then(|start| self.regex.captures_at(text, start).filter(|captures| !sticky || captures.get(0).is_some_and(|whole| whole.start() == start)));
+        match &found {
+            Some(captures) => self.last_index.set(utf16_offset(text, captures.get(0).map_or(0, |whole| whole.end()))),
+            None => self.last_index.set(0),
+        }
+        found
+    }
+    
+    fn exec(&self, text: &JsString) -> Value {
+        let text_str = text.as_str();
+        match self.next_match(text_str) {
+            Some(captures) => self.match_array(&captures, text),
+            None => Value::Null,
+        }
+    }
+    
+    // The array exec gives back: the match and its groups, with the index
+    // of the match, the whole input and the named groups
+    fn match_array(&self, captures: &Captures, text: &JsString) -> Value {
+        let found = Found::new(&self.regex, captures);
+        let mut array = PropertyMap::array(found.groups.iter().map(optional_string).collect());
+        array.insert("index".to_string(), Value::Number(utf16_offset(text.as_str(), found.start) as f64));
+        array.insert("input".to_string(), Value::String(text.clone()));
+        array.insert("groups".to_string(), found.named_groups());
+        Value::Object(Rc::new(RefCell::new(array)))
+    }
+    
+    // The properties and methods of a RegExp object
+    pub(crate) fn property(self: &Rc<Self>, name: &str) -> Option<Value> {
+        let flag = |flag: char| Some(Value::Boolean(self.has_flag(flag)));
+        let regexp = Rc::clone(self);
+        match name {
+            "source" => Some(Value::String(JsString::from(self.source.as_str()))),
+            "flags" => Some(Value::String(JsString::from(self.flags.as_str()))),
+            "global" => flag('g'),
+            "ignoreCase" => flag('i'),
+            "multiline" => flag('m'),
+            "dotAll" => flag('s'),
+            "unicode" => flag('u'),
+            "sticky" => flag('y'),
+            "lastIndex" => Some(Value::Number(self.last_index.get() as f64)),
+            "test" => Some(method(move |arguments| {
+                let text = argument(arguments, 0).to_string();
+                Ok(Value::Boolean(regexp.next_match(&text).is_some()))
+            })),
+            "exec" => Some(method(move |arguments| {
+                let text = match argument(arguments, 0) {
+                    Value::String(text) => text,
+                    other => JsString::from(other.to_string()),
+                };
+                Ok(regexp.exec(&text))
+            })),
+            "toString" => Some(method(move |_| Ok(Value::String(JsString::from(regexp.to_string()))))),
+            _ => None,
+        }
+    }
+    
+    // lastIndex is the only property scripts can change; returns false for
+    // the others
+    pub(crate) fn set_property(&self, name: &str, value: &Value) -> bool {
+        if name != "lastIndex" {
+            return false;
+        }
+        let index = crate::stdlib::to_number(value);
+        self.last_index.set(if index > 0.0 { index as usize } else { 0 });
+        true
+    }
+}
+
+impl std::fmt::Display for RegExp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "/{}/{}", self.source, self.flags)
+    }
+}
+
+// The global `RegExp(pattern, flags)`. A RegExp passed as the pattern is
+// copied, keeping its flags unless new ones are given.
+pub(crate) fn regexp_constructor() -> Value {
+    method(|arguments| {
+        let flags = match argument(arguments, 1) {
+            Value::Undefined => None,
+            flags => Some(flags.to_string()),
+        };
+        let regexp = match (RegExp::of(&argument(arguments, 0)), argument(arguments, 0)) {
+            (Some(regexp), _) => RegExp::new(&regexp.source, flags.as_deref().unwrap_or(&regexp.flags))?,
+            (None, Value::Undefined) => RegExp::new("(?:)", flags.as_deref().unwrap_or(""))?,
+            (None, pattern) => RegExp::new(&pattern.to_string(), flags.as_deref().unwrap_or(""))?,
+        };
+        Ok(regexp.into_value())
+    })
+}
+
+// String.prototype.match: the first match as exec gives it, or every match
+// when the expression is global. A pattern that is not a RegExp is made
+// into one.
+pub(crate) fn string_match(text: &JsString, pattern: &Value) -> Result<Value, JsEngineError> {
+    let regexp = match (RegExp::of(pattern), pattern) {
+        (Some(regexp), _) => regexp,
+        (None, Value::Undefined) => RegExp::new("(?:)", "")?,
+        (None, pattern) => RegExp::new(&pattern.to_string(), "")?,
+    };
+    if !regexp.has_flag('g') {
+        return Ok(regexp.exec(text));
+    }
+    
+    regexp.last_index.set(0);
+    let matches: Vec<Value> = regexp.regex.find_iter(text.as_str()).map(|found| Value::String(JsString::from(found.as_str()))).collect();
+    if matches.is_empty() {
+        return Ok(Value::Null);
+    }
+    Ok(Value::Object(Rc::new(RefCell::new(PropertyMap::array(matches)))))
+}
+
+// String.prototype.replace. A string pattern replaces its first occurrence,
+// a RegExp its next match, or every match when it is global. The
+// replacement is a function called with the match, its groups, its index
+// and the whole string, or a string in which $&, $1, $<name>, $`, $' and $$
+// stand for parts of the match.
+pub(crate) fn string_replace(interpreter: &mut Interpreter, text: &JsString, pattern: &Value, replacement: &Value) -> Result<Value, JsEngineError> {
+    let text_str = text.as_str();
+    let replacement_text = (!replacement.is_callable()).then(|| replacement.to_string());
+    let mut replace = |found: &Found| -> Result<String, JsEngineError> {
+        match &replacement_text {
+            Some(replacement) => Ok(found.expand(replacement, text_str)),
+            None => {
+                let mut arguments: Vec<Value> = found.groups.iter().map(optional_string).collect();
+                arguments.push(Value::Number(utf16_offset(text_str, found.start) as f64));
+                arguments.push(Value::String(text.clone()));
+                if !found.names.is_empty() {
+                    arguments.push(found.named_groups());
+                }
+                Ok(interpreter.call_function(replacement, &arguments)?.to_string())
+            },
+        }
+    };
+    
+    let Some(regexp) = RegExp::of(pattern) else {
+        let pattern = pattern.to_string();
+        let Some(start) = text_str.find(&pattern) else {
+            return Ok(Value::String(text.clone()));
+        };
+        let found = Found::literal(&text_str[start..start + pattern.len()], start);
+        let replaced = replace(&found)?;
+        return Ok(Value::String(JsString::from(format!("{}{}{}", &text_str[..start], replaced, &text_str[found.end..]))));
+    };
+    
+    let matches: Vec<Found> = if regexp.has_flag('g') {
+        regexp.last_index.set(0);
+        regexp.regex.captures_iter(text_str).map(|captures| Found::new(&regexp.regex, &captures)).collect()
+    } else {
+        regexp.next_match(text_str).map(|captures| Found::new(&regexp.regex, &captures)).into_iter().collect()
+    };
+    
+    let mut result = String::new();
+    let mut copied = 0;
+    for found in &matches {
+        result.push_str(&text_str[copied..found.start]);
+        result.push_str(&replace(found)?);
+        copied = found.end;
+    }
+    result.push_str(&text_str[copied..]);
+    Ok(Value::String(JsString::from(result)))
+}
+
+// A match with its groups copied out of the searched string, so that the
+// string can be handed to a replacement function while the match is in use
+struct Found {
+    start: usize, // Byte offsets of the whole match
+    end: usize,
+    groups: Vec<Option<String>>, // The whole match, then each group
+    names: Vec<(String, usize)>, // Named groups and their indices
+}
+
+impl Found {
+    fn new(regex: &Regex, captures: &Captures) -> Self {
+        let whole = captures.get(0).expect("a match has group 0");
+        Found {
+            start: whole.start(),
+            end: whole.end(),
+            groups: captures.iter().map(|group| group.map(|group| group.as_str().to_string())).collect(),
+            names: regex
+                .capture_names()
+                .enumerate()
+                .filter_map(|(index, name)| Some((name?.to_string(), index)))
+                .collect(),
+        }
+    }
+    
+    fn literal(matched: &str, start: usize) -> Self {
+        Found {
+            start,
+            end: start + matched.len(),
+            groups: vec![Some(matched.to_string())],
+            names: Vec::new(),
+        }
+    }
+    
+    // The groups object of a match, undefined without named groups
+    fn named_groups(&self) -> Value {
+        if self.names.is_empty() {
+            return Value::Undefined;
+        }
+        let groups: PropertyMap = self.names.iter().map(|(name, index)| (name.as_str(), optional_string(&self.groups[*index]))).collect();
+        Value::Object(Rc::new(RefCell::new(groups)))
+    }
+    
+    // GetSubstitution: the replacement string with its $ patterns filled in.
+    // Patterns naming groups that do not exist are left as they are.
+    fn expand(&self, replacement: &str, text: &str) -> String {
+        let group_count = self.groups.len() - 1;
+        let mut result = String::new();
+        let mut rest = replacement;
+        while let Some(dollar) = rest.find('$') {
+            result.push_str(&rest[..dollar]);
+            let after = &rest[dollar + 1..];
+            let digits: String = after.chars().take(2).take_while(char::is_ascii_digit).collect();
+            let (text_for, consumed) = match after.chars().next() {
+                Some('$') => (Some("$".to_string()), 1),
+                Some('&') => (self.groups[0].clone(), 1),
+                Some('`') => (Some(text[..self.start].to_string()), 1),
+                Some('\'') => (Some(text[self.end..].to_string()), 1),
+                Some('<') if !self.names.is_empty() => match after.find('>') {
+                    Some(close) => {
+                        let index = self.names.iter().find(|(name, _)| *name == after[1..close]).map(|(_, index)| *index);
+                        (Some(index.and_then(|index| self.groups[index].clone()).unwrap_or_default()), close + 1)
+                    },
+                    None => (None, 0),
+                },
+                Some(c) if c.is_ascii_digit() => {
+                    // Two digits when they name a group, else one
+                    let two = digits.parse::<usize>().ok().filter(|&n| digits.len() == 2 && (1..=group_count).contains(&n));
+                    let one = digits[..1].parse::<usize>().ok().filter(|&n| (1..=group_count).contains(&n));
+                    match (two, one) {
+                        (Some(n), _) => (Some(self.groups[n].clone().unwrap_or_default()), 2),
+                        (None, Some(n)) => (Some(self.groups[n].clone().unwrap_or_default()), 1),
+                        (None, None) => (None, 0),
+                    }
+                },
+                _ => (None, 0),
+            };
+            match text_for {
+                Some(text) => result.push_str(&text),
+                None => result.push('$'),
+            }
+            rest = &after[consumed..];
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+// Rewrites a JavaScript pattern in the regex crate's syntax
+fn translate(source: &str) -> Result<String, &'static str> {
+    let mut pattern = String::with_capacity(source.len());
+    let mut in_class = false;
+    let mut rest = source;
+    
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            '\\' => {
+                let Some(escaped) = rest.chars().next() else {
+                    return Err("\\ at end of pattern");
+                };
+                rest = &rest[escaped.len_utf8()..];
+                match (escaped, in_class) {
+                    ('d', false) => pattern.push_str("[0-9]"),
+                    ('D', false) => pattern.push_str("[^0-9]"),
+                    ('w', false) => pattern.push_str("[0-9A-Za-z_]"),
+                    ('W', false) => pattern.push_str("[^0-9A-Za-z_]"),
+                    ('d', true) => pattern.push_str("0-9"),
+                    ('w', true) => pattern.push_str("0-9A-Za-z_"),
+                    ('b', false) => pattern.push_str("(?-u:\\b)"),
+                    ('B', false) => pattern.push_str("(?-u:\\B)"),
+                    ('b', true) => pattern.push_str("\\x08"),
+                    ('0', _) => pattern.push_str("\\x00"),
+                    ('1'..='9', _) => return Err("backreferences are not supported"),
+                    ('c', _) => match rest.chars().next() {
+                        Some(letter) if letter.is_ascii_alphabetic() => {
+                            rest = &rest[1..];
+                            pattern.push_str(&format!("\\x{:02X}", letter as u8 % 32));
+                        },
+                        _ => return Err("invalid control escape"),
+                    },
+                    (escaped, _) if escaped.is_ascii_alphanumeric() => {
+                        pattern.push('\\');
+                        pattern.push(escaped);
+                    },
+                    (escaped, _) => pattern.push_str(&regex::escape(escaped.encode_utf8(&mut [0; 4]))),
+                }
+            },
+            '[' if !in_class => {
+                if let Some(after) = rest.strip_prefix("^]") {
+                    pattern.push_str("[\\s\\S]");
+                    rest = after;
+                } else if let Some(after) = rest.strip_prefix(']') {
+                    pattern.push_str("[^\\s\\S]");
+                    rest = after;
+                } else {
+                    in_class = true;
+                    pattern.push('[');
+                    if let Some(after) = rest.strip_prefix('^') {
+                        pattern.push('^');
+                        rest = after;
+                    }
+                }
+            },
+            ']' if in_class => {
+                in_class = false;
+                pattern.push(']');
+            },
+            // Nested classes and set operations in the crate's syntax
+            '[' | '&' | '~' if in_class => {
+                pattern.push('\\');
+                pattern.push(c);
+            },
+            '(' if !in_class && ["?=", "?!", "?<=", "?<!"].iter().any(|prefix| rest.starts_with(prefix)) => {
+                return Err("lookaround is not supported");
+            },
+            c => pattern.push(c),
+        }
+    }
+    
+    if in_class {
+        return Err("missing terminating ] for character class");
+    }
+    Ok(pattern)
+}
+
+// The UTF-16 length of `text` up to byte `offset`
+fn utf16_offset(text: &str, offset: usize) -> usize {
+    text[..offset].encode_utf16().count()
+}
+
+// The byte offset of UTF-16 `index` in `text`, rounded up to a character
+// boundary; None past the end
+fn byte_offset(text: &str, index: usize) -> Option<usize> {
+    let mut units = 0;
+    for (offset, c) in text.char_indices() {
+        if units >= index {
+            return Some(offset);
+        }
+        units += c.len_utf16();
+    }
+    (units >= index).then_some(text.len())
+}
+
+fn optional_string(group: &Option<String>) -> Value {
+    match group {
+        Some(group) => Value::String(JsString::from(group.as_str())),
+        None => Value::Undefined,
+    }
+}
+
+fn method(function: impl Fn(&[Value]) -> Result<Value, JsEngineError> + 'static) -> Value {
+    Value::NativeFunction(Rc::new(function))
+}
+
+fn argument(arguments: &[Value], index: usize) -> Value {
+    arguments.get(index).cloned().unwrap_or(Value::Undefined)
+}
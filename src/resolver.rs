@@ -0,0 +1,400 @@
+// src/resolver.rs
+use crate::ast::{var_bindings, walk_expr_mut, walk_pattern_mut, walk_stmt_mut, DeclarationKind, Expr, Parameter, Stmt, VisitorMut};
+use crate::heap::json_string;
+use crate::interpreter::GlobalCache;
+use crate::lexer::{line_column, Span};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Static pass run before execution. Any variable read whose name is not bound
+// by an enclosing block or function is rewritten to Expr::Global, which the
+// interpreter looks up directly in the global environment instead of walking
+// the whole environment chain.
+//
+// Along the way it records every declaration and reference in a symbol table,
+// which `analyze` exposes to editors and refactoring tools.
+//
+// Every construct that introduces a local binding must be registered here,
+// otherwise references to it would wrongly be treated as globals.
+pub fn resolve(statements: &mut [Stmt]) {
+    Resolver::new().run(statements);
+}
+
+// Builds the symbol table for a program without modifying it
+pub fn analyze(statements: &[Stmt]) -> SymbolTable {
+    let mut statements = statements.to_vec();
+    let mut resolver = Resolver::new();
+    resolver.run(&mut statements);
+    resolver.table
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScopeKind {
+    Global,
+    Function,
+    Block,
+}
+
+#[derive(Debug, Clone)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub parent: Option<usize>,
+    // Name of the function, for named function scopes, and of the block
+    // scope that binds a function expression's name
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+    Variable,
+    Function,
+    Parameter,
+}
+
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub scope: usize,
+    // The declared name for variables and parameters; the whole function
+    // for functions
+    pub span: Span,
+    pub references: Vec<Reference>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Reference {
+    pub span: Span,
+    pub write: bool, // Assignment rather than read
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    pub scopes: Vec<Scope>,
+    pub symbols: Vec<Symbol>,
+    // References to names declared nowhere in the program, such as the
+    // globals the interpreter provides
+    pub unresolved: Vec<(String, Reference)>,
+}
+
+impl SymbolTable {
+    // Positions are reported as 1-based lines and columns of `source`, the
+    // text the program was parsed from, alongside the raw byte offsets
+    pub fn to_json(&self, source: &str) -> String {
+        let position = |span: &Span| {
+            let (line, column) = line_column(source, span.start);
+            format!("\"line\":{},\"column\":{},\"start\":{},\"end\":{}", line, column, span.start, span.end)
+        };
+        let reference = |reference: &Reference| format!("{{{},\"write\":{}}}", position(&reference.span), reference.write);
+        
+        let scopes: Vec<String> = self
+            .scopes
+            .iter()
+            .enumerate()
+            .map(|(id, scope)| {
+                format!(
+                    "{{\"id\":{},\"kind\":{},\"parent\":{},\"name\":{}}}",
+                    id,
+                    json_string(scope_kind_name(scope.kind)),
+                    scope.parent.map_or("null".to_string(), |parent| parent.to_string()),
+                    scope.name.as_deref().map_or("null".to_string(), json_string),
+                )
+            })
+            .collect();
+        
+        let symbols: Vec<String> = self
+            .symbols
+            .iter()
+            .map(|symbol| {
+                format!(
+                    "{{\"name\":{},\"kind\":{},\"scope\":{},{},\"references\":[{}]}}",
+                    json_string(&symbol.name),
+                    json_string(symbol_kind_name(symbol.kind)),
+                    symbol.scope,
+                    position(&symbol.span),
+                    symbol.references.iter().map(reference).collect::<Vec<_>>().join(","),
+                )
+            })
+            .collect();
+        
+        let unresolved: Vec<String> = self
+            .unresolved
+            .iter()
+            .map(|(name, unresolved)| format!("{{\"name\":{},{}", json_string(name), &reference(unresolved)[1..]))
+            .collect();
+        
+        format!(
+            "{{\"scopes\":[{}],\"symbols\":[{}],\"unresolved\":[{}]}}",
+            scopes.join(","),
+            symbols.join(","),
+            unresolved.join(","),
+        )
+    }
+}
+
+fn scope_kind_name(kind: ScopeKind) -> &'static str {
+    match kind {
+        ScopeKind::Global => "global",
+        ScopeKind::Function => "function",
+        ScopeKind::Block => "block",
+    }
+}
+
+fn symbol_kind_name(kind: SymbolKind) -> &'static str {
+    match kind {
+        SymbolKind::Variable => "variable",
+        SymbolKind::Function => "function",
+        SymbolKind::Parameter => "parameter",
+    }
+}
+
+struct Resolver {
+    // Local scopes, innermost last: the scope id and its names' symbols
+    scopes: Vec<(usize, HashMap<String, usize>)>,
+    // Top-level names; references to these still become Expr::Global
+    globals: HashMap<String, usize>,
+    table: SymbolTable,
+}
+
+impl VisitorMut for Resolver {
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Block(statements) => {
+                // Declarations are visible to the whole block at runtime, even
+                // from code that runs before them, so collect them up front
+                self.push_scope(ScopeKind::Block, None);
+                self.declare_all(statements);
+                
+                for statement in statements.iter_mut() {
+                    self.visit_stmt_mut(statement);
+                }
+                
+                self.scopes.pop();
+            },
+            Stmt::For(initializer, ..) => {
+                // Variables declared in the head are scoped to the loop
+                self.push_scope(ScopeKind::Block, None);
+                if let Some(initializer) = initializer {
+                    self.declare_all(std::slice::from_ref(initializer.as_ref()));
+                }
+                
+                walk_stmt_mut(self, stmt);
+                self.scopes.pop();
+            },
+            Stmt::Switch(discriminant, clauses) => {
+                self.visit_expr_mut(discriminant);
+                
+                // The clauses form one block
+                self.push_scope(ScopeKind::Block, None);
+                for (_, body) in clauses.iter() {
+                    self.declare_all(body);
+                }
+                
+                for (test, body) in clauses.iter_mut() {
+                    if let Some(test) = test {
+                        self.visit_expr_mut(test);
+                    }
+                    for statement in body.iter_mut() {
+                        self.visit_stmt_mut(statement);
+                    }
+                }
+                self.scopes.pop();
+            },
+            Stmt::ForOf(name, iterable, body, span, declared) => {
+                self.visit_expr_mut(iterable);
+                
+                // A declared loop variable is scoped to the loop
+                if *declared {
+                    self.push_scope(ScopeKind::Block, None);
+                    self.declare(name, SymbolKind::Variable, *span);
+                    self.visit_stmt_mut(body);
+                    self.scopes.pop();
+                } else {
+                    self.reference(name, *span, true);
+                    self.visit_stmt_mut(body);
+                }
+            },
+            Stmt::Try(block, handler, finalizer) => {
+                self.visit_stmt_mut(block);
+                
+                // The catch parameter is scoped to its clause
+                if let Some((parameter, body)) = handler {
+                    self.push_scope(ScopeKind::Block, None);
+                    if let Some(parameter) = parameter {
+                        for (name, span) in parameter.bindings() {
+                            self.declare(name, SymbolKind::Variable, span);
+                        }
+                        walk_pattern_mut(self, parameter);
+                    }
+                    self.visit_stmt_mut(body);
+                    self.scopes.pop();
+                }
+                if let Some(finalizer) = finalizer {
+                    self.visit_stmt_mut(finalizer);
+                }
+            },
+            Stmt::Function(name, parameters, body, _, _) => {
+                let name = Some(name.clone());
+                self.function(name, parameters, body);
+            },
+            _ => walk_stmt_mut(self, stmt),
+        }
+    }
+    
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Variable(name, span) => {
+                self.reference(name, *span, false);
+                
+                if !self.is_local(name) {
+                    *expr = Expr::Global(std::mem::take(name), *span, GlobalCache::default());
+                }
+            },
+            Expr::Global(name, span, _) => self.reference(name, *span, false),
+            Expr::Assign(name, value, span) => {
+                self.visit_expr_mut(value);
+                self.reference(name, *span, true);
+            },
+            Expr::CompoundAssign(target, _, value) => {
+                // A variable target is read, then written
+                self.visit_expr_mut(target);
+                self.visit_expr_mut(value);
+                if let Expr::Variable(name, span) | Expr::Global(name, span, _) = target.as_ref() {
+                    self.reference(name, *span, true);
+                }
+            },
+            Expr::Function(parameters, body, _, _) => self.function(None, parameters, body),
+            Expr::NamedFunction(function) => {
+                // The name is only visible inside the function
+                let name = match function.as_ref() {
+                    Stmt::Function(name, ..) => Some(name.clone()),
+                    _ => None,
+                };
+                self.push_scope(ScopeKind::Block, name);
+                self.declare_all(std::slice::from_ref(function.as_ref()));
+                self.visit_stmt_mut(function);
+                self.scopes.pop();
+            },
+            _ => walk_expr_mut(self, expr),
+        }
+    }
+}
+
+impl Resolver {
+    fn new() -> Self {
+        Resolver {
+            scopes: Vec::new(),
+            globals: HashMap::new(),
+            table: SymbolTable::default(),
+        }
+    }
+    
+    fn run(&mut self, statements: &mut [Stmt]) {
+        // Top-level declarations live in the global environment, so no local
+        // scope is pushed for them
+        self.table.scopes.push(Scope {
+            kind: ScopeKind::Global,
+            parent: None,
+            name: None,
+        });
+        self.declare_vars(statements);
+        self.declare_all(statements);
+        
+        for statement in statements {
+            self.visit_stmt_mut(statement);
+        }
+    }
+    
+    fn function(&mut self, name: Option<String>, parameters: &[Parameter], body: &mut Rc<Stmt>) {
+        self.push_scope(ScopeKind::Function, name);
+        
+        for (parameter, parameter_span) in parameters {
+            self.declare(parameter, SymbolKind::Parameter, *parameter_span);
+        }
+        self.declare_vars(std::slice::from_ref(&**body));
+        
+        self.visit_stmt_mut(Rc::make_mut(body));
+        self.scopes.pop();
+    }
+    
+    fn push_scope(&mut self, kind: ScopeKind, name: Option<String>) {
+        let id = self.table.scopes.len();
+        self.table.scopes.push(Scope {
+            kind,
+            parent: Some(self.current_scope()),
+            name,
+        });
+        self.scopes.push((id, HashMap::new()));
+    }
+    
+    fn current_scope(&self) -> usize {
+        self.scopes.last().map_or(0, |(id, _)| *id)
+    }
+    
+    // Declares the `var` names of a function body or script in its scope,
+    // however deeply they are nested in blocks
+    fn declare_vars(&mut self, statements: &[Stmt]) {
+        for (name, span) in var_bindings(statements) {
+            self.declare(name, SymbolKind::Variable, span);
+        }
+    }
+    
+    // Declares the let, const and function names bound directly in a
+    // statement list
+    fn declare_all(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            match statement {
+                Stmt::Declaration(_, _, _, DeclarationKind::Var) | Stmt::Destructure(_, _, DeclarationKind::Var) => {},
+                Stmt::Declaration(name, _, span, _) => self.declare(name, SymbolKind::Variable, *span),
+                Stmt::Destructure(pattern, ..) => {
+                    for (name, span) in pattern.bindings() {
+                        self.declare(name, SymbolKind::Variable, span);
+                    }
+                },
+                Stmt::Function(name, _, _, span, _) => self.declare(name, SymbolKind::Function, *span),
+                _ => {},
+            }
+        }
+    }
+    
+    // Redeclaring a name in the same scope refers to the existing binding
+    fn declare(&mut self, name: &str, kind: SymbolKind, span: Span) {
+        let scope = self.current_scope();
+        let names = match self.scopes.last_mut() {
+            Some((_, names)) => names,
+            None => &mut self.globals,
+        };
+        
+        if names.contains_key(name) {
+            return;
+        }
+        
+        names.insert(name.to_string(), self.table.symbols.len());
+        self.table.symbols.push(Symbol {
+            name: name.to_string(),
+            kind,
+            scope,
+            span,
+            references: Vec::new(),
+        });
+    }
+    
+    fn reference(&mut self, name: &str, span: Span, write: bool) {
+        let reference = Reference { span, write };
+        let symbol = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|(_, names)| names.get(name))
+            .or_else(|| self.globals.get(name));
+        
+        match symbol {
+            Some(&symbol) => self.table.symbols[symbol].references.push(reference),
+            None => self.table.unresolved.push((name.to_string(), reference)),
+        }
+    }
+    
+    fn is_local(&self, name: &str) -> bool {
+        self.scopes.iter().any(|(_, names)| names.contains_key(name))
+    }
+}
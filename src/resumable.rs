@@ -0,0 +1,100 @@
+// src/resumable.rs
+use crate::ast::Stmt;
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, Value};
+use crate::native_stack;
+use corosensei::stack::{DefaultStack, Stack};
+use corosensei::{Coroutine, CoroutineResult, Yielder};
+
+// Runs a script in bounded slices so a single-threaded host, such as a game
+// frame loop or a GUI thread, can interleave it with its own work. The
+// interpreter runs on a separate stack and suspends itself once a slice has
+// executed its statements, keeping its full state until the next resume.
+
+// Room for the default call depth limit even in debug builds. Only the
+// pages a script touches are committed; deeper recursion than the stack
+// holds fails like it does on a thread (see native_stack.rs).
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+pub enum Slice {
+    Paused,
+    Done(Result<Value, JsEngineError>),
+}
+
+// Steps left in the current slice, and how to suspend once they run out
+pub(crate) struct Budget {
+    pub remaining: u64,
+    // Suspends the script and returns the next slice's steps. Only valid
+    // while the coroutine that set it is running, which is the only time
+    // the interpreter executes statements.
+    pub pause: *const dyn Fn() -> u64,
+}
+
+type Body = Coroutine<u64, (), (Interpreter, Result<Value, JsEngineError>), DefaultStack>;
+
+pub struct Execution {
+    coroutine: Option<Body>,
+    interpreter: Option<Interpreter>,
+}
+
+impl Interpreter {
+    // Prepares a program to run in slices; nothing runs until the first
+    // resume. The interpreter is handed back by into_interpreter once the
+    // program has finished.
+    pub fn start(self, statements: Vec<Stmt>) -> Result<Execution, JsEngineError> {
+        let stack = DefaultStack::new(STACK_SIZE).map_err(|error| JsEngineError::RuntimeError {
+            message: format!("Cannot allocate a stack for the script: {}", error),
+        })?;
+        
+        let stack_limit = native_stack::limit_for(stack.limit().get());
+        let mut interpreter = self;
+        let coroutine = Coroutine::with_stack(stack, move |yielder: &Yielder<u64, ()>, steps: u64| {
+            let pause = || yielder.suspend(());
+            let pause: &dyn Fn() -> u64 = &pause;
+            
+            // SAFETY: the budget is cleared before `pause` goes out of scope,
+            // and the interpreter only uses it while running inside here
+            let pause: *const (dyn Fn() -> u64 + 'static) = unsafe { std::mem::transmute(pause) };
+            interpreter.budget = Some(Budget { remaining: steps, pause });
+            let outer_limit = std::mem::replace(&mut interpreter.stack_limit, stack_limit);
+            
+            let result = interpreter.interpret(statements);
+            interpreter.budget = None;
+            interpreter.stack_limit = outer_limit;
+            (interpreter, result)
+        });
+        
+        Ok(Execution {
+            coroutine: Some(coroutine),
+            interpreter: None,
+        })
+    }
+}
+
+impl Execution {
+    // Runs until `max_steps` more statements have executed or the program
+    // ends. Resuming a finished execution reports Done with undefined.
+    pub fn resume(&mut self, max_steps: u64) -> Slice {
+        let Some(coroutine) = &mut self.coroutine else {
+            return Slice::Done(Ok(Value::Undefined));
+        };
+        
+        match coroutine.resume(max_steps) {
+            CoroutineResult::Yield(()) => Slice::Paused,
+            CoroutineResult::Return((interpreter, result)) => {
+                self.coroutine = None;
+                self.interpreter = Some(interpreter);
+                Slice::Done(result)
+            },
+        }
+    }
+    
+    pub fn is_done(&self) -> bool {
+        self.coroutine.is_none()
+    }
+    
+    // The interpreter, once the program has finished
+    pub fn into_interpreter(self) -> Option<Interpreter> {
+        self.interpreter
+    }
+}
@@ -0,0 +1,48 @@
+// src/roots.rs
+use crate::interpreter::{Environment, Value};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+// A value the host keeps alive across eval and call_function invocations,
+// e.g. a callback a script registered. Rooted values live in a table the
+// interpreter owns and reports as a heap root, so they stay reachable for
+// heap inspection (and for a collector, should one replace reference
+// counting) without the host holding raw references into the heap.
+// Dropping the root releases the value.
+pub struct Root {
+    slot: Rc<str>,
+    table: Weak<RefCell<Environment>>,
+}
+
+impl Root {
+    pub(crate) fn new(table: &Rc<RefCell<Environment>>, slot: Rc<str>, value: Value) -> Self {
+        table.borrow_mut().define(Rc::clone(&slot), value);
+        Root {
+            slot,
+            table: Rc::downgrade(table),
+        }
+    }
+    
+    // The rooted value, or undefined once its interpreter is gone
+    pub fn get(&self) -> Value {
+        match self.table.upgrade() {
+            Some(table) => table.borrow().get(&self.slot).unwrap_or(Value::Undefined),
+            None => Value::Undefined,
+        }
+    }
+    
+    // Replaces the rooted value
+    pub fn set(&self, value: Value) {
+        if let Some(table) = self.table.upgrade() {
+            table.borrow_mut().define(Rc::clone(&self.slot), value);
+        }
+    }
+}
+
+impl Drop for Root {
+    fn drop(&mut self) {
+        if let Some(table) = self.table.upgrade() {
+            table.borrow_mut().remove(&self.slot);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// src/shape.rs
+use indexmap::IndexSet;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+// Hidden classes. Objects that were given the same keys in the same order
+// share a Shape, which records where each key's value sits in the object's
+// list of values. Adding a key moves an object to the child shape for that
+// key; children are kept in a transition table, so objects built the same
+// way end up with the very same shape.
+//
+// A property access site keeps a PropertyCache of the last shape it saw and
+// where its key was in it. When the next object has that shape, the value is
+// read or written by position without looking the key up.
+//
+// Only small objects with ordinary keys have shapes. Array indices, removed
+// keys and objects with many keys use a plain map instead, as do objects
+// added to a shape that already has many children, such as objects used as
+// dictionaries with arbitrary keys.
+pub struct Shape {
+    keys: IndexSet<String>,
+    // Kept alive by its children, so that the transitions leading to a
+    // shape in use are still there for the next object built the same way
+    _parent: Option<Rc<Shape>>,
+    transitions: RefCell<HashMap<String, Weak<Shape>>>,
+}
+
+// Keys a shaped object can have
+pub(crate) const MAX_SHAPED_KEYS: usize = 64;
+
+// Children a shape can have
+const MAX_TRANSITIONS: usize = 64;
+
+// Shapes kept alive after their last object is gone
+const RECENT_SHAPES: usize = 1024;
+
+thread_local! {
+    static EMPTY: Rc<Shape> = Rc::new(Shape {
+        keys: IndexSet::new(),
+        _parent: None,
+        transitions: RefCell::new(HashMap::new()),
+    });
+    
+    // The shapes made last. Transitions only hold shapes weakly, so without
+    // these, objects that are made and dropped over and over, such as the
+    // objects of a literal in a loop, would make their shapes anew each time.
+    static RECENT: RefCell<VecDeque<Rc<Shape>>> = RefCell::new(VecDeque::with_capacity(RECENT_SHAPES));
+}
+
+impl Shape {
+    // The shape of objects without properties, shared by all of them
+    pub(crate) fn empty() -> Rc<Shape> {
+        EMPTY.with(Rc::clone)
+    }
+    
+    pub(crate) fn position(&self, key: &str) -> Option<usize> {
+        self.keys.get_index_of(key)
+    }
+    
+    pub(crate) fn key(&self, position: usize) -> Option<&String> {
+        self.keys.get_index(position)
+    }
+    
+    pub(crate) fn len(&self) -> usize {
+        self.keys.len()
+    }
+    
+    // The shape after adding `key`, which this one must not have, or None
+    // when the object should stop having a shape
+    pub(crate) fn with_key(self: &Rc<Self>, key: &str) -> Option<Rc<Shape>> {
+        let mut transitions = self.transitions.borrow_mut();
+        if let Some(child) = transitions.get(key).and_then(Weak::upgrade) {
+            return Some(child);
+        }
+        
+        if self.keys.len() >= MAX_SHAPED_KEYS {
+            return None;
+        }
+        if transitions.len() >= MAX_TRANSITIONS {
+            transitions.retain(|_, child| child.strong_count() > 0);
+            if transitions.len() >= MAX_TRANSITIONS {
+                return None;
+            }
+        }
+        
+        let mut keys = self.keys.clone();
+        keys.insert(key.to_string());
+        let child = Rc::new(Shape {
+            keys,
+            _parent: Some(Rc::clone(self)),
+            transitions: RefCell::new(HashMap::new()),
+        });
+        transitions.insert(key.to_string(), Rc::downgrade(&child));
+        RECENT.with(|recent| {
+            let mut recent = recent.borrow_mut();
+            if recent.len() == RECENT_SHAPES {
+                recent.pop_front();
+            }
+            recent.push_back(Rc::clone(&child));
+        });
+        Some(child)
+    }
+}
+
+impl fmt::Debug for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.keys.iter()).finish()
+    }
+}
+
+// The inline cache of a property access site: a shape and the position of
+// the site's key in it
+#[derive(Clone, Default)]
+pub struct PropertyCache {
+    entry: RefCell<Option<(Rc<Shape>, usize)>>,
+}
+
+impl PropertyCache {
+    // Where the key is in objects of `shape`, if that is the shape this
+    // site last saw
+    pub(crate) fn position(&self, shape: &Rc<Shape>) -> Option<usize> {
+        match &*self.entry.borrow() {
+            Some((cached, position)) if Rc::ptr_eq(cached, shape) => Some(*position),
+            _ => None,
+        }
+    }
+    
+    // Remembers where `key` is in objects of `shape`, if they have it
+    pub(crate) fn remember(&self, shape: &Rc<Shape>, key: &str) {
+        if let Some(position) = shape.position(key) {
+            *self.entry.borrow_mut() = Some((Rc::clone(shape), position));
+        }
+    }
+}
+
+impl fmt::Debug for PropertyCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PropertyCache")
+    }
+}
@@ -0,0 +1,123 @@
+// src/snapshot.rs
+use crate::heap::{json_string, HeapGraph, NodeKind};
+use std::collections::HashMap;
+
+// Serializes a heap graph in the V8 `.heapsnapshot` format understood by the
+// Chrome DevTools memory tab.
+//
+// The format stores nodes and edges as flat integer arrays described by the
+// `meta` block. Each node's edges follow one another in node order, edges
+// refer to their target by its offset in the node array, and all names are
+// indexes into a shared string table.
+
+const NODE_FIELDS: [&str; 7] = ["type", "name", "id", "self_size", "edge_count", "trace_node_id", "detachedness"];
+const NODE_TYPES: [&str; 15] = [
+    "hidden", "array", "string", "object", "code", "closure", "regexp", "number",
+    "native", "synthetic", "concatenated string", "sliced string", "symbol", "bigint", "object shape",
+];
+const EDGE_FIELDS: [&str; 3] = ["type", "name_or_index", "to_node"];
+const EDGE_TYPES: [&str; 7] = ["context", "element", "property", "internal", "hidden", "shortcut", "weak"];
+
+pub fn heap_snapshot(graph: &HeapGraph) -> String {
+    let mut strings = StringTable::default();
+    
+    // Node 0 is a synthetic root whose edges point at the graph's roots;
+    // graph node `i` becomes snapshot node `i + 1`
+    let mut nodes: Vec<usize> = Vec::with_capacity((graph.nodes.len() + 1) * NODE_FIELDS.len());
+    let mut edges: Vec<usize> = Vec::with_capacity((graph.edges.len() + graph.roots.len()) * EDGE_FIELDS.len());
+    
+    nodes.extend([node_type("synthetic"), strings.index(""), 1, 0, graph.roots.len(), 0, 0]);
+    for (i, root) in graph.roots.iter().enumerate() {
+        edges.extend([edge_type("element"), i + 1, node_offset(root + 1)]);
+    }
+    
+    let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); graph.nodes.len()];
+    for (i, edge) in graph.edges.iter().enumerate() {
+        outgoing[edge.from].push(i);
+    }
+    
+    for node in &graph.nodes {
+        let (kind, name) = match node.kind {
+            NodeKind::Environment => ("hidden", format!("system / Context ({})", node.label)),
+            NodeKind::Object => ("object", node.label.clone()),
+            NodeKind::Function => ("closure", node.label.clone()),
+            NodeKind::NativeFunction => ("native", node.label.clone()),
+            NodeKind::String => ("string", node.label.clone()),
+            NodeKind::Primitive if node.label.parse::<f64>().is_ok() => ("number", node.label.clone()),
+            NodeKind::Primitive => ("hidden", node.label.clone()),
+        };
+        
+        // Ids are conventionally odd for heap objects; 1 is the root
+        let id = (node.id + 1) * 2 + 1;
+        nodes.extend([node_type(kind), strings.index(&name), id, node.size, outgoing[node.id].len(), 0, 0]);
+        
+        for &i in &outgoing[node.id] {
+            let edge = &graph.edges[i];
+            let kind = match (node.kind, edge.name.starts_with('(')) {
+                (_, true) => "internal",
+                (NodeKind::Environment, false) => "context",
+                _ => "property",
+            };
+            
+            edges.extend([edge_type(kind), strings.index(&edge.name), node_offset(edge.to + 1)]);
+        }
+    }
+    
+    let meta = format!(
+        "{{\"node_fields\":{},\"node_types\":[{},\"string\",\"number\",\"number\",\"number\",\"number\",\"number\"],\"edge_fields\":{},\"edge_types\":[{},\"string_or_number\",\"node\"],\"trace_function_info_fields\":[],\"trace_node_fields\":[],\"sample_fields\":[],\"location_fields\":[]}}",
+        json_array(&NODE_FIELDS),
+        json_array(&NODE_TYPES),
+        json_array(&EDGE_FIELDS),
+        json_array(&EDGE_TYPES),
+    );
+    
+    format!(
+        "{{\"snapshot\":{{\"meta\":{},\"node_count\":{},\"edge_count\":{},\"trace_function_count\":0}},\"nodes\":[{}],\"edges\":[{}],\"trace_function_infos\":[],\"trace_tree\":[],\"samples\":[],\"locations\":[],\"strings\":[{}]}}",
+        meta,
+        graph.nodes.len() + 1,
+        edges.len() / EDGE_FIELDS.len(),
+        join_numbers(&nodes),
+        join_numbers(&edges),
+        strings.values.iter().map(|value| json_string(value)).collect::<Vec<_>>().join(","),
+    )
+}
+
+fn node_type(name: &str) -> usize {
+    NODE_TYPES.iter().position(|t| *t == name).expect("unknown node type")
+}
+
+fn edge_type(name: &str) -> usize {
+    EDGE_TYPES.iter().position(|t| *t == name).expect("unknown edge type")
+}
+
+// Edges refer to nodes by their position in the flat node array
+fn node_offset(index: usize) -> usize {
+    index * NODE_FIELDS.len()
+}
+
+fn json_array(values: &[&str]) -> String {
+    format!("[{}]", values.iter().map(|value| json_string(value)).collect::<Vec<_>>().join(","))
+}
+
+fn join_numbers(values: &[usize]) -> String {
+    values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(",")
+}
+
+#[derive(Default)]
+struct StringTable {
+    values: Vec<String>,
+    indexes: HashMap<String, usize>,
+}
+
+impl StringTable {
+    fn index(&mut self, value: &str) -> usize {
+        if let Some(&index) = self.indexes.get(value) {
+            return index;
+        }
+        
+        let index = self.values.len();
+        self.values.push(value.to_string());
+        self.indexes.insert(value.to_string(), index);
+        index
+    }
+}
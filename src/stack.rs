@@ -0,0 +1,177 @@
+// src/stack.rs
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, Value};
+use crate::lexer::line_column;
+use crate::object::PropertyMap;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// The scripts and functions currently running, innermost last, for the
+// `stack` property of errors. Each frame remembers how far its code has got,
+// as of the last call it made, so a trace reads like V8's:
+//
+//     RangeError: Invalid code point -1
+//         at check (<anonymous>:3:12)
+//         at <anonymous>:6:1
+//
+// Scripts have no file name, so locations are given as `<anonymous>` with
+// the line and column in the script's text.
+#[derive(Default)]
+pub struct CallStack {
+    frames: Vec<Frame>,
+}
+
+struct Frame {
+    function: Option<Rc<str>>, // None for a script or an anonymous function
+    source: Option<Rc<str>>,
+    position: usize, // Byte offset into `source`
+}
+
+impl CallStack {
+    pub(crate) fn enter(&mut self, function: Option<Rc<str>>, source: Option<Rc<str>>, position: usize) {
+        self.frames.push(Frame {
+            function,
+            source,
+            position,
+        });
+    }
+    
+    pub(crate) fn exit(&mut self) {
+        self.frames.pop();
+    }
+    
+    pub(crate) fn set_position(&mut self, position: usize) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.position = position;
+        }
+    }
+    
+    // One "    at ..." line per frame, innermost first
+    pub fn trace(&self) -> String {
+        let mut lines = Vec::with_capacity(self.frames.len());
+        for frame in self.frames.iter().rev() {
+            let (line, column) = frame.source.as_deref().map_or((1, 1), |source| line_column(source, frame.position));
+            lines.push(match &frame.function {
+                Some(name) => format!("    at {} (<anonymous>:{}:{})", name, line, column),
+                None => format!("    at <anonymous>:{}:{}", line, column),
+            });
+        }
+        lines.join("\n")
+    }
+    
+    // Sets `stack` on an error object raised as an exception, unless it
+    // already has one
+    pub(crate) fn attach(&self, error: &JsEngineError) {
+        if let JsEngineError::Thrown { value: Value::Object(object) } = error
+            && !object.borrow().contains_key("stack")
+        {
+            let stack = self.stack_for(&object.borrow());
+            object.borrow_mut().insert("stack".to_string(), Value::String(stack.into()));
+        }
+    }
+    
+    // The error's "Name: message" line followed by the trace
+    fn stack_for(&self, object: &PropertyMap) -> String {
+        let name = object.lookup("name").map_or("Error".to_string(), |name| name.to_string());
+        let header = match object.lookup("message") {
+            Some(message) if !message.to_string().is_empty() => format!("{}: {}", name, message),
+            _ => name,
+        };
+        
+        if self.frames.is_empty() {
+            header
+        } else {
+            format!("{}\n{}", header, self.trace())
+        }
+    }
+}
+
+// The error types built in as globals, Error first
+const ERROR_TYPES: [&str; 5] = ["Error", "TypeError", "ReferenceError", "RangeError", "SyntaxError"];
+
+// The prototype of each error type's errors, which gives them their `name`.
+// The prototypes of the other types inherit from Error's.
+pub(crate) struct ErrorPrototypes {
+    prototypes: Vec<(&'static str, Rc<RefCell<PropertyMap>>)>,
+}
+
+impl ErrorPrototypes {
+    pub(crate) fn new() -> Self {
+        let mut prototypes: Vec<(&'static str, Rc<RefCell<PropertyMap>>)> = Vec::new();
+        for name in ERROR_TYPES {
+            let mut prototype = PropertyMap::from([
+                ("name", Value::String(name.into())),
+                ("message", Value::String("".into())),
+            ]);
+            prototype.set_prototype(prototypes.first().map(|(_, error)| Rc::clone(error)));
+            prototypes.push((name, Rc::new(RefCell::new(prototype))));
+        }
+        ErrorPrototypes { prototypes }
+    }
+    
+    // The prototype for errors named `name`, Error's for unknown names
+    pub(crate) fn get(&self, name: &str) -> &Rc<RefCell<PropertyMap>> {
+        let (_, prototype) = self.prototypes.iter().find(|(type_name, _)| *type_name == name).unwrap_or(&self.prototypes[0]);
+        prototype
+    }
+    
+    // A new error of the type `prototype` belongs to, with a `stack` as of
+    // now. The message is inherited, as an empty string, when None.
+    pub(crate) fn create(prototype: &Rc<RefCell<PropertyMap>>, message: Option<String>, stack: &CallStack) -> Value {
+        let mut properties = PropertyMap::new();
+        properties.set_prototype(Some(Rc::clone(prototype)));
+        if let Some(message) = message {
+            properties.insert("message".to_string(), Value::String(message.into()));
+        }
+        let trace = stack.stack_for(&properties);
+        properties.insert("stack".to_string(), Value::String(trace.into()));
+        Value::Object(Rc::new(RefCell::new(properties)))
+    }
+    
+    // The global constructors, which like V8's make an error whether or not
+    // they are called with `new`: `TypeError("bad")`. Each has its type's
+    // prototype as `prototype`, and Error has V8's
+    // Error.captureStackTrace(object) for giving custom error objects a
+    // `stack`.
+    pub(crate) fn constructors(&self) -> Vec<(&'static str, Value)> {
+        let mut constructors = Vec::new();
+        for (name, prototype) in &self.prototypes {
+            let instance_prototype = Rc::clone(prototype);
+            let construct = Rc::new(move |interpreter: &mut Interpreter, arguments: &[Value]| -> Result<Value, JsEngineError> {
+                let message = match arguments.first() {
+                    None | Some(Value::Undefined) => None,
+                    Some(message) => Some(interpreter.primitive_value(message.clone(), true)?.to_string()),
+                };
+                let error = ErrorPrototypes::create(&instance_prototype, message, &interpreter.call_stack().borrow());
+                
+                // new Error(message, { cause })
+                if let (Value::Object(error), Some(Value::Object(options))) = (&error, arguments.get(1))
+                    && let Some(cause) = options.borrow().lookup("cause")
+                {
+                    error.borrow_mut().insert("cause".to_string(), cause);
+                }
+                Ok(error)
+            });
+            
+            let mut constructor = PropertyMap::callable(construct);
+            constructor.insert("prototype".to_string(), Value::Object(Rc::clone(prototype)));
+            if *name == "Error" {
+                constructor.insert("captureStackTrace".to_string(), Value::Object(Rc::new(RefCell::new(PropertyMap::callable(Rc::new(capture_stack_trace))))));
+            }
+            constructors.push((*name, Value::Object(Rc::new(RefCell::new(constructor)))));
+        }
+        constructors
+    }
+}
+
+fn capture_stack_trace(interpreter: &mut Interpreter, arguments: &[Value]) -> Result<Value, JsEngineError> {
+    let Some(Value::Object(object)) = arguments.first() else {
+        return Err(JsEngineError::TypeError {
+            message: "Error.captureStackTrace: argument must be an object".to_string(),
+        });
+    };
+    
+    let trace = interpreter.call_stack().borrow().stack_for(&object.borrow());
+    object.borrow_mut().insert("stack".to_string(), Value::String(trace.into()));
+    Ok(Value::Undefined)
+}
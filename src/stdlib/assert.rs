@@ -0,0 +1,140 @@
+// src/stdlib/assert.rs
+use crate::error::JsEngineError;
+use crate::inspect::inspect;
+use crate::interpreter::{Interpreter, Value};
+use crate::object::{CallBehaviour, PropertyMap};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// The assert function, after Node's: assert(value, message) throws an
+// AssertionError unless the value is truthy, and it carries
+//
+//     assert.ok(value, message)          the same check
+//     assert.equal(actual, expected)     primitives by value, objects by identity
+//     assert.deepEqual(actual, expected) objects compared property by property
+//     assert.throws(fn, message)         fn must throw
+//
+// A message passed as the last argument replaces the generated one.
+pub fn namespace() -> Value {
+    let mut assert = PropertyMap::callable(Rc::new(ok));
+    for (name, value) in [
+        ("ok", callable(ok)),
+        ("equal", callable(equal)),
+        ("deepEqual", callable(deep_equal)),
+        ("throws", callable(throws)),
+    ] {
+        assert.insert(name.to_string(), value);
+    }
+    Value::Object(Rc::new(RefCell::new(assert)))
+}
+
+fn callable(call: impl Fn(&mut Interpreter, &[Value]) -> Result<Value, JsEngineError> + 'static) -> Value {
+    let call: Rc<CallBehaviour> = Rc::new(call);
+    Value::Object(Rc::new(RefCell::new(PropertyMap::callable(call))))
+}
+
+fn ok(interpreter: &mut Interpreter, arguments: &[Value]) -> Result<Value, JsEngineError> {
+    let value = arguments.first().unwrap_or(&Value::Undefined);
+    if interpreter.is_truthy(value) {
+        return Ok(Value::Undefined);
+    }
+    Err(failure(arguments.get(1), "The expression evaluated to a falsy value".to_string()))
+}
+
+fn equal(_: &mut Interpreter, arguments: &[Value]) -> Result<Value, JsEngineError> {
+    let actual = arguments.first().unwrap_or(&Value::Undefined);
+    let expected = arguments.get(1).unwrap_or(&Value::Undefined);
+    if same_value(actual, expected) {
+        return Ok(Value::Undefined);
+    }
+    Err(failure(arguments.get(2), format!("{} == {}", inspect_quoted(actual), inspect_quoted(expected))))
+}
+
+fn deep_equal(_: &mut Interpreter, arguments: &[Value]) -> Result<Value, JsEngineError> {
+    let actual = arguments.first().unwrap_or(&Value::Undefined);
+    let expected = arguments.get(1).unwrap_or(&Value::Undefined);
+    if structurally_equal(actual, expected, &mut Vec::new()) {
+        return Ok(Value::Undefined);
+    }
+    let message = format!(
+        "Expected values to be deep-equal:\n\n{}\n\nshould deep-equal\n\n{}",
+        inspect_quoted(actual),
+        inspect_quoted(expected)
+    );
+    Err(failure(arguments.get(2), message))
+}
+
+fn throws(interpreter: &mut Interpreter, arguments: &[Value]) -> Result<Value, JsEngineError> {
+    let function = arguments.first().unwrap_or(&Value::Undefined);
+    if !function.is_callable() {
+        return Err(JsEngineError::TypeError {
+            message: "assert.throws: argument 1 must be a function".to_string(),
+        });
+    }
+    
+    match interpreter.call_function(function, &[]) {
+        Err(_) => Ok(Value::Undefined),
+        Ok(_) => Err(JsEngineError::throw("AssertionError", match arguments.get(1) {
+            Some(Value::Undefined) | None => "Missing expected exception.".to_string(),
+            Some(message) => format!("Missing expected exception: {}", message),
+        })),
+    }
+}
+
+// The AssertionError to throw, with the caller's message if they gave one
+fn failure(message: Option<&Value>, generated: String) -> JsEngineError {
+    let message = match message {
+        Some(Value::Undefined) | None => generated,
+        Some(message) => message.to_string(),
+    };
+    JsEngineError::throw("AssertionError", message)
+}
+
+// Strings are quoted so that 1 and "1" can be told apart in messages
+fn inspect_quoted(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("'{}'", s.as_str()),
+        other => inspect(other),
+    }
+}
+
+// Primitives of the same type and value, NaN included, or the same object
+// or function
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a == b || (a.is_nan() && b.is_nan()),
+        (Value::String(a), Value::String(b)) => a == b,
+        (Value::Boolean(a), Value::Boolean(b)) => a == b,
+        (Value::Null, Value::Null) | (Value::Undefined, Value::Undefined) => true,
+        (Value::Object(a), Value::Object(b)) => Rc::ptr_eq(a, b),
+        (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+        (Value::NativeFunction(a), Value::NativeFunction(b)) => Rc::as_ptr(a) as *const () == Rc::as_ptr(b) as *const (),
+        _ => false,
+    }
+}
+
+// Objects are equal when they have the same keys, in any order, with
+// structurally equal values. `visiting` holds the pairs being compared
+// further up, so cyclic structures compare equal instead of recursing
+// forever.
+fn structurally_equal(a: &Value, b: &Value, visiting: &mut Vec<(*const (), *const ())>) -> bool {
+    let (Value::Object(a), Value::Object(b)) = (a, b) else {
+        return same_value(a, b);
+    };
+    
+    let pair = (Rc::as_ptr(a) as *const (), Rc::as_ptr(b) as *const ());
+    if Rc::ptr_eq(a, b) || visiting.contains(&pair) {
+        return true;
+    }
+    
+    // Callable objects are functions, which are only equal to themselves
+    let (a, b) = (a.borrow(), b.borrow());
+    if a.len() != b.len() || a.call().is_some() || b.call().is_some() {
+        return false;
+    }
+    
+    visiting.push(pair);
+    let equal = a.iter().all(|(key, value)| b.get(key).is_some_and(|other| structurally_equal(value, other, visiting)));
+    visiting.pop();
+    equal
+}
@@ -0,0 +1,34 @@
+// src/stdlib/fs.rs
+use super::{function, object, string_argument};
+use crate::error::JsEngineError;
+use crate::interpreter::Value;
+use crate::string::JsString;
+use std::path::Path;
+
+// Synchronous file access, named after Node's fs functions. Paths are
+// relative to the working directory of the host process.
+pub fn namespace() -> Value {
+    object(vec![
+        ("readFileSync", function(|arguments| {
+            let path = string_argument(arguments, 0, "fs.readFileSync")?;
+            let contents = std::fs::read_to_string(&path).map_err(|error| io_error(&path, error))?;
+            Ok(Value::String(JsString::from(contents)))
+        })),
+        ("writeFileSync", function(|arguments| {
+            let path = string_argument(arguments, 0, "fs.writeFileSync")?;
+            let contents = string_argument(arguments, 1, "fs.writeFileSync")?;
+            std::fs::write(&path, contents).map_err(|error| io_error(&path, error))?;
+            Ok(Value::Undefined)
+        })),
+        ("existsSync", function(|arguments| {
+            let path = string_argument(arguments, 0, "fs.existsSync")?;
+            Ok(Value::Boolean(Path::new(&path).exists()))
+        })),
+    ])
+}
+
+fn io_error(path: &str, error: std::io::Error) -> JsEngineError {
+    JsEngineError::RuntimeError {
+        message: format!("{}: {}", path, error),
+    }
+}
@@ -0,0 +1,328 @@
+// src/stdlib/json.rs
+use super::{function, object, string_argument};
+use crate::error::JsEngineError;
+use crate::heap::json_string;
+use crate::interpreter::Value;
+use crate::number::number_to_string;
+use crate::object::PropertyMap;
+use crate::string::JsString;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Deeper input is rejected rather than risking the native stack
+const MAX_DEPTH: usize = 512;
+
+// The JSON namespace
+pub fn namespace() -> Value {
+    object(vec![
+        ("stringify", function(|arguments| stringify(arguments.first().unwrap_or(&Value::Undefined)))),
+        ("parse", function(|arguments| parse(&string_argument(arguments, 0, "JSON.parse")?))),
+    ])
+}
+
+fn stringify(value: &Value) -> Result<Value, JsEngineError> {
+    let mut out = String::new();
+    let mut stack = Vec::new();
+    
+    if write_value(value, &mut out, &mut stack)? {
+        Ok(Value::String(JsString::from(out)))
+    } else {
+        Ok(Value::Undefined)
+    }
+}
+
+// Returns false for values JSON has no representation for (undefined and
+// functions), which are left out of objects. Keys are written in property
+// order.
+fn write_value(value: &Value, out: &mut String, stack: &mut Vec<*const ()>) -> Result<bool, JsEngineError> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) if n.is_finite() => out.push_str(&number_to_string(*n)),
+        Value::Number(_) => out.push_str("null"),
+        Value::String(s) => out.push_str(&json_string(s.as_str())),
+        Value::Undefined | Value::Function(_) | Value::NativeFunction(_) => return Ok(false),
+        Value::Object(object) if object.borrow().call().is_some() => return Ok(false),
+        Value::Object(array) if array.borrow().is_array() => {
+            let id = Rc::as_ptr(array) as *const ();
+            if stack.contains(&id) {
+                return Err(circular_error());
+            }
+            stack.push(id);
+            
+            // Elements JSON cannot represent become null to keep positions
+            out.push('[');
+            let length = array.borrow().array_length().unwrap_or(0);
+            for index in 0..length {
+                if index > 0 {
+                    out.push(',');
+                }
+                let element = array.borrow().get(&index.to_string()).cloned().unwrap_or(Value::Undefined);
+                if !write_value(&element, out, stack)? {
+                    out.push_str("null");
+                }
+            }
+            out.push(']');
+            
+            stack.pop();
+        },
+        Value::Object(properties) => {
+            let id = Rc::as_ptr(properties) as *const ();
+            if stack.contains(&id) {
+                return Err(circular_error());
+            }
+            stack.push(id);
+            
+            out.push('{');
+            let mut first = true;
+            for (key, value) in properties.borrow().iter() {
+                let start = out.len();
+                if !first {
+                    out.push(',');
+                }
+                out.push_str(&json_string(key));
+                out.push(':');
+                
+                if write_value(value, out, stack)? {
+                    first = false;
+                } else {
+                    out.truncate(start);
+                }
+            }
+            out.push('}');
+            
+            stack.pop();
+        },
+    }
+    
+    Ok(true)
+}
+
+fn circular_error() -> JsEngineError {
+    JsEngineError::TypeError {
+        message: "Converting circular structure to JSON".to_string(),
+    }
+}
+
+fn parse(text: &str) -> Result<Value, JsEngineError> {
+    let mut parser = JsonParser { text: text.as_bytes(), position: 0 };
+    let value = parser.value(0)?;
+    
+    parser.skip_whitespace();
+    if parser.position < parser.text.len() {
+        return Err(parser.error("Unexpected token"));
+    }
+    
+    Ok(value)
+}
+
+struct JsonParser<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl JsonParser<'_> {
+    fn value(&mut self, depth: usize) -> Result<Value, JsEngineError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("Nesting too deep"));
+        }
+        
+        self.skip_whitespace();
+        match self.text.get(self.position) {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => Ok(Value::String(JsString::from(self.string()?))),
+            Some(b't') => self.literal("true", Value::Boolean(true)),
+            Some(b'f') => self.literal("false", Value::Boolean(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("Unexpected token")),
+            None => Err(self.error("Unexpected end of JSON input")),
+        }
+    }
+    
+    fn object(&mut self, depth: usize) -> Result<Value, JsEngineError> {
+        self.position += 1; // Opening brace
+        let mut properties = PropertyMap::new();
+        
+        self.skip_whitespace();
+        if self.eat(b'}') {
+            return Ok(Value::Object(Rc::new(RefCell::new(properties))));
+        }
+        
+        loop {
+            self.skip_whitespace();
+            if self.text.get(self.position) != Some(&b'"') {
+                return Err(self.error("Expected property name"));
+            }
+            let key = self.string()?;
+            
+            self.skip_whitespace();
+            if !self.eat(b':') {
+                return Err(self.error("Expected ':' after property name"));
+            }
+            
+            let value = self.value(depth + 1)?;
+            properties.insert(key, value);
+            
+            self.skip_whitespace();
+            if self.eat(b'}') {
+                return Ok(Value::Object(Rc::new(RefCell::new(properties))));
+            }
+            if !self.eat(b',') {
+                return Err(self.error("Expected ',' or '}' after property value"));
+            }
+        }
+    }
+    
+    fn array(&mut self, depth: usize) -> Result<Value, JsEngineError> {
+        self.position += 1; // Opening bracket
+        let mut elements = Vec::new();
+        
+        self.skip_whitespace();
+        if !self.eat(b']') {
+            loop {
+                elements.push(self.value(depth + 1)?);
+                
+                self.skip_whitespace();
+                if self.eat(b']') {
+                    break;
+                }
+                if !self.eat(b',') {
+                    return Err(self.error("Expected ',' or ']' after array element"));
+                }
+            }
+        }
+        
+        Ok(Value::Object(Rc::new(RefCell::new(PropertyMap::array(elements)))))
+    }
+    
+    fn string(&mut self) -> Result<String, JsEngineError> {
+        self.position += 1; // Opening quote
+        let mut value = String::new();
+        
+        loop {
+            let start = self.position;
+            while let Some(&byte) = self.text.get(self.position) {
+                if byte == b'"' || byte == b'\\' || byte < 0x20 {
+                    break;
+                }
+                self.position += 1;
+            }
+            // The input came from a &str and only ASCII bytes end a run, so
+            // the run is valid UTF-8
+            value.push_str(std::str::from_utf8(&self.text[start..self.position]).unwrap_or_default());
+            
+            match self.text.get(self.position) {
+                Some(b'"') => {
+                    self.position += 1;
+                    return Ok(value);
+                },
+                Some(b'\\') => {
+                    self.position += 1;
+                    let escaped = self.escape()?;
+                    value.push(escaped);
+                },
+                Some(_) => return Err(self.error("Bad control character in string literal")),
+                None => return Err(self.error("Unterminated string")),
+            }
+        }
+    }
+    
+    fn escape(&mut self) -> Result<char, JsEngineError> {
+        let byte = self.text.get(self.position).copied();
+        self.position += 1;
+        
+        Ok(match byte {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                let high = self.hex4()?;
+                // Surrogate pairs arrive as two escapes
+                if (0xD800..0xDC00).contains(&high) && self.text[self.position..].starts_with(b"\\u") {
+                    self.position += 2;
+                    let low = self.hex4()?;
+                    let code = 0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+                    char::from_u32(code).unwrap_or('\u{FFFD}')
+                } else {
+                    char::from_u32(high).unwrap_or('\u{FFFD}')
+                }
+            },
+            _ => return Err(self.error("Bad escaped character")),
+        })
+    }
+    
+    fn hex4(&mut self) -> Result<u32, JsEngineError> {
+        let digits = self
+            .text
+            .get(self.position..self.position + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("Bad Unicode escape"))?;
+        self.position += 4;
+        Ok(digits)
+    }
+    
+    fn number(&mut self) -> Result<Value, JsEngineError> {
+        let start = self.position;
+        self.eat(b'-');
+        self.digits();
+        if self.eat(b'.') {
+            self.digits();
+        }
+        if self.eat(b'e') || self.eat(b'E') {
+            if !self.eat(b'+') {
+                self.eat(b'-');
+            }
+            self.digits();
+        }
+        
+        std::str::from_utf8(&self.text[start..self.position])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .map(Value::Number)
+            .ok_or_else(|| self.error("Invalid number"))
+    }
+    
+    fn digits(&mut self) {
+        while self.text.get(self.position).is_some_and(u8::is_ascii_digit) {
+            self.position += 1;
+        }
+    }
+    
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, JsEngineError> {
+        if self.text[self.position..].starts_with(word.as_bytes()) {
+            self.position += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("Unexpected token"))
+        }
+    }
+    
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.text.get(self.position) == Some(&byte) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+    
+    fn skip_whitespace(&mut self) {
+        while matches!(self.text.get(self.position), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.position += 1;
+        }
+    }
+    
+    fn error(&self, message: &str) -> JsEngineError {
+        JsEngineError::SyntaxError {
+            message: format!("{} in JSON at position {}", message, self.position),
+        }
+    }
+}
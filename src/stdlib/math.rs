@@ -0,0 +1,102 @@
+// src/stdlib/math.rs
+use super::{function, number_argument, object};
+use crate::interpreter::{Interpreter, Value};
+use crate::number::power;
+use std::f64::consts;
+
+// The Math namespace, but for random, which install adds
+pub fn namespace() -> Value {
+    object(vec![
+        ("PI", Value::Number(consts::PI)),
+        ("E", Value::Number(consts::E)),
+        ("LN2", Value::Number(consts::LN_2)),
+        ("LN10", Value::Number(consts::LN_10)),
+        ("LOG2E", Value::Number(consts::LOG2_E)),
+        ("LOG10E", Value::Number(consts::LOG10_E)),
+        ("SQRT2", Value::Number(consts::SQRT_2)),
+        ("SQRT1_2", Value::Number(consts::FRAC_1_SQRT_2)),
+        ("abs", unary(f64::abs)),
+        ("floor", unary(f64::floor)),
+        ("ceil", unary(f64::ceil)),
+        // Halves round towards +Infinity, unlike f64::round, and negative
+        // numbers that round to zero give -0
+        ("round", unary(|x| {
+            let rounded = if x - x.floor() >= 0.5 { x.floor() + 1.0 } else { x.floor() };
+            if rounded == 0.0 { rounded.copysign(x) } else { rounded }
+        })),
+        ("trunc", unary(f64::trunc)),
+        // Zeros and NaN are their own sign, unlike with f64::signum
+        ("sign", unary(|x| if x == 0.0 || x.is_nan() { x } else { x.signum() })),
+        ("sqrt", unary(f64::sqrt)),
+        ("cbrt", unary(f64::cbrt)),
+        ("pow", function(|arguments| Ok(Value::Number(power(number_argument(arguments, 0), number_argument(arguments, 1)))))),
+        ("exp", unary(f64::exp)),
+        ("expm1", unary(f64::exp_m1)),
+        ("log", unary(f64::ln)),
+        ("log1p", unary(f64::ln_1p)),
+        ("log2", unary(f64::log2)),
+        ("log10", unary(f64::log10)),
+        ("sin", unary(f64::sin)),
+        ("cos", unary(f64::cos)),
+        ("tan", unary(f64::tan)),
+        ("asin", unary(f64::asin)),
+        ("acos", unary(f64::acos)),
+        ("atan", unary(f64::atan)),
+        ("atan2", function(|arguments| Ok(Value::Number(number_argument(arguments, 0).atan2(number_argument(arguments, 1)))))),
+        ("sinh", unary(f64::sinh)),
+        ("cosh", unary(f64::cosh)),
+        ("tanh", unary(f64::tanh)),
+        ("asinh", unary(f64::asinh)),
+        ("acosh", unary(f64::acosh)),
+        ("atanh", unary(f64::atanh)),
+        ("fround", unary(|x| x as f32 as f64)),
+        ("clz32", unary(|x| to_uint32(x).leading_zeros() as f64)),
+        ("imul", function(|arguments| {
+            let (a, b) = (to_uint32(number_argument(arguments, 0)), to_uint32(number_argument(arguments, 1)));
+            Ok(Value::Number(a.wrapping_mul(b) as i32 as f64))
+        })),
+        ("min", variadic(f64::INFINITY, f64::min)),
+        ("max", variadic(f64::NEG_INFINITY, f64::max)),
+        // An infinite argument wins over NaN
+        ("hypot", function(|arguments| {
+            let numbers: Vec<f64> = (0..arguments.len()).map(|i| number_argument(arguments, i)).collect();
+            if numbers.iter().any(|n| n.is_infinite()) {
+                return Ok(Value::Number(f64::INFINITY));
+            }
+            Ok(Value::Number(numbers.into_iter().fold(0.0, f64::hypot)))
+        })),
+    ])
+}
+
+// Adds Math.random, which draws from the interpreter's generator
+pub fn install(interpreter: &mut Interpreter) {
+    let rng = interpreter.rng();
+    let math = interpreter.globals().borrow().get("Math");
+    if let Ok(Value::Object(math)) = math {
+        math.borrow_mut().insert("random".to_string(), function(move |_| Ok(Value::Number(rng.borrow_mut().next_f64()))));
+    }
+}
+
+fn unary(operation: fn(f64) -> f64) -> Value {
+    function(move |arguments| Ok(Value::Number(operation(number_argument(arguments, 0)))))
+}
+
+// ToUint32: wraps modulo 2^32, with NaN and the infinities as 0
+fn to_uint32(x: f64) -> u32 {
+    if x.is_finite() { x.trunc().rem_euclid(4_294_967_296.0) as u32 } else { 0 }
+}
+
+// Folds all arguments; any NaN argument makes the result NaN
+fn variadic(initial: f64, operation: fn(f64, f64) -> f64) -> Value {
+    function(move |arguments| {
+        let mut result = initial;
+        for i in 0..arguments.len() {
+            let n = number_argument(arguments, i);
+            if n.is_nan() {
+                return Ok(Value::Number(f64::NAN));
+            }
+            result = operation(result, n);
+        }
+        Ok(Value::Number(result))
+    })
+}
@@ -0,0 +1,163 @@
+// src/stdlib/mod.rs
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, Value};
+use crate::number;
+use crate::object::PropertyMap;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[cfg(feature = "assert")]
+mod assert;
+#[cfg(feature = "fs")]
+mod fs;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "math")]
+mod math;
+#[cfg(feature = "net")]
+mod net;
+#[cfg(feature = "performance")]
+mod performance;
+#[cfg(feature = "regex")]
+mod regex;
+#[cfg(feature = "timers")]
+mod timers;
+
+// Built-in globals beyond the language core. Each namespace sits behind a
+// cargo feature of the same name; all are on by default, and building with
+// `--no-default-features` leaves only console, String, Number and the
+// number functions, Object, Array, Proxy, Reflect, Symbol, ArrayBuffer, the
+// typed arrays, DataView, TextEncoder, TextDecoder, the error constructors,
+// queueMicrotask, Promise, Map, Set and the event functions.
+// Which of the compiled namespaces a given interpreter gets is chosen at
+// runtime with InterpreterBuilder::with_stdlib.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Std {
+    #[cfg(feature = "math")]
+    Math,
+    #[cfg(feature = "json")]
+    Json,
+    #[cfg(feature = "timers")]
+    Timers,
+    #[cfg(feature = "fs")]
+    Fs,
+    #[cfg(feature = "net")]
+    Net,
+    #[cfg(feature = "regex")]
+    Regex,
+    #[cfg(feature = "assert")]
+    Assert,
+    #[cfg(feature = "performance")]
+    Performance,
+}
+
+impl Std {
+    // Every namespace compiled into this build
+    pub fn all() -> Vec<Std> {
+        vec![
+            #[cfg(feature = "math")]
+            Std::Math,
+            #[cfg(feature = "json")]
+            Std::Json,
+            #[cfg(feature = "timers")]
+            Std::Timers,
+            #[cfg(feature = "fs")]
+            Std::Fs,
+            #[cfg(feature = "net")]
+            Std::Net,
+            #[cfg(feature = "regex")]
+            Std::Regex,
+            #[cfg(feature = "assert")]
+            Std::Assert,
+            #[cfg(feature = "performance")]
+            Std::Performance,
+        ]
+    }
+}
+
+// The namespaces that keep no state of their own; see
+// InterpreterBuilder::builtins
+#[cfg_attr(not(any(feature = "math", feature = "json", feature = "fs", feature = "net", feature = "regex", feature = "assert")), allow(unused_mut))]
+pub fn builtins(namespaces: &[Std]) -> Vec<(&'static str, Value)> {
+    let mut builtins = Vec::new();
+    for namespace in namespaces {
+        match *namespace {
+            #[cfg(feature = "math")]
+            Std::Math => builtins.push(("Math", math::namespace())),
+            #[cfg(feature = "json")]
+            Std::Json => builtins.push(("JSON", json::namespace())),
+            #[cfg(feature = "fs")]
+            Std::Fs => builtins.push(("fs", fs::namespace())),
+            #[cfg(feature = "net")]
+            Std::Net => builtins.push(("net", net::namespace())),
+            #[cfg(feature = "regex")]
+            Std::Regex => {
+                builtins.push(("regex", regex::namespace()));
+                builtins.push(("RegExp", crate::regexp::regexp_constructor()));
+            },
+            #[cfg(feature = "assert")]
+            Std::Assert => builtins.push(("assert", assert::namespace())),
+            #[allow(unreachable_patterns)]
+            _ => {},
+        }
+    }
+    builtins
+}
+
+// Installs the namespaces, or the parts of them, that hold state of the
+// interpreter: its random number generator, task queue, clock or timeline
+#[cfg_attr(not(any(feature = "math", feature = "timers", feature = "performance")), allow(unused_variables, clippy::never_loop))]
+pub fn install(interpreter: &mut Interpreter, namespaces: &[Std]) {
+    for namespace in namespaces {
+        match *namespace {
+            #[cfg(feature = "math")]
+            Std::Math => math::install(interpreter),
+            #[cfg(feature = "timers")]
+            Std::Timers => timers::install(interpreter),
+            #[cfg(feature = "performance")]
+            Std::Performance => performance::install(interpreter),
+            #[allow(unreachable_patterns)]
+            _ => {},
+        }
+    }
+}
+
+// Helpers shared by the namespaces; unused when every feature is off
+
+#[allow(dead_code)]
+fn object(properties: Vec<(&str, Value)>) -> Value {
+    let properties: PropertyMap = properties.into_iter().collect();
+    Value::Object(Rc::new(RefCell::new(properties)))
+}
+
+#[allow(dead_code)]
+fn function(function: impl Fn(&[Value]) -> Result<Value, JsEngineError> + 'static) -> Value {
+    Value::NativeFunction(Rc::new(function))
+}
+
+// JavaScript's ToNumber for primitives; objects and functions are NaN
+pub(crate) fn to_number(value: &Value) -> f64 {
+    match value {
+        Value::Number(n) => *n,
+        Value::Boolean(b) => *b as u8 as f64,
+        Value::Null => 0.0,
+        Value::String(s) => number::string_to_number(s.as_str()),
+        _ => f64::NAN,
+    }
+}
+
+#[allow(dead_code)]
+fn number_argument(arguments: &[Value], index: usize) -> f64 {
+    arguments.get(index).map_or(f64::NAN, to_number)
+}
+
+// Missing arguments are reported instead of being converted to "undefined"
+#[allow(dead_code)]
+fn string_argument(arguments: &[Value], index: usize, function: &str) -> Result<String, JsEngineError> {
+    match arguments.get(index) {
+        Some(Value::Undefined) | None => Err(JsEngineError::TypeError {
+            message: format!("{}: argument {} must be a string", function, index + 1),
+        }),
+        Some(value) => Ok(value.to_string()),
+    }
+}
@@ -0,0 +1,57 @@
+// src/stdlib/net.rs
+use super::{function, object, string_argument};
+use crate::error::JsEngineError;
+use crate::interpreter::Value;
+use crate::string::JsString;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// Blocking network access. Only plain http:// URLs are supported, since TLS
+// would pull a large dependency into the interpreter.
+pub fn namespace() -> Value {
+    object(vec![
+        ("get", function(|arguments| {
+            let url = string_argument(arguments, 0, "net.get")?;
+            get(&url).map(|body| Value::String(JsString::from(body)))
+        })),
+    ])
+}
+
+// Fetches a URL with HTTP/1.0 and returns the response body, failing on
+// statuses other than 2xx
+fn get(url: &str) -> Result<String, JsEngineError> {
+    let error = |message: String| JsEngineError::RuntimeError {
+        message: format!("net.get {}: {}", url, message),
+    };
+    
+    let Some(rest) = url.strip_prefix("http://") else {
+        return Err(error("only http:// URLs are supported".to_string()));
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    let host = authority.split(':').next().unwrap_or_default();
+    let address = if authority.contains(':') { authority.to_string() } else { format!("{}:80", authority) };
+    
+    let mut stream = TcpStream::connect(&address).map_err(|e| error(e.to_string()))?;
+    stream.set_read_timeout(Some(TIMEOUT)).map_err(|e| error(e.to_string()))?;
+    write!(stream, "GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n", path, host).map_err(|e| error(e.to_string()))?;
+    
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).map_err(|e| error(e.to_string()))?;
+    let response = String::from_utf8_lossy(&response);
+    
+    let Some((head, body)) = response.split_once("\r\n\r\n") else {
+        return Err(error("malformed response".to_string()));
+    };
+    let status = head.lines().next().and_then(|line| line.split_whitespace().nth(1)).unwrap_or_default();
+    if !status.starts_with('2') {
+        return Err(error(format!("status {}", status)));
+    }
+    
+    Ok(body.to_string())
+}
@@ -0,0 +1,120 @@
+// src/stdlib/performance.rs
+use super::{function, object, string_argument};
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, Value};
+use crate::object::PropertyMap;
+use crate::performance::{EntryType, PerformanceEntry, Timeline};
+use crate::string::JsString;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// The performance namespace: now() and the User Timing API, recording into
+// the interpreter's timeline. Times count from when this is installed.
+//
+//     performance.mark("parsed");
+//     performance.measure("parse", "start", "parsed");
+//     performance.getEntriesByType("measure");
+pub fn install(interpreter: &mut Interpreter) {
+    let clock = interpreter.clock();
+    let origin = clock.now();
+    let now = Rc::new(move || clock.now() - origin);
+    let timeline = Rc::downgrade(&interpreter.timeline());
+    
+    let now_function = {
+        let now = Rc::clone(&now);
+        function(move |_| Ok(Value::Number(now())))
+    };
+    
+    let mark = {
+        let now = Rc::clone(&now);
+        let timeline = timeline.clone();
+        function(move |arguments| {
+            let name = string_argument(arguments, 0, "performance.mark")?;
+            let Some(timeline) = timeline.upgrade() else {
+                return Ok(Value::Undefined);
+            };
+            
+            let entry = PerformanceEntry {
+                name,
+                entry_type: EntryType::Mark,
+                start_time: now(),
+                duration: 0.0,
+            };
+            Ok(record(&timeline, entry))
+        })
+    };
+    
+    let measure = {
+        let timeline = timeline.clone();
+        function(move |arguments| {
+            let name = string_argument(arguments, 0, "performance.measure")?;
+            let Some(timeline) = timeline.upgrade() else {
+                return Ok(Value::Undefined);
+            };
+            
+            // The measure runs from the time origin to now unless marks or
+            // timestamps say otherwise
+            let start_time = endpoint(&timeline.borrow(), arguments.get(1))?.unwrap_or(0.0);
+            let end_time = endpoint(&timeline.borrow(), arguments.get(2))?.unwrap_or_else(|| now());
+            let entry = PerformanceEntry {
+                name,
+                entry_type: EntryType::Measure,
+                start_time,
+                duration: end_time - start_time,
+            };
+            Ok(record(&timeline, entry))
+        })
+    };
+    
+    // Unknown types have no entries
+    let get_entries_by_type = function(move |arguments| {
+        let entry_type = arguments.first().and_then(|name| EntryType::from_name(&name.to_string()));
+        let (Some(timeline), Some(entry_type)) = (timeline.upgrade(), entry_type) else {
+            return Ok(array(Vec::new()));
+        };
+        let entries = timeline.borrow().entries_by_type(entry_type).map(entry_value).collect();
+        Ok(array(entries))
+    });
+    
+    interpreter.define_global("performance", object(vec![
+        ("now", now_function),
+        ("mark", mark),
+        ("measure", measure),
+        ("getEntriesByType", get_entries_by_type),
+    ]));
+}
+
+// Adds the entry and returns it as scripts see it
+fn record(timeline: &RefCell<Timeline>, entry: PerformanceEntry) -> Value {
+    let value = entry_value(&entry);
+    timeline.borrow_mut().record(entry);
+    value
+}
+
+// A mark name or a timestamp; None when the argument is missing
+fn endpoint(timeline: &Timeline, argument: Option<&Value>) -> Result<Option<f64>, JsEngineError> {
+    match argument {
+        None | Some(Value::Undefined) => Ok(None),
+        Some(Value::Number(time)) => Ok(Some(*time)),
+        Some(name) => {
+            let name = name.to_string();
+            match timeline.mark(&name) {
+                Some(mark) => Ok(Some(mark.start_time)),
+                None => Err(JsEngineError::throw("SyntaxError", format!("The mark '{}' does not exist.", name))),
+            }
+        },
+    }
+}
+
+fn entry_value(entry: &PerformanceEntry) -> Value {
+    object(vec![
+        ("name", Value::String(JsString::from(entry.name.as_str()))),
+        ("entryType", Value::String(JsString::from(entry.entry_type.name()))),
+        ("startTime", Value::Number(entry.start_time)),
+        ("duration", Value::Number(entry.duration)),
+    ])
+}
+
+fn array(elements: Vec<Value>) -> Value {
+    Value::Object(Rc::new(RefCell::new(PropertyMap::array(elements))))
+}
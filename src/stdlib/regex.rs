@@ -0,0 +1,43 @@
+// src/stdlib/regex.rs
+use super::{function, object, string_argument};
+use crate::error::JsEngineError;
+use crate::interpreter::Value;
+use crate::string::JsString;
+use regex::Regex;
+
+// Regular expressions over the regex crate, as plain functions taking the
+// pattern as a string. The pattern syntax is the crate's, which covers the
+// common subset of JavaScript's but has no backreferences or lookaround.
+pub fn namespace() -> Value {
+    object(vec![
+        ("test", function(|arguments| {
+            let (pattern, text) = pattern_and_text(arguments, "regex.test")?;
+            Ok(Value::Boolean(pattern.is_match(&text)))
+        })),
+        ("match", function(|arguments| {
+            let (pattern, text) = pattern_and_text(arguments, "regex.match")?;
+            Ok(pattern
+                .find(&text)
+                .map_or(Value::Null, |found| Value::String(JsString::from(found.as_str()))))
+        })),
+        ("replace", function(|arguments| {
+            let (pattern, text) = pattern_and_text(arguments, "regex.replace")?;
+            let replacement = string_argument(arguments, 2, "regex.replace")?;
+            Ok(Value::String(JsString::from(pattern.replace(&text, replacement.as_str()).into_owned())))
+        })),
+        ("replaceAll", function(|arguments| {
+            let (pattern, text) = pattern_and_text(arguments, "regex.replaceAll")?;
+            let replacement = string_argument(arguments, 2, "regex.replaceAll")?;
+            Ok(Value::String(JsString::from(pattern.replace_all(&text, replacement.as_str()).into_owned())))
+        })),
+    ])
+}
+
+fn pattern_and_text(arguments: &[Value], function: &str) -> Result<(Regex, String), JsEngineError> {
+    let pattern = string_argument(arguments, 0, function)?;
+    let text = string_argument(arguments, 1, function)?;
+    let pattern = Regex::new(&pattern).map_err(|error| JsEngineError::SyntaxError {
+        message: format!("{}: invalid regular expression: {}", function, error),
+    })?;
+    Ok((pattern, text))
+}
@@ -0,0 +1,63 @@
+// src/stdlib/timers.rs
+use super::{function, number_argument};
+use crate::error::JsEngineError;
+use crate::interpreter::{Interpreter, Value};
+use std::rc::Rc;
+
+// setTimeout, setInterval and their clear functions, backed by the
+// interpreter's task queue. The queue is held weakly, since queued callbacks
+// can reach these functions. Timeouts and intervals share one set of ids, so
+// either clear function cancels either kind of timer.
+pub fn install(interpreter: &mut Interpreter) {
+    let set_timeout = timer(interpreter, "setTimeout", false);
+    let set_interval = timer(interpreter, "setInterval", true);
+    
+    let tasks = Rc::downgrade(&interpreter.tasks());
+    let clear_timer = function(move |arguments| {
+        let id = number_argument(arguments, 0);
+        if let Some(tasks) = tasks.upgrade()
+            && id >= 1.0
+        {
+            tasks.borrow_mut().cancel(id as u64);
+        }
+        Ok(Value::Undefined)
+    });
+    
+    interpreter.define_global("setTimeout", set_timeout);
+    interpreter.define_global("setInterval", set_interval);
+    interpreter.define_global("clearTimeout", clear_timer.clone());
+    interpreter.define_global("clearInterval", clear_timer);
+}
+
+fn timer(interpreter: &Interpreter, name: &'static str, repeating: bool) -> Value {
+    let tasks = Rc::downgrade(&interpreter.tasks());
+    let clock = interpreter.clock();
+    function(move |arguments| {
+        let callback = match arguments.first() {
+            Some(callback) if callback.is_callable() => callback.clone(),
+            _ => {
+                return Err(JsEngineError::TypeError {
+                    message: format!("{}: callback must be a function", name),
+                });
+            },
+        };
+        
+        // Negative and NaN delays run as soon as possible; intervals wait at
+        // least a millisecond between runs so that one cannot starve the
+        // other timers
+        let delay = number_argument(arguments, 1);
+        let delay = if delay > 0.0 { delay } else { 0.0 };
+        let due = clock.now() + delay;
+        let rest = arguments.get(2..).unwrap_or_default().to_vec();
+        
+        let Some(tasks) = tasks.upgrade() else {
+            return Ok(Value::Undefined);
+        };
+        let id = if repeating {
+            tasks.borrow_mut().schedule_repeating(due, delay.max(1.0), callback, rest)
+        } else {
+            tasks.borrow_mut().schedule(due, callback, rest)
+        };
+        Ok(Value::Number(id as f64))
+    })
+}
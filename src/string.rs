@@ -0,0 +1,204 @@
+// src/string.rs
+use std::cell::{OnceCell, RefCell};
+use std::fmt;
+use std::rc::Rc;
+
+// Concatenations shorter than this are copied eagerly instead of building a
+// rope node, since the copy is cheaper than the extra indirection
+const ROPE_THRESHOLD: usize = 64;
+
+// Immutable JavaScript string value. Cloning is O(1), and concatenation
+// builds a rope that is only flattened into a single buffer when the
+// contents are actually read, so `s = s + chunk` loops stay linear.
+#[derive(Clone)]
+pub struct JsString(Rc<Node>);
+
+struct Node {
+    len: usize,
+    // Length in UTF-16 code units, computed on first use
+    utf16_len: OnceCell<usize>,
+    // Contiguous contents, present from the start for flat strings and
+    // filled in on first read for ropes
+    flat: OnceCell<Rc<str>>,
+    // Pending concatenation, released once the node has been flattened
+    parts: RefCell<Option<(JsString, JsString)>>,
+}
+
+impl JsString {
+    pub fn new(value: &str) -> Self {
+        JsString::from(Rc::<str>::from(value))
+    }
+    
+    // Length in bytes of the UTF-8 contents
+    pub fn len(&self) -> usize {
+        self.0.len
+    }
+    
+    // Length as scripts see it, in UTF-16 code units: characters outside
+    // the Basic Multilingual Plane, such as most emoji, count twice
+    pub fn utf16_len(&self) -> usize {
+        *self.0.utf16_len.get_or_init(|| self.as_str().encode_utf16().count())
+    }
+    
+    // The UTF-16 code unit at `index`, which may be half of a surrogate pair
+    pub fn code_unit_at(&self, index: usize) -> Option<u16> {
+        let text = self.as_str();
+        if text.is_ascii() {
+            return text.as_bytes().get(index).map(|byte| *byte as u16);
+        }
+        text.encode_utf16().nth(index)
+    }
+    
+    // The code point starting at UTF-16 `index`, combining a surrogate pair;
+    // an index inside a pair gives the lone trailing surrogate
+    pub fn code_point_at(&self, index: usize) -> Option<u32> {
+        let first = self.code_unit_at(index)?;
+        if (0xD800..0xDC00).contains(&first)
+            && let Some(second) = self.code_unit_at(index + 1)
+            && (0xDC00..0xE000).contains(&second)
+        {
+            return Some(0x10000 + ((first as u32 - 0xD800) << 10) + (second as u32 - 0xDC00));
+        }
+        Some(first as u32)
+    }
+    
+    // Builds a string from UTF-16 code units. Rust strings cannot hold
+    // unpaired surrogates, so those become U+FFFD.
+    pub fn from_utf16_lossy(units: &[u16]) -> Self {
+        JsString::from(String::from_utf16_lossy(units))
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.0.len == 0
+    }
+    
+    // Identity of the underlying allocation, shared by all clones
+    pub fn id(&self) -> *const () {
+        Rc::as_ptr(&self.0) as *const ()
+    }
+    
+    pub fn concat(&self, other: &JsString) -> JsString {
+        if other.is_empty() {
+            return self.clone();
+        }
+        if self.is_empty() {
+            return other.clone();
+        }
+        
+        let len = self.len() + other.len();
+        
+        if len < ROPE_THRESHOLD {
+            let mut value = String::with_capacity(len);
+            value.push_str(self.as_str());
+            value.push_str(other.as_str());
+            return JsString::from(value);
+        }
+        
+        JsString(Rc::new(Node {
+            len,
+            utf16_len: OnceCell::new(),
+            flat: OnceCell::new(),
+            parts: RefCell::new(Some((self.clone(), other.clone()))),
+        }))
+    }
+    
+    pub fn as_str(&self) -> &str {
+        self.flatten()
+    }
+    
+    fn flatten(&self) -> &Rc<str> {
+        if let Some(flat) = self.0.flat.get() {
+            return flat;
+        }
+        
+        // Walk the rope left to right with an explicit stack so deeply
+        // nested concatenations cannot overflow the Rust stack
+        let mut value = String::with_capacity(self.0.len);
+        let mut stack = vec![self.clone()];
+        
+        while let Some(string) = stack.pop() {
+            if let Some(flat) = string.0.flat.get() {
+                value.push_str(flat);
+                continue;
+            }
+            
+            let parts = string.0.parts.borrow();
+            let (left, right) = parts.as_ref().expect("unflattened rope node without parts");
+            stack.push(right.clone());
+            stack.push(left.clone());
+        }
+        
+        let flat = self.0.flat.get_or_init(|| Rc::from(value));
+        
+        // The children are no longer needed
+        release(self.0.parts.borrow_mut().take());
+        
+        flat
+    }
+}
+
+// Drops a rope iteratively; the default recursive drop could overflow the
+// stack for strings built from many small appends
+fn release(parts: Option<(JsString, JsString)>) {
+    let mut stack = Vec::new();
+    if let Some((left, right)) = parts {
+        stack.push(left);
+        stack.push(right);
+    }
+    
+    while let Some(string) = stack.pop() {
+        if let Ok(node) = Rc::try_unwrap(string.0)
+            && let Some((left, right)) = node.parts.take()
+        {
+            stack.push(left);
+            stack.push(right);
+        }
+    }
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        release(self.parts.get_mut().take());
+    }
+}
+
+impl From<Rc<str>> for JsString {
+    fn from(value: Rc<str>) -> Self {
+        JsString(Rc::new(Node {
+            len: value.len(),
+            utf16_len: OnceCell::new(),
+            flat: OnceCell::from(value),
+            parts: RefCell::new(None),
+        }))
+    }
+}
+
+impl From<String> for JsString {
+    fn from(value: String) -> Self {
+        JsString::from(Rc::<str>::from(value))
+    }
+}
+
+impl From<&str> for JsString {
+    fn from(value: &str) -> Self {
+        JsString::new(value)
+    }
+}
+
+impl PartialEq for JsString {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0) || (self.len() == other.len() && self.as_str() == other.as_str())
+    }
+}
+
+impl fmt::Display for JsString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for JsString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
@@ -0,0 +1,89 @@
+// src/syntax.rs
+use crate::ast::{Expr, Stmt};
+use crate::error::JsEngineError;
+use crate::lexer::TokenType;
+use crate::parser::Parser;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Syntax extensions let experimental syntax be prototyped without forking the
+// parser. An extension can:
+//
+// - register new operators, which the lexer emits as TokenType::Operator
+// - claim statements that start with a given identifier, e.g. `defer f();`
+// - parse an expression that starts with a given token
+// - parse an infix operator with a given precedence, e.g. `x |> f`
+//
+// Handlers are called with the parser positioned just after the token that
+// triggered them, and use the parser's public helpers (`expression`,
+// `binary`, `consume`, ...) to read the rest of the construct. Extensions are
+// consulted before the built-in grammar.
+
+// Parses the rest of a statement after its leading keyword
+pub type StatementParser = dyn Fn(&mut Parser) -> Result<Stmt, JsEngineError>;
+
+// Parses the rest of an expression after its leading token
+pub type PrefixParser = dyn Fn(&mut Parser) -> Result<Expr, JsEngineError>;
+
+// Parses the right-hand side of an infix operator given its left operand
+pub type InfixParser = dyn Fn(&mut Parser, Expr) -> Result<Expr, JsEngineError>;
+
+#[derive(Clone, Default)]
+pub struct SyntaxExtensions {
+    operators: Vec<String>,
+    statements: HashMap<String, Rc<StatementParser>>,
+    prefixes: Vec<(TokenType, Rc<PrefixParser>)>,
+    infixes: Vec<(TokenType, u8, Rc<InfixParser>)>,
+}
+
+impl SyntaxExtensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // Adds a punctuation operator such as "|>". Longer operators take
+    // priority over shorter ones and over the built-in tokens.
+    pub fn add_operator(&mut self, operator: &str) -> &mut Self {
+        self.operators.push(operator.to_string());
+        self.operators.sort_by_key(|operator| std::cmp::Reverse(operator.len()));
+        self
+    }
+    
+    // Handles statements that start with the identifier `keyword`
+    pub fn add_statement(&mut self, keyword: &str, parser: Rc<StatementParser>) -> &mut Self {
+        self.statements.insert(keyword.to_string(), parser);
+        self
+    }
+    
+    // Handles expressions that start with `token`
+    pub fn add_prefix(&mut self, token: TokenType, parser: Rc<PrefixParser>) -> &mut Self {
+        self.prefixes.push((token, parser));
+        self
+    }
+    
+    // Handles `token` as an infix operator. Precedence uses the same scale as
+    // the built-in operators, listed in `parser::binary_operator`.
+    pub fn add_infix(&mut self, token: TokenType, precedence: u8, parser: Rc<InfixParser>) -> &mut Self {
+        self.infixes.push((token, precedence, parser));
+        self
+    }
+    
+    pub fn operators(&self) -> &[String] {
+        &self.operators
+    }
+    
+    pub fn statement(&self, keyword: &str) -> Option<Rc<StatementParser>> {
+        self.statements.get(keyword).cloned()
+    }
+    
+    pub fn prefix(&self, token: &TokenType) -> Option<Rc<PrefixParser>> {
+        self.prefixes.iter().find(|(t, _)| t == token).map(|(_, parser)| Rc::clone(parser))
+    }
+    
+    pub fn infix(&self, token: &TokenType) -> Option<(u8, Rc<InfixParser>)> {
+        self.infixes
+            .iter()
+            .find(|(t, _, _)| t == token)
+            .map(|(_, precedence, parser)| (*precedence, Rc::clone(parser)))
+    }
+}
@@ -0,0 +1,99 @@
+// src/tasks.rs
+use crate::interpreter::Value;
+use std::collections::VecDeque;
+
+// Callbacks scheduled to run after the current script, such as timers. The
+// interpreter drains the queue once a script finishes, running each task when
+// it is due, earliest first and in scheduling order for equal due times. Due
+// times are in milliseconds on the interpreter's clock. A repeating task is
+// scheduled again under the same id each time it runs, until it is cancelled.
+//
+// Microtasks (queueMicrotask, and promise reactions; see promise.rs)
+// run sooner: all of them, in the order they were queued, as soon as the
+// script or the current task finishes and before the next task starts.
+// Microtasks queued by a microtask run in the same round, so a zero-delay
+// timer never runs before a microtask queued ahead of it.
+#[derive(Default)]
+pub struct TaskQueue {
+    tasks: Vec<Task>,
+    microtasks: VecDeque<Value>,
+    next_id: u64,
+    next_sequence: u64,
+}
+
+pub struct Task {
+    pub id: u64,
+    pub due: f64,
+    pub callback: Value,
+    pub arguments: Vec<Value>,
+    pub interval: Option<f64>, // Delay between runs of a repeating task
+    sequence: u64, // Scheduling order, renewed each time a repeating task is scheduled again
+}
+
+impl TaskQueue {
+    // Returns an id for cancelling the task; ids start at 1 so that 0 is
+    // never a valid handle
+    pub fn schedule(&mut self, due: f64, callback: Value, arguments: Vec<Value>) -> u64 {
+        self.schedule_task(due, None, callback, arguments)
+    }
+    
+    // Schedules a task that runs first at `due` and then every `interval`
+    // milliseconds after each run
+    pub fn schedule_repeating(&mut self, due: f64, interval: f64, callback: Value, arguments: Vec<Value>) -> u64 {
+        self.schedule_task(due, Some(interval), callback, arguments)
+    }
+    
+    fn schedule_task(&mut self, due: f64, interval: Option<f64>, callback: Value, arguments: Vec<Value>) -> u64 {
+        self.next_id += 1;
+        self.next_sequence += 1;
+        self.tasks.push(Task {
+            id: self.next_id,
+            due,
+            callback,
+            arguments,
+            interval,
+            sequence: self.next_sequence,
+        });
+        self.next_id
+    }
+    
+    // Schedules the next run of a repeating task that is about to run, so
+    // that the task can still cancel itself
+    pub fn repeat(&mut self, task: &Task, due: f64) {
+        self.next_sequence += 1;
+        self.tasks.push(Task {
+            id: task.id,
+            due,
+            callback: task.callback.clone(),
+            arguments: task.arguments.clone(),
+            interval: task.interval,
+            sequence: self.next_sequence,
+        });
+    }
+    
+    pub fn queue_microtask(&mut self, callback: Value) {
+        self.microtasks.push_back(callback);
+    }
+    
+    pub fn pop_microtask(&mut self) -> Option<Value> {
+        self.microtasks.pop_front()
+    }
+    
+    pub fn cancel(&mut self, id: u64) {
+        self.tasks.retain(|task| task.id != id);
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty() && self.microtasks.is_empty()
+    }
+    
+    // Removes the task that should run next, breaking ties between equal due
+    // times by scheduling order
+    pub fn pop_next(&mut self) -> Option<Task> {
+        let index = (0..self.tasks.len()).min_by(|&a, &b| {
+            let (a, b) = (&self.tasks[a], &self.tasks[b]);
+            a.due.total_cmp(&b.due).then(a.sequence.cmp(&b.sequence))
+        })?;
+        Some(self.tasks.remove(index))
+    }
+}
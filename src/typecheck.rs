@@ -0,0 +1,605 @@
+// src/typecheck.rs
+use crate::ast::{BinaryOp, Expr, FunctionKind, Pattern, PropertyKey, Stmt, UnaryOp};
+use crate::lexer::Span;
+use std::collections::HashMap;
+use std::fmt;
+
+// Static pass that infers simple types from the AST and reports probable
+// TypeErrors: calling something that is not a function, reading properties
+// of null or undefined, iterating over a value that is not iterable, using
+// an arithmetic operator on operands the engine does not convert, and
+// calling a function with more arguments than it declares. All but the last
+// throw whenever they are reached; extra arguments are dropped, but are
+// almost always a mistake.
+//
+// The engine converts less than JavaScript does. `+` adds two numbers or
+// joins a string with anything, objects and functions being made strings
+// first, so `{} + 1` is "[object Object]1" but `true + 1` throws; the other
+// arithmetic operators and unary minus only take numbers, so `"a" * 2` and
+// `-"x"` throw too.
+//
+// The analysis is deliberately shallow. Parameters and anything it cannot
+// follow are `Unknown`, and unknown types never produce a diagnostic.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Number,
+    String,
+    Boolean,
+    Null,
+    Undefined,
+    Function { arity: Option<usize>, returns: Box<Type> }, // None for natives
+    Object, // An object or array literal without valueOf or toString of its own
+    Unknown,
+}
+
+impl Type {
+    fn function(arity: Option<usize>) -> Type {
+        Type::Function { arity, returns: Box::new(Type::Unknown) }
+    }
+    
+    // The type of a value that is one of `self` or `other`
+    fn join(&self, other: &Type) -> Type {
+        if self == other { self.clone() } else { Type::Unknown }
+    }
+    
+    fn is_known(&self) -> bool {
+        *self != Type::Unknown
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Number => write!(f, "number"),
+            Type::String => write!(f, "string"),
+            Type::Boolean => write!(f, "boolean"),
+            Type::Null => write!(f, "null"),
+            Type::Undefined => write!(f, "undefined"),
+            Type::Function { .. } => write!(f, "function"),
+            Type::Object => write!(f, "object"),
+            Type::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeDiagnostic {
+    pub message: String,
+    pub span: Span, // Of the offending expression, or the nearest one with a position
+    pub function: Option<String>, // Enclosing named function, if any
+}
+
+impl fmt::Display for TypeDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.function {
+            Some(name) => write!(f, "type error in function '{}': {}", name, self.message),
+            None => write!(f, "type error: {}", self.message),
+        }
+    }
+}
+
+pub fn check_types(statements: &[Stmt]) -> Vec<TypeDiagnostic> {
+    // Globals provided by the interpreter whose types are known; the others
+    // are unknown like any name the checker has not seen declared
+    let mut globals = HashMap::new();
+    for name in ["parseInt", "parseFloat", "isNaN", "isFinite"] {
+        globals.insert(name.to_string(), Type::function(None));
+    }
+    for name in ["NaN", "Infinity"] {
+        globals.insert(name.to_string(), Type::Number);
+    }
+    globals.insert("undefined".to_string(), Type::Undefined);
+    globals.insert("queueMicrotask".to_string(), Type::function(Some(1)));
+    
+    let mut checker = Checker {
+        scopes: vec![globals],
+        functions: Vec::new(),
+        span: Span::default(),
+        diagnostics: Vec::new(),
+    };
+    
+    checker.hoist(statements);
+    for statement in statements {
+        checker.statement(statement);
+    }
+    
+    checker.diagnostics
+}
+
+// Function being checked: its name and the join of its return types so far
+struct FunctionFrame {
+    name: Option<String>,
+    returns: Option<Type>,
+}
+
+struct Checker {
+    scopes: Vec<HashMap<String, Type>>,
+    functions: Vec<FunctionFrame>,
+    span: Span, // Of the last node visited that has one
+    diagnostics: Vec<TypeDiagnostic>,
+}
+
+impl Checker {
+    fn statement(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Expression(expr) => {
+                self.expression(expr);
+            },
+            Stmt::Declaration(name, initializer, span, _) => {
+                self.span = *span;
+                let value_type = match initializer {
+                    Some(expr) => self.expression(expr),
+                    None => Type::Undefined,
+                };
+                
+                self.define(name, value_type);
+            },
+            Stmt::Destructure(pattern, initializer, _) => {
+                self.expression(initializer);
+                self.pattern(pattern);
+            },
+            Stmt::Block(statements) => {
+                self.scopes.push(HashMap::new());
+                self.hoist(statements);
+                
+                for statement in statements {
+                    self.statement(statement);
+                }
+                
+                self.scopes.pop();
+            },
+            Stmt::If(condition, then_branch, else_branch) => {
+                self.expression(condition);
+                self.statement(then_branch);
+                
+                if let Some(else_stmt) = else_branch {
+                    self.statement(else_stmt);
+                }
+            },
+            Stmt::While(condition, body) => {
+                self.expression(condition);
+                self.statement(body);
+            },
+            Stmt::For(initializer, condition, update, body, _) => {
+                self.scopes.push(HashMap::new());
+                
+                if let Some(initializer) = initializer {
+                    self.statement(initializer);
+                }
+                if let Some(condition) = condition {
+                    self.expression(condition);
+                }
+                if let Some(update) = update {
+                    self.expression(update);
+                }
+                self.statement(body);
+                
+                self.scopes.pop();
+            },
+            Stmt::ForOf(name, iterable, body, span, declared) => {
+                self.span = *span;
+                let iterable_type = self.expression(iterable);
+                if iterable_type.is_known() && !matches!(iterable_type, Type::String | Type::Object) {
+                    self.report(span_of(iterable).unwrap_or(*span), format!("Cannot iterate over a {}", iterable_type));
+                }
+                
+                self.scopes.push(HashMap::new());
+                if *declared {
+                    self.define(name, Type::Unknown);
+                } else {
+                    self.assign(name, &Type::Unknown);
+                }
+                self.statement(body);
+                self.scopes.pop();
+            },
+            Stmt::Switch(discriminant, clauses) => {
+                self.expression(discriminant);
+                
+                self.scopes.push(HashMap::new());
+                for (_, body) in clauses {
+                    self.hoist(body);
+                }
+                for (test, body) in clauses {
+                    if let Some(test) = test {
+                        self.expression(test);
+                    }
+                    for statement in body {
+                        self.statement(statement);
+                    }
+                }
+                self.scopes.pop();
+            },
+            Stmt::Break => {},
+            Stmt::Try(block, handler, finalizer) => {
+                self.statement(block);
+                
+                if let Some((parameter, body)) = handler {
+                    self.scopes.push(HashMap::new());
+                    if let Some(parameter) = parameter {
+                        self.pattern(parameter);
+                    }
+                    self.statement(body);
+                    self.scopes.pop();
+                }
+                if let Some(finalizer) = finalizer {
+                    self.statement(finalizer);
+                }
+            },
+            Stmt::Throw(value) => {
+                self.expression(value);
+            },
+            Stmt::Return(value) => {
+                let value_type = match value {
+                    Some(expr) => self.expression(expr),
+                    None => Type::Undefined,
+                };
+                
+                if let Some(frame) = self.functions.last_mut() {
+                    frame.returns = Some(match &frame.returns {
+                        Some(returns) => returns.join(&value_type),
+                        None => value_type,
+                    });
+                }
+            },
+            Stmt::Function(name, parameters, body, span, kind) => {
+                self.span = *span;
+                let function_type = self.function(Some(name), parameters.len(), body, *kind);
+                self.define(name, function_type);
+            },
+        }
+    }
+    
+    fn expression(&mut self, expr: &Expr) -> Type {
+        match expr {
+            Expr::Number(_) => Type::Number,
+            Expr::String(_) => Type::String,
+            Expr::Boolean(_) => Type::Boolean,
+            Expr::Null => Type::Null,
+            Expr::RegExp(..) => Type::Unknown,
+            Expr::Variable(name, span) | Expr::Global(name, span, _) => {
+                self.span = *span;
+                self.lookup(name)
+            },
+            Expr::This => Type::Unknown,
+            Expr::Object(properties) => {
+                let mut converts = false;
+                for (key, value) in properties {
+                    match key {
+                        PropertyKey::Computed(key) => {
+                            self.expression(key);
+                            converts = true; // Could be either
+                        },
+                        PropertyKey::Name(name) => converts |= matches!(name.as_ref(), "valueOf" | "toString"),
+                    }
+                    self.expression(value);
+                }
+                if converts { Type::Unknown } else { Type::Object }
+            },
+            Expr::Array(elements) => {
+                for element in elements {
+                    self.expression(element);
+                }
+                Type::Object
+            },
+            Expr::Get(object, name, _) => {
+                let object_type = self.expression(object);
+                if matches!(object_type, Type::Null | Type::Undefined) {
+                    self.report(span_of(object).unwrap_or(self.span), format!("Cannot read property '{}' of {}", name, object_type));
+                }
+                Type::Unknown
+            },
+            Expr::Index(object, key) => {
+                let object_type = self.expression(object);
+                self.expression(key);
+                if matches!(object_type, Type::Null | Type::Undefined) {
+                    self.report(span_of(object).unwrap_or(self.span), format!("Cannot read properties of {}", object_type));
+                }
+                Type::Unknown
+            },
+            Expr::Set(object, name, value, _) => {
+                let object_type = self.expression(object);
+                if matches!(object_type, Type::Null | Type::Undefined) {
+                    self.report(span_of(object).unwrap_or(self.span), format!("Cannot set property '{}' of {}", name, object_type));
+                }
+                self.expression(value)
+            },
+            Expr::SetIndex(object, key, value) => {
+                let object_type = self.expression(object);
+                self.expression(key);
+                if matches!(object_type, Type::Null | Type::Undefined) {
+                    self.report(span_of(object).unwrap_or(self.span), format!("Cannot set properties of {}", object_type));
+                }
+                self.expression(value)
+            },
+            Expr::Binary(left, operator, right) => {
+                let left_type = self.expression(left);
+                let right_type = self.expression(right);
+                self.binary(expr, operator, &left_type, &right_type)
+            },
+            Expr::Unary(operator, right) => {
+                let operand = self.expression(right);
+                
+                match operator {
+                    UnaryOp::Negate => {
+                        if operand.is_known() && operand != Type::Number {
+                            self.report(span_of(expr).unwrap_or(self.span), format!("Cannot negate {}", described(&operand)));
+                        }
+                        Type::Number
+                    },
+                    UnaryOp::Not => Type::Boolean,
+                    UnaryOp::Plus => Type::Number,
+                    UnaryOp::Void => Type::Undefined,
+                }
+            },
+            Expr::Conditional(condition, then_branch, else_branch) => {
+                self.expression(condition);
+                let then_type = self.expression(then_branch);
+                let else_type = match else_branch {
+                    Some(else_expr) => self.expression(else_expr),
+                    None => Type::Undefined,
+                };
+                
+                then_type.join(&else_type)
+            },
+            Expr::CompoundAssign(target, operator, value) => {
+                let target_type = self.expression(target);
+                let value_type = self.expression(value);
+                let result_type = self.binary(expr, operator, &target_type, &value_type);
+                if let Expr::Variable(name, _) | Expr::Global(name, _, _) = target.as_ref() {
+                    self.assign(name, &result_type);
+                }
+                result_type
+            },
+            Expr::Assign(name, value, span) => {
+                self.span = *span;
+                let value_type = self.expression(value);
+                self.assign(name, &value_type);
+                value_type
+            },
+            Expr::Call(callee, arguments, span) => {
+                self.span = *span;
+                let callee_type = self.expression(callee);
+                for argument in arguments {
+                    self.expression(argument);
+                }
+                
+                // Missing arguments are undefined, which is how optional
+                // parameters are written. How many a spread passes is not
+                // known.
+                match callee_type {
+                    Type::Function { arity, returns } => {
+                        if let Some(arity) = arity
+                            && arguments.len() > arity
+                            && !arguments.iter().any(|argument| matches!(argument, Expr::Spread(_)))
+                        {
+                            self.report(*span, format!(
+                                "{} expects {} argument{} but is called with {}",
+                                callee_name(callee),
+                                arity,
+                                if arity == 1 { "" } else { "s" },
+                                arguments.len(),
+                            ));
+                        }
+                        *returns
+                    },
+                    Type::Unknown => Type::Unknown,
+                    other => {
+                        self.report(*span, format!("{} is {}, not a function", callee_name(callee), described(&other)));
+                        Type::Unknown
+                    },
+                }
+            },
+            // Constructors are not tracked, but always give an object
+            Expr::New(constructor, arguments, span) => {
+                self.span = *span;
+                self.expression(constructor);
+                for argument in arguments {
+                    self.expression(argument);
+                }
+                Type::Object
+            },
+            // A chain may be cut short, giving undefined, and its optional
+            // bases are never accessed while null or undefined
+            Expr::Chain(chain) => self.expression(chain).join(&Type::Undefined),
+            Expr::Optional(base) => match self.expression(base) {
+                Type::Null | Type::Undefined => Type::Unknown,
+                base_type => base_type,
+            },
+            Expr::Spread(iterable) => {
+                self.expression(iterable);
+                Type::Unknown
+            },
+            // Promises are not tracked
+            Expr::Await(operand) => {
+                self.expression(operand);
+                Type::Unknown
+            },
+            Expr::Function(parameters, body, span, kind) => {
+                self.span = *span;
+                self.function(None, parameters.len(), body, *kind)
+            },
+            Expr::NamedFunction(function) => {
+                let Stmt::Function(name, ..) = function.as_ref() else {
+                    return Type::Unknown;
+                };
+                
+                // The name is only visible inside the function
+                self.scopes.push(HashMap::new());
+                self.hoist(std::slice::from_ref(function.as_ref()));
+                self.statement(function);
+                let function_type = self.lookup(name);
+                self.scopes.pop();
+                
+                function_type
+            },
+        }
+    }
+    
+    // Result type of a binary operation, reporting operands the engine
+    // rejects. `expr` is the operation, for its position.
+    fn binary(&mut self, expr: &Expr, operator: &BinaryOp, left: &Type, right: &Type) -> Type {
+        let span = span_of(expr).unwrap_or(self.span);
+        match operator {
+            // Objects and functions convert to strings first
+            BinaryOp::Add => match (left, right) {
+                (Type::Number, Type::Number) => Type::Number,
+                (Type::String | Type::Object | Type::Function { .. }, _) | (_, Type::String | Type::Object | Type::Function { .. }) => Type::String,
+                _ if left.is_known() && right.is_known() => {
+                    self.report(span, format!("Cannot add {} and {}", described(left), described(right)));
+                    Type::Unknown
+                },
+                _ => Type::Unknown,
+            },
+            BinaryOp::Subtract | BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Remainder | BinaryOp::Exponent => {
+                if [left, right].iter().any(|operand| operand.is_known() && **operand != Type::Number) {
+                    let (left, right) = (described(left), described(right));
+                    let message = match operator {
+                        BinaryOp::Subtract => format!("Cannot subtract {} from {}", right, left),
+                        BinaryOp::Multiply => format!("Cannot multiply {} and {}", left, right),
+                        BinaryOp::Divide => format!("Cannot divide {} by {}", left, right),
+                        BinaryOp::Remainder => format!("Cannot take the remainder of {} by {}", left, right),
+                        _ => format!("Cannot raise {} to the power of {}", left, right),
+                    };
+                    self.report(span, message);
+                }
+                Type::Number
+            },
+            BinaryOp::Less | BinaryOp::LessEqual | BinaryOp::Greater | BinaryOp::GreaterEqual => Type::Boolean,
+            BinaryOp::Equal | BinaryOp::NotEqual => Type::Boolean,
+            BinaryOp::And | BinaryOp::Or => left.join(right),
+            BinaryOp::Nullish => match left {
+                Type::Null | Type::Undefined => right.clone(),
+                _ if left.is_known() => left.clone(),
+                _ => left.join(right),
+            },
+        }
+    }
+    
+    // Async functions return a promise, whose type is not tracked
+    fn function(&mut self, name: Option<&str>, arity: usize, body: &Stmt, kind: FunctionKind) -> Type {
+        self.functions.push(FunctionFrame {
+            name: name.map(str::to_string),
+            returns: None,
+        });
+        
+        // Parameters are unknown; the body block adds its own scope
+        self.scopes.push(HashMap::new());
+        self.statement(body);
+        self.scopes.pop();
+        
+        let frame = self.functions.pop().expect("function frame");
+        if kind.is_async() {
+            return Type::function(Some(arity));
+        }
+        Type::Function {
+            arity: Some(arity),
+            returns: Box::new(frame.returns.unwrap_or(Type::Undefined)),
+        }
+    }
+    
+    // The types of destructured properties are not tracked
+    fn pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Identifier(name, _) => self.define(name, Type::Unknown),
+            Pattern::Object(properties) => {
+                for (_, target, default) in properties {
+                    if let Some(default) = default {
+                        self.expression(default);
+                    }
+                    self.pattern(target);
+                }
+            },
+        }
+    }
+    
+    // Defines the functions declared among `statements` before they are
+    // checked, so that calls made before a declaration are checked against
+    // its arity
+    fn hoist(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            if let Stmt::Function(name, parameters, ..) = statement {
+                self.define(name, Type::function(Some(parameters.len())));
+            }
+        }
+    }
+    
+    fn define(&mut self, name: &str, value_type: Type) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), value_type);
+        }
+    }
+    
+    // Assigning a value of a different type makes the variable's type unknown
+    fn assign(&mut self, name: &str, value_type: &Type) {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(existing) = scope.get_mut(name) {
+                *existing = existing.join(value_type);
+                return;
+            }
+        }
+    }
+    
+    fn lookup(&self, name: &str) -> Type {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+            .unwrap_or(Type::Unknown)
+    }
+    
+    // Each problem is reported once, though a node may be checked again
+    fn report(&mut self, span: Span, message: String) {
+        let function = self.functions.iter().rev().find_map(|frame| frame.name.clone());
+        let diagnostic = TypeDiagnostic { message, span, function };
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+}
+
+// How a callee is referred to in messages
+fn callee_name(callee: &Expr) -> String {
+    match callee {
+        Expr::Variable(name, _) | Expr::Global(name, _, _) => format!("'{}'", name),
+        _ => "expression".to_string(),
+    }
+}
+
+// A type as a noun in messages: "a number", "an object", "undefined"
+fn described(value_type: &Type) -> String {
+    match value_type {
+        Type::Null | Type::Undefined => value_type.to_string(),
+        Type::Object => format!("an {}", value_type),
+        _ => format!("a {}", value_type),
+    }
+}
+
+// The source covered by the positions within an expression; None when it
+// holds none, as literals do not
+fn span_of(expr: &Expr) -> Option<Span> {
+    let join = |a: Option<Span>, b: Option<Span>| match (a, b) {
+        (Some(a), Some(b)) => Some(Span { start: a.start.min(b.start), end: a.end.max(b.end) }),
+        (a, b) => a.or(b),
+    };
+    match expr {
+        Expr::Variable(_, span) | Expr::Global(_, span, _) | Expr::Call(_, _, span) | Expr::New(_, _, span) | Expr::Function(_, _, span, _) => Some(*span),
+        Expr::Assign(_, value, span) => join(Some(*span), span_of(value)),
+        Expr::Binary(left, _, right) | Expr::CompoundAssign(left, _, right) | Expr::Index(left, right) => join(span_of(left), span_of(right)),
+        Expr::NamedFunction(function) => match function.as_ref() {
+            Stmt::Function(_, _, _, span, _) => Some(*span),
+            _ => None,
+        },
+        Expr::Get(object, ..) | Expr::Unary(_, object) | Expr::Chain(object) | Expr::Optional(object) | Expr::Spread(object) | Expr::Await(object) => span_of(object),
+        Expr::Set(object, _, value, _) => join(span_of(object), span_of(value)),
+        Expr::SetIndex(object, key, value) => join(join(span_of(object), span_of(key)), span_of(value)),
+        Expr::Conditional(condition, then_branch, else_branch) => {
+            let span = join(span_of(condition), span_of(then_branch));
+            join(span, else_branch.as_deref().and_then(span_of))
+        },
+        Expr::Object(properties) => properties.iter().map(|(_, value)| span_of(value)).fold(None, join),
+        Expr::Array(elements) => elements.iter().map(span_of).fold(None, join),
+        Expr::Number(_) | Expr::String(_) | Expr::Boolean(_) | Expr::Null | Expr::RegExp(..) | Expr::This => None,
+    }
+}
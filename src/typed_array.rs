@@ -0,0 +1,609 @@
+// src/typed_array.rs
+use crate::error::JsEngineError;
+use crate::interpreter::{NativeFunction, Value};
+use crate::iterator::ValueIterator;
+use crate::object::{InternalSlot, PropertyMap};
+use crate::primitives::integer;
+use crate::stdlib::to_number;
+use crate::string::JsString;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Binary data. An ArrayBuffer holds bytes; typed arrays and data views are
+// views onto part of one, which they share with any other views onto it. A
+// typed array reads and writes its bytes as numbers of one type, in the
+// platform order, which is taken to be little-endian; a data view reads and
+// writes numbers of any type at any byte offset, big-endian unless asked
+// otherwise. TextEncoder and TextDecoder turn strings into UTF-8 bytes and
+// back.
+//
+// Elements of typed arrays are read and written by index like those of
+// arrays. An index outside the array reads as undefined and writing to one
+// does nothing. Written values are converted to the element type, so a
+// Uint8Array keeps the low eight bits of integers and a Float32Array rounds
+// to single precision.
+
+pub struct ArrayBuffer {
+    bytes: RefCell<Vec<u8>>,
+}
+
+impl ArrayBuffer {
+    pub fn new(bytes: Vec<u8>) -> Rc<ArrayBuffer> {
+        Rc::new(ArrayBuffer {
+            bytes: RefCell::new(bytes),
+        })
+    }
+    
+    // The buffer behind an ArrayBuffer object
+    pub fn of(value: &Value) -> Option<Rc<ArrayBuffer>> {
+        match value {
+            Value::Object(object) => match object.borrow().slot() {
+                Some(InternalSlot::ArrayBuffer(buffer)) => Some(Rc::clone(buffer)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    
+    pub fn into_value(self: Rc<Self>) -> Value {
+        Value::Object(Rc::new(RefCell::new(PropertyMap::with_slot(InternalSlot::ArrayBuffer(self)))))
+    }
+    
+    pub fn len(&self) -> usize {
+        self.bytes.borrow().len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    pub fn bytes(&self) -> Vec<u8> {
+        self.bytes.borrow().clone()
+    }
+    
+    // byteLength and slice
+    pub(crate) fn property(self: &Rc<Self>, name: &str) -> Option<Value> {
+        match name {
+            "byteLength" => Some(Value::Number(self.len() as f64)),
+            "slice" => {
+                let buffer = Rc::clone(self);
+                Some(method(move |arguments| {
+                    let (start, end) = range_arguments(arguments, buffer.len());
+                    let bytes = buffer.bytes.borrow()[start..end.max(start)].to_vec();
+                    Ok(ArrayBuffer::new(bytes).into_value())
+                }))
+            },
+            _ => None,
+        }
+    }
+}
+
+// A number type that views read and write
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ElementKind {
+    Int8,
+    Uint8,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+}
+
+impl ElementKind {
+    pub const ALL: [ElementKind; 8] = [
+        ElementKind::Int8,
+        ElementKind::Uint8,
+        ElementKind::Int16,
+        ElementKind::Uint16,
+        ElementKind::Int32,
+        ElementKind::Uint32,
+        ElementKind::Float32,
+        ElementKind::Float64,
+    ];
+    
+    // The type's name in the names of typed arrays and DataView methods
+    pub fn type_name(self) -> &'static str {
+        match self {
+            ElementKind::Int8 => "Int8",
+            ElementKind::Uint8 => "Uint8",
+            ElementKind::Int16 => "Int16",
+            ElementKind::Uint16 => "Uint16",
+            ElementKind::Int32 => "Int32",
+            ElementKind::Uint32 => "Uint32",
+            ElementKind::Float32 => "Float32",
+            ElementKind::Float64 => "Float64",
+        }
+    }
+    
+    // The typed array global for the type
+    pub fn name(self) -> String {
+        format!("{}Array", self.type_name())
+    }
+    
+    // Bytes per element
+    pub fn size(self) -> usize {
+        match self {
+            ElementKind::Int8 | ElementKind::Uint8 => 1,
+            ElementKind::Int16 | ElementKind::Uint16 => 2,
+            ElementKind::Int32 | ElementKind::Uint32 | ElementKind::Float32 => 4,
+            ElementKind::Float64 => 8,
+        }
+    }
+    
+    fn read(self, bytes: &[u8], little_endian: bool) -> f64 {
+        let mut le = [0; 8];
+        le[..bytes.len()].copy_from_slice(bytes);
+        if !little_endian {
+            le[..bytes.len()].reverse();
+        }
+        let [b0, b1, b2, b3, ..] = le;
+        match self {
+            ElementKind::Int8 => b0 as i8 as f64,
+            ElementKind::Uint8 => b0 as f64,
+            ElementKind::Int16 => i16::from_le_bytes([b0, b1]) as f64,
+            ElementKind::Uint16 => u16::from_le_bytes([b0, b1]) as f64,
+            ElementKind::Int32 => i32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            ElementKind::Uint32 => u32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            ElementKind::Float32 => f32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            ElementKind::Float64 => f64::from_le_bytes(le),
+        }
+    }
+    
+    // Integers wrap around, keeping the bits that fit
+    fn write(self, bytes: &mut [u8], n: f64, little_endian: bool) {
+        let mut le = [0; 8];
+        match self {
+            ElementKind::Float32 => le[..4].copy_from_slice(&(n as f32).to_le_bytes()),
+            ElementKind::Float64 => le = n.to_le_bytes(),
+            _ => le = (modulo(n, 2f64.powi(8 * self.size() as i32)) as u64).to_le_bytes(),
+        }
+        bytes.copy_from_slice(&le[..bytes.len()]);
+        if !little_endian {
+            bytes.reverse();
+        }
+    }
+}
+
+pub struct TypedArray {
+    kind: ElementKind,
+    buffer: Rc<ArrayBuffer>,
+    buffer_object: RefCell<Option<Value>>, // Made when a script first asks for the buffer
+    offset: usize, // In bytes
+    length: usize, // In elements
+}
+
+impl TypedArray {
+    // A new array of zeroed elements, with a buffer of its own
+    pub fn new(kind: ElementKind, length: usize) -> Rc<TypedArray> {
+        TypedArray::view(kind, ArrayBuffer::new(vec![0; length * kind.size()]), None, 0, length)
+    }
+    
+    // A Uint8Array holding `bytes`
+    pub fn from_bytes(bytes: Vec<u8>) -> Rc<TypedArray> {
+        let length = bytes.len();
+        TypedArray::view(ElementKind::Uint8, ArrayBuffer::new(bytes), None, 0, length)
+    }
+    
+    // The caller checks that the elements lie within the buffer
+    fn view(kind: ElementKind, buffer: Rc<ArrayBuffer>, buffer_object: Option<Value>, offset: usize, length: usize) -> Rc<TypedArray> {
+        Rc::new(TypedArray {
+            kind,
+            buffer,
+            buffer_object: RefCell::new(buffer_object),
+            offset,
+            length,
+        })
+    }
+    
+    // The typed array behind a typed array object
+    pub fn of(value: &Value) -> Option<Rc<TypedArray>> {
+        match value {
+            Value::Object(object) => match object.borrow().slot() {
+                Some(InternalSlot::TypedArray(array)) => Some(Rc::clone(array)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    
+    pub fn into_value(self: Rc<Self>) -> Value {
+        Value::Object(Rc::new(RefCell::new(PropertyMap::with_slot(InternalSlot::TypedArray(self)))))
+    }
+    
+    pub fn kind(&self) -> ElementKind {
+        self.kind
+    }
+    
+    pub fn len(&self) -> usize {
+        self.length
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+    
+    pub fn get(&self, index: usize) -> Option<f64> {
+        if index >= self.length {
+            return None;
+        }
+        let start = self.offset + index * self.kind.size();
+        Some(self.kind.read(&self.buffer.bytes.borrow()[start..start + self.kind.size()], true))
+    }
+    
+    // Does nothing outside the array
+    pub fn set(&self, index: usize, n: f64) {
+        if index >= self.length {
+            return;
+        }
+        let start = self.offset + index * self.kind.size();
+        self.kind.write(&mut self.buffer.bytes.borrow_mut()[start..start + self.kind.size()], n, true);
+    }
+    
+    // The bytes the array views
+    pub fn bytes(&self) -> Vec<u8> {
+        self.buffer.bytes.borrow()[self.offset..self.offset + self.length * self.kind.size()].to_vec()
+    }
+    
+    pub fn values(&self) -> Vec<f64> {
+        (0..self.length).filter_map(|index| self.get(index)).collect()
+    }
+    
+    // The ArrayBuffer object the array views, made the first time it is
+    // asked for
+    pub(crate) fn buffer_value(&self) -> Value {
+        self.buffer_object.borrow_mut().get_or_insert_with(|| Rc::clone(&self.buffer).into_value()).clone()
+    }
+    
+    // The same view onto another ArrayBuffer object, for copies of values;
+    // None if `buffer_object` is not one
+    pub(crate) fn with_buffer(&self, buffer_object: Value) -> Option<Rc<TypedArray>> {
+        let buffer = ArrayBuffer::of(&buffer_object)?;
+        Some(TypedArray::view(self.kind, buffer, Some(buffer_object), self.offset, self.length))
+    }
+    
+    // An element, the sizes, the buffer, or subarray, which gives a view
+    // onto part of the same elements
+    pub(crate) fn property(self: &Rc<Self>, name: &str) -> Option<Value> {
+        if let Some(index) = canonical_index(name) {
+            return Some(self.get(index).map_or(Value::Undefined, Value::Number));
+        }
+        match name {
+            "length" => Some(Value::Number(self.length as f64)),
+            "byteLength" => Some(Value::Number((self.length * self.kind.size()) as f64)),
+            "byteOffset" => Some(Value::Number(self.offset as f64)),
+            "BYTES_PER_ELEMENT" => Some(Value::Number(self.kind.size() as f64)),
+            "buffer" => Some(self.buffer_value()),
+            "subarray" => {
+                let array = Rc::clone(self);
+                Some(method(move |arguments| {
+                    let (start, end) = range_arguments(arguments, array.length);
+                    let offset = array.offset + start * array.kind.size();
+                    let view = TypedArray::view(array.kind, Rc::clone(&array.buffer), Some(array.buffer_value()), offset, end.saturating_sub(start));
+                    Ok(view.into_value())
+                }))
+            },
+            _ => None,
+        }
+    }
+    
+    // Assigns an element, returning false when `name` is not an index
+    pub(crate) fn set_property(&self, name: &str, value: &Value) -> bool {
+        match canonical_index(name) {
+            Some(index) => {
+                self.set(index, to_number(value));
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+pub struct DataView {
+    buffer: Rc<ArrayBuffer>,
+    buffer_object: Value,
+    offset: usize,
+    length: usize,
+}
+
+impl DataView {
+    pub(crate) fn into_value(self: Rc<Self>) -> Value {
+        Value::Object(Rc::new(RefCell::new(PropertyMap::with_slot(InternalSlot::DataView(self)))))
+    }
+    
+    pub fn bytes(&self) -> Vec<u8> {
+        self.buffer.bytes.borrow()[self.offset..self.offset + self.length].to_vec()
+    }
+    
+    // The ArrayBuffer object the view was made on
+    pub fn buffer(&self) -> &Value {
+        &self.buffer_object
+    }
+    
+    // The same view onto another ArrayBuffer object, for copies of values;
+    // None if `buffer_object` is not one
+    pub(crate) fn with_buffer(&self, buffer_object: Value) -> Option<Rc<DataView>> {
+        let buffer = ArrayBuffer::of(&buffer_object)?;
+        Some(Rc::new(DataView {
+            buffer,
+            buffer_object,
+            offset: self.offset,
+            length: self.length,
+        }))
+    }
+    
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+    
+    pub fn len(&self) -> usize {
+        self.length
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+    
+    // The sizes, the buffer, and the getters and setters for each number
+    // type, such as getInt32(byteOffset, littleEndian) and
+    // setInt32(byteOffset, value, littleEndian)
+    pub(crate) fn property(self: &Rc<Self>, name: &str) -> Option<Value> {
+        match name {
+            "byteLength" => return Some(Value::Number(self.length as f64)),
+            "byteOffset" => return Some(Value::Number(self.offset as f64)),
+            "buffer" => return Some(self.buffer_object.clone()),
+            _ => {},
+        }
+        let (setter, type_name) = match (name.strip_prefix("get"), name.strip_prefix("set")) {
+            (Some(type_name), _) => (false, type_name),
+            (_, Some(type_name)) => (true, type_name),
+            _ => return None,
+        };
+        let kind = ElementKind::ALL.into_iter().find(|kind| kind.type_name() == type_name)?;
+        
+        let view = Rc::clone(self);
+        Some(method(move |arguments| {
+            let offset = integer(arguments.first().map_or(0.0, to_number));
+            if offset < 0.0 || offset + kind.size() as f64 > view.length as f64 {
+                return Err(JsEngineError::throw("RangeError", "Offset is outside the bounds of the DataView"));
+            }
+            let start = view.offset + offset as usize;
+            let little_endian = matches!(arguments.get(if setter { 2 } else { 1 }), Some(Value::Boolean(true)));
+            if setter {
+                let value = arguments.get(1).map_or(f64::NAN, to_number);
+                kind.write(&mut view.buffer.bytes.borrow_mut()[start..start + kind.size()], value, little_endian);
+                return Ok(Value::Undefined);
+            }
+            Ok(Value::Number(kind.read(&view.buffer.bytes.borrow()[start..start + kind.size()], little_endian)))
+        }))
+    }
+}
+
+// The bytes of an ArrayBuffer, typed array or data view
+pub fn bytes_of(value: &Value) -> Option<Vec<u8>> {
+    let Value::Object(object) = value else {
+        return None;
+    };
+    match object.borrow().slot()? {
+        InternalSlot::ArrayBuffer(buffer) => Some(buffer.bytes()),
+        InternalSlot::TypedArray(array) => Some(array.bytes()),
+        InternalSlot::DataView(view) => Some(view.bytes()),
+        _ => None,
+    }
+}
+
+// The global `ArrayBuffer(length)`, which does what `new ArrayBuffer(length)`
+// does, making a buffer of zeros. ArrayBuffer.isView tells typed arrays and
+// data views apart from other values.
+pub(crate) fn array_buffer_constructor() -> Value {
+    let mut constructor = PropertyMap::callable(Rc::new(|_, arguments| {
+        let length = length_argument(arguments.first(), "Array buffer")?;
+        Ok(ArrayBuffer::new(vec![0; length]).into_value())
+    }));
+    let is_view = method(|arguments| {
+        let is_view = match arguments.first() {
+            Some(Value::Object(object)) => matches!(object.borrow().slot(), Some(InternalSlot::TypedArray(_) | InternalSlot::DataView(_))),
+            _ => false,
+        };
+        Ok(Value::Boolean(is_view))
+    });
+    constructor.insert("isView".to_string(), is_view);
+    Value::Object(Rc::new(RefCell::new(constructor)))
+}
+
+// The global for a kind of typed array. Called with a length it makes an
+// array of zeros; with a typed array, array or other iterable, an array of
+// its elements converted to the element type; and with an ArrayBuffer, a
+// byte offset and a length, a view onto that part of the buffer, to its end
+// if the length is left out.
+pub(crate) fn constructor(kind: ElementKind) -> Value {
+    let mut constructor = PropertyMap::callable(Rc::new(move |interpreter, arguments| {
+        let argument = arguments.first().cloned().unwrap_or(Value::Undefined);
+        if let Some(buffer) = ArrayBuffer::of(&argument) {
+            let name = kind.name();
+            let offset = integer(arguments.get(1).map_or(0.0, to_number));
+            if offset < 0.0 || !(offset as usize).is_multiple_of(kind.size()) {
+                return Err(JsEngineError::throw("RangeError", format!("start offset of {} should be a multiple of {}", name, kind.size())));
+            }
+            let offset = offset as usize;
+            if offset > buffer.len() {
+                return Err(JsEngineError::throw("RangeError", format!("Start offset {} is outside the bounds of the buffer", offset)));
+            }
+            let length = match arguments.get(2) {
+                None | Some(Value::Undefined) => {
+                    if !(buffer.len() - offset).is_multiple_of(kind.size()) {
+                        return Err(JsEngineError::throw("RangeError", format!("byte length of {} should be a multiple of {}", name, kind.size())));
+                    }
+                    (buffer.len() - offset) / kind.size()
+                },
+                length => {
+                    let length = length_argument(length, "typed array")?;
+                    if offset + length * kind.size() > buffer.len() {
+                        return Err(JsEngineError::throw("RangeError", format!("Invalid typed array length: {}", length)));
+                    }
+                    length
+                },
+            };
+            return Ok(TypedArray::view(kind, buffer, Some(argument), offset, length).into_value());
+        }
+        
+        let values = match &argument {
+            Value::Undefined => Vec::new(),
+            Value::Object(_) => ValueIterator::new(interpreter, &argument)?.collect_values(interpreter)?,
+            _ => return Ok(TypedArray::new(kind, length_argument(Some(&argument), "typed array")?).into_value()),
+        };
+        let array = TypedArray::new(kind, values.len());
+        for (index, value) in values.iter().enumerate() {
+            array.set(index, to_number(value));
+        }
+        Ok(array.into_value())
+    }));
+    constructor.insert("BYTES_PER_ELEMENT".to_string(), Value::Number(kind.size() as f64));
+    Value::Object(Rc::new(RefCell::new(constructor)))
+}
+
+// The global `DataView(buffer, byteOffset, byteLength)`, which does what
+// `new DataView(...)` does. The view reaches to the end of the buffer if
+// the length is left out.
+pub(crate) fn data_view_constructor() -> Value {
+    method(|arguments| {
+        let buffer_object = arguments.first().cloned().unwrap_or(Value::Undefined);
+        let Some(buffer) = ArrayBuffer::of(&buffer_object) else {
+            return Err(JsEngineError::TypeError {
+                message: "First argument to DataView constructor must be an ArrayBuffer".to_string(),
+            });
+        };
+        let offset = integer(arguments.get(1).map_or(0.0, to_number));
+        if offset < 0.0 || offset > buffer.len() as f64 {
+            return Err(JsEngineError::throw("RangeError", format!("Start offset {} is outside the bounds of the buffer", offset)));
+        }
+        let offset = offset as usize;
+        let length = match arguments.get(2) {
+            None | Some(Value::Undefined) => buffer.len() - offset,
+            length => {
+                let length = length_argument(length, "DataView")?;
+                if offset + length > buffer.len() {
+                    return Err(JsEngineError::throw("RangeError", format!("Invalid DataView length {}", length)));
+                }
+                length
+            },
+        };
+        Ok(Rc::new(DataView { buffer, buffer_object, offset, length }).into_value())
+    })
+}
+
+// The global `TextEncoder()`, which does what `new TextEncoder()` does. Its
+// encode method gives the UTF-8 bytes of a string as a Uint8Array.
+pub(crate) fn text_encoder() -> Value {
+    Value::NativeFunction(Rc::new(|_| {
+        let encode = Rc::new(|arguments: &[Value]| -> Result<Value, JsEngineError> {
+            let text = match arguments.first() {
+                None | Some(Value::Undefined) => String::new(),
+                Some(value) => value.to_string(),
+            };
+            Ok(TypedArray::from_bytes(text.into_bytes()).into_value())
+        });
+        let encoder = PropertyMap::from([
+            ("encoding", Value::String(JsString::from("utf-8"))),
+            ("encode", Value::NativeFunction(encode)),
+        ]);
+        Ok(Value::Object(Rc::new(RefCell::new(encoder))))
+    }))
+}
+
+// The global `TextDecoder(label, options)`, which does what
+// `new TextDecoder(label, options)` does. Only UTF-8 is supported. decode
+// takes an ArrayBuffer or a view onto one and replaces malformed sequences with U+FFFD, or
+// throws a TypeError on them when the `fatal` option is set. A leading byte
+// order mark is dropped.
+pub(crate) fn text_decoder() -> Value {
+    Value::NativeFunction(Rc::new(|arguments| {
+        let label = match arguments.first() {
+            None | Some(Value::Undefined) => "utf-8".to_string(),
+            Some(label) => label.to_string().trim().to_ascii_lowercase(),
+        };
+        if !matches!(label.as_str(), "utf-8" | "utf8" | "unicode-1-1-utf-8") {
+            return Err(JsEngineError::throw("RangeError", format!("The \"{}\" encoding is not supported", label)));
+        }
+        let fatal = match arguments.get(1) {
+            Some(Value::Object(options)) => matches!(options.borrow().lookup("fatal"), Some(Value::Boolean(true))),
+            _ => false,
+        };
+        
+        let decode = Rc::new(move |arguments: &[Value]| -> Result<Value, JsEngineError> {
+            let bytes = match arguments.first() {
+                None | Some(Value::Undefined) => Vec::new(),
+                Some(input) => match bytes_of(input) {
+                    Some(bytes) => bytes,
+                    None => {
+                        return Err(JsEngineError::TypeError {
+                            message: "The \"input\" argument must be an ArrayBuffer or a view onto one".to_string(),
+                        });
+                    },
+                },
+            };
+            let bytes = bytes.strip_prefix("\u{feff}".as_bytes()).unwrap_or(&bytes);
+            let text = match std::str::from_utf8(bytes) {
+                Ok(text) => text.to_string(),
+                Err(_) if fatal => {
+                    return Err(JsEngineError::TypeError {
+                        message: "The encoded data was not valid for encoding utf-8".to_string(),
+                    });
+                },
+                Err(_) => String::from_utf8_lossy(bytes).into_owned(),
+            };
+            Ok(Value::String(JsString::from(text)))
+        });
+        let decoder = PropertyMap::from([
+            ("encoding", Value::String(JsString::from("utf-8"))),
+            ("fatal", Value::Boolean(fatal)),
+            ("decode", Value::NativeFunction(decode)),
+        ]);
+        Ok(Value::Object(Rc::new(RefCell::new(decoder))))
+    }))
+}
+
+// The index a property name stands for, if it is a canonical one
+pub(crate) fn canonical_index(name: &str) -> Option<usize> {
+    let index = name.parse::<usize>().ok()?;
+    (index.to_string() == name).then_some(index)
+}
+
+// `n` modulo `m`, as the integer conversions of element types take it;
+// NaN and the infinities are 0
+fn modulo(n: f64, m: f64) -> f64 {
+    if n.is_finite() { n.trunc().rem_euclid(m) } else { 0.0 }
+}
+
+// A length or size argument, which must be a whole number of at most 2^32
+fn length_argument(argument: Option<&Value>, what: &str) -> Result<usize, JsEngineError> {
+    let argument = argument.unwrap_or(&Value::Undefined);
+    let length = match argument {
+        Value::Undefined => 0.0,
+        _ => to_number(argument),
+    };
+    if length.fract() != 0.0 || !(0.0..=u32::MAX as f64).contains(&length) {
+        return Err(JsEngineError::throw("RangeError", format!("Invalid {} length: {}", what, argument)));
+    }
+    Ok(length as usize)
+}
+
+// The start and end of slice and subarray, which count back from the end
+// when negative and default to the whole of `length`
+fn range_arguments(arguments: &[Value], length: usize) -> (usize, usize) {
+    let relative = |index: Option<&Value>, default: usize| match index {
+        None | Some(Value::Undefined) => default,
+        Some(index) => {
+            let index = integer(to_number(index));
+            let index = if index < 0.0 { index + length as f64 } else { index };
+            index.clamp(0.0, length as f64) as usize
+        },
+    };
+    (relative(arguments.first(), 0), relative(arguments.get(1), length))
+}
+
+fn method(function: impl Fn(&[Value]) -> Result<Value, JsEngineError> + 'static) -> Value {
+    let function: Rc<NativeFunction> = Rc::new(function);
+    Value::NativeFunction(function)
+}
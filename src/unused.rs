@@ -0,0 +1,104 @@
+// src/unused.rs
+use crate::ast::Stmt;
+use crate::heap::json_string;
+use crate::lexer::{line_column, Span};
+use crate::resolver::{self, ScopeKind, SymbolKind};
+
+// Finds variables, functions and parameters that are declared but never read.
+//
+// - Assigning to a variable does not count as using it.
+// - A function that is only referenced from its own body is unused.
+// - Only trailing parameters are reported, since a parameter cannot be
+//   removed while a later one is still used.
+// - Names starting with `_` are never reported, and neither are the names
+//   of function expressions, which are often only there for stack traces.
+#[derive(Debug, Clone)]
+pub struct UnusedBinding {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub span: Span,
+}
+
+impl UnusedBinding {
+    pub fn message(&self) -> String {
+        let kind = match self.kind {
+            SymbolKind::Variable => "variable",
+            SymbolKind::Function => "function",
+            SymbolKind::Parameter => "parameter",
+        };
+        
+        format!("{} '{}' is declared but never used", kind, self.name)
+    }
+}
+
+pub fn find_unused(statements: &[Stmt]) -> Vec<UnusedBinding> {
+    let table = resolver::analyze(statements);
+    
+    let is_used = |index: usize| {
+        let symbol = &table.symbols[index];
+        symbol.references.iter().any(|reference| {
+            let recursive = symbol.kind == SymbolKind::Function
+                && reference.span.start >= symbol.span.start
+                && reference.span.end <= symbol.span.end;
+            !reference.write && !recursive
+        })
+    };
+    
+    // Last used parameter in each scope
+    let mut last_used_parameter = vec![None; table.scopes.len()];
+    for (index, symbol) in table.symbols.iter().enumerate() {
+        if symbol.kind == SymbolKind::Parameter && is_used(index) {
+            last_used_parameter[symbol.scope] = Some(index);
+        }
+    }
+    
+    table
+        .symbols
+        .iter()
+        .enumerate()
+        .filter(|(index, symbol)| {
+            if symbol.name.starts_with('_') || is_used(*index) {
+                return false;
+            }
+            
+            let scope = &table.scopes[symbol.scope];
+            if scope.kind == ScopeKind::Block && scope.name.is_some() {
+                return false;
+            }
+            
+            match symbol.kind {
+                SymbolKind::Parameter => last_used_parameter[symbol.scope].is_none_or(|last| *index > last),
+                _ => true,
+            }
+        })
+        .map(|(_, symbol)| UnusedBinding {
+            name: symbol.name.clone(),
+            kind: symbol.kind,
+            span: symbol.span,
+        })
+        .collect()
+}
+
+// Machine-readable diagnostics for CI integrations: one JSON object per
+// unused binding in `file`, to be gathered across files by to_json
+pub fn json_entries(unused: &[UnusedBinding], file: &str, source: &str) -> Vec<String> {
+    unused
+        .iter()
+        .map(|binding| {
+            let (line, column) = line_column(source, binding.span.start);
+            format!(
+                "{{\"file\":{},\"line\":{},\"column\":{},\"name\":{},\"message\":{}}}",
+                json_string(file),
+                line,
+                column,
+                json_string(&binding.name),
+                json_string(&binding.message()),
+            )
+        })
+        .collect()
+}
+
+// The entries of every file analyzed, as one JSON array
+pub fn to_json(entries: &[String]) -> String {
+    format!("[{}]", entries.join(","))
+}
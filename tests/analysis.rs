@@ -0,0 +1,84 @@
+// tests/analysis.rs
+use jays::lexer::line_column;
+use jays::typecheck::check_types;
+use jays::unused::{find_unused, json_entries, to_json};
+
+// Each diagnostic as "line:column: message"
+fn type_errors(source: &str) -> Vec<String> {
+    let statements = jays::parse_str(source).unwrap();
+    check_types(&statements)
+        .iter()
+        .map(|diagnostic| {
+            let (line, column) = line_column(source, diagnostic.span.start);
+            format!("{}:{}: {}", line, column, diagnostic.message)
+        })
+        .collect()
+}
+
+// Each unused binding as "line:column: message"
+fn unused(source: &str) -> Vec<String> {
+    let statements = jays::parse_str(source).unwrap();
+    find_unused(&statements)
+        .iter()
+        .map(|binding| {
+            let (line, column) = line_column(source, binding.span.start);
+            format!("{}:{}: {}", line, column, binding.message())
+        })
+        .collect()
+}
+
+#[test]
+fn type_errors_point_at_the_code() {
+    let source = "let n = 5;\nfunction twice() { n(); n(); }\nlet total = [1] + n;\n";
+    assert_eq!(type_errors(source), [
+        "2:20: 'n' is a number, not a function",
+        "2:25: 'n' is a number, not a function",
+    ]);
+}
+
+#[test]
+fn operands_the_engine_rejects_are_type_errors() {
+    let source = "let flag = true;\nlet total = flag + 1;\nlet product = \"a\" * 2;\nlet negated = -\"x\";\n";
+    assert_eq!(type_errors(source), [
+        "2:13: Cannot add a boolean and a number",
+        "3:5: Cannot multiply a string and a number",
+        "4:5: Cannot negate a string",
+    ]);
+}
+
+#[test]
+fn conversions_the_engine_makes_are_not_type_errors() {
+    let source = "function pad(text, width) { return text; }\npad(\"a\");\n({} + 1);\n[1] + 2;\n\"a\" + true;\n({ valueOf() { return 1; } }) + 1;\n";
+    assert_eq!(type_errors(source), Vec::<String>::new());
+}
+
+#[test]
+fn unused_parameters_point_at_the_parameter() {
+    let source = "function scale(value,\n               factor, unit) {\n  return value;\n}\nscale(1);\nlet first = (x, y) => x;\nfirst(1);\n";
+    assert_eq!(unused(source), [
+        "2:16: parameter 'factor' is declared but never used",
+        "2:24: parameter 'unit' is declared but never used",
+        "6:17: parameter 'y' is declared but never used",
+    ]);
+}
+
+#[test]
+fn unused_json_gathers_every_file_into_one_array() {
+    let mut entries = Vec::new();
+    for (file, source) in [("a.js", "let a = 1;"), ("used.js", "let b = 2;\nb;"), ("c.js", "\nlet c;\nlet d;")] {
+        let statements = jays::parse_str(source).unwrap();
+        entries.extend(json_entries(&find_unused(&statements), file, source));
+    }
+    assert_eq!(to_json(&entries), concat!(
+        r#"[{"file":"a.js","line":1,"column":5,"name":"a","message":"variable 'a' is declared but never used"},"#,
+        r#"{"file":"c.js","line":2,"column":5,"name":"c","message":"variable 'c' is declared but never used"},"#,
+        r#"{"file":"c.js","line":3,"column":5,"name":"d","message":"variable 'd' is declared but never used"}]"#,
+    ));
+    assert_eq!(to_json(&[]), "[]");
+}
+
+#[test]
+fn undefined_is_known_to_the_checker() {
+    let source = "let missing = undefined;\nmissing();\n";
+    assert_eq!(type_errors(source), ["2:1: 'missing' is undefined, not a function"]);
+}
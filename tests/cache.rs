@@ -0,0 +1,95 @@
+// tests/cache.rs
+use jays::cache::ParseCache;
+use jays::interpreter::{Interpreter, Value};
+
+fn run(statements: Vec<jays::ast::Stmt>) -> f64 {
+    match Interpreter::new().interpret(statements) {
+        Ok(Value::Number(n)) => n,
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn repeated_sources_are_parsed_once() {
+    let mut cache = ParseCache::new();
+    assert_eq!(run(cache.parse("1 + 1;", false).unwrap()), 2.0);
+    assert_eq!(run(cache.parse("1 + 1;", false).unwrap()), 2.0);
+    assert_eq!(cache.len(), 1);
+    
+    // The TypeScript flag is part of the key
+    cache.parse("1 + 1;", true).unwrap();
+    cache.parse("2 + 2;", false).unwrap();
+    assert_eq!(cache.len(), 3);
+    
+    // Failures are not cached
+    assert!(cache.parse("1 +;", false).is_err());
+    assert_eq!(cache.len(), 3);
+    
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[cfg(feature = "cache")]
+mod on_disk {
+    use super::run;
+    use jays::cache::ParseCache;
+    use std::fs;
+    use std::path::PathBuf;
+    
+    // A fresh directory for one test, holding the file the cache wrote
+    // for `source`
+    fn cached_file(name: &str, source: &str) -> (PathBuf, PathBuf) {
+        let directory = std::env::temp_dir().join(format!("jays-cache-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&directory);
+        ParseCache::new().with_directory(&directory).parse(source, false).unwrap();
+        
+        let files: Vec<PathBuf> = fs::read_dir(&directory).unwrap().map(|entry| entry.unwrap().path()).collect();
+        assert_eq!(files.len(), 1);
+        let file = files[0].clone();
+        (directory, file)
+    }
+    
+    // Writes an entry for `source` holding the statements of `parsed`, as
+    // the given version of the engine would
+    fn forge(file: &PathBuf, version: &str, source: &str, parsed: &str) {
+        let statements = jays::parse_str(parsed).unwrap();
+        let bytes = bincode::serialize(&(version, (false, source, statements))).unwrap();
+        fs::write(file, bytes).unwrap();
+    }
+    
+    #[test]
+    fn later_caches_read_the_files() {
+        let (directory, file) = cached_file("read", "1 + 1;");
+        forge(&file, env!("CARGO_PKG_VERSION"), "1 + 1;", "40 + 2;");
+        
+        // The forged statements show that the file was used
+        let mut cache = ParseCache::new().with_directory(&directory);
+        assert_eq!(run(cache.parse("1 + 1;", false).unwrap()), 42.0);
+        fs::remove_dir_all(directory).unwrap();
+    }
+    
+    #[test]
+    fn files_from_other_versions_are_misses() {
+        let (directory, file) = cached_file("version", "1 + 1;");
+        forge(&file, "0.0.0-other", "1 + 1;", "40 + 2;");
+        
+        let mut cache = ParseCache::new().with_directory(&directory);
+        assert_eq!(run(cache.parse("1 + 1;", false).unwrap()), 2.0);
+        
+        // The file was replaced with the current version's
+        let (version, _): (String, (bool, String, Vec<jays::ast::Stmt>)) = bincode::deserialize(&fs::read(&file).unwrap()).unwrap();
+        assert_eq!(version, env!("CARGO_PKG_VERSION"));
+        fs::remove_dir_all(directory).unwrap();
+    }
+    
+    #[test]
+    fn files_for_other_sources_are_misses() {
+        // As if "40 + 2;" hashed to the same file as "1 + 1;"
+        let (directory, file) = cached_file("source", "1 + 1;");
+        forge(&file, env!("CARGO_PKG_VERSION"), "40 + 2;", "40 + 2;");
+        
+        let mut cache = ParseCache::new().with_directory(&directory);
+        assert_eq!(run(cache.parse("1 + 1;", false).unwrap()), 2.0);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,40 @@
+// tests/convert.rs
+use jays::convert::native_mut;
+use jays::interpreter::{Interpreter, Value};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[test]
+fn stateful_natives_keep_their_state() {
+    let mut interpreter = Interpreter::new();
+    let mut count = 0;
+    interpreter.define_global("next", native_mut(move |_| {
+        count += 1;
+        Ok(Value::Number(count as f64))
+    }));
+    let result = interpreter.eval("next(); next(); next();").unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 3.0));
+}
+
+#[test]
+fn stateful_natives_refuse_to_reenter() {
+    // The function calls itself while it runs, as a host callback that
+    // leads back into it would
+    let itself = Rc::new(RefCell::new(Value::Undefined));
+    let inner = Rc::clone(&itself);
+    let function = native_mut(move |arguments| {
+        let Value::NativeFunction(function) = &*inner.borrow() else {
+            unreachable!();
+        };
+        function(arguments)
+    });
+    *itself.borrow_mut() = function.clone();
+    
+    let mut interpreter = Interpreter::new();
+    interpreter.define_global("reenter", function);
+    let error = interpreter.eval("reenter();").unwrap_err();
+    assert!(error.to_string().contains("cannot be called while it is running"), "{}", error);
+    
+    // Break the cycle between the function and itself
+    *itself.borrow_mut() = Value::Undefined;
+}
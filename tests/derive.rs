@@ -0,0 +1,69 @@
+// tests/derive.rs
+use jays::convert::JsClass;
+use jays::error::JsEngineError;
+use jays::interpreter::{Interpreter, Value};
+use jays::{js_function, JsClass};
+
+#[js_function]
+fn add(a: f64, b: Option<f64>) -> f64 {
+    a + b.unwrap_or(0.0)
+}
+
+#[js_function(name = "checkedRepeat")]
+fn repeat(text: String, count: u32) -> Result<String, JsEngineError> {
+    if count > 100 {
+        return Err(JsEngineError::RuntimeError { message: "count is too large".to_string() });
+    }
+    Ok(text.repeat(count as usize))
+}
+
+#[derive(JsClass, Debug, PartialEq)]
+struct Point {
+    x: f64,
+    y: f64,
+    label: Option<String>,
+}
+
+#[js_function]
+fn norm(point: Point) -> f64 {
+    (point.x * point.x + point.y * point.y).sqrt()
+}
+
+fn eval(interpreter: &mut Interpreter, source: &str) -> Result<Value, JsEngineError> {
+    interpreter.interpret(jays::parse_str(source)?)
+}
+
+#[test]
+fn bindings_convert_arguments_and_results() {
+    let mut interpreter = Interpreter::new();
+    add::register(&mut interpreter);
+    repeat::register(&mut interpreter);
+    norm::register(&mut interpreter);
+    Point::register(&mut interpreter);
+    
+    assert_eq!(format!("{:?}", eval(&mut interpreter, "add(1, 2);")), "Ok(3)");
+    assert_eq!(format!("{:?}", eval(&mut interpreter, "add(1);")), "Ok(1)");
+    assert_eq!(format!("{:?}", eval(&mut interpreter, "checkedRepeat(\"ab\", 3);")), "Ok(\"ababab\")");
+    assert_eq!(format!("{:?}", eval(&mut interpreter, "norm(Point(3, 4));")), "Ok(5)");
+    
+    let point = eval(&mut interpreter, "Point(1, 2, \"p\");").unwrap();
+    assert_eq!(
+        jays::convert::FromValue::from_value(&point).ok(),
+        Some(Point { x: 1.0, y: 2.0, label: Some("p".to_string()) }),
+    );
+}
+
+#[test]
+fn bindings_report_bad_arguments() {
+    let mut interpreter = Interpreter::new();
+    add::register(&mut interpreter);
+    repeat::register(&mut interpreter);
+    Point::register(&mut interpreter);
+    
+    let error = |interpreter: &mut Interpreter, source| eval(interpreter, source).unwrap_err().to_string();
+    assert_eq!(error(&mut interpreter, "add();"), "Type error: add expects 1 argument, got 0");
+    assert_eq!(error(&mut interpreter, "add(\"1\");"), "Type error: add: argument 1: expected a number, got \"1\"");
+    assert_eq!(error(&mut interpreter, "checkedRepeat(\"ab\", 1.5);"), "Type error: checkedRepeat: argument 2: expected an integer in the range of u32, got 1.5");
+    assert_eq!(error(&mut interpreter, "checkedRepeat(\"ab\", 1000);"), "Runtime error: count is too large");
+    assert_eq!(error(&mut interpreter, "Point(1);"), "Type error: Point expects 2 arguments, got 1");
+}
@@ -0,0 +1,44 @@
+// tests/events.rs
+use jays::interpreter::{Interpreter, Value};
+
+#[test]
+fn once_handlers_are_removed_after_running() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval("let ticks = 0; let firsts = 0; on(\"tick\", () => { ticks = ticks + 1; }); once(\"tick\", () => { firsts = firsts + 1; });").unwrap();
+    assert_eq!(interpreter.listener_count("tick"), 2);
+    
+    assert_eq!(interpreter.emit("tick", &[]).unwrap(), 2);
+    assert_eq!(interpreter.listener_count("tick"), 1);
+    assert_eq!(interpreter.emit("tick", &[]).unwrap(), 1);
+    
+    let result = interpreter.eval("ticks * 10 + firsts;").unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 21.0));
+}
+
+#[test]
+fn off_removes_one_handler_or_all_of_them() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval("function first() {} function second() {} on(\"save\", first); on(\"save\", second); on(\"save\", first);").unwrap();
+    assert_eq!(interpreter.listener_count("save"), 3);
+    
+    // With a callback, every registration of that callback goes
+    interpreter.eval("off(\"save\", first);").unwrap();
+    assert_eq!(interpreter.listener_count("save"), 1);
+    
+    interpreter.eval("on(\"save\", first); off(\"save\");").unwrap();
+    assert_eq!(interpreter.listener_count("save"), 0);
+    assert_eq!(interpreter.emit("save", &[]).unwrap(), 0);
+}
+
+#[test]
+fn emit_stops_at_the_first_failing_handler() {
+    let mut interpreter = Interpreter::new();
+    interpreter
+        .eval("let seen = []; on(\"load\", (n) => { seen[seen.length] = n; }); on(\"load\", () => { throw \"broken\"; }); on(\"load\", () => { seen[seen.length] = \"late\"; });")
+        .unwrap();
+    
+    let error = interpreter.emit("load", &[Value::Number(1.0)]).unwrap_err();
+    assert!(error.to_string().contains("broken"), "{}", error);
+    let result = interpreter.eval("seen.length == 1 && seen[0] == 1;").unwrap();
+    assert!(matches!(result, Value::Boolean(true)));
+}
@@ -0,0 +1,109 @@
+// tests/ffi.rs
+use jays::ffi::*;
+use std::ffi::{c_void, CStr, CString};
+use std::ptr;
+
+fn eval(engine: *mut JaysEngine, source: &str) -> *mut JaysValue {
+    let source = CString::new(source).unwrap();
+    unsafe { jays_eval(engine, source.as_ptr()) }
+}
+
+fn last_error(engine: *mut JaysEngine) -> Option<String> {
+    let message = unsafe { jays_last_error(engine) };
+    (!message.is_null()).then(|| unsafe { CStr::from_ptr(message) }.to_string_lossy().into_owned())
+}
+
+fn to_string(value: *const JaysValue) -> String {
+    unsafe {
+        let text = jays_value_to_string(value);
+        let owned = CStr::from_ptr(text).to_string_lossy().into_owned();
+        jays_string_free(text);
+        owned
+    }
+}
+
+// Sums its number arguments into the total behind `user_data`, and returns
+// the new total as a value the engine takes ownership of
+unsafe extern "C" fn add_to_total(user_data: *mut c_void, arguments: *const *const JaysValue, count: usize) -> *mut JaysValue {
+    let total = unsafe { &mut *(user_data as *mut f64) };
+    for index in 0..count {
+        *total += unsafe { jays_value_as_number(*arguments.add(index)) };
+    }
+    jays_value_number(*total)
+}
+
+unsafe extern "C" fn nothing(_: *mut c_void, _: *const *const JaysValue, _: usize) -> *mut JaysValue {
+    ptr::null_mut()
+}
+
+#[test]
+fn eval_returns_values_and_reports_errors() {
+    let engine = jays_engine_new();
+    
+    let value = eval(engine, "let greeting = \"hi \" + 2; greeting;");
+    assert!(!value.is_null());
+    assert_eq!(unsafe { jays_value_type(value) }, JAYS_STRING);
+    assert_eq!(to_string(value), "hi 2");
+    assert_eq!(last_error(engine), None);
+    unsafe { jays_value_free(value) };
+    
+    let value = eval(engine, "missing;");
+    assert!(value.is_null());
+    assert!(last_error(engine).is_some_and(|message| message.contains("missing")));
+    
+    // A successful eval clears the error
+    let value = eval(engine, "1;");
+    assert_eq!(last_error(engine), None);
+    unsafe {
+        jays_value_free(value);
+        jays_engine_free(engine);
+    }
+}
+
+#[test]
+fn call_passes_arguments_to_functions() {
+    let engine = jays_engine_new();
+    let function = eval(engine, "(function(a, b) { return a * b + 1; });");
+    assert_eq!(unsafe { jays_value_type(function) }, JAYS_FUNCTION);
+    
+    unsafe {
+        let arguments = [jays_value_number(6.0), jays_value_number(7.0)];
+        let pointers = arguments.map(|argument| argument as *const JaysValue);
+        let result = jays_call(engine, function, pointers.as_ptr(), pointers.len());
+        assert_eq!(jays_value_as_number(result), 43.0);
+        
+        let thrower = eval(engine, "(function() { throw \"refused\"; });");
+        assert!(jays_call(engine, thrower, ptr::null(), 0).is_null());
+        assert!(last_error(engine).is_some_and(|message| message.contains("refused")));
+        
+        for value in [result, thrower, function, arguments[0], arguments[1]] {
+            jays_value_free(value);
+        }
+        jays_engine_free(engine);
+    }
+}
+
+#[test]
+fn registered_functions_call_back_into_the_host() {
+    let engine = jays_engine_new();
+    let mut total = 0.0f64;
+    let name = CString::new("addToTotal").unwrap();
+    let status = unsafe { jays_register_fn(engine, name.as_ptr(), add_to_total, &mut total as *mut f64 as *mut c_void) };
+    assert_eq!(status, 0);
+    let name = CString::new("nothing").unwrap();
+    assert_eq!(unsafe { jays_register_fn(engine, name.as_ptr(), nothing, ptr::null_mut()) }, 0);
+    
+    // The value the callback returns is owned by the engine from then on
+    let value = eval(engine, "addToTotal(1, 2); addToTotal(3) + 100;");
+    assert_eq!(unsafe { jays_value_as_number(value) }, 106.0);
+    assert_eq!(total, 6.0);
+    
+    // NULL from a callback is undefined
+    let undefined = eval(engine, "nothing(1);");
+    assert_eq!(unsafe { jays_value_type(undefined) }, JAYS_UNDEFINED);
+    unsafe {
+        jays_value_free(value);
+        jays_value_free(undefined);
+        jays_engine_free(engine);
+    }
+}
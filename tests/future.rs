@@ -0,0 +1,50 @@
+// tests/future.rs
+use jays::interpreter::{Interpreter, Value};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[tokio::test(flavor = "current_thread")]
+async fn eval_future_waits_for_timers() {
+    let fired = Rc::new(RefCell::new(Vec::new()));
+    let record = Rc::clone(&fired);
+    
+    let mut interpreter = Interpreter::new();
+    interpreter.define_global("record", Value::NativeFunction(Rc::new(move |args: &[Value]| {
+        record.borrow_mut().push(args[0].to_string());
+        Ok(Value::Undefined)
+    })));
+    
+    let result = interpreter
+        .eval_future("setTimeout(function() { record(\"late\"); }, 20); setTimeout(record, 5, \"early\"); 42;")
+        .await
+        .unwrap();
+    
+    assert!(matches!(result, Value::Number(n) if n == 42.0));
+    assert_eq!(*fired.borrow(), ["early", "late"]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn eval_future_reports_errors() {
+    let mut interpreter = Interpreter::new();
+    let error = interpreter.eval_future("missing();").await.unwrap_err();
+    assert_eq!(error.to_string(), "Reference error: 'missing' is not defined");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn eval_future_runs_microtasks_before_timers() {
+    let fired = Rc::new(RefCell::new(Vec::new()));
+    let record = Rc::clone(&fired);
+    
+    let mut interpreter = Interpreter::new();
+    interpreter.define_global("record", Value::NativeFunction(Rc::new(move |args: &[Value]| {
+        record.borrow_mut().push(args[0].to_string());
+        Ok(Value::Undefined)
+    })));
+    
+    interpreter
+        .eval_future("setTimeout(() => { record(\"timer\"); queueMicrotask(() => record(\"after timer\")); }, 0); setTimeout(record, 0, \"second timer\"); queueMicrotask(() => record(\"microtask\"));")
+        .await
+        .unwrap();
+    
+    assert_eq!(*fired.borrow(), ["microtask", "timer", "after timer", "second timer"]);
+}
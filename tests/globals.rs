@@ -0,0 +1,37 @@
+// tests/globals.rs
+use jays::interpreter::{Interpreter, Value};
+
+fn number(interpreter: &mut Interpreter, source: &str) -> f64 {
+    match interpreter.eval(source) {
+        Ok(Value::Number(n)) => n,
+        other => panic!("{}: {:?}", source, other),
+    }
+}
+
+#[test]
+fn global_reads_follow_redefinitions() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval("let scale = 2; function scaled(x) { return x * scale; }").unwrap();
+    assert_eq!(number(&mut interpreter, "scaled(1);"), 2.0);
+    
+    // Assigned by the script, replaced by the host, and moved by new
+    // globals defined before it is read again
+    interpreter.eval("scale = 3;").unwrap();
+    assert_eq!(number(&mut interpreter, "scaled(1);"), 3.0);
+    interpreter.define_global("scale", Value::Number(4.0));
+    assert_eq!(number(&mut interpreter, "scaled(1);"), 4.0);
+    for name in ["a", "b", "c"] {
+        interpreter.define_global(name, Value::Number(0.0));
+    }
+    interpreter.eval("var later = 1; scale = 5;").unwrap();
+    assert_eq!(number(&mut interpreter, "scaled(1);"), 5.0);
+}
+
+#[test]
+fn global_reads_see_globals_defined_later() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval("function read() { return missing; }").unwrap();
+    assert!(interpreter.eval("read();").is_err());
+    interpreter.define_global("missing", Value::Number(1.0));
+    assert_eq!(number(&mut interpreter, "read();"), 1.0);
+}
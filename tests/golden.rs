@@ -0,0 +1,123 @@
+// tests/golden.rs
+use jays::error::JsEngineError;
+use jays::interpreter::{Interpreter, Value};
+use jays::{lexer, parser};
+use std::cell::RefCell;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+// Runs every script in examples/ and compares what it printed, its result
+// and any error against the `.out` file next to it. Adding a regression test
+// is a matter of dropping in a new script; run with UPDATE_GOLDEN=1 to
+// (re)write the expected output after an intended change. Scripts that use
+// optional built-ins name their features in a `// requires: math, json`
+// line among their leading comments, and are skipped when the features are
+// off.
+#[test]
+fn golden_examples() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut failures = Vec::new();
+    
+    for script in fixtures(&directory) {
+        let source = fs::read_to_string(&script).unwrap();
+        if !required_features(&source).into_iter().all(enabled) {
+            continue;
+        }
+        let actual = run(&source, script.extension().is_some_and(|extension| extension == "ts"));
+        let expected_path = script.with_extension("out");
+        
+        if update {
+            fs::write(&expected_path, &actual).unwrap();
+            continue;
+        }
+        
+        match fs::read_to_string(&expected_path) {
+            Ok(expected) if expected == actual => {},
+            Ok(expected) => failures.push(format!(
+                "{}:\n--- expected\n{}--- actual\n{}",
+                script.display(),
+                expected,
+                actual,
+            )),
+            Err(_) => failures.push(format!("{}: missing {}", script.display(), expected_path.display())),
+        }
+    }
+    
+    if !failures.is_empty() {
+        panic!("{} golden test(s) failed; run with UPDATE_GOLDEN=1 to accept the new output\n\n{}", failures.len(), failures.join("\n"));
+    }
+}
+
+fn fixtures(directory: &Path) -> Vec<PathBuf> {
+    let mut scripts: Vec<PathBuf> = fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "js" || extension == "ts"))
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+fn required_features(source: &str) -> Vec<&str> {
+    source
+        .lines()
+        .take_while(|line| line.starts_with("//"))
+        .filter_map(|line| line.strip_prefix("// requires:"))
+        .flat_map(|features| features.split(','))
+        .map(str::trim)
+        .collect()
+}
+
+fn enabled(feature: &str) -> bool {
+    match feature {
+        "math" => cfg!(feature = "math"),
+        "json" => cfg!(feature = "json"),
+        "timers" => cfg!(feature = "timers"),
+        "fs" => cfg!(feature = "fs"),
+        "net" => cfg!(feature = "net"),
+        "regex" => cfg!(feature = "regex"),
+        "assert" => cfg!(feature = "assert"),
+        "performance" => cfg!(feature = "performance"),
+        "async" => cfg!(feature = "async"),
+        _ => panic!("unknown feature {:?} in a requires line", feature),
+    }
+}
+
+// Everything the console printed, on either stream, then the result as the CLI shows it, or
+// the error that stopped the script
+fn run(source: &str, typescript: bool) -> String {
+    let output = Capture::default();
+    let mut interpreter = Interpreter::new();
+    interpreter.set_output(Box::new(output.clone()));
+    interpreter.set_error_output(Box::new(output.clone()));
+    
+    let result = lexer::Lexer::new(source)
+        .scan_tokens()
+        .and_then(|tokens| parser::Parser::new(tokens).with_typescript(typescript).parse())
+        .and_then(|statements| interpreter.interpret_with_source(statements, source).map_err(JsEngineError::uncaught));
+    
+    let mut text = String::from_utf8(output.0.borrow().clone()).unwrap();
+    match result {
+        Ok(Value::Undefined) => {},
+        Ok(value) => text.push_str(&format!("=> {:?}\n", value)),
+        Err(error) => text.push_str(&format!("error: {}\n", error)),
+    }
+    text
+}
+
+#[derive(Clone, Default)]
+struct Capture(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+// tests/heap.rs
+use jays::heap::{HeapEdge, HeapGraph, HeapNode, NodeKind};
+use jays::interpreter::Interpreter;
+
+// A global scope holding one object under an awkward name
+fn small_graph() -> HeapGraph {
+    HeapGraph {
+        nodes: vec![
+            HeapNode { id: 0, kind: NodeKind::Environment, label: "global scope".to_string(), size: 0 },
+            HeapNode { id: 1, kind: NodeKind::Object, label: "Object".to_string(), size: 24 },
+        ],
+        edges: vec![HeapEdge { from: 0, to: 1, name: "say \"hi\"\n".to_string() }],
+        roots: vec![0],
+    }
+}
+
+#[test]
+fn graphs_serialize_as_json() {
+    assert_eq!(
+        small_graph().to_json(),
+        concat!(
+            "{\"nodes\":[{\"id\":0,\"type\":\"environment\",\"label\":\"global scope\"},",
+            "{\"id\":1,\"type\":\"object\",\"label\":\"Object\"}],",
+            "\"edges\":[{\"from\":0,\"to\":1,\"name\":\"say \\\"hi\\\"\\n\"}]}",
+        ),
+    );
+}
+
+#[test]
+fn graphs_serialize_as_dot() {
+    assert_eq!(
+        small_graph().to_dot(),
+        concat!(
+            "digraph heap {\n",
+            "  n0 [label=\"global scope\" shape=box];\n",
+            "  n1 [label=\"Object\" shape=ellipse];\n",
+            "  n0 -> n1 [label=\"say \\\"hi\\\"\\n\"];\n",
+            "}",
+        ),
+    );
+}
+
+#[test]
+fn graphs_follow_bindings_properties_and_closures() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval("let point = { x: 1 }; let getX = () => point.x;").unwrap();
+    let graph = interpreter.heap_graph();
+    
+    let target = |from: usize, name: &str| {
+        let edge = graph.edges.iter().find(|edge| edge.from == from && edge.name == name).unwrap_or_else(|| panic!("no edge {} from {}", name, from));
+        &graph.nodes[edge.to]
+    };
+    let global = graph.roots[0];
+    assert_eq!(graph.nodes[global].label, "global scope");
+    
+    let point = target(global, "point");
+    assert_eq!(point.kind, NodeKind::Object);
+    assert_eq!(target(point.id, "x").label, "1");
+    
+    // The arrow function captures the global scope
+    let function = target(global, "getX");
+    assert_eq!(function.kind, NodeKind::Function);
+    assert_eq!(target(function.id, "(closure)").id, global);
+}
+
+#[test]
+fn stats_count_what_scripts_create() {
+    let mut interpreter = Interpreter::new();
+    let before = interpreter.heap_stats();
+    interpreter.eval("let first = { name: \"a string\" }; let second = [1, 2]; function f() {}").unwrap();
+    let after = interpreter.heap_stats();
+    
+    assert_eq!(after.bindings, before.bindings + 3);
+    assert_eq!(after.objects, before.objects + 2);
+    assert_eq!(after.functions, before.functions + 1);
+    assert_eq!(after.strings, before.strings + 1);
+    assert_eq!(after.string_bytes, before.string_bytes + "a string".len());
+    assert!(after.bytes > before.bytes);
+}
+
+#[test]
+fn console_memory_matches_heap_stats() {
+    let mut interpreter = Interpreter::new();
+    let result = interpreter.eval("let before = console.memory.objects; let extra = {}; console.memory.objects - before;").unwrap();
+    assert_eq!(result.to_string(), "1");
+    
+    let bindings = interpreter.eval("console.memory.bindings;").unwrap();
+    assert_eq!(bindings.to_string(), interpreter.heap_stats().bindings.to_string());
+}
+
+#[test]
+fn snapshots_use_the_v8_layout() {
+    let snapshot = jays::snapshot::heap_snapshot(&small_graph());
+    
+    assert!(snapshot.starts_with("{\"snapshot\":{\"meta\":{\"node_fields\":[\"type\",\"name\",\"id\",\"self_size\",\"edge_count\",\"trace_node_id\",\"detachedness\"],"), "{}", snapshot);
+    assert!(snapshot.contains("\"node_count\":3,\"edge_count\":2,"), "{}", snapshot);
+    // A synthetic root with an element edge to the global scope, which has
+    // a context edge to the object. Edges point at node offsets, seven
+    // fields per node.
+    assert!(snapshot.contains("\"nodes\":[9,0,1,0,1,0,0,0,1,3,0,1,0,0,3,3,5,24,0,0,0]"), "{}", snapshot);
+    assert!(snapshot.contains("\"edges\":[1,1,7,0,2,14]"), "{}", snapshot);
+    assert!(snapshot.ends_with("\"strings\":[\"\",\"system / Context (global scope)\",\"say \\\"hi\\\"\\n\",\"Object\"]}"), "{}", snapshot);
+}
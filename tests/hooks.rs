@@ -0,0 +1,132 @@
+// tests/hooks.rs
+use jays::ast::{Expr, Stmt};
+use jays::error::JsEngineError;
+use jays::hooks::ExecutionObserver;
+use jays::interpreter::{Interpreter, Value};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Writes each event as a line of text
+struct Recorder(Rc<RefCell<Vec<String>>>);
+
+fn name(callee: &Value) -> String {
+    match callee {
+        Value::Function(function) => function.name.as_deref().unwrap_or("anonymous").to_string(),
+        _ => "native".to_string(),
+    }
+}
+
+impl ExecutionObserver for Recorder {
+    fn on_statement(&mut self, stmt: &Stmt) {
+        let kind = match stmt {
+            Stmt::Function(..) => "function",
+            Stmt::Return(_) => "return",
+            Stmt::If(..) => "if",
+            _ => "other",
+        };
+        self.0.borrow_mut().push(format!("statement {}", kind));
+    }
+    
+    fn on_call_enter(&mut self, callee: &Value, arguments: &[Value]) {
+        let arguments: Vec<String> = arguments.iter().map(Value::to_string).collect();
+        self.0.borrow_mut().push(format!("enter {}({})", name(callee), arguments.join(", ")));
+    }
+    
+    fn on_call_exit(&mut self, callee: &Value, result: Result<&Value, &JsEngineError>) {
+        let result = match result {
+            Ok(value) => value.to_string(),
+            Err(_) => "error".to_string(),
+        };
+        self.0.borrow_mut().push(format!("exit {} {}", name(callee), result));
+    }
+    
+    fn on_error(&mut self, error: &JsEngineError) {
+        self.0.borrow_mut().push(format!("error {}", error));
+    }
+}
+
+fn observe(source: &str) -> Vec<String> {
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let mut interpreter = Interpreter::new();
+    interpreter.add_observer(Box::new(Recorder(Rc::clone(&events))));
+    let _ = interpreter.eval(source);
+    events.take()
+}
+
+#[test]
+fn statements_and_calls_are_reported_in_order() {
+    let events = observe("function inner(x) { return x + 1; }\nfunction outer(x) { return inner(x) * 2; }\nouter(1);");
+    assert_eq!(events, [
+        "statement function",
+        "statement function",
+        "statement other",
+        "enter outer(1)",
+        "statement other", // the body
+        "statement return",
+        "enter inner(1)",
+        "statement other",
+        "statement return",
+        "exit inner 2",
+        "exit outer 4",
+    ]);
+}
+
+// The exits of a chain of tail calls come once it finishes, innermost
+// first, so that observers still see nested calls
+#[test]
+fn tail_calls_are_reported_as_separate_calls() {
+    let events = observe("function count(n) { if (n == 0) { return \"done\"; } return count(n - 1); }\ncount(2);");
+    let calls: Vec<&String> = events.iter().filter(|event| !event.starts_with("statement")).collect();
+    assert_eq!(calls, [
+        "enter count(2)",
+        "enter count(1)",
+        "enter count(0)",
+        "exit count done",
+        "exit count done",
+        "exit count done",
+    ]);
+}
+
+#[test]
+fn errors_are_reported_after_the_calls_they_unwind() {
+    let events = observe("function fail() { throw \"broken\"; }\nfunction outer() { return 1 + fail(); }\nouter();");
+    let calls: Vec<&String> = events.iter().filter(|event| !event.starts_with("statement")).collect();
+    assert_eq!(calls.len(), 5, "{:?}", calls);
+    assert_eq!(calls[..4], ["enter outer()", "enter fail()", "exit fail error", "exit outer error"]);
+    assert!(calls[4].starts_with("error ") && calls[4].contains("broken"), "{}", calls[4]);
+}
+
+#[test]
+fn transforms_rewrite_the_program_before_it_is_resolved() {
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let record = Rc::clone(&seen);
+    let mut interpreter = Interpreter::new();
+    // Replaces each top-level `answer` with 42; the resolver would have
+    // turned the undeclared name into a global
+    interpreter.add_transform(Box::new(move |statements: Vec<Stmt>| {
+        statements
+            .into_iter()
+            .map(|statement| match statement {
+                Stmt::Expression(Expr::Binary(left, op, right)) => {
+                    let left = match *left {
+                        Expr::Variable(name, _) if name == "answer" => {
+                            record.borrow_mut().push("variable");
+                            Expr::Number(42.0)
+                        },
+                        Expr::Global(..) => {
+                            record.borrow_mut().push("global");
+                            *left
+                        },
+                        left => left,
+                    };
+                    Stmt::Expression(Expr::Binary(Box::new(left), op, right))
+                },
+                statement => statement,
+            })
+            .collect()
+    }));
+    
+    let result = interpreter.eval("answer + 1;").unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 43.0));
+    assert_eq!(*seen.borrow(), ["variable"]);
+}
@@ -0,0 +1,31 @@
+// tests/host.rs
+use jays::host::{Clock, ManualClock, SeededRng};
+use jays::interpreter::Interpreter;
+use std::rc::Rc;
+
+#[test]
+fn a_manual_clock_drives_timers() {
+    let clock = Rc::new(ManualClock::new(1000.0));
+    let mut interpreter = Interpreter::builder().with_clock(Rc::clone(&clock) as Rc<dyn Clock>).build();
+    
+    // Waiting for the timers skips the clock ahead instead of sleeping
+    let result = interpreter
+        .eval("let order = \"\"; setTimeout(() => { order = order + \"b\"; }, 5000); setTimeout(() => { order = order + \"a\"; }, 20); order;")
+        .unwrap();
+    assert_eq!(result.to_string(), "");
+    assert_eq!(clock.now(), 6000.0);
+    assert_eq!(interpreter.eval("order;").unwrap().to_string(), "ab");
+    
+    clock.advance(500.0);
+    assert_eq!(clock.now(), 6500.0);
+}
+
+#[test]
+fn a_seeded_rng_replays_math_random() {
+    let numbers = |seed| {
+        let mut interpreter = Interpreter::builder().with_rng(SeededRng::new(seed)).build();
+        interpreter.eval("Math.random() + \" \" + Math.random() + \" \" + Math.random();").unwrap().to_string()
+    };
+    assert_eq!(numbers(7), numbers(7));
+    assert_ne!(numbers(7), numbers(8));
+}
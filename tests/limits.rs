@@ -0,0 +1,33 @@
+// tests/limits.rs
+use jays::interpreter::Limits;
+
+// Scripts run on a thread with the default stack size, which a debug build
+// runs out of before it reaches the default call depth
+fn eval_error(source: &'static str, limits: Limits) -> Option<String> {
+    std::thread::spawn(move || jays::eval_str_with_limits(source, &limits).err().map(|error| error.to_string()))
+        .join()
+        .unwrap()
+}
+
+#[test]
+fn default_limits_stop_runaway_recursion() {
+    let error = eval_error("function down() { return 1 + down(); }\ndown();", Limits::default());
+    assert_eq!(error.as_deref(), Some("Runtime error: Maximum call stack size exceeded"));
+}
+
+#[test]
+fn recursion_within_the_limit_runs() {
+    let limits = Limits {
+        max_call_depth: Some(20),
+        ..Limits::default()
+    };
+    let source = "function down(n) { if (n == 0) { return 0; } return 1 + down(n - 1); }\ndown(15);";
+    assert_eq!(eval_error(source, limits.clone()), None);
+    assert!(eval_error("function down(n) { return 1 + down(n - 1); }\ndown(40);", limits).is_some());
+}
+
+#[test]
+fn deep_expressions_in_recursion_stop_cleanly() {
+    let error = eval_error("function f(n) { return 1 + f(n); }\nf(1);", Limits::default());
+    assert_eq!(error.as_deref(), Some("Runtime error: Maximum call stack size exceeded"));
+}
@@ -0,0 +1,37 @@
+// tests/lockdown.rs
+use jays::interpreter::{Interpreter, Value};
+
+#[test]
+fn hardened_globals_cannot_be_replaced() {
+    let mut interpreter = Interpreter::builder().hardened().build();
+    
+    let error = interpreter.eval("Math = 1;").unwrap_err();
+    assert_eq!(error.to_string(), "Type error: Cannot assign to read only global 'Math'");
+    
+    let error = interpreter.eval("function JSON() {}").unwrap_err();
+    assert_eq!(error.to_string(), "Type error: Cannot assign to read only global 'JSON'");
+    
+    // Scripts' own globals and later host globals stay writable
+    interpreter.define_global("config", Value::Number(1.0));
+    let result = interpreter.eval("var total = 1; total = total + 1; config = 2; total + config;").unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 4.0));
+}
+
+#[test]
+fn lockdown_freezes_reachable_objects() {
+    let mut interpreter = Interpreter::new();
+    interpreter.lockdown();
+    
+    let Value::Object(math) = interpreter.eval("Math;").unwrap() else {
+        panic!("Math is not an object");
+    };
+    assert!(math.borrow().is_frozen());
+    assert!(interpreter.eval("Math.max(1, 2);").is_ok());
+    
+    let error = interpreter.eval("Math.max = function () { return 0; };").unwrap_err();
+    assert_eq!(error.to_string(), "Type error: Cannot assign to read only property 'max' of object");
+    
+    // Objects the script creates afterwards are its own
+    let result = interpreter.eval("let point = { x: 1 }; point.x = 2; point[\"x\"];").unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 2.0));
+}
@@ -0,0 +1,53 @@
+// tests/output.rs
+use jays::interpreter::Interpreter;
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+// An output stream the test can read back
+#[derive(Clone, Default)]
+struct Shared(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buffer);
+        Ok(buffer.len())
+    }
+    
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Shared {
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.0.borrow()).into_owned()
+    }
+}
+
+#[test]
+fn captured_output_is_kept_apart() {
+    let mut interpreter = Interpreter::new();
+    let (result, captured) = interpreter.eval_captured("console.log(\"out\"); console.error(\"err\"); 1 + 1;");
+    assert_eq!(result.unwrap().to_string(), "2");
+    assert_eq!(captured.stdout, "out\n");
+    assert_eq!(captured.stderr, "err\n");
+}
+
+#[test]
+fn previous_streams_are_restored_after_an_error() {
+    let stdout = Shared::default();
+    let stderr = Shared::default();
+    let mut interpreter = Interpreter::new();
+    interpreter.set_output(Box::new(stdout.clone()));
+    interpreter.set_error_output(Box::new(stderr.clone()));
+    
+    let (result, captured) = interpreter.eval_captured("console.log(\"before\"); console.warn(\"warned\"); missing;");
+    assert!(result.is_err());
+    assert_eq!(captured.stdout, "before\n");
+    assert_eq!(captured.stderr, "warned\n");
+    
+    interpreter.eval("console.log(\"after\"); console.error(\"failed\");").unwrap();
+    assert_eq!(stdout.text(), "after\n");
+    assert_eq!(stderr.text(), "failed\n");
+}
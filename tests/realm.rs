@@ -0,0 +1,159 @@
+// tests/realm.rs
+use jays::interpreter::{Interpreter, Value};
+
+#[test]
+fn transfer_copies_objects() {
+    let source = Interpreter::new();
+    let mut target = Interpreter::new();
+    let mut scratch = Interpreter::new();
+    let value = scratch.eval("let point = { x: 1, y: [2, 3] }; point.self = point; point;").unwrap();
+    
+    let copy = source.transfer(&value, &target).unwrap();
+    target.define_global("point", copy);
+    let result = target.eval("point.self.y[1] + point.x;").unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 4.0));
+}
+
+#[test]
+fn transfer_refuses_state_it_cannot_copy() {
+    let mut source = Interpreter::new();
+    let target = Interpreter::new();
+    for script in ["Promise.resolve(1);", "Proxy({}, {});", "[1, 2][Symbol.iterator]();"] {
+        let value = source.eval(script).unwrap();
+        let error = source.transfer(&value, &target).unwrap_err();
+        assert!(error.to_string().contains("DataCloneError"), "{}: {}", script, error);
+    }
+}
+
+#[test]
+fn templates_refuse_globals_they_cannot_copy() {
+    let result = Interpreter::builder().template(|interpreter| {
+        interpreter.eval("let ready = Promise.resolve(1);")?;
+        Ok(())
+    });
+    let error = result.err().expect("the template was built");
+    assert!(error.to_string().contains("DataCloneError"), "{}", error);
+}
+
+#[test]
+fn transfer_copies_maps_and_sets() {
+    let mut source = Interpreter::new();
+    let mut target = Interpreter::new();
+    let value = source
+        .eval("let key = { id: 1 }; let m = Map([[key, Set([1, 2])], [\"self\", null]]); m.set(\"self\", m); [m, key];")
+        .unwrap();
+    
+    let copy = source.transfer(&value, &target).unwrap();
+    target.define_global("copied", copy);
+    let result = target.eval("let m = copied[0]; m.get(\"self\") == m && m.get(copied[1]).has(2) && m.size == 2;").unwrap();
+    assert!(matches!(result, Value::Boolean(true)));
+    
+    // The copy is independent of the original
+    target.eval("m.get(copied[1]).add(3);").unwrap();
+    let result = source.eval("m.get(key).size;").unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 2.0));
+}
+
+#[test]
+fn template_instances_get_their_own_maps() {
+    let template = Interpreter::builder()
+        .template(|interpreter| {
+            interpreter.eval("let seen = Map([[\"visits\", 0]]); function visit() { seen.set(\"visits\", seen.get(\"visits\") + 1); return seen.get(\"visits\"); }")?;
+            Ok(())
+        })
+        .unwrap();
+    
+    let mut first = template.instantiate();
+    let mut second = template.instantiate();
+    first.eval("visit(); visit();").unwrap();
+    let result = second.eval("visit();").unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 1.0));
+}
+
+#[test]
+fn transfer_copies_binary_data() {
+    let mut source = Interpreter::new();
+    let mut target = Interpreter::new();
+    let value = source
+        .eval("let bytes = Uint8Array([1, 2, 3, 4]); let view = DataView(bytes.buffer, 2); [bytes, bytes.subarray(1), view];")
+        .unwrap();
+    
+    let copy = source.transfer(&value, &target).unwrap();
+    target.define_global("copied", copy);
+    // Views of one buffer still share it, and not with the original
+    let result = target
+        .eval("copied[2].setUint8(0, 9); copied[0][2] + copied[1][1] + copied[0].buffer.byteLength;")
+        .unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 22.0));
+    let result = source.eval("bytes[2];").unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 3.0));
+}
+
+#[test]
+fn template_instances_get_their_own_buffers() {
+    let template = Interpreter::builder()
+        .template(|interpreter| {
+            interpreter.eval("let counts = Float64Array(2);")?;
+            Ok(())
+        })
+        .unwrap();
+    
+    let mut first = template.instantiate();
+    let mut second = template.instantiate();
+    first.eval("counts[0] = 5;").unwrap();
+    let result = second.eval("counts[0] + counts.length;").unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 2.0));
+}
+
+#[test]
+fn template_instances_get_their_own_closures() {
+    let template = Interpreter::builder()
+        .template(|interpreter| {
+            interpreter.eval("let counter = (function() { let n = 0; return function() { n = n + 1; return n; }; })();")?;
+            Ok(())
+        })
+        .unwrap();
+    
+    let mut first = template.instantiate();
+    let mut second = template.instantiate();
+    first.eval("counter(); counter();").unwrap();
+    let result = second.eval("counter();").unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 1.0));
+}
+
+#[test]
+fn template_instances_get_their_own_builtins() {
+    let template = Interpreter::builder()
+        .template(|interpreter| {
+            interpreter.eval("Object.version = 2;")?;
+            Ok(())
+        })
+        .unwrap();
+    
+    let mut first = template.instantiate();
+    let mut second = template.instantiate();
+    first.eval("Object.version = 3; Object.extra = 1;").unwrap();
+    let result = second.eval("Object.version == 2 && Object.extra == undefined;").unwrap();
+    assert!(matches!(result, Value::Boolean(true)));
+}
+
+#[test]
+fn template_instances_read_their_own_globals() {
+    let template = Interpreter::builder()
+        .template(|interpreter| {
+            interpreter.eval("let value = 1; function read() { return value; }")?;
+            Ok(())
+        })
+        .unwrap();
+    
+    // The instances share read's body, and so the cache of where it last
+    // found `value`, but not the layout of their globals
+    let mut first = template.instantiate();
+    let mut second = template.instantiate();
+    first.define_global("extra", Value::Number(0.0));
+    first.eval("value = 2;").unwrap();
+    for _ in 0..2 {
+        assert!(matches!(first.eval("read();").unwrap(), Value::Number(n) if n == 2.0));
+        assert!(matches!(second.eval("read();").unwrap(), Value::Number(n) if n == 1.0));
+    }
+}
@@ -0,0 +1,39 @@
+// tests/resumable.rs
+use jays::interpreter::{Interpreter, Value};
+use jays::resumable::{Execution, Slice};
+
+fn start(source: &str) -> Execution {
+    Interpreter::new().start(jays::parse_str(source).unwrap()).unwrap()
+}
+
+fn finish(execution: &mut Execution) -> Result<Value, String> {
+    loop {
+        if let Slice::Done(result) = execution.resume(1000) {
+            return result.map_err(|error| error.to_string());
+        }
+    }
+}
+
+#[test]
+fn recursion_up_to_the_default_depth_runs() {
+    let mut execution = start("function down(n) { if (n == 0) { return 0; } return 1 + down(n - 1); }\ndown(990);");
+    let result = finish(&mut execution);
+    assert!(matches!(result, Ok(Value::Number(n)) if n == 990.0), "{:?}", result);
+}
+
+#[test]
+fn runaway_recursion_stops_cleanly() {
+    let mut execution = start("function down() { return 1 + down(); }\ndown();");
+    let result = finish(&mut execution);
+    assert_eq!(result.err().as_deref(), Some("Runtime error: Maximum call stack size exceeded"));
+    assert!(execution.is_done());
+}
+
+#[test]
+fn slices_pause_and_resume() {
+    let mut execution = start("let total = 0; for (let i = 0; i < 100; i = i + 1) { total = total + i; } total;");
+    assert!(matches!(execution.resume(10), Slice::Paused));
+    let result = finish(&mut execution);
+    assert!(matches!(result, Ok(Value::Number(n)) if n == 4950.0), "{:?}", result);
+    assert!(execution.into_interpreter().is_some());
+}
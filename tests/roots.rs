@@ -0,0 +1,38 @@
+// tests/roots.rs
+use jays::interpreter::{Interpreter, Value};
+use std::rc::Rc;
+
+#[test]
+fn dropping_a_root_releases_its_value() {
+    let mut interpreter = Interpreter::new();
+    let callback = interpreter.eval("(function(x) { return x * 2; });").unwrap();
+    let Value::Function(function) = &callback else {
+        panic!("not a function: {:?}", callback);
+    };
+    let weak = Rc::downgrade(function);
+    let before = interpreter.heap_stats();
+    
+    let root = interpreter.root(callback);
+    assert_eq!(interpreter.heap_stats().bindings, before.bindings + 1);
+    let result = interpreter.call_function(&root.get(), &[Value::Number(21.0)]).unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 42.0));
+    
+    drop(root);
+    assert_eq!(interpreter.heap_stats(), before);
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn roots_outlive_their_interpreter_harmlessly() {
+    let mut interpreter = Interpreter::new();
+    let value = interpreter.eval("({ name: \"kept\" });").unwrap();
+    let root = interpreter.root(value);
+    root.set(Value::Number(1.0));
+    assert!(matches!(root.get(), Value::Number(n) if n == 1.0));
+    
+    drop(interpreter);
+    assert!(matches!(root.get(), Value::Undefined));
+    root.set(Value::Number(2.0));
+    assert!(matches!(root.get(), Value::Undefined));
+    drop(root);
+}
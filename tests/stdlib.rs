@@ -0,0 +1,25 @@
+// tests/stdlib.rs
+use jays::interpreter::{Interpreter, Value};
+use jays::stdlib::Std;
+
+#[test]
+fn excluded_namespaces_are_absent() {
+    let mut interpreter = Interpreter::builder().with_stdlib(&[Std::Math]).build();
+    let result = interpreter.eval("Math.floor(Math.random() + 1.5);").unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 1.0 || n == 2.0));
+    
+    for name in ["JSON", "fs", "setTimeout"] {
+        let error = interpreter.eval(&format!("{};", name)).unwrap_err();
+        assert!(error.to_string().contains(name), "{}: {}", name, error);
+    }
+}
+
+#[test]
+fn the_core_is_always_present() {
+    let mut interpreter = Interpreter::builder().with_stdlib(&[]).build();
+    let result = interpreter.eval("Math;").unwrap_err();
+    assert!(result.to_string().contains("Math"), "{}", result);
+    
+    let result = interpreter.eval("console.log != undefined && Promise.resolve(1) != undefined && Map != undefined;").unwrap();
+    assert!(matches!(result, Value::Boolean(true)));
+}
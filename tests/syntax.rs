@@ -0,0 +1,86 @@
+// tests/syntax.rs
+use jays::ast::{Expr, Stmt, UnaryOp};
+use jays::error::JsEngineError;
+use jays::interpreter::{Interpreter, Value};
+use jays::lexer::{Lexer, TokenType};
+use jays::parser::Parser;
+use jays::syntax::SyntaxExtensions;
+use std::rc::Rc;
+
+// Precedence of `|>`, the same as `||`
+const PIPELINE_PRECEDENCE: u8 = 1;
+
+// `x |> f` calls f with x; `unless (condition) statement` runs the statement
+// when the condition is falsy; `@name` is the string "name"
+fn extensions() -> SyntaxExtensions {
+    let mut extensions = SyntaxExtensions::new();
+    extensions
+        .add_operator("|>")
+        .add_operator("@")
+        .add_infix(
+            TokenType::Operator("|>".to_string()),
+            PIPELINE_PRECEDENCE,
+            Rc::new(|parser: &mut Parser, left: Expr| {
+                let function = parser.binary(PIPELINE_PRECEDENCE + 1)?;
+                Ok(Expr::call(function, vec![left]))
+            }),
+        )
+        .add_statement(
+            "unless",
+            Rc::new(|parser: &mut Parser| {
+                parser.consume(TokenType::LeftParen, "Expect '(' after 'unless'.")?;
+                let condition = parser.expression()?;
+                parser.consume(TokenType::RightParen, "Expect ')' after condition.")?;
+                let body = parser.statement()?;
+                Ok(Stmt::If(Expr::unary(UnaryOp::Not, condition), Box::new(body), None))
+            }),
+        )
+        .add_prefix(
+            TokenType::Operator("@".to_string()),
+            Rc::new(|parser: &mut Parser| match &parser.advance().token_type {
+                TokenType::Identifier(name) => Ok(Expr::string(&name.clone())),
+                _ => Err(parser.error("Expect a name after '@'.")),
+            }),
+        );
+    extensions
+}
+
+fn run(source: &str) -> Result<Value, JsEngineError> {
+    let extensions = extensions();
+    let tokens = Lexer::new(source).with_extensions(&extensions).scan_tokens()?;
+    let statements = Parser::new(tokens).with_extensions(&extensions).parse()?;
+    Interpreter::new().interpret(statements)
+}
+
+fn number(source: &str) -> f64 {
+    match run(source) {
+        Ok(Value::Number(n)) => n,
+        other => panic!("{}: {:?}", source, other),
+    }
+}
+
+#[test]
+fn infix_operators_bind_by_their_precedence() {
+    let functions = "function double(x) { return x * 2; } function inc(x) { return x + 1; }\n";
+    // Looser than +, and left-associative
+    assert_eq!(number(&format!("{}1 + 2 |> double |> inc;", functions)), 7.0);
+    // As loose as ||, so the two group from the left
+    assert_eq!(number(&format!("{}0 || 5 |> double;", functions)), 10.0);
+    assert_eq!(number(&format!("{}5 |> double || 0;", functions)), 10.0);
+    // Tighter than ??
+    assert_eq!(number(&format!("{}null ?? 4 |> double;", functions)), 8.0);
+}
+
+#[test]
+fn statements_and_prefixes_extend_the_grammar() {
+    let result = run("let seen = 0; unless (seen > 0) seen = 3; unless (true) seen = 10; seen;").unwrap();
+    assert!(matches!(result, Value::Number(n) if n == 3.0));
+    
+    let result = run("@greeting + \"!\";").unwrap();
+    assert_eq!(result.to_string(), "greeting!");
+}
+
+#[test]
+fn extensions_only_apply_where_they_are_registered() {
+    assert!(jays::parse_str("1 |> double;").is_err());
+}
@@ -0,0 +1,79 @@
+// tests/tracing.rs
+use jays::interpreter::Interpreter;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+// Records the spans opened and events raised, with their fields
+#[derive(Default)]
+struct Recorder {
+    lines: Arc<Mutex<Vec<String>>>,
+    next_id: AtomicU64,
+}
+
+// Collects fields as `name=value` pairs
+struct Fields(Vec<String>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push(format!("{}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+    
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut fields = Fields(vec![span.metadata().name().to_string()]);
+        span.record(&mut fields);
+        self.lines.lock().unwrap().push(fields.0.join(" "));
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+    
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+    
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(vec!["event".to_string()]);
+        event.record(&mut fields);
+        self.lines.lock().unwrap().push(fields.0.join(" "));
+    }
+    
+    fn enter(&self, _: &Id) {}
+    
+    fn exit(&self, _: &Id) {}
+}
+
+fn trace(source: &str) -> Vec<String> {
+    let recorder = Recorder::default();
+    let lines = Arc::clone(&recorder.lines);
+    tracing::subscriber::with_default(recorder, || {
+        let _ = Interpreter::new().eval(source);
+    });
+    lines.lock().unwrap().clone()
+}
+
+#[test]
+fn phases_and_calls_open_spans() {
+    let source = "function twice(x) { return x * 2; }\ntwice(2);";
+    let lines = trace(source);
+    assert_eq!(lines, [
+        &format!("lex bytes={}", source.len()),
+        "parse tokens=18",
+        "resolve",
+        "eval statements=2",
+        "call function=\"twice\" arguments=1",
+    ]);
+}
+
+#[test]
+fn failures_are_reported_as_events() {
+    let lines = trace("missing;");
+    assert_eq!(lines.last().unwrap(), "event message=script failed error=Reference error: 'missing' is not defined");
+}